@@ -10,18 +10,25 @@ use tokio::sync::mpsc;
 use crate::components::address_view::AddressView;
 use crate::components::block_detail::BlockDetailView;
 use crate::components::block_list::BlockList;
-use crate::components::contract_read::ContractRead;
+use crate::components::call_tree::CallTreeView;
+use crate::components::contract_read::{AbiFunction, ContractRead};
 use crate::components::dashboard::Dashboard;
+use crate::components::disassembly::DisassemblyView;
+use crate::components::gas_profile::GasProfileView;
 use crate::components::gas_tracker::GasTracker;
 use crate::components::header::Header;
 use crate::components::help::HelpOverlay;
 use crate::components::mempool::MempoolView;
 use crate::components::search::SearchBar;
+use crate::components::simulation::SimulationView;
+use crate::components::state_diff::StateDiffView;
 use crate::components::status_bar::StatusBar;
 use crate::components::storage_inspector::StorageInspector;
 use crate::components::tx_debugger::TxDebugger;
 use crate::components::watch_list::WatchListView;
 use crate::components::Component;
+use crate::data::decoder::TxDecoder;
+use crate::data::export;
 use crate::data::DataService;
 use crate::events::{AppEvent, View};
 use crate::theme::THEME;
@@ -43,7 +50,12 @@ pub struct App {
     watch_list: WatchListView,
     mempool: MempoolView,
     tx_debugger: TxDebugger,
+    call_tree: CallTreeView,
+    state_diff: StateDiffView,
+    gas_profile: GasProfileView,
+    simulation: SimulationView,
     storage_inspector: StorageInspector,
+    disassembly: DisassemblyView,
     status_bar: StatusBar,
     search_bar: SearchBar,
     help: HelpOverlay,
@@ -77,7 +89,12 @@ impl App {
             watch_list: WatchListView::new(),
             mempool: MempoolView::new(),
             tx_debugger: TxDebugger::new(),
+            call_tree: CallTreeView::new(),
+            state_diff: StateDiffView::new(),
+            gas_profile: GasProfileView::new(),
+            simulation: SimulationView::new(),
             storage_inspector: StorageInspector::new(),
+            disassembly: DisassemblyView::new(),
             status_bar: StatusBar::new(),
             search_bar: SearchBar::new(),
             help: HelpOverlay::new(),
@@ -152,8 +169,13 @@ impl App {
             View::WatchList => self.watch_list.render(frame, chunks[1]),
             View::Mempool => self.mempool.render(frame, chunks[1]),
             View::TxDebugger(_) => self.tx_debugger.render(frame, chunks[1]),
+            View::CallTree(_) => self.call_tree.render(frame, chunks[1]),
+            View::StateDiff(_) => self.state_diff.render(frame, chunks[1]),
+            View::GasProfile(_) => self.gas_profile.render(frame, chunks[1]),
+            View::Simulation => self.simulation.render(frame, chunks[1]),
             View::ContractRead(_) => self.contract_read.render(frame, chunks[1]),
             View::StorageInspector(_) => self.storage_inspector.render(frame, chunks[1]),
+            View::Disassembly(_) => self.disassembly.render(frame, chunks[1]),
         }
 
         // Status bar
@@ -187,8 +209,18 @@ impl App {
                 return;
             }
 
+            // Components with an active input prompt take every key
+            let captures_input = match &self.current_view {
+                View::TxDebugger(_) => self.tx_debugger.captures_input(),
+                View::Simulation => self.simulation.captures_input(),
+                View::Disassembly(_) => self.disassembly.captures_input(),
+                View::StorageInspector(_) => self.storage_inspector.captures_input(),
+                _ => false,
+            };
+
             // Global keys
             match key.code {
+                _ if captures_input => {}
                 KeyCode::Char('q') => {
                     self.should_quit = true;
                     return;
@@ -227,6 +259,26 @@ impl App {
                     return;
                 }
                 KeyCode::Char('e') => {
+                    if let View::GasProfile(hash) = &self.current_view {
+                        if let Some(ref profile) = self.gas_profile.profile {
+                            let path = format!("gas_{hash}.folded");
+                            self.status_bar.error_message =
+                                Some(match export::export_gas_folded(profile, &path) {
+                                    Ok(msg) | Err(msg) => msg,
+                                });
+                        }
+                        return;
+                    }
+                    if let View::TransactionDetail(hash) = &self.current_view {
+                        if let Some(ref detail) = self.tx_detail.detail {
+                            let path = format!("tx_{hash}.json");
+                            self.status_bar.error_message =
+                                Some(match export::export_tx_json(detail, &path) {
+                                    Ok(msg) | Err(msg) => msg,
+                                });
+                        }
+                        return;
+                    }
                     // Export current view data
                     let export_event = match &self.current_view {
                         View::BlockDetail(n) => {
@@ -236,9 +288,6 @@ impl App {
                                 Some(format!("block_{n}"))
                             }
                         }
-                        View::TransactionDetail(hash) => {
-                            Some(format!("tx_{hash}"))
-                        }
                         View::AddressView(addr) => {
                             Some(format!("address_{addr}"))
                         }
@@ -267,8 +316,13 @@ impl App {
                 View::WatchList => self.watch_list.handle_key(key),
                 View::Mempool => self.mempool.handle_key(key),
                 View::TxDebugger(_) => self.tx_debugger.handle_key(key),
+                View::CallTree(_) => self.call_tree.handle_key(key),
+                View::StateDiff(_) => self.state_diff.handle_key(key),
+                View::GasProfile(_) => self.gas_profile.handle_key(key),
+                View::Simulation => self.simulation.handle_key(key),
                 View::ContractRead(_) => self.contract_read.handle_key(key),
                 View::StorageInspector(_) => self.storage_inspector.handle_key(key),
+                View::Disassembly(_) => self.disassembly.handle_key(key),
             };
 
             if let Some(event) = app_event {
@@ -349,6 +403,9 @@ impl App {
             AppEvent::Error(msg) => {
                 self.status_bar.error_message = Some(msg);
                 self.status_bar.loading = false;
+                self.call_tree.loading = false;
+                self.state_diff.loading = false;
+                self.gas_profile.loading = false;
             }
             // New feature events - will be fully implemented by agents
             AppEvent::EnsResolved { address, .. } => {
@@ -360,14 +417,68 @@ impl App {
                 self.search_bar.error = Some(msg.clone());
                 self.status_bar.error_message = Some(msg);
             }
-            AppEvent::TokenMetadataLoaded(_meta) => {
-                // Agent D will update tx_detail with token metadata
+            AppEvent::TokenMetadataLoaded(tokens) => {
+                if let Some(ref mut detail) = self.tx_detail.detail {
+                    TxDecoder::apply_token_metadata(&mut detail.token_transfers, &tokens);
+                }
+                if let Some(ref mut result) = self.simulation.result {
+                    TxDecoder::apply_token_metadata(&mut result.token_transfers, &tokens);
+                }
             }
-            AppEvent::InternalTransactionsLoaded { calls, .. } => {
+            AppEvent::InternalTransactionsLoaded { tx_hash, calls } => {
+                if matches!(self.current_view, View::CallTree(h) if h == tx_hash) {
+                    self.call_tree.set_calls(calls.clone());
+                }
                 self.tx_detail.internal_calls = calls;
             }
-            AppEvent::DecodedLogsLoaded { logs, .. } => {
-                self.tx_detail.decoded_logs = logs;
+            AppEvent::RequestBlockTrace(number) => {
+                self.status_bar.loading = true;
+                self.data_service.fetch_block_trace(number);
+            }
+            AppEvent::BlockTraceLoaded(trace) => {
+                if matches!(self.current_view, View::BlockDetail(n) if n == trace.number) {
+                    self.status_bar.loading = false;
+                    self.block_detail.set_trace(*trace);
+                }
+            }
+            AppEvent::BlockTraceFailed { number, error } => {
+                if matches!(self.current_view, View::BlockDetail(n) if n == number) {
+                    self.status_bar.loading = false;
+                    self.status_bar.error_message = Some(error.clone());
+                    self.block_detail.set_trace_error(error);
+                }
+            }
+            AppEvent::GasProfileLoaded { tx_hash, profile } => {
+                if matches!(self.current_view, View::GasProfile(h) if h == tx_hash) {
+                    self.status_bar.loading = false;
+                    self.gas_profile.set_profile(Some(*profile));
+                }
+            }
+            AppEvent::StateDiffLoaded { tx_hash, diffs } => {
+                if matches!(self.current_view, View::StateDiff(h) if h == tx_hash) {
+                    self.state_diff.set_diffs(diffs);
+                }
+            }
+            AppEvent::DecodedLogsLoaded { tx_hash, logs } => {
+                if matches!(self.current_view, View::TransactionDetail(h) if h == tx_hash) {
+                    self.tx_detail.decoded_logs = logs;
+                }
+            }
+            AppEvent::ContractFunctionsLoaded { address, functions, untyped } => {
+                if self.contract_read.address == Some(address) {
+                    self.contract_read.loading = false;
+                    let untyped = untyped.iter().map(|f| AbiFunction {
+                        raw_output: true,
+                        ..AbiFunction::from(f)
+                    });
+                    self.contract_read.set_functions(
+                        functions
+                            .iter()
+                            .map(AbiFunction::from)
+                            .chain(untyped)
+                            .collect(),
+                    );
+                }
             }
             AppEvent::ContractReadResult { result, .. } => {
                 self.contract_read.loading = false;
@@ -401,13 +512,68 @@ impl App {
                     self.mempool.pending_txs.truncate(100);
                 }
             }
-            AppEvent::TraceLoaded { trace, .. } => {
-                self.tx_debugger.trace = Some(trace);
-                self.tx_debugger.loading = false;
+            AppEvent::TraceLoaded { tx_hash, trace } => {
+                // Ignore traces for a transaction the user already navigated away from
+                if matches!(self.current_view, View::TxDebugger(h) if h == tx_hash) {
+                    self.status_bar.loading = false;
+                    self.tx_debugger.set_trace(Some(trace));
+                    self.tx_debugger.loading = false;
+                }
             }
-            AppEvent::StorageValueLoaded { slot, value, .. } => {
-                self.storage_inspector.results.push((slot, value));
-                self.storage_inspector.loading = false;
+            AppEvent::TraceFailed { tx_hash, error } => {
+                if matches!(self.current_view, View::TxDebugger(h) if h == tx_hash) {
+                    self.status_bar.loading = false;
+                    self.status_bar.error_message = Some(error.clone());
+                    self.tx_debugger.error = Some(error);
+                    self.tx_debugger.loading = false;
+                }
+            }
+            AppEvent::ContractSourceLoaded { tx_hash, address, source } => {
+                if matches!(self.current_view, View::TxDebugger(h) if h == tx_hash) {
+                    self.tx_debugger.sources.insert(address, *source);
+                }
+            }
+            AppEvent::RequestTrace { tx_hash, options } => {
+                self.status_bar.loading = true;
+                self.data_service.fetch_execution_trace(tx_hash, options);
+            }
+            AppEvent::RequestSimulation(request) => {
+                self.status_bar.loading = true;
+                self.data_service.simulate(*request);
+            }
+            AppEvent::SimulationLoaded(result) => {
+                self.status_bar.loading = false;
+                self.simulation.set_result(*result);
+            }
+            AppEvent::SimulationFailed(error) => {
+                self.status_bar.loading = false;
+                self.simulation.set_error(error);
+            }
+            AppEvent::OpenSimulation { from, to, value, input, block } => {
+                self.simulation.prefill(from, to, value, input, block);
+                self.navigate_to(View::Simulation);
+            }
+            AppEvent::StorageLayoutLoaded { address, layout } => {
+                if self.storage_inspector.address == Some(address) {
+                    let items = self.storage_inspector.set_layout(layout.map(|l| *l));
+                    if !items.is_empty() {
+                        self.data_service.fetch_storage_items(address, items);
+                    }
+                }
+            }
+            AppEvent::RequestStorageItems { address, items } => {
+                self.data_service.fetch_storage_items(address, items);
+            }
+            AppEvent::StorageItemsLoaded { address, values } => {
+                if self.storage_inspector.address == Some(address) {
+                    self.storage_inspector.set_values(values);
+                }
+            }
+            AppEvent::DisassemblyLoaded { address, disassembly } => {
+                if self.disassembly.address == Some(address) {
+                    self.disassembly.loading = false;
+                    self.disassembly.set_disassembly(Some(*disassembly));
+                }
             }
             AppEvent::ExportComplete(path) => {
                 self.status_bar.error_message = Some(format!("Exported to {path}"));
@@ -445,16 +611,17 @@ impl App {
                 }
             }
             View::BlockDetail(number) => {
-                self.block_detail.detail = None;
-                self.block_detail.loading = true;
+                self.block_detail.reset_for(*number);
                 self.status_bar.loading = true;
                 self.data_service.fetch_block_detail(*number);
             }
             View::TransactionDetail(hash) => {
-                self.tx_detail.detail = None;
+                self.tx_detail.reset();
                 self.tx_detail.loading = true;
                 self.status_bar.loading = true;
                 self.data_service.fetch_transaction_detail(*hash);
+                self.data_service.fetch_internal_transactions(*hash, false);
+                self.data_service.fetch_decoded_logs(*hash);
             }
             View::AddressView(address) => {
                 self.address_view.info = None;
@@ -475,17 +642,52 @@ impl App {
                 // Requires WebSocket - agents will handle
             }
             View::TxDebugger(hash) => {
-                self.tx_debugger.trace = None;
+                self.tx_debugger.tx_hash = Some(*hash);
+                self.tx_debugger.set_trace(None);
+                self.tx_debugger.sources.clear();
+                self.tx_debugger.error = None;
                 self.tx_debugger.loading = true;
                 self.status_bar.loading = true;
-                self.data_service.fetch_internal_transactions(*hash);
+                self.data_service
+                    .fetch_execution_trace(*hash, self.tx_debugger.options);
+            }
+            View::CallTree(hash) => {
+                self.call_tree.tx_hash = Some(*hash);
+                self.call_tree.reset();
+                self.call_tree.loading = true;
+                self.data_service.fetch_internal_transactions(*hash, true);
+            }
+            View::GasProfile(hash) => {
+                self.gas_profile.tx_hash = Some(*hash);
+                self.gas_profile.set_profile(None);
+                self.gas_profile.loading = true;
+                self.status_bar.loading = true;
+                self.data_service.fetch_gas_profile(*hash);
+            }
+            View::StateDiff(hash) => {
+                self.state_diff.tx_hash = Some(*hash);
+                self.state_diff.set_diffs(Vec::new());
+                self.state_diff.loading = true;
+                self.data_service.fetch_state_diff(*hash);
+            }
+            View::Simulation => {
+                // The form keeps its contents between visits
             }
             View::ContractRead(address) => {
                 self.contract_read.address = Some(*address);
                 self.contract_read.loading = true;
+                self.contract_read.set_functions(Vec::new());
+                self.data_service.fetch_contract_functions(*address);
             }
             View::StorageInspector(address) => {
-                self.storage_inspector.address = Some(*address);
+                self.storage_inspector.reset(*address);
+                self.data_service.fetch_storage_layout(*address);
+            }
+            View::Disassembly(address) => {
+                self.disassembly.address = Some(*address);
+                self.disassembly.loading = true;
+                self.disassembly.set_disassembly(None);
+                self.data_service.fetch_disassembly(*address);
             }
         }
     }
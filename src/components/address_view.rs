@@ -1,9 +1,10 @@
+use alloy::primitives::{Bytes, U256};
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
 use crate::components::Component;
-use crate::data::types::AddressInfo;
+use crate::data::types::{AddressInfo, ProxyInfo};
 use crate::events::{AppEvent, View};
 use crate::theme::THEME;
 use crate::utils;
@@ -116,6 +117,13 @@ fn render_info_rows(info: &AddressInfo) -> Vec<Row<'static>> {
             ]));
         }
 
+        if let Some(ref compiler) = ci.compiler {
+            rows.push(Row::new(vec![
+                Cell::from("Compiler").style(THEME.muted_style()),
+                Cell::from(compiler.clone()),
+            ]));
+        }
+
         if let Some(ref symbol) = ci.symbol {
             rows.push(Row::new(vec![
                 Cell::from("Symbol").style(THEME.muted_style()),
@@ -130,21 +138,51 @@ fn render_info_rows(info: &AddressInfo) -> Vec<Row<'static>> {
             ]));
         }
 
-        if ci.is_proxy {
-            let impl_str = ci
-                .implementation
-                .map(|a| format!("{a}"))
-                .unwrap_or_else(|| "Unknown".to_string());
-            rows.push(Row::new(vec![
-                Cell::from("Proxy Target").style(THEME.muted_style()),
-                Cell::from(impl_str).style(THEME.address_style()),
-            ]));
+        if let Some(ref proxy) = ci.proxy {
+            rows.extend(proxy_rows(proxy));
         }
     }
 
     rows
 }
 
+/// Kind of a proxy and the contracts behind it.
+fn proxy_rows(proxy: &ProxyInfo) -> Vec<Row<'static>> {
+    let address_row = |label: &str, address: String| {
+        Row::new(vec![
+            Cell::from(label.to_string()).style(THEME.muted_style()),
+            Cell::from(address).style(THEME.address_style()),
+        ])
+    };
+
+    let mut rows = vec![Row::new(vec![
+        Cell::from("Proxy").style(THEME.muted_style()),
+        Cell::from(proxy.kind.to_string()).style(THEME.accent_style()),
+    ])];
+    if let Some(beacon) = proxy.beacon {
+        rows.push(address_row("Beacon", format!("{beacon}")));
+    }
+    if proxy.facets.is_empty() {
+        let implementation = proxy
+            .implementation
+            .map(|a| format!("{a}"))
+            .unwrap_or_else(|| "Unknown".to_string());
+        rows.push(address_row("Implementation", implementation));
+    }
+    for (i, (facet, selectors)) in proxy.facets.iter().enumerate() {
+        let label = if i == 0 { "Facets" } else { "" };
+        let plural = if *selectors == 1 { "" } else { "s" };
+        rows.push(address_row(
+            label,
+            format!("{facet}  ({selectors} selector{plural})"),
+        ));
+    }
+    if let Some(admin) = proxy.admin {
+        rows.push(address_row("Admin", format!("{admin}")));
+    }
+    rows
+}
+
 fn build_tx_rows(info: &AddressInfo) -> Vec<Row<'static>> {
     info.transactions
         .iter()
@@ -181,14 +219,17 @@ fn info_row_count(info: &AddressInfo) -> usize {
         if ci.name.is_some() {
             count += 1;
         }
+        if ci.compiler.is_some() {
+            count += 1;
+        }
         if ci.symbol.is_some() {
             count += 1;
         }
         if ci.decimals.is_some() {
             count += 1;
         }
-        if ci.is_proxy {
-            count += 1;
+        if let Some(ref proxy) = ci.proxy {
+            count += proxy_rows(proxy).len();
         }
     }
     count
@@ -234,6 +275,25 @@ impl Component for AddressView {
                 }
                 None
             }
+            KeyCode::Char('c') => {
+                // Disassemble the contract's runtime code
+                if let Some(info) = &self.info
+                    && info.is_contract
+                {
+                    return Some(AppEvent::Navigate(View::Disassembly(info.address)));
+                }
+                None
+            }
+            KeyCode::Char('x') => {
+                // Simulate a call to this address
+                self.info.as_ref().map(|info| AppEvent::OpenSimulation {
+                    from: None,
+                    to: Some(info.address),
+                    value: U256::ZERO,
+                    input: Bytes::new(),
+                    block: None,
+                })
+            }
             KeyCode::Char('w') => {
                 // Add to watch list (navigate to watch list for now)
                 Some(AppEvent::Navigate(View::WatchList))
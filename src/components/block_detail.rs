@@ -1,17 +1,30 @@
+use alloy::primitives::U256;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
 use crate::components::Component;
-use crate::data::types::BlockDetail;
+use crate::data::types::{BlockDetail, BlockTrace};
 use crate::events::{AppEvent, View};
 use crate::theme::THEME;
 use crate::utils;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Transactions,
+    Transfers,
+}
+
 pub struct BlockDetailView {
     pub detail: Option<BlockDetail>,
     pub loading: bool,
+    /// Block-wide trace, loaded on demand
+    pub trace: Option<BlockTrace>,
+    pub trace_loading: bool,
+    pub trace_error: Option<String>,
+    focus: Focus,
     tx_table_state: TableState,
+    transfer_table_state: TableState,
     scroll_offset: u16,
 }
 
@@ -20,11 +33,45 @@ impl BlockDetailView {
         Self {
             detail: None,
             loading: false,
+            trace: None,
+            trace_loading: false,
+            trace_error: None,
+            focus: Focus::Transactions,
             tx_table_state: TableState::default(),
+            transfer_table_state: TableState::default(),
             scroll_offset: 0,
         }
     }
 
+    /// Prepare for showing `number`, keeping its trace when coming back to it.
+    pub fn reset_for(&mut self, number: u64) {
+        self.detail = None;
+        self.loading = true;
+        if self.trace.as_ref().is_none_or(|t| t.number != number) {
+            self.trace = None;
+            self.trace_loading = false;
+            self.trace_error = None;
+            self.focus = Focus::Transactions;
+            self.transfer_table_state = TableState::default();
+        }
+    }
+
+    pub fn set_trace(&mut self, trace: BlockTrace) {
+        self.trace = Some(trace);
+        self.trace_loading = false;
+        self.trace_error = None;
+        self.transfer_table_state.select(Some(0));
+    }
+
+    pub fn set_trace_error(&mut self, error: String) {
+        self.trace_error = Some(error);
+        self.trace_loading = false;
+    }
+
+    fn transfer_count(&self) -> usize {
+        self.trace.as_ref().map(|t| t.transfers.len()).unwrap_or(0)
+    }
+
     fn tx_count(&self) -> usize {
         self.detail
             .as_ref()
@@ -32,24 +79,37 @@ impl BlockDetailView {
             .unwrap_or(0)
     }
 
+    fn focused_table(&mut self) -> (&mut TableState, usize) {
+        match self.focus {
+            Focus::Transactions => {
+                let len = self.tx_count();
+                (&mut self.tx_table_state, len)
+            }
+            Focus::Transfers => {
+                let len = self.transfer_count();
+                (&mut self.transfer_table_state, len)
+            }
+        }
+    }
+
     fn select_next_tx(&mut self) {
-        let len = self.tx_count();
+        let (state, len) = self.focused_table();
         if len == 0 {
             return;
         }
-        let current = self.tx_table_state.selected().unwrap_or(0);
+        let current = state.selected().unwrap_or(0);
         let next = if current + 1 >= len { current } else { current + 1 };
-        self.tx_table_state.select(Some(next));
+        state.select(Some(next));
     }
 
     fn select_prev_tx(&mut self) {
-        let len = self.tx_count();
+        let (state, len) = self.focused_table();
         if len == 0 {
             return;
         }
-        let current = self.tx_table_state.selected().unwrap_or(0);
+        let current = state.selected().unwrap_or(0);
         let prev = current.saturating_sub(1);
-        self.tx_table_state.select(Some(prev));
+        state.select(Some(prev));
     }
 }
 
@@ -117,7 +177,7 @@ fn render_info_section(detail: &BlockDetail) -> Vec<Row<'static>> {
     rows
 }
 
-fn build_tx_rows(detail: &BlockDetail) -> Vec<Row<'static>> {
+fn build_tx_rows(detail: &BlockDetail, trace: Option<&BlockTrace>) -> Vec<Row<'static>> {
     detail
         .transactions
         .iter()
@@ -134,12 +194,52 @@ fn build_tx_rows(detail: &BlockDetail) -> Vec<Row<'static>> {
                 .or_else(|| tx.method_id.as_ref().map(|id| utils::format_selector(id)))
                 .unwrap_or_else(|| "Transfer".to_string());
 
-            Row::new(vec![
+            let mut cells = vec![
                 Cell::from(utils::truncate_hash(&tx.hash)).style(THEME.hash_style()),
                 Cell::from(from_to).style(THEME.address_style()),
                 Cell::from(utils::format_eth(tx.value)).style(THEME.eth_style()),
                 Cell::from(method).style(THEME.muted_style()),
-            ])
+            ];
+            if let Some(trace) = trace {
+                let stats = trace.stats.get(&tx.hash).cloned().unwrap_or_default();
+                cells.push(Cell::from(stats.call_count.to_string()));
+                cells.push(if stats.internal_value.is_zero() {
+                    Cell::from("-").style(THEME.muted_style())
+                } else {
+                    Cell::from(utils::format_eth(stats.internal_value)).style(THEME.eth_style())
+                });
+            }
+            Row::new(cells)
+        })
+        .collect()
+}
+
+fn build_transfer_rows(trace: &BlockTrace) -> Vec<Row<'static>> {
+    trace
+        .transfers
+        .iter()
+        .map(|transfer| {
+            let from_to = format!(
+                "{} \u{2192} {}",
+                utils::truncate_address(&transfer.from),
+                utils::truncate_address(&transfer.to)
+            );
+            let kind = if transfer.reverted {
+                format!("{} (reverted)", transfer.call_type)
+            } else {
+                transfer.call_type.clone()
+            };
+            let row = Row::new(vec![
+                Cell::from(utils::truncate_hash(&transfer.tx_hash)).style(THEME.hash_style()),
+                Cell::from(kind),
+                Cell::from(from_to).style(THEME.address_style()),
+                Cell::from(utils::format_eth(transfer.value)).style(THEME.eth_style()),
+            ]);
+            if transfer.reverted {
+                row.style(THEME.muted_style().add_modifier(Modifier::CROSSED_OUT))
+            } else {
+                row
+            }
         })
         .collect()
 }
@@ -156,6 +256,29 @@ impl Component for BlockDetailView {
                 self.select_prev_tx();
                 None
             }
+            KeyCode::Char('t') => {
+                let number = self.detail.as_ref()?.summary.number;
+                if self.trace_loading || self.trace.is_some() {
+                    return None;
+                }
+                self.trace_loading = true;
+                self.trace_error = None;
+                Some(AppEvent::RequestBlockTrace(number))
+            }
+            KeyCode::Tab => {
+                if self.transfer_count() > 0 {
+                    self.focus = match self.focus {
+                        Focus::Transactions => Focus::Transfers,
+                        Focus::Transfers => Focus::Transactions,
+                    };
+                }
+                None
+            }
+            KeyCode::Enter if self.focus == Focus::Transfers => {
+                let idx = self.transfer_table_state.selected()?;
+                let transfer = self.trace.as_ref()?.transfers.get(idx)?;
+                Some(AppEvent::Navigate(View::TransactionDetail(transfer.tx_hash)))
+            }
             KeyCode::Enter => {
                 if let Some(detail) = &self.detail {
                     if let Some(idx) = self.tx_table_state.selected() {
@@ -271,29 +394,73 @@ impl Component for BlockDetailView {
 
         frame.render_widget(gauge, chunks[1]);
 
-        // -- 3. Transaction table --
+        // -- 3. Transaction table, with internal transfers below once traced --
         if has_txs {
+            let transfers = self.trace.as_ref().filter(|t| !t.transfers.is_empty());
+            let areas = if transfers.is_some() {
+                Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(6), Constraint::Percentage(40)])
+                    .split(chunks[2])
+            } else {
+                Layout::default()
+                    .constraints([Constraint::Min(6)])
+                    .split(chunks[2])
+            };
+
+            let trace_hint = if self.trace_loading {
+                " tracing... ".to_string()
+            } else if let Some(ref err) = self.trace_error {
+                format!(" trace failed: {err} ")
+            } else if self.trace.is_none() {
+                " t: trace internal calls ".to_string()
+            } else if transfers.is_some() {
+                " Tab: switch table ".to_string()
+            } else {
+                String::new()
+            };
+            let hint_style = if self.trace_error.is_some() {
+                THEME.error_style()
+            } else {
+                THEME.muted_style()
+            };
+            let border_style = |focus: Focus| {
+                if self.focus == focus {
+                    THEME.border_focused_style()
+                } else {
+                    THEME.border_style()
+                }
+            };
+
             let tx_block = Block::default()
                 .title(format!(" Transactions ({}) ", detail.transactions.len()))
+                .title_bottom(Line::from(Span::styled(trace_hint, hint_style)))
                 .borders(Borders::ALL)
-                .border_style(THEME.border_style());
+                .border_style(border_style(Focus::Transactions));
 
-            let tx_header = Row::new(vec![
+            let mut header_cells = vec![
                 Cell::from("Hash"),
                 Cell::from("From / To"),
                 Cell::from("Value"),
                 Cell::from("Method"),
-            ])
-            .style(THEME.table_header_style())
-            .bottom_margin(0);
-
-            let tx_rows = build_tx_rows(&detail);
-            let tx_widths = [
+            ];
+            let mut tx_widths = vec![
                 Constraint::Length(14),
                 Constraint::Min(24),
                 Constraint::Length(16),
                 Constraint::Length(12),
             ];
+            if self.trace.is_some() {
+                header_cells.push(Cell::from("Calls"));
+                header_cells.push(Cell::from("Internal ETH"));
+                tx_widths.push(Constraint::Length(6));
+                tx_widths.push(Constraint::Length(16));
+            }
+            let tx_header = Row::new(header_cells)
+                .style(THEME.table_header_style())
+                .bottom_margin(0);
+
+            let tx_rows = build_tx_rows(&detail, self.trace.as_ref());
 
             let tx_table = Table::new(tx_rows, tx_widths)
                 .header(tx_header)
@@ -301,7 +468,48 @@ impl Component for BlockDetailView {
                 .row_highlight_style(THEME.selected_style())
                 .highlight_symbol(" > ");
 
-            frame.render_stateful_widget(tx_table, chunks[2], &mut self.tx_table_state);
+            frame.render_stateful_widget(tx_table, areas[0], &mut self.tx_table_state);
+
+            if let Some(trace) = transfers {
+                let total_calls: usize = trace.stats.values().map(|s| s.call_count).sum();
+                let total_value = trace
+                    .stats
+                    .values()
+                    .fold(U256::ZERO, |acc, s| acc + s.internal_value);
+                let transfer_block = Block::default()
+                    .title(format!(
+                        " Internal Transfers ({}) \u{00b7} {} internal calls \u{00b7} {} moved ",
+                        trace.transfers.len(),
+                        utils::format_number(total_calls as u64),
+                        utils::format_eth(total_value)
+                    ))
+                    .borders(Borders::ALL)
+                    .border_style(border_style(Focus::Transfers));
+                let transfer_header = Row::new(vec![
+                    Cell::from("Tx"),
+                    Cell::from("Type"),
+                    Cell::from("From / To"),
+                    Cell::from("Value"),
+                ])
+                .style(THEME.table_header_style());
+                let transfer_widths = [
+                    Constraint::Length(14),
+                    Constraint::Length(24),
+                    Constraint::Min(28),
+                    Constraint::Length(16),
+                ];
+                let transfer_table = Table::new(build_transfer_rows(trace), transfer_widths)
+                    .header(transfer_header)
+                    .block(transfer_block)
+                    .row_highlight_style(THEME.selected_style())
+                    .highlight_symbol(" > ");
+
+                frame.render_stateful_widget(
+                    transfer_table,
+                    areas[1],
+                    &mut self.transfer_table_state,
+                );
+            }
         }
     }
 }
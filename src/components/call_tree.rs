@@ -0,0 +1,402 @@
+use std::collections::HashSet;
+
+use alloy::primitives::B256;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use crate::components::Component;
+use crate::data::types::InternalCall;
+use crate::events::{AppEvent, View};
+use crate::theme::THEME;
+use crate::utils;
+
+/// Collapsible tree of the internal calls made by a transaction.
+pub struct CallTreeView {
+    pub tx_hash: Option<B256>,
+    pub calls: Vec<InternalCall>,
+    pub loading: bool,
+    /// Indices (into `calls`) of frames whose children are hidden
+    collapsed: HashSet<usize>,
+    /// Index (into the visible rows) of the selected frame
+    selected: usize,
+    list_state: ListState,
+}
+
+impl CallTreeView {
+    pub fn new() -> Self {
+        Self {
+            tx_hash: None,
+            calls: Vec::new(),
+            loading: false,
+            collapsed: HashSet::new(),
+            selected: 0,
+            list_state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    /// Replace the calls, keeping the expansion state if the shape is unchanged
+    /// (e.g. when decoded names arrive for the same tree).
+    pub fn set_calls(&mut self, calls: Vec<InternalCall>) {
+        if calls.len() != self.calls.len() {
+            self.collapsed.clear();
+            self.selected = 0;
+            self.list_state.select(Some(0));
+        }
+        self.calls = calls;
+        self.loading = false;
+    }
+
+    pub fn reset(&mut self) {
+        self.calls.clear();
+        self.collapsed.clear();
+        self.selected = 0;
+        self.list_state.select(Some(0));
+    }
+
+    /// Whether the call at `index` has any children.
+    fn has_children(&self, index: usize) -> bool {
+        self.calls
+            .get(index + 1)
+            .is_some_and(|next| next.depth > self.calls[index].depth)
+    }
+
+    /// Indices of the calls not hidden inside a collapsed frame.
+    fn visible(&self) -> Vec<usize> {
+        let mut rows = Vec::new();
+        let mut hide_below: Option<usize> = None;
+        for (i, call) in self.calls.iter().enumerate() {
+            if let Some(depth) = hide_below {
+                if call.depth > depth {
+                    continue;
+                }
+                hide_below = None;
+            }
+            rows.push(i);
+            if self.collapsed.contains(&i) {
+                hide_below = Some(call.depth);
+            }
+        }
+        rows
+    }
+
+    /// Per-call flag: the call or one of its ancestors reverted.
+    fn reverted(&self) -> Vec<bool> {
+        let mut flags = Vec::with_capacity(self.calls.len());
+        let mut reverted_depth: Option<usize> = None;
+        for call in &self.calls {
+            if reverted_depth.is_some_and(|d| call.depth <= d) {
+                reverted_depth = None;
+            }
+            if reverted_depth.is_none() && call.error.is_some() {
+                reverted_depth = Some(call.depth);
+            }
+            flags.push(reverted_depth.is_some());
+        }
+        flags
+    }
+
+    fn selected_call(&self) -> Option<usize> {
+        self.visible().get(self.selected).copied()
+    }
+
+    fn select(&mut self, row: usize) {
+        let len = self.visible().len();
+        if len == 0 {
+            return;
+        }
+        self.selected = row.min(len - 1);
+        self.list_state.select(Some(self.selected));
+    }
+
+    fn collapse(&mut self) {
+        let Some(index) = self.selected_call() else {
+            return;
+        };
+        if self.has_children(index) && !self.collapsed.contains(&index) {
+            self.collapsed.insert(index);
+            return;
+        }
+        // Already collapsed or a leaf: move to the parent frame
+        let depth = self.calls[index].depth;
+        let rows = self.visible();
+        if let Some(parent_row) = rows[..self.selected]
+            .iter()
+            .rposition(|&i| self.calls[i].depth < depth)
+        {
+            self.select(parent_row);
+        }
+    }
+
+    fn expand(&mut self) {
+        if let Some(index) = self.selected_call() {
+            self.collapsed.remove(&index);
+        }
+    }
+
+    fn toggle(&mut self) {
+        let Some(index) = self.selected_call() else {
+            return;
+        };
+        if !self.collapsed.remove(&index) && self.has_children(index) {
+            self.collapsed.insert(index);
+        }
+    }
+
+    fn collapse_all(&mut self) {
+        self.collapsed = (0..self.calls.len())
+            .filter(|&i| self.calls[i].depth > 0 && self.has_children(i))
+            .collect();
+        self.select(0);
+    }
+
+    fn call_label(call: &InternalCall) -> Vec<Span<'static>> {
+        let mut spans = vec![
+            Span::styled(
+                call.call_type.clone(),
+                Style::default().fg(THEME.warning).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" "),
+            Span::styled(utils::truncate_address(&call.to), THEME.address_style()),
+        ];
+        match call.decoded {
+            Some(ref decoded) => {
+                let args = decoded
+                    .params
+                    .iter()
+                    .map(|p| utils::shorten(&p.formatted(), 20))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                spans.push(Span::styled(
+                    format!(".{}({args})", decoded.function_name),
+                    THEME.accent_style(),
+                ));
+            }
+            None if call.input.len() >= 4 => {
+                spans.push(Span::styled(
+                    format!(".{}", alloy::hex::encode_prefixed(&call.input[..4])),
+                    THEME.muted_style(),
+                ));
+            }
+            None => {}
+        }
+        if !call.value.is_zero() {
+            spans.push(Span::styled(
+                format!("  {}", utils::format_eth(call.value)),
+                THEME.eth_style(),
+            ));
+        }
+        spans.push(Span::styled(
+            format!("  gas {}", utils::format_number(call.gas_used)),
+            THEME.muted_style(),
+        ));
+        spans
+    }
+
+    fn render_details(&self, frame: &mut Frame, area: Rect, index: usize) {
+        let call = &self.calls[index];
+        let block = Block::default()
+            .title(" Frame ")
+            .borders(Borders::ALL)
+            .border_style(THEME.border_style());
+        let label = Style::default().fg(THEME.text_muted);
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("  From:   ", label),
+                Span::styled(format!("{}", call.from), THEME.address_style()),
+            ]),
+            Line::from(vec![
+                Span::styled("  To:     ", label),
+                Span::styled(format!("{}", call.to), THEME.address_style()),
+            ]),
+        ];
+
+        if let Some(ref decoded) = call.decoded {
+            lines.push(Line::from(vec![
+                Span::styled("  Call:   ", label),
+                Span::styled(decoded.function_name.clone(), THEME.accent_style()),
+            ]));
+            for param in &decoded.params {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("    {}: ", param.name()), label),
+                    Span::raw(param.formatted()),
+                ]));
+            }
+        } else if !call.input.is_empty() {
+            lines.push(Line::from(vec![
+                Span::styled("  Input:  ", label),
+                Span::styled(utils::shorten(&format!("{}", call.input), 66), THEME.hash_style()),
+            ]));
+        }
+
+        if !call.decoded_output.is_empty() {
+            lines.push(Line::from(Span::styled("  Return:", label)));
+            for param in &call.decoded_output {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("    {}: ", param.name()), label),
+                    Span::raw(param.formatted()),
+                ]));
+            }
+        } else if !call.output.is_empty() {
+            lines.push(Line::from(vec![
+                Span::styled("  Output: ", label),
+                Span::styled(utils::shorten(&format!("{}", call.output), 66), THEME.hash_style()),
+            ]));
+        }
+
+        if let Some(ref err) = call.error {
+            lines.push(Line::from(vec![
+                Span::styled("  Error:  ", label),
+                Span::styled(err.clone(), THEME.error_style()),
+            ]));
+        }
+        if let Some(ref reason) = call.revert_reason {
+            lines.push(Line::from(vec![
+                Span::styled("  Reason: ", label),
+                Span::styled(reason.clone(), THEME.error_style()),
+            ]));
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false });
+        frame.render_widget(paragraph, area);
+    }
+}
+
+impl Component for CallTreeView {
+    fn handle_key(&mut self, key: KeyEvent) -> Option<AppEvent> {
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('j'), _) | (KeyCode::Down, _) => {
+                self.select(self.selected + 1);
+                None
+            }
+            (KeyCode::Char('k'), _) | (KeyCode::Up, _) => {
+                self.select(self.selected.saturating_sub(1));
+                None
+            }
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
+                self.select(self.selected + 20);
+                None
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                self.select(self.selected.saturating_sub(20));
+                None
+            }
+            (KeyCode::Char('g'), _) => {
+                self.select(0);
+                None
+            }
+            (KeyCode::Char('G'), _) => {
+                self.select(usize::MAX);
+                None
+            }
+            (KeyCode::Char('h'), _) | (KeyCode::Left, _) => {
+                self.collapse();
+                None
+            }
+            (KeyCode::Char('l'), _) | (KeyCode::Right, _) => {
+                self.expand();
+                None
+            }
+            (KeyCode::Char(' '), _) => {
+                self.toggle();
+                None
+            }
+            (KeyCode::Char('-'), _) => {
+                self.collapse_all();
+                None
+            }
+            (KeyCode::Char('+'), _) | (KeyCode::Char('='), _) => {
+                self.collapsed.clear();
+                None
+            }
+            (KeyCode::Enter, _) => self
+                .selected_call()
+                .map(|i| AppEvent::Navigate(View::AddressView(self.calls[i].to))),
+            _ => None,
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let title = match self.tx_hash {
+            Some(hash) => format!(" Call Tree: {} ", utils::truncate_hash(&hash)),
+            None => " Call Tree ".to_string(),
+        };
+        let outer_block = Block::default()
+            .title(title)
+            .title_bottom(Line::from(Span::styled(
+                " Space: toggle | h/l: collapse/expand | -/+: all | Enter: open callee ",
+                THEME.muted_style(),
+            )))
+            .borders(Borders::ALL)
+            .border_style(THEME.border_focused_style());
+        let inner = outer_block.inner(area);
+        frame.render_widget(outer_block, area);
+
+        if self.calls.is_empty() {
+            let msg = if self.loading {
+                "Loading call tree..."
+            } else {
+                "No internal calls (requires trace_transaction or debug_traceTransaction)"
+            };
+            let text = Paragraph::new(msg)
+                .style(THEME.muted_style())
+                .alignment(Alignment::Center);
+            frame.render_widget(text, inner);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(12)])
+            .split(inner);
+
+        let reverted = self.reverted();
+        let rows = self.visible();
+        let items: Vec<ListItem> = rows
+            .iter()
+            .map(|&i| {
+                let call = &self.calls[i];
+                let marker = if !self.has_children(i) {
+                    "  "
+                } else if self.collapsed.contains(&i) {
+                    "▸ "
+                } else {
+                    "▾ "
+                };
+                let mut spans = vec![Span::raw(format!("{}{marker}", "  ".repeat(call.depth)))];
+                spans.extend(Self::call_label(call));
+                if let Some(ref err) = call.error {
+                    let text = match call.revert_reason {
+                        Some(ref reason) => format!("  [{err}: {reason}]"),
+                        None => format!("  [{err}]"),
+                    };
+                    spans.push(Span::styled(text, THEME.error_style()));
+                }
+
+                let item = ListItem::new(Line::from(spans));
+                if reverted[i] {
+                    item.style(Style::default().bg(Color::Rgb(60, 20, 20)))
+                } else {
+                    item
+                }
+            })
+            .collect();
+
+        let list_block = Block::default()
+            .title(format!(" Calls ({}) ", self.calls.len()))
+            .borders(Borders::ALL)
+            .border_style(THEME.border_style());
+        let list = List::new(items)
+            .block(list_block)
+            .highlight_style(THEME.selected_style())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, chunks[0], &mut self.list_state);
+
+        if let Some(index) = self.selected_call() {
+            self.render_details(frame, chunks[1], index);
+        }
+    }
+}
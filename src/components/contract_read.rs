@@ -12,6 +12,27 @@ pub struct AbiFunction {
     pub name: String,
     pub inputs: Vec<(String, String)>, // (param_name, param_type)
     pub outputs: Vec<String>,          // type strings
+    /// Known only by its text signature, so the return data is shown raw
+    pub raw_output: bool,
+}
+
+impl From<&alloy::json_abi::Function> for AbiFunction {
+    fn from(function: &alloy::json_abi::Function) -> Self {
+        Self {
+            name: function.name.clone(),
+            inputs: function
+                .inputs
+                .iter()
+                .map(|p| (p.name.clone(), p.selector_type().into_owned()))
+                .collect(),
+            outputs: function
+                .outputs
+                .iter()
+                .map(|p| p.selector_type().into_owned())
+                .collect(),
+            raw_output: false,
+        }
+    }
 }
 
 pub struct ContractRead {
@@ -45,7 +66,8 @@ impl ContractRead {
         }
     }
 
-    /// Set the list of callable functions (view/pure only).
+    /// Set the list of callable functions (view/pure, and those whose
+    /// outputs are unknown).
     pub fn set_functions(&mut self, functions: Vec<AbiFunction>) {
         self.functions = functions;
         self.selected = 0;
@@ -292,7 +314,9 @@ impl Component for ContractRead {
                         .collect();
                     format!("({})", params.join(", "))
                 };
-                let outputs = if f.outputs.is_empty() {
+                let outputs = if f.raw_output {
+                    "raw bytes".to_string()
+                } else if f.outputs.is_empty() {
                     "void".to_string()
                 } else {
                     f.outputs.join(", ")
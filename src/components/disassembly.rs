@@ -0,0 +1,403 @@
+use alloy::primitives::Address;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use crate::components::Component;
+use crate::data::disasm::{Disassembly, Instruction};
+use crate::events::AppEvent;
+use crate::theme::THEME;
+use crate::utils;
+
+/// Opcodes worth spotting when auditing code.
+const CALL_OPS: &[&str] = &["CALL", "CALLCODE", "DELEGATECALL", "STATICCALL"];
+const DANGER_OPS: &[&str] = &["SELFDESTRUCT", "CREATE", "CREATE2"];
+
+/// Instructions moved by Ctrl-d / Ctrl-u.
+const PAGE_SIZE: usize = 20;
+
+pub struct DisassemblyView {
+    pub address: Option<Address>,
+    pub disassembly: Option<Disassembly>,
+    pub loading: bool,
+    selected: usize,
+    /// Whether the search prompt is capturing keys.
+    input_mode: bool,
+    search_input: String,
+    /// Last submitted search, repeated by n / N.
+    query: Option<String>,
+    /// Instructions jumped away from, returned to with `b`.
+    jump_history: Vec<usize>,
+    /// Feedback from the last search or jump.
+    message: Option<String>,
+    table_state: TableState,
+    scroll_state: ScrollbarState,
+}
+
+impl DisassemblyView {
+    pub fn new() -> Self {
+        Self {
+            address: None,
+            disassembly: None,
+            loading: false,
+            selected: 0,
+            input_mode: false,
+            search_input: String::new(),
+            query: None,
+            jump_history: Vec::new(),
+            message: None,
+            table_state: TableState::default().with_selected(0),
+            scroll_state: ScrollbarState::default(),
+        }
+    }
+
+    /// Replace the shown code and move back to its first instruction.
+    pub fn set_disassembly(&mut self, disassembly: Option<Disassembly>) {
+        self.disassembly = disassembly;
+        self.jump_history.clear();
+        self.message = None;
+        self.select(0);
+    }
+
+    fn len(&self) -> usize {
+        self.disassembly
+            .as_ref()
+            .map(|d| d.instructions.len())
+            .unwrap_or(0)
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.len().saturating_sub(1));
+        self.table_state.select(Some(self.selected));
+        self.scroll_state = self.scroll_state.position(self.selected);
+    }
+
+    fn search(&mut self, forward: bool) {
+        let Some(query) = self.query.clone() else {
+            self.message = Some("No search yet; press f to search".to_string());
+            return;
+        };
+        let found = self
+            .disassembly
+            .as_ref()
+            .and_then(|d| d.find(&query, self.selected, forward));
+        match found {
+            Some(index) => {
+                self.message = None;
+                self.select(index);
+            }
+            None => self.message = Some(format!("No {query} in this code")),
+        }
+    }
+
+    /// Follow a PUSH of a jump destination or a dispatcher branch.
+    fn follow_jump(&mut self) {
+        let Some(ref disassembly) = self.disassembly else {
+            return;
+        };
+        let pc = disassembly.instructions.get(self.selected).map(|i| i.pc);
+        let target = disassembly.jump_target(self.selected).or_else(|| {
+            let entry = disassembly.dispatch_at(pc?)?;
+            disassembly.index_of_pc(entry.target)
+        });
+        match target {
+            Some(target) => {
+                self.jump_history.push(self.selected);
+                self.message = None;
+                self.select(target);
+            }
+            None => self.message = Some("Not a jump to a JUMPDEST".to_string()),
+        }
+    }
+
+    fn op_style(name: &str) -> Style {
+        if DANGER_OPS.contains(&name) {
+            Style::default()
+                .fg(THEME.error)
+                .add_modifier(Modifier::BOLD)
+        } else if CALL_OPS.contains(&name) {
+            Style::default().fg(THEME.info).add_modifier(Modifier::BOLD)
+        } else if name == "JUMPDEST" {
+            THEME.accent_style().add_modifier(Modifier::BOLD)
+        } else if name == "JUMP" || name == "JUMPI" {
+            Style::default().fg(THEME.warning)
+        } else if name == "UNKNOWN" {
+            THEME.muted_style()
+        } else {
+            Style::default().fg(THEME.text)
+        }
+    }
+}
+
+/// Name of a dispatched function, or its selector.
+fn function_label(name: Option<&str>, selector: [u8; 4]) -> String {
+    match name {
+        Some(name) => format!("{name} ({})", utils::format_selector(&selector)),
+        None => utils::format_selector(&selector),
+    }
+}
+
+fn instruction_row(disassembly: &Disassembly, instruction: &Instruction) -> Row<'static> {
+    let name = instruction.name();
+    let argument = if instruction.push_data.is_empty() {
+        String::new()
+    } else {
+        format!("0x{}", alloy::hex::encode(&instruction.push_data))
+    };
+
+    let dispatch = disassembly.dispatch_at(instruction.pc);
+    let note = match (dispatch, disassembly.dispatch_to(instruction.pc)) {
+        (Some(entry), _) if entry.pc == instruction.pc => format!(
+            "dispatch {} \u{2192} {:#06x}",
+            function_label(entry.name.as_deref(), entry.selector),
+            entry.target
+        ),
+        (_, Some(entry)) if instruction.is_jumpdest() => {
+            format!(
+                "function {}",
+                function_label(entry.name.as_deref(), entry.selector)
+            )
+        }
+        _ => String::new(),
+    };
+    let marker = if dispatch.is_some() { "\u{2502}" } else { " " };
+
+    Row::new(vec![
+        Cell::from(format!("{marker}{:#06x}", instruction.pc)).style(THEME.muted_style()),
+        Cell::from(name).style(DisassemblyView::op_style(name)),
+        Cell::from(argument).style(THEME.hash_style()),
+        Cell::from(note).style(THEME.accent_style()),
+    ])
+}
+
+/// Code size and counts, then the compiler and metadata hash of the trailer.
+fn summary_lines(address: Option<Address>, disassembly: &Disassembly) -> Vec<Line<'static>> {
+    let jumpdests = disassembly
+        .instructions
+        .iter()
+        .filter(|i| i.is_jumpdest())
+        .count();
+    let mut first = vec![Span::styled("  Address: ", THEME.muted_style())];
+    if let Some(address) = address {
+        first.push(Span::styled(format!("{address}"), THEME.address_style()));
+    }
+    first.extend([
+        Span::styled("  Size: ", THEME.muted_style()),
+        Span::raw(format!(
+            "{} bytes",
+            utils::format_number(disassembly.code_size as u64)
+        )),
+        Span::styled("  Instructions: ", THEME.muted_style()),
+        Span::raw(utils::format_number(disassembly.instructions.len() as u64)),
+        Span::styled("  JUMPDESTs: ", THEME.muted_style()),
+        Span::raw(utils::format_number(jumpdests as u64)),
+        Span::styled("  Dispatched functions: ", THEME.muted_style()),
+        Span::raw(format!("{}", disassembly.dispatch.len())),
+    ]);
+
+    let second = match disassembly.metadata {
+        Some(ref metadata) => {
+            let mut spans = vec![
+                Span::styled("  Compiler: ", THEME.muted_style()),
+                Span::raw(
+                    metadata
+                        .compiler
+                        .clone()
+                        .unwrap_or_else(|| "unknown".to_string()),
+                ),
+            ];
+            if metadata.experimental {
+                spans.push(Span::styled(
+                    " (experimental)",
+                    Style::default().fg(THEME.warning),
+                ));
+            }
+            if let Some(ref ipfs) = metadata.ipfs {
+                spans.push(Span::styled("  IPFS: ", THEME.muted_style()));
+                spans.push(Span::styled(ipfs.clone(), THEME.hash_style()));
+            }
+            if let Some((ref version, ref hash)) = metadata.swarm {
+                spans.push(Span::styled(
+                    format!("  Swarm ({version}): "),
+                    THEME.muted_style(),
+                ));
+                spans.push(Span::styled(hash.clone(), THEME.hash_style()));
+            }
+            spans.push(Span::styled(
+                format!("  ({} byte metadata trailer)", metadata.len),
+                THEME.muted_style(),
+            ));
+            Line::from(spans)
+        }
+        None => Line::from(Span::styled("  No metadata trailer", THEME.muted_style())),
+    };
+
+    vec![Line::from(first), second]
+}
+
+impl Component for DisassemblyView {
+    fn captures_input(&self) -> bool {
+        self.input_mode
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<AppEvent> {
+        if self.input_mode {
+            match key.code {
+                KeyCode::Esc => {
+                    self.input_mode = false;
+                    self.search_input.clear();
+                }
+                KeyCode::Enter => {
+                    self.input_mode = false;
+                    let query = self.search_input.trim().to_string();
+                    self.search_input.clear();
+                    if !query.is_empty() {
+                        self.query = Some(query);
+                        self.search(true);
+                    }
+                }
+                KeyCode::Backspace => {
+                    self.search_input.pop();
+                }
+                KeyCode::Char(c) => self.search_input.push(c),
+                _ => {}
+            }
+            return None;
+        }
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('j'), _) | (KeyCode::Down, _) => self.select(self.selected + 1),
+            (KeyCode::Char('k'), _) | (KeyCode::Up, _) => {
+                self.select(self.selected.saturating_sub(1))
+            }
+            (KeyCode::Char('g'), _) => self.select(0),
+            (KeyCode::Char('G'), _) => self.select(self.len().saturating_sub(1)),
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) => self.select(self.selected + PAGE_SIZE),
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                self.select(self.selected.saturating_sub(PAGE_SIZE))
+            }
+            (KeyCode::Char('f'), _) => {
+                self.input_mode = true;
+                self.search_input.clear();
+            }
+            (KeyCode::Char('n'), _) => self.search(true),
+            (KeyCode::Char('N'), _) => self.search(false),
+            (KeyCode::Enter, _) => self.follow_jump(),
+            (KeyCode::Char('b'), _) => match self.jump_history.pop() {
+                Some(index) => self.select(index),
+                None => self.message = Some("No jump to return from".to_string()),
+            },
+            (KeyCode::Esc, _) | (KeyCode::Backspace, _) => return Some(AppEvent::Back),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let footer = if self.input_mode {
+            Line::from(vec![
+                Span::styled(
+                    " Find (OPCODE, family like LOG, or 0x push data): ",
+                    THEME.muted_style(),
+                ),
+                Span::raw(format!("{}_ ", self.search_input)),
+            ])
+        } else if let Some(ref msg) = self.message {
+            Line::from(Span::styled(format!(" {msg} "), THEME.muted_style()))
+        } else {
+            Line::from(Span::styled(
+                " j/k: move | f: find | n/N: next/prev match | Enter: follow jump | b: jump back | Esc: back ",
+                THEME.muted_style(),
+            ))
+        };
+
+        let outer_block = Block::default()
+            .title(" Bytecode ")
+            .title_bottom(footer)
+            .borders(Borders::ALL)
+            .border_style(THEME.border_focused_style());
+        let inner = outer_block.inner(area);
+        frame.render_widget(outer_block, area);
+
+        let disassembly = match self.disassembly {
+            Some(ref d) => d,
+            None => {
+                let text = if self.loading {
+                    "Loading bytecode..."
+                } else {
+                    "No bytecode loaded"
+                };
+                let paragraph = Paragraph::new(text)
+                    .style(THEME.muted_style())
+                    .alignment(Alignment::Center);
+                frame.render_widget(paragraph, inner);
+                return;
+            }
+        };
+
+        if disassembly.code_size == 0 {
+            let paragraph = Paragraph::new("No code at this address")
+                .style(THEME.muted_style())
+                .alignment(Alignment::Center);
+            frame.render_widget(paragraph, inner);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(5)])
+            .split(inner);
+
+        frame.render_widget(
+            Paragraph::new(summary_lines(self.address, disassembly)),
+            chunks[0],
+        );
+
+        let header = Row::new(vec![
+            Cell::from(" PC"),
+            Cell::from("Opcode"),
+            Cell::from("Push Data"),
+            Cell::from("Note"),
+        ])
+        .style(THEME.table_header_style());
+
+        let rows: Vec<Row> = disassembly
+            .instructions
+            .iter()
+            .map(|instruction| instruction_row(disassembly, instruction))
+            .collect();
+
+        let widths = [
+            Constraint::Length(8),
+            Constraint::Length(14),
+            Constraint::Length(68),
+            Constraint::Min(20),
+        ];
+
+        let count = disassembly.instructions.len();
+        let table_block = Block::default()
+            .title(format!(" Instructions ({}/{count}) ", self.selected + 1))
+            .borders(Borders::ALL)
+            .border_style(THEME.border_style());
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(table_block)
+            .row_highlight_style(THEME.selected_style())
+            .highlight_symbol(" > ");
+        frame.render_stateful_widget(table, chunks[1], &mut self.table_state);
+
+        self.scroll_state = self.scroll_state.content_length(count);
+        if count > chunks[1].height as usize {
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(Some("^"))
+                .end_symbol(Some("v"));
+            let scrollbar_area = Rect {
+                x: chunks[1].x + chunks[1].width.saturating_sub(1),
+                y: chunks[1].y + 1,
+                width: 1,
+                height: chunks[1].height.saturating_sub(2),
+            };
+            frame.render_stateful_widget(scrollbar, scrollbar_area, &mut self.scroll_state);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use alloy::primitives::B256;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use crate::components::Component;
+use crate::data::types::{GasEntry, GasProfile};
+use crate::events::{AppEvent, View};
+use crate::theme::THEME;
+use crate::utils;
+
+const TABS: [&str; 3] = ["By Contract", "By Function", "By Opcode"];
+
+/// Gas breakdown of a transaction by contract, function and opcode.
+pub struct GasProfileView {
+    pub tx_hash: Option<B256>,
+    pub profile: Option<GasProfile>,
+    pub loading: bool,
+    tab: usize,
+    selected: usize,
+    list_state: ListState,
+}
+
+impl GasProfileView {
+    pub fn new() -> Self {
+        Self {
+            tx_hash: None,
+            profile: None,
+            loading: false,
+            tab: 0,
+            selected: 0,
+            list_state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn set_profile(&mut self, profile: Option<GasProfile>) {
+        self.profile = profile;
+        self.loading = false;
+        self.select(0);
+    }
+
+    fn entries(&self) -> &[GasEntry] {
+        let Some(ref profile) = self.profile else {
+            return &[];
+        };
+        match self.tab {
+            0 => &profile.by_contract,
+            1 => &profile.by_function,
+            _ => &profile.by_opcode,
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        let len = self.entries().len();
+        self.selected = index.min(len.saturating_sub(1));
+        self.list_state.select(Some(self.selected));
+    }
+
+    fn switch_tab(&mut self, tab: usize) {
+        self.tab = tab % TABS.len();
+        self.select(0);
+    }
+
+    fn entry_label(entry: &GasEntry) -> String {
+        match entry.address {
+            Some(address) if entry.label.is_empty() => utils::truncate_address(&address),
+            Some(address) => format!("{}.{}", utils::truncate_address(&address), entry.label),
+            None => entry.label.clone(),
+        }
+    }
+}
+
+impl Component for GasProfileView {
+    fn handle_key(&mut self, key: KeyEvent) -> Option<AppEvent> {
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('j'), _) | (KeyCode::Down, _) => {
+                self.select(self.selected + 1);
+                None
+            }
+            (KeyCode::Char('k'), _) | (KeyCode::Up, _) => {
+                self.select(self.selected.saturating_sub(1));
+                None
+            }
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
+                self.select(self.selected + 20);
+                None
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                self.select(self.selected.saturating_sub(20));
+                None
+            }
+            (KeyCode::Char('g'), _) => {
+                self.select(0);
+                None
+            }
+            (KeyCode::Char('G'), _) => {
+                self.select(usize::MAX);
+                None
+            }
+            (KeyCode::Tab, _) | (KeyCode::Char('l'), _) | (KeyCode::Right, _) => {
+                self.switch_tab(self.tab + 1);
+                None
+            }
+            (KeyCode::BackTab, _) | (KeyCode::Char('h'), _) | (KeyCode::Left, _) => {
+                self.switch_tab(self.tab + TABS.len() - 1);
+                None
+            }
+            (KeyCode::Enter, _) => self
+                .entries()
+                .get(self.selected)
+                .and_then(|e| e.address)
+                .map(|address| AppEvent::Navigate(View::AddressView(address))),
+            _ => None,
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let title = match self.tx_hash {
+            Some(hash) => format!(" Gas Profile: {} ", utils::truncate_hash(&hash)),
+            None => " Gas Profile ".to_string(),
+        };
+        let outer_block = Block::default()
+            .title(title)
+            .title_bottom(Line::from(Span::styled(
+                " Tab/h/l: group | Enter: open contract | e: export folded stacks ",
+                THEME.muted_style(),
+            )))
+            .borders(Borders::ALL)
+            .border_style(THEME.border_focused_style());
+        let inner = outer_block.inner(area);
+        frame.render_widget(outer_block, area);
+
+        let Some(ref profile) = self.profile else {
+            let msg = if self.loading {
+                "Profiling transaction gas..."
+            } else {
+                "No gas profile (requires debug_traceTransaction)"
+            };
+            let text = Paragraph::new(msg)
+                .style(THEME.muted_style())
+                .alignment(Alignment::Center);
+            frame.render_widget(text, inner);
+            return;
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Length(1), Constraint::Min(3)])
+            .split(inner);
+
+        let execution = profile.gas_used.saturating_sub(profile.intrinsic);
+        let summary = Paragraph::new(vec![Line::from(vec![
+            Span::styled("  Gas Used: ", THEME.muted_style()),
+            Span::raw(utils::format_number(profile.gas_used)),
+            Span::styled("   Execution: ", THEME.muted_style()),
+            Span::raw(utils::format_number(execution)),
+            Span::styled("   Intrinsic/Refund: ", THEME.muted_style()),
+            Span::raw(utils::format_number(profile.intrinsic)),
+        ])]);
+        frame.render_widget(summary, chunks[0]);
+
+        let tabs = Tabs::new(TABS.to_vec())
+            .select(self.tab)
+            .style(THEME.muted_style())
+            .highlight_style(THEME.accent_style().add_modifier(Modifier::BOLD))
+            .divider(" | ");
+        frame.render_widget(tabs, chunks[1]);
+
+        // Bars are scaled to the largest entry; percentages are of execution gas
+        let entries = self.entries();
+        let max_gas = entries.iter().map(|e| e.gas).max().unwrap_or(0).max(1);
+        let label_width = entries
+            .iter()
+            .map(|e| Self::entry_label(e).len())
+            .max()
+            .unwrap_or(0)
+            .min(40);
+        let bar_width = (chunks[2].width as usize).saturating_sub(label_width + 36).max(10);
+
+        let items: Vec<ListItem> = entries
+            .iter()
+            .map(|entry| {
+                let filled = (entry.gas as u128 * bar_width as u128 / max_gas as u128) as usize;
+                let pct = if execution > 0 {
+                    entry.gas as f64 * 100.0 / execution as f64
+                } else {
+                    0.0
+                };
+                let bar_style = if pct >= 25.0 {
+                    Style::default().fg(THEME.gas_high)
+                } else if pct >= 5.0 {
+                    Style::default().fg(THEME.gas_med)
+                } else {
+                    Style::default().fg(THEME.gas_low)
+                };
+                let mut label = Self::entry_label(entry);
+                label.truncate(label_width);
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!(" {label:<label_width$} "), THEME.address_style()),
+                    Span::styled("\u{2588}".repeat(filled), bar_style),
+                    Span::raw(" ".repeat(bar_width - filled)),
+                    Span::raw(format!(" {:>11}", utils::format_number(entry.gas))),
+                    Span::styled(format!(" {pct:>5.1}%"), THEME.muted_style()),
+                    Span::styled(format!(" x{}", entry.count), THEME.muted_style()),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items).highlight_style(THEME.selected_style());
+        frame.render_stateful_widget(list, chunks[2], &mut self.list_state);
+    }
+}
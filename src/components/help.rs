@@ -153,6 +153,10 @@ impl HelpOverlay {
                 Span::styled("  S        ", Style::default().fg(THEME.text_accent)),
                 Span::styled("Storage Inspector (address view)", Style::default().fg(THEME.text)),
             ]),
+            Line::from(vec![
+                Span::styled("  c        ", Style::default().fg(THEME.text_accent)),
+                Span::styled("Bytecode Disassembly (address view)", Style::default().fg(THEME.text)),
+            ]),
             Line::from(""),
             Line::from(Span::styled(
                 "Other",
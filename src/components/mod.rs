@@ -1,17 +1,23 @@
 pub mod address_view;
 pub mod block_detail;
 pub mod block_list;
+pub mod call_tree;
 pub mod contract_read;
 pub mod dashboard;
+pub mod disassembly;
+pub mod gas_profile;
 pub mod gas_tracker;
 pub mod header;
 pub mod help;
 pub mod mempool;
 pub mod search;
+pub mod simulation;
+pub mod state_diff;
 pub mod status_bar;
 pub mod storage_inspector;
 pub mod tx_debugger;
 pub mod tx_detail;
+pub mod value_tree;
 pub mod watch_list;
 
 use crossterm::event::KeyEvent;
@@ -27,4 +33,9 @@ pub trait Component {
 
     /// Render the component into the given area
     fn render(&mut self, frame: &mut Frame, area: Rect);
+
+    /// Whether the component is capturing text input and global keys should be skipped
+    fn captures_input(&self) -> bool {
+        false
+    }
 }
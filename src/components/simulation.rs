@@ -0,0 +1,423 @@
+use alloy::primitives::{Address, Bytes, U256};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use crate::components::Component;
+use crate::components::tx_detail::{
+    event_lines, internal_call_lines, method_lines, section_title, transfer_sections,
+};
+use crate::data::simulate;
+use crate::data::types::{SimulationRequest, SimulationResult};
+use crate::events::AppEvent;
+use crate::theme::THEME;
+use crate::utils;
+
+const FROM: usize = 0;
+const TO: usize = 1;
+const VALUE: usize = 2;
+const BLOCK: usize = 3;
+const GAS: usize = 4;
+const CALLDATA: usize = 5;
+const STATE_OVERRIDES: usize = 6;
+const BLOCK_OVERRIDES: usize = 7;
+
+/// Form labels and the hint shown while a field is empty.
+const FIELDS: [(&str, &str); 8] = [
+    ("From", "zero address"),
+    ("To", "empty deploys the calldata as init code"),
+    ("Value", "wei, or with a unit: 1.5ether, 30gwei"),
+    ("Block", "latest"),
+    ("Gas limit", "node default"),
+    (
+        "Calldata",
+        "0x..., name(args) with the target's ABI, or name(types)(args)",
+    ),
+    (
+        "State overrides",
+        "0xaddr balance=1ether nonce=1 code=0x.. 0xslot=0xvalue; ...",
+    ),
+    (
+        "Block overrides",
+        "number=N time=T basefee=1gwei gaslimit=N coinbase=0x..",
+    ),
+];
+
+/// Compose a call and run it against a chosen block, with optional state and
+/// block overrides, without sending a transaction.
+pub struct SimulationView {
+    fields: [String; 8],
+    field: usize,
+    editing: bool,
+    pub loading: bool,
+    pub result: Option<SimulationResult>,
+    pub error: Option<String>,
+    scroll: u16,
+    max_scroll: u16,
+}
+
+impl SimulationView {
+    pub fn new() -> Self {
+        Self {
+            fields: Default::default(),
+            field: 0,
+            editing: true,
+            loading: false,
+            result: None,
+            error: None,
+            scroll: 0,
+            max_scroll: 0,
+        }
+    }
+
+    /// Start a new simulation from an existing call, e.g. to replay a transaction.
+    pub fn prefill(
+        &mut self,
+        from: Option<Address>,
+        to: Option<Address>,
+        value: U256,
+        input: Bytes,
+        block: Option<u64>,
+    ) {
+        self.fields = Default::default();
+        self.fields[FROM] = from.map(|a| format!("{a}")).unwrap_or_default();
+        self.fields[TO] = to.map(|a| format!("{a}")).unwrap_or_default();
+        if !value.is_zero() {
+            self.fields[VALUE] = value.to_string();
+        }
+        self.fields[BLOCK] = block.map(|n| n.to_string()).unwrap_or_default();
+        if !input.is_empty() {
+            self.fields[CALLDATA] = format!("{input}");
+        }
+        self.field = if to.is_some() && input.is_empty() {
+            CALLDATA
+        } else {
+            FROM
+        };
+        self.editing = true;
+        self.loading = false;
+        self.result = None;
+        self.error = None;
+        self.scroll = 0;
+    }
+
+    pub fn set_result(&mut self, result: SimulationResult) {
+        self.result = Some(result);
+        self.error = None;
+        self.loading = false;
+        self.scroll = 0;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+        self.loading = false;
+    }
+
+    fn build_request(&self) -> Result<SimulationRequest, String> {
+        let address = |index: usize| -> Result<Option<Address>, String> {
+            let text = self.fields[index].trim();
+            if text.is_empty() {
+                return Ok(None);
+            }
+            text.parse()
+                .map(Some)
+                .map_err(|_| format!("Invalid {} address: {text}", FIELDS[index].0))
+        };
+        let gas = self.fields[GAS].trim();
+
+        Ok(SimulationRequest {
+            from: address(FROM)?,
+            to: address(TO)?,
+            value: simulate::parse_value(&self.fields[VALUE])?,
+            calldata: self.fields[CALLDATA].trim().to_string(),
+            gas: if gas.is_empty() {
+                None
+            } else {
+                Some(
+                    gas.parse()
+                        .map_err(|_| format!("Invalid gas limit: {gas}"))?,
+                )
+            },
+            block: simulate::parse_block(&self.fields[BLOCK])?,
+            state_overrides: simulate::parse_state_overrides(&self.fields[STATE_OVERRIDES])?,
+            block_overrides: simulate::parse_block_overrides(&self.fields[BLOCK_OVERRIDES])?,
+        })
+    }
+
+    fn submit(&mut self) -> Option<AppEvent> {
+        match self.build_request() {
+            Ok(request) => {
+                self.editing = false;
+                self.loading = true;
+                self.error = None;
+                self.result = None;
+                Some(AppEvent::RequestSimulation(Box::new(request)))
+            }
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+
+    fn form_lines(&self, width: usize) -> Vec<Line<'static>> {
+        let label_width = 17;
+        let value_width = width.saturating_sub(label_width + 3).max(10);
+        FIELDS
+            .iter()
+            .enumerate()
+            .map(|(i, (label, hint))| {
+                let selected = i == self.field && self.editing;
+                let label_style = if selected {
+                    THEME.accent_style().add_modifier(Modifier::BOLD)
+                } else {
+                    THEME.muted_style()
+                };
+                let text = &self.fields[i];
+                let value = if text.is_empty() && !selected {
+                    Span::styled(
+                        (*hint).to_string(),
+                        THEME.muted_style().add_modifier(Modifier::ITALIC),
+                    )
+                } else if selected {
+                    // Keep the cursor visible by showing the end of long input
+                    let skip = text.chars().count().saturating_sub(value_width - 1);
+                    let visible: String = text.chars().skip(skip).collect();
+                    Span::styled(
+                        format!("{visible}_"),
+                        Style::default().fg(THEME.text).add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    let visible = if text.chars().count() > value_width {
+                        let cut: String = text.chars().take(value_width - 3).collect();
+                        format!("{cut}...")
+                    } else {
+                        text.clone()
+                    };
+                    Span::styled(visible, Style::default().fg(THEME.text))
+                };
+                Line::from(vec![
+                    Span::styled(format!("  {label:<label_width$}"), label_style),
+                    value,
+                ])
+            })
+            .collect()
+    }
+
+    fn result_lines(result: &SimulationResult) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+
+        let status = if result.success {
+            Span::styled(
+                "\u{2713} Success",
+                Style::default()
+                    .fg(THEME.success)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::styled(
+                "\u{2717} Reverted",
+                Style::default()
+                    .fg(THEME.error)
+                    .add_modifier(Modifier::BOLD),
+            )
+        };
+        lines.push(Line::from(vec![
+            Span::styled("  Status:  ", THEME.muted_style()),
+            status,
+        ]));
+        if let Some(ref reason) = result.revert_reason {
+            lines.push(Line::from(vec![
+                Span::styled("  Reason:  ", THEME.muted_style()),
+                Span::styled(reason.clone(), THEME.error_style()),
+            ]));
+        }
+        if let Some(gas_used) = result.gas_used {
+            lines.push(Line::from(vec![
+                Span::styled("  Gas Used:  ", THEME.muted_style()),
+                Span::raw(utils::format_number(gas_used)),
+            ]));
+        }
+
+        if !result.decoded_output.is_empty() {
+            lines.push(Line::from(Span::styled("  Returns:", THEME.muted_style())));
+            for param in &result.decoded_output {
+                lines.push(Line::from(vec![
+                    Span::raw("      "),
+                    Span::styled(format!("{}: ", param.name()), THEME.muted_style()),
+                    Span::raw(param.formatted()),
+                ]));
+            }
+        } else if !result.output.is_empty() {
+            lines.push(Line::from(vec![
+                Span::styled("  Output:  ", THEME.muted_style()),
+                Span::styled(format!("{}", result.output), THEME.hash_style()),
+            ]));
+        }
+
+        if !result.traced {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "  Ran with eth_call: calls, events and gas need debug_traceCall",
+                THEME.muted_style(),
+            )));
+        }
+
+        if let Some(ref decoded) = result.decoded_input {
+            lines.extend(section_title("Method"));
+            lines.extend(method_lines(decoded));
+        }
+        lines.extend(transfer_sections(&result.token_transfers));
+        if !result.calls.is_empty() {
+            lines.extend(section_title("Internal Transactions"));
+            lines.extend(internal_call_lines(&result.calls));
+        }
+        if !result.logs.is_empty() {
+            lines.extend(section_title("Events"));
+            lines.extend(event_lines(&result.logs));
+        }
+
+        lines.extend(section_title("Calldata"));
+        let input_hex = format!("{}", result.input);
+        let truncated = if input_hex.len() > 200 {
+            format!("  {}...", &input_hex[..200])
+        } else {
+            format!("  {input_hex}")
+        };
+        lines.push(Line::from(Span::styled(truncated, THEME.muted_style())));
+
+        lines
+    }
+}
+
+impl Component for SimulationView {
+    fn handle_key(&mut self, key: KeyEvent) -> Option<AppEvent> {
+        if self.editing {
+            match (key.code, key.modifiers) {
+                (KeyCode::Esc, _) => self.editing = false,
+                (KeyCode::Enter, _) => return self.submit(),
+                (KeyCode::Tab, _) | (KeyCode::Down, _) => {
+                    self.field = (self.field + 1) % FIELDS.len();
+                }
+                (KeyCode::BackTab, _) | (KeyCode::Up, _) => {
+                    self.field = (self.field + FIELDS.len() - 1) % FIELDS.len();
+                }
+                (KeyCode::Char('u'), KeyModifiers::CONTROL) => self.fields[self.field].clear(),
+                (KeyCode::Backspace, _) => {
+                    self.fields[self.field].pop();
+                }
+                (KeyCode::Char(c), _) => self.fields[self.field].push(c),
+                _ => {}
+            }
+            return None;
+        }
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('i'), _) | (KeyCode::Enter, _) => {
+                self.editing = true;
+                None
+            }
+            (KeyCode::Char('r'), _) => self.submit(),
+            (KeyCode::Char('j'), _) | (KeyCode::Down, _) => {
+                self.scroll = (self.scroll + 1).min(self.max_scroll);
+                None
+            }
+            (KeyCode::Char('k'), _) | (KeyCode::Up, _) => {
+                self.scroll = self.scroll.saturating_sub(1);
+                None
+            }
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
+                self.scroll = self.scroll.saturating_add(20).min(self.max_scroll);
+                None
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                self.scroll = self.scroll.saturating_sub(20);
+                None
+            }
+            (KeyCode::Char('g'), _) => {
+                self.scroll = 0;
+                None
+            }
+            (KeyCode::Char('G'), _) => {
+                self.scroll = self.max_scroll;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let hints = if self.editing {
+            " Tab/\u{2191}\u{2193}: field | Enter: simulate | Ctrl+U: clear field | Esc: stop editing "
+        } else {
+            " i/Enter: edit | r: re-run | j/k: scroll | Esc: back "
+        };
+        let outer_block = Block::default()
+            .title(" Simulation ")
+            .title_bottom(Line::from(Span::styled(hints, THEME.muted_style())))
+            .borders(Borders::ALL)
+            .border_style(THEME.border_focused_style());
+        let inner = outer_block.inner(area);
+        frame.render_widget(outer_block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(FIELDS.len() as u16 + 2),
+                Constraint::Min(3),
+            ])
+            .split(inner);
+
+        let form_block = Block::default()
+            .title(" Call ")
+            .borders(Borders::ALL)
+            .border_style(if self.editing {
+                THEME.border_focused_style()
+            } else {
+                THEME.border_style()
+            });
+        let form = Paragraph::new(self.form_lines(chunks[0].width as usize)).block(form_block);
+        frame.render_widget(form, chunks[0]);
+
+        let result_block = Block::default()
+            .title(" Result ")
+            .borders(Borders::ALL)
+            .border_style(THEME.border_style());
+        let result_inner = result_block.inner(chunks[1]);
+        frame.render_widget(result_block, chunks[1]);
+
+        let mut lines = Vec::new();
+        if let Some(ref err) = self.error {
+            lines.push(Line::from(vec![
+                Span::styled("  Error:  ", THEME.muted_style()),
+                Span::styled(err.clone(), THEME.error_style()),
+            ]));
+        }
+        if self.loading {
+            lines.push(Line::from(Span::styled(
+                "  Simulating...",
+                THEME.muted_style(),
+            )));
+        } else if let Some(ref result) = self.result {
+            lines.extend(Self::result_lines(result));
+        } else if self.error.is_none() {
+            lines.push(Line::from(Span::styled(
+                "  Fill in the call and press Enter to simulate it",
+                THEME.muted_style(),
+            )));
+        }
+
+        let total_lines = lines.len() as u16;
+        self.max_scroll = total_lines.saturating_sub(result_inner.height);
+        self.scroll = self.scroll.min(self.max_scroll);
+
+        let paragraph = Paragraph::new(lines)
+            .style(Style::default().fg(THEME.text))
+            .scroll((self.scroll, 0));
+        frame.render_widget(paragraph, result_inner);
+    }
+
+    fn captures_input(&self) -> bool {
+        self.editing
+    }
+}
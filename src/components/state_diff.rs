@@ -0,0 +1,222 @@
+use alloy::primitives::{Address, B256, U256};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use crate::components::Component;
+use crate::data::state_diff::describe_code;
+use crate::data::types::AccountDiff;
+use crate::events::{AppEvent, View};
+use crate::theme::THEME;
+use crate::utils;
+
+/// Per-account balance, nonce, code and storage changes of a transaction.
+pub struct StateDiffView {
+    pub tx_hash: Option<B256>,
+    pub diffs: Vec<AccountDiff>,
+    pub loading: bool,
+    selected: usize,
+    list_state: ListState,
+}
+
+impl StateDiffView {
+    pub fn new() -> Self {
+        Self {
+            tx_hash: None,
+            diffs: Vec::new(),
+            loading: false,
+            selected: 0,
+            list_state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn set_diffs(&mut self, diffs: Vec<AccountDiff>) {
+        self.diffs = diffs;
+        self.loading = false;
+        self.selected = 0;
+        self.list_state.select(Some(0));
+    }
+
+    /// The account each rendered row belongs to, in display order.
+    fn row_accounts(&self) -> Vec<Address> {
+        let mut rows = Vec::new();
+        for diff in &self.diffs {
+            let fields = diff.balance.is_some() as usize
+                + diff.nonce.is_some() as usize
+                + diff.code.is_some() as usize
+                + diff.storage.len();
+            rows.extend(std::iter::repeat_n(diff.address, fields + 1));
+        }
+        rows
+    }
+
+    fn select(&mut self, row: usize) {
+        let len = self.row_accounts().len();
+        if len == 0 {
+            return;
+        }
+        self.selected = row.min(len - 1);
+        self.list_state.select(Some(self.selected));
+    }
+
+    fn change_line(
+        label: &str,
+        before: String,
+        after: String,
+        note: Option<Span<'static>>,
+    ) -> ListItem<'static> {
+        let mut spans = vec![
+            Span::styled(format!("    {label:<10}"), THEME.muted_style()),
+            Span::raw(before),
+            Span::styled(" \u{2192} ", THEME.muted_style()),
+            Span::styled(
+                after,
+                Style::default().fg(THEME.text).add_modifier(Modifier::BOLD),
+            ),
+        ];
+        if let Some(note) = note {
+            spans.push(Span::raw("  "));
+            spans.push(note);
+        }
+        ListItem::new(Line::from(spans))
+    }
+}
+
+/// Storage words are shown without leading zeros.
+fn compact_word(word: &B256) -> String {
+    format!("{:#x}", U256::from_be_bytes(word.0))
+}
+
+impl Component for StateDiffView {
+    fn handle_key(&mut self, key: KeyEvent) -> Option<AppEvent> {
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('j'), _) | (KeyCode::Down, _) => {
+                self.select(self.selected + 1);
+                None
+            }
+            (KeyCode::Char('k'), _) | (KeyCode::Up, _) => {
+                self.select(self.selected.saturating_sub(1));
+                None
+            }
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
+                self.select(self.selected + 20);
+                None
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                self.select(self.selected.saturating_sub(20));
+                None
+            }
+            (KeyCode::Char('g'), _) => {
+                self.select(0);
+                None
+            }
+            (KeyCode::Char('G'), _) => {
+                self.select(usize::MAX);
+                None
+            }
+            (KeyCode::Enter, _) => self
+                .row_accounts()
+                .get(self.selected)
+                .map(|address| AppEvent::Navigate(View::AddressView(*address))),
+            _ => None,
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let title = match self.tx_hash {
+            Some(hash) => format!(" State Diff: {} ", utils::truncate_hash(&hash)),
+            None => " State Diff ".to_string(),
+        };
+        let block = Block::default()
+            .title(title)
+            .title_bottom(Line::from(Span::styled(
+                " j/k: move | Enter: open account ",
+                THEME.muted_style(),
+            )))
+            .borders(Borders::ALL)
+            .border_style(THEME.border_focused_style());
+
+        if self.diffs.is_empty() {
+            let msg = if self.loading {
+                "Loading state diff..."
+            } else {
+                "No state changes (requires debug_traceTransaction or trace_replayTransaction)"
+            };
+            let text = Paragraph::new(msg)
+                .block(block)
+                .style(THEME.muted_style())
+                .alignment(Alignment::Center);
+            frame.render_widget(text, area);
+            return;
+        }
+
+        let mut items: Vec<ListItem> = Vec::new();
+        for diff in &self.diffs {
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled(format!("{}", diff.address), THEME.address_style()),
+                Span::styled(
+                    format!(
+                        "  ({} slot{} changed)",
+                        diff.storage.len(),
+                        if diff.storage.len() == 1 { "" } else { "s" }
+                    ),
+                    THEME.muted_style(),
+                ),
+            ])));
+
+            if let Some((before, after)) = diff.balance {
+                let (sign, delta, style) = if after >= before {
+                    ("+", after - before, THEME.success_style())
+                } else {
+                    ("-", before - after, THEME.error_style())
+                };
+                items.push(Self::change_line(
+                    "balance",
+                    utils::format_eth(before),
+                    utils::format_eth(after),
+                    Some(Span::styled(
+                        format!("({sign}{})", utils::format_eth(delta)),
+                        style,
+                    )),
+                ));
+            }
+            if let Some((before, after)) = diff.nonce {
+                items.push(Self::change_line(
+                    "nonce",
+                    before.to_string(),
+                    after.to_string(),
+                    None,
+                ));
+            }
+            if let Some((ref before, ref after)) = diff.code {
+                items.push(Self::change_line(
+                    "code",
+                    describe_code(before),
+                    describe_code(after),
+                    None,
+                ));
+            }
+            for change in &diff.storage {
+                items.push(ListItem::new(Line::from(vec![
+                    Span::styled("    slot ", THEME.muted_style()),
+                    Span::styled(compact_word(&change.slot), THEME.hash_style()),
+                    Span::styled(": ", THEME.muted_style()),
+                    Span::raw(compact_word(&change.before)),
+                    Span::styled(" \u{2192} ", THEME.muted_style()),
+                    Span::styled(
+                        compact_word(&change.after),
+                        Style::default().fg(THEME.text).add_modifier(Modifier::BOLD),
+                    ),
+                ])));
+            }
+        }
+
+        let list =
+            List::new(items)
+                .block(block.title_top(
+                    Line::from(format!(" {} accounts ", self.diffs.len())).right_aligned(),
+                ))
+                .highlight_style(THEME.selected_style());
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+}
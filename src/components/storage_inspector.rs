@@ -4,15 +4,40 @@ use ratatui::prelude::*;
 use ratatui::widgets::*;
 
 use crate::components::Component;
+use crate::data::storage_layout::{self, Encoding, StorageItem, StorageLayout, StorageValue};
 use crate::events::AppEvent;
 use crate::theme::THEME;
 
+/// A row of the storage tree: a state variable, or a member, mapping entry
+/// or array element under one, or a raw slot read by number.
+struct Entry {
+    item: StorageItem,
+    depth: usize,
+    value: Option<StorageValue>,
+    /// Whether the rows below with greater depth are its children
+    expanded: bool,
+}
+
+/// What the input line is asking for.
+#[derive(Clone, Copy)]
+enum Prompt {
+    Slot,
+    /// A key into the mapping at this row
+    Key(usize),
+    /// An index into the array at this row
+    Index(usize),
+}
+
 pub struct StorageInspector {
     pub address: Option<Address>,
-    pub slot_input: String,
-    pub results: Vec<(U256, B256)>,
-    pub input_mode: bool,
+    pub layout: Option<StorageLayout>,
+    /// Whether the storage layout is still being fetched
     pub loading: bool,
+    entries: Vec<Entry>,
+    prompt: Option<Prompt>,
+    input: String,
+    /// Feedback from the last key, index or slot entered.
+    message: Option<String>,
     selected: usize,
     table_state: TableState,
     scroll_state: ScrollbarState,
@@ -22,139 +47,433 @@ impl StorageInspector {
     pub fn new() -> Self {
         Self {
             address: None,
-            slot_input: String::new(),
-            results: Vec::new(),
-            input_mode: false,
+            layout: None,
             loading: false,
+            entries: Vec::new(),
+            prompt: None,
+            input: String::new(),
+            message: None,
             selected: 0,
             table_state: TableState::default().with_selected(0),
             scroll_state: ScrollbarState::default(),
         }
     }
 
-    /// Add a storage result to the table.
-    pub fn add_result(&mut self, slot: U256, value: B256) {
-        // Replace if same slot already queried
-        if let Some(existing) = self.results.iter_mut().find(|(s, _)| *s == slot) {
-            existing.1 = value;
-        } else {
-            self.results.push((slot, value));
-        }
+    /// Start over for another contract.
+    pub fn reset(&mut self, address: Address) {
+        self.address = Some(address);
+        self.layout = None;
+        self.loading = true;
+        self.entries.clear();
+        self.prompt = None;
+        self.message = None;
+        self.select(0);
+    }
+
+    /// Show the contract's state variables, returning those to read. A
+    /// second layout, from reopening the view before the first arrived, is
+    /// dropped rather than listing every variable twice.
+    pub fn set_layout(&mut self, layout: Option<StorageLayout>) -> Vec<StorageItem> {
         self.loading = false;
+        if self.layout.is_some() {
+            return Vec::new();
+        }
+        self.layout = layout;
+        let Some(ref layout) = self.layout else {
+            return Vec::new();
+        };
+        let roots: Vec<Entry> = layout
+            .roots()
+            .into_iter()
+            .map(|item| Entry {
+                item,
+                depth: 0,
+                value: None,
+                expanded: false,
+            })
+            .collect();
+        // Raw slots read before the layout arrived stay at the end
+        self.entries.splice(0..0, roots);
+        self.readable(0..self.entries.len())
     }
 
-    fn select_next(&mut self) {
-        if self.results.is_empty() {
-            return;
+    /// Fill in values read from storage.
+    pub fn set_values(&mut self, values: Vec<(StorageItem, StorageValue)>) {
+        for (item, value) in values {
+            for entry in self.entries.iter_mut().filter(|e| e.item == item) {
+                entry.value = Some(if entry.item.type_id.is_empty() {
+                    raw_value(value.raw)
+                } else {
+                    value.clone()
+                });
+            }
         }
-        let next = if self.selected + 1 >= self.results.len() {
-            self.selected
-        } else {
-            self.selected + 1
+    }
+
+    /// Add a row for a storage slot read by number, returning its request.
+    fn add_raw_slot(&mut self, slot: U256) -> Option<AppEvent> {
+        let item = StorageItem {
+            path: format!("slot {slot:#x}"),
+            slot,
+            offset: 0,
+            type_id: String::new(),
+            label: "bytes32".to_string(),
+            encoding: Encoding::Inplace,
+            size: 32,
+        };
+        // Reuse the row if the same slot was already queried
+        let index = match self.entries.iter().position(|e| e.item == item) {
+            Some(index) => index,
+            None => {
+                self.entries.push(Entry {
+                    item: item.clone(),
+                    depth: 0,
+                    value: None,
+                    expanded: false,
+                });
+                self.entries.len() - 1
+            }
         };
-        self.selected = next;
-        self.table_state.select(Some(next));
-        self.scroll_state = self.scroll_state.position(next);
+        self.select(index);
+        self.request(vec![item])
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.entries.len().saturating_sub(1));
+        self.table_state.select(Some(self.selected));
+        self.scroll_state = self.scroll_state.position(self.selected);
+    }
+
+    /// Items among `range` that hold a value of their own; mappings,
+    /// structs and static arrays are read through their children.
+    fn readable(&self, range: std::ops::Range<usize>) -> Vec<StorageItem> {
+        self.entries[range]
+            .iter()
+            .filter(|e| !self.is_container(&e.item))
+            .map(|e| e.item.clone())
+            .collect()
+    }
+
+    fn is_container(&self, item: &StorageItem) -> bool {
+        let Some(ty) = self.layout.as_ref().and_then(|l| l.type_of(item)) else {
+            return false;
+        };
+        item.encoding == Encoding::Mapping
+            || (item.encoding == Encoding::Inplace && (ty.base.is_some() || !ty.members.is_empty()))
+    }
+
+    /// Index just past the rows below `index` with greater depth.
+    fn subtree_end(&self, index: usize) -> usize {
+        let depth = self.entries[index].depth;
+        self.entries[index + 1..]
+            .iter()
+            .position(|e| e.depth <= depth)
+            .map_or(self.entries.len(), |p| index + 1 + p)
     }
 
-    fn select_prev(&mut self) {
-        if self.results.is_empty() {
+    fn request(&self, items: Vec<StorageItem>) -> Option<AppEvent> {
+        if items.is_empty() {
+            return None;
+        }
+        Some(AppEvent::RequestStorageItems {
+            address: self.address?,
+            items,
+        })
+    }
+
+    /// Expand a struct, or ask for the key or index to drill into.
+    fn open(&mut self) -> Option<AppEvent> {
+        let entry = self.entries.get(self.selected)?;
+        let layout = self.layout.as_ref()?;
+        let ty = layout.type_of(&entry.item)?;
+        match entry.item.encoding {
+            Encoding::Mapping => self.prompt = Some(Prompt::Key(self.selected)),
+            Encoding::DynamicArray => self.prompt = Some(Prompt::Index(self.selected)),
+            Encoding::Inplace if ty.base.is_some() => {
+                self.prompt = Some(Prompt::Index(self.selected))
+            }
+            Encoding::Inplace if !ty.members.is_empty() => {
+                if entry.expanded {
+                    self.collapse();
+                    return None;
+                }
+                let depth = entry.depth + 1;
+                let members: Vec<Entry> = layout
+                    .members(&entry.item)
+                    .into_iter()
+                    .map(|item| Entry {
+                        item,
+                        depth,
+                        value: None,
+                        expanded: false,
+                    })
+                    .collect();
+                let start = self.selected + 1;
+                let end = start + members.len();
+                self.entries[self.selected].expanded = true;
+                self.entries.splice(start..start, members);
+                return self.request(self.readable(start..end));
+            }
+            _ => {}
+        }
+        self.input.clear();
+        None
+    }
+
+    /// Hide the selected row's children, or go up to its parent.
+    fn collapse(&mut self) {
+        let Some(entry) = self.entries.get(self.selected) else {
             return;
+        };
+        if entry.expanded {
+            let end = self.subtree_end(self.selected);
+            self.entries.drain(self.selected + 1..end);
+            self.entries[self.selected].expanded = false;
+        } else if entry.depth > 0 {
+            let depth = entry.depth;
+            let parent = self.entries[..self.selected]
+                .iter()
+                .rposition(|e| e.depth < depth);
+            if let Some(parent) = parent {
+                self.select(parent);
+            }
+        }
+    }
+
+    /// Add the mapping entry or array element named by the input under the
+    /// row it was asked for, and read it.
+    fn drill(&mut self, prompt: Prompt) -> Option<AppEvent> {
+        let layout = self.layout.as_ref()?;
+        let (index, child) = match prompt {
+            Prompt::Key(index) => {
+                let child = layout.mapping_entry(&self.entries[index].item, &self.input);
+                (index, child)
+            }
+            Prompt::Index(index) => {
+                let child = match storage_layout::parse_uint(&self.input) {
+                    Some(i) => layout.array_element(&self.entries[index].item, i),
+                    None => Err(format!("Not an index: {}", self.input.trim())),
+                };
+                (index, child)
+            }
+            Prompt::Slot => return None,
+        };
+        let child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                self.message = Some(e);
+                return None;
+            }
+        };
+
+        let end = self.subtree_end(index);
+        let existing = self.entries[index + 1..end]
+            .iter()
+            .position(|e| e.item == child)
+            .map(|p| index + 1 + p);
+        let position = match existing {
+            Some(position) => position,
+            None => {
+                let depth = self.entries[index].depth + 1;
+                self.entries[index].expanded = true;
+                self.entries.insert(
+                    end,
+                    Entry {
+                        item: child,
+                        depth,
+                        value: None,
+                        expanded: false,
+                    },
+                );
+                end
+            }
+        };
+        self.message = None;
+        self.select(position);
+        self.request(self.readable(position..position + 1))
+    }
+
+    /// Parse the raw slot input and ask for it.
+    fn query_slot(&mut self) -> Option<AppEvent> {
+        match storage_layout::parse_uint(&self.input) {
+            Some(slot) => {
+                self.message = None;
+                self.add_raw_slot(slot)
+            }
+            None => {
+                self.message = Some(format!("Not a slot number: {}", self.input.trim()));
+                None
+            }
+        }
+    }
+
+    fn prompt_label(&self, prompt: Prompt) -> String {
+        let type_label = |index: usize| {
+            self.layout
+                .as_ref()
+                .zip(self.entries.get(index))
+                .and_then(|(layout, e)| layout.type_of(&e.item))
+                .and_then(|ty| ty.key.clone())
+                .and_then(|key| self.layout.as_ref()?.types.get(&key))
+                .map(|ty| ty.label.clone())
+                .unwrap_or_default()
+        };
+        match prompt {
+            Prompt::Slot => " Slot (decimal or 0x hex): ".to_string(),
+            Prompt::Key(index) => format!(
+                " Key ({}) into {}: ",
+                type_label(index),
+                self.entries[index].item.path
+            ),
+            Prompt::Index(index) => format!(" Index into {}: ", self.entries[index].item.path),
+        }
+    }
+
+    /// What a row holds, or how to get at its children.
+    fn value_cell(&self, entry: &Entry) -> Cell<'static> {
+        if let Some(ref value) = entry.value {
+            return Cell::from(value.decoded.clone()).style(value_style(&entry.item));
         }
-        let prev = self.selected.saturating_sub(1);
-        self.selected = prev;
-        self.table_state.select(Some(prev));
-        self.scroll_state = self.scroll_state.position(prev);
+        let layout = self.layout.as_ref();
+        let hint = match entry.item.encoding {
+            Encoding::Mapping => "Enter: look up a key".to_string(),
+            Encoding::Inplace if self.is_container(&entry.item) => {
+                match layout.and_then(|l| l.static_length(&entry.item)) {
+                    Some(length) => format!("length {length}, Enter: element"),
+                    None if entry.expanded => String::new(),
+                    None => {
+                        let members = layout.map_or(0, |l| l.members(&entry.item).len());
+                        format!("{members} members, Enter: expand")
+                    }
+                }
+            }
+            _ => "...".to_string(),
+        };
+        Cell::from(hint).style(THEME.muted_style())
+    }
+}
+
+/// A raw word in decimal when small, as the slot's type is unknown.
+fn raw_value(raw: B256) -> StorageValue {
+    let word = U256::from_be_bytes(raw.0);
+    let decoded = if word < U256::from(1u64 << 53) {
+        word.to_string()
+    } else {
+        raw.to_string()
+    };
+    StorageValue { raw, decoded }
+}
+
+fn value_style(item: &StorageItem) -> Style {
+    if item.label == "address" || item.label.starts_with("contract ") {
+        THEME.address_style()
+    } else if item.label.starts_with("bytes") || item.type_id.is_empty() {
+        THEME.hash_style()
+    } else {
+        Style::default().fg(THEME.text)
+    }
+}
+
+/// A slot shortened to fit its column, with the value's byte offset.
+fn slot_label(item: &StorageItem) -> String {
+    let slot_hex = format!("{:#x}", item.slot);
+    let slot = if slot_hex.len() > 20 {
+        format!("{}...{}", &slot_hex[..10], &slot_hex[slot_hex.len() - 6..])
+    } else {
+        slot_hex
+    };
+    if item.offset > 0 {
+        format!("{slot} +{}", item.offset)
+    } else {
+        slot
     }
 }
 
 impl Component for StorageInspector {
+    fn captures_input(&self) -> bool {
+        self.prompt.is_some()
+    }
+
     fn handle_key(&mut self, key: KeyEvent) -> Option<AppEvent> {
-        if self.input_mode {
+        if let Some(prompt) = self.prompt {
             match key.code {
                 KeyCode::Esc => {
-                    self.input_mode = false;
-                    None
+                    self.prompt = None;
+                    self.input.clear();
                 }
                 KeyCode::Enter => {
-                    // Parse the slot and trigger a query
-                    self.input_mode = false;
-                    self.loading = true;
-                    // The app layer reads slot_input to make the RPC call
-                    None
-                }
-                KeyCode::Char(c) => {
-                    // Allow hex digits and 'x' prefix
-                    if c.is_ascii_hexdigit() || c == 'x' || c == 'X' {
-                        self.slot_input.push(c);
-                    }
-                    None
+                    self.prompt = None;
+                    let event = if self.input.trim().is_empty() {
+                        None
+                    } else if let Prompt::Slot = prompt {
+                        self.query_slot()
+                    } else {
+                        self.drill(prompt)
+                    };
+                    self.input.clear();
+                    return event;
                 }
                 KeyCode::Backspace => {
-                    self.slot_input.pop();
-                    None
+                    self.input.pop();
                 }
-                _ => None,
+                KeyCode::Char(c) => self.input.push(c),
+                _ => {}
             }
-        } else {
-            match key.code {
-                KeyCode::Char('i') => {
-                    self.input_mode = true;
-                    self.slot_input.clear();
-                    None
-                }
-                KeyCode::Char('j') | KeyCode::Down => {
-                    self.select_next();
-                    None
-                }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    self.select_prev();
-                    None
-                }
-                KeyCode::Char('g') => {
-                    if !self.results.is_empty() {
-                        self.selected = 0;
-                        self.table_state.select(Some(0));
-                        self.scroll_state = self.scroll_state.position(0);
-                    }
-                    None
-                }
-                KeyCode::Char('G') => {
-                    if !self.results.is_empty() {
-                        let last = self.results.len() - 1;
-                        self.selected = last;
-                        self.table_state.select(Some(last));
-                        self.scroll_state = self.scroll_state.position(last);
-                    }
-                    None
-                }
-                KeyCode::Esc | KeyCode::Backspace => Some(AppEvent::Back),
-                _ => None,
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Char('i') => {
+                self.prompt = Some(Prompt::Slot);
+                self.input.clear();
             }
+            KeyCode::Char('j') | KeyCode::Down => self.select(self.selected + 1),
+            KeyCode::Char('k') | KeyCode::Up => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Char('g') => self.select(0),
+            KeyCode::Char('G') => self.select(self.entries.len().saturating_sub(1)),
+            KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => return self.open(),
+            KeyCode::Char('h') | KeyCode::Left => self.collapse(),
+            KeyCode::Char('r') => return self.request(self.readable(0..self.entries.len())),
+            KeyCode::Esc | KeyCode::Backspace => return Some(AppEvent::Back),
+            _ => {}
         }
+        None
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let footer = if let Some(prompt) = self.prompt {
+            Line::from(vec![
+                Span::styled(self.prompt_label(prompt), THEME.muted_style()),
+                Span::raw(format!("{}_ ", self.input)),
+            ])
+        } else if let Some(ref msg) = self.message {
+            Line::from(Span::styled(format!(" {msg} "), THEME.muted_style()))
+        } else {
+            Line::from(Span::styled(
+                " j/k: move | Enter: expand / look up key or index | h: collapse | r: refresh | i: raw slot | Esc: back ",
+                THEME.muted_style(),
+            ))
+        };
+
         let outer_block = Block::default()
             .title(" Storage Inspector ")
+            .title_bottom(footer)
             .borders(Borders::ALL)
             .border_style(THEME.border_focused_style());
 
         let inner = outer_block.inner(area);
         frame.render_widget(outer_block, area);
 
-        // Layout: address header + input area, results table
-        let constraints = vec![
-            Constraint::Length(5), // Header + input
-            Constraint::Min(4),   // Results table
-        ];
-
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints(constraints)
+            .constraints([
+                Constraint::Length(3), // Address + layout
+                Constraint::Min(4),    // Storage tree
+                Constraint::Length(2), // Selected slot
+            ])
             .split(inner);
 
-        // -- Header + input --
+        // -- Header --
         let mut header_lines: Vec<Line> = Vec::new();
 
         if let Some(addr) = self.address {
@@ -169,41 +488,32 @@ impl Component for StorageInspector {
             )));
         }
 
-        header_lines.push(Line::from(""));
-
-        if self.input_mode {
-            let cursor = "_";
-            header_lines.push(Line::from(vec![
-                Span::styled("  Slot: ", THEME.muted_style()),
+        header_lines.push(match (&self.layout, self.loading) {
+            (Some(layout), _) => Line::from(vec![
+                Span::styled("  Layout: ", THEME.muted_style()),
+                Span::styled(layout.contract.clone(), THEME.accent_style()),
                 Span::styled(
-                    format!("{}{cursor}", self.slot_input),
-                    Style::default()
-                        .fg(THEME.text)
-                        .add_modifier(Modifier::BOLD),
+                    format!(" ({} state variables)", layout.variables.len()),
+                    THEME.muted_style(),
                 ),
-            ]));
-            header_lines.push(Line::from(Span::styled(
-                "  Enter slot number (decimal or 0x hex). [Enter] Query  [Esc] Cancel",
-                THEME.muted_style(),
-            )));
-        } else if self.loading {
-            header_lines.push(Line::from(Span::styled(
-                "  Querying storage...",
+            ]),
+            (None, true) => Line::from(Span::styled(
+                "  Loading storage layout...",
                 THEME.muted_style(),
-            )));
-        } else {
-            header_lines.push(Line::from(Span::styled(
-                "  Press 'i' to enter a storage slot number",
+            )),
+            (None, false) => Line::from(Span::styled(
+                "  No storage layout published for this contract; press 'i' to read a slot by number",
                 THEME.muted_style(),
-            )));
-        }
+            )),
+        });
 
-        let header_paragraph =
-            Paragraph::new(header_lines).style(Style::default().fg(THEME.text));
-        frame.render_widget(header_paragraph, chunks[0]);
+        frame.render_widget(
+            Paragraph::new(header_lines).style(Style::default().fg(THEME.text)),
+            chunks[0],
+        );
 
-        // -- Results table --
-        if self.results.is_empty() {
+        // -- Storage tree --
+        if self.entries.is_empty() {
             let empty_msg = Paragraph::new("  No storage slots queried yet")
                 .style(THEME.muted_style());
             frame.render_widget(empty_msg, chunks[1]);
@@ -211,63 +521,56 @@ impl Component for StorageInspector {
         }
 
         let table_block = Block::default()
-            .title(format!(" Results ({}) ", self.results.len()))
+            .title(format!(" Storage ({}) ", self.entries.len()))
             .borders(Borders::ALL)
             .border_style(THEME.border_style());
 
         let header = Row::new(vec![
-            Cell::from("#"),
+            Cell::from("Name"),
+            Cell::from("Type"),
             Cell::from("Slot"),
-            Cell::from("Value (hex)"),
-            Cell::from("Value (dec)"),
+            Cell::from("Value"),
         ])
         .style(THEME.table_header_style())
         .bottom_margin(0);
 
         let rows: Vec<Row> = self
-            .results
+            .entries
             .iter()
-            .enumerate()
-            .map(|(i, (slot, value))| {
-                let slot_hex = format!("{slot:#x}");
-                let slot_display = if slot_hex.len() > 20 {
-                    format!("{}...{}", &slot_hex[..10], &slot_hex[slot_hex.len() - 6..])
+            .map(|entry| {
+                let marker = if self.is_container(&entry.item) {
+                    if entry.expanded { "\u{25be} " } else { "\u{25b8} " }
                 } else {
-                    slot_hex
+                    "  "
                 };
-
-                let value_hex = format!("{value}");
-                let value_display = if value_hex.len() > 34 {
-                    format!("{}...{}", &value_hex[..18], &value_hex[value_hex.len() - 8..])
-                } else {
-                    value_hex
+                let name = entry.item.path.rsplit('.').next().unwrap_or_default();
+                let name = match entry.depth {
+                    // Mapping keys and indexes read best with their parent
+                    0 => entry.item.path.clone(),
+                    _ if entry.item.path.ends_with(']') => entry
+                        .item
+                        .path
+                        .rfind('[')
+                        .map_or(name.to_string(), |i| entry.item.path[i..].to_string()),
+                    _ => name.to_string(),
                 };
-
-                // Try to show decimal for small values
-                let value_u256 = U256::from_be_bytes(value.0);
-                let dec_display = if value_u256 < U256::from(1u64 << 53) {
-                    format!("{value_u256}")
-                } else {
-                    "large".to_string()
-                };
-
                 Row::new(vec![
-                    Cell::from(format!("{}", i + 1)),
-                    Cell::from(slot_display).style(THEME.accent_style()),
-                    Cell::from(value_display).style(THEME.hash_style()),
-                    Cell::from(dec_display),
+                    Cell::from(format!("{}{marker}{name}", "  ".repeat(entry.depth))),
+                    Cell::from(entry.item.label.clone()).style(THEME.muted_style()),
+                    Cell::from(slot_label(&entry.item)).style(THEME.accent_style()),
+                    self.value_cell(entry),
                 ])
             })
             .collect();
 
         let widths = [
-            Constraint::Length(4),
-            Constraint::Length(22),
+            Constraint::Length(32),
+            Constraint::Length(24),
+            Constraint::Length(26),
             Constraint::Min(24),
-            Constraint::Length(16),
         ];
 
-        self.scroll_state = self.scroll_state.content_length(self.results.len());
+        self.scroll_state = self.scroll_state.content_length(self.entries.len());
 
         let table = Table::new(rows, widths)
             .header(header)
@@ -278,7 +581,7 @@ impl Component for StorageInspector {
         frame.render_stateful_widget(table, chunks[1], &mut self.table_state);
 
         // Scrollbar
-        if self.results.len() > chunks[1].height as usize {
+        if self.entries.len() > chunks[1].height as usize {
             let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
                 .begin_symbol(Some("^"))
                 .end_symbol(Some("v"));
@@ -292,5 +595,20 @@ impl Component for StorageInspector {
 
             frame.render_stateful_widget(scrollbar, scrollbar_area, &mut self.scroll_state);
         }
+
+        // -- Full slot and word of the selected row --
+        if let Some(entry) = self.entries.get(self.selected) {
+            let mut lines = vec![Line::from(vec![
+                Span::styled("  Slot: ", THEME.muted_style()),
+                Span::styled(format!("{}", B256::from(entry.item.slot)), THEME.hash_style()),
+            ])];
+            if let Some(ref value) = entry.value {
+                lines.push(Line::from(vec![
+                    Span::styled("  Word: ", THEME.muted_style()),
+                    Span::styled(format!("{}", value.raw), THEME.hash_style()),
+                ]));
+            }
+            frame.render_widget(Paragraph::new(lines), chunks[2]);
+        }
     }
 }
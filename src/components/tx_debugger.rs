@@ -1,9 +1,16 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, Bytes, B256};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
 use crate::components::Component;
-use crate::data::types::ExecutionTrace;
+use crate::data::decoder::TxDecoder;
+use crate::data::gas_profile;
+use crate::data::sourcemap::{ContractSource, SourceLocation};
+use crate::data::trace::{self, Breakpoint, TraceOptions};
+use crate::data::types::{AccessCounts, ExecutionTrace, TraceStats, TraceStep};
 use crate::events::AppEvent;
 use crate::theme::THEME;
 use crate::utils;
@@ -12,10 +19,31 @@ use crate::utils;
 const CALL_OPS: &[&str] = &["CALL", "CALLCODE", "DELEGATECALL", "STATICCALL"];
 const CREATE_OPS: &[&str] = &["CREATE", "CREATE2"];
 
+/// Bytes per row in the memory pane (one EVM word).
+const MEMORY_ROW_BYTES: usize = 32;
+
 pub struct TxDebugger {
+    pub tx_hash: Option<B256>,
+    /// Capture options used for the next trace request.
+    pub options: TraceOptions,
     pub trace: Option<ExecutionTrace>,
     pub current_step: usize,
     pub loading: bool,
+    pub error: Option<String>,
+    /// Verified sources of the contracts executed in the trace.
+    pub sources: HashMap<Address, ContractSource>,
+    pub breakpoints: Vec<Breakpoint>,
+    /// Whether the breakpoint prompt is capturing keys.
+    pub input_mode: bool,
+    pub bp_input: String,
+    /// Aggregates of the loaded trace, shown in the summary panel.
+    stats: Option<TraceStats>,
+    show_summary: bool,
+    /// Whether j/k/Enter act on the hotspot list instead of the steps.
+    hotspot_focus: bool,
+    hotspot_state: TableState,
+    /// Feedback from the last navigation or breakpoint command.
+    message: Option<String>,
     table_state: TableState,
     scroll_state: ScrollbarState,
 }
@@ -23,14 +51,36 @@ pub struct TxDebugger {
 impl TxDebugger {
     pub fn new() -> Self {
         Self {
+            tx_hash: None,
+            options: TraceOptions::default(),
             trace: None,
             current_step: 0,
             loading: false,
+            error: None,
+            sources: HashMap::new(),
+            breakpoints: Vec::new(),
+            input_mode: false,
+            bp_input: String::new(),
+            stats: None,
+            show_summary: false,
+            hotspot_focus: false,
+            hotspot_state: TableState::default().with_selected(0),
+            message: None,
             table_state: TableState::default().with_selected(0),
             scroll_state: ScrollbarState::default(),
         }
     }
 
+    /// Replace the loaded trace and move the cursor back to the first step.
+    pub fn set_trace(&mut self, trace: Option<ExecutionTrace>) {
+        self.stats = trace.as_ref().map(|t| gas_profile::trace_stats(&t.steps));
+        self.hotspot_state.select(Some(0));
+        self.trace = trace;
+        self.current_step = 0;
+        self.table_state.select(Some(0));
+        self.scroll_state = ScrollbarState::default();
+    }
+
     fn step_count(&self) -> usize {
         self.trace.as_ref().map(|t| t.steps.len()).unwrap_or(0)
     }
@@ -100,6 +150,165 @@ impl TxDebugger {
         self.scroll_state = self.scroll_state.position(self.current_step);
     }
 
+    /// Move the cursor to `index`, clamped to the trace length.
+    fn jump_to(&mut self, index: usize) {
+        let len = self.step_count();
+        if len == 0 {
+            return;
+        }
+        let index = index.min(len - 1);
+        self.current_step = index;
+        self.table_state.select(Some(index));
+        self.scroll_state = self.scroll_state.position(index);
+    }
+
+    fn select_hotspot(&mut self, forward: bool) {
+        let len = self.stats.as_ref().map(|s| s.hotspots.len()).unwrap_or(0);
+        if len == 0 {
+            return;
+        }
+        let current = self.hotspot_state.selected().unwrap_or(0);
+        let next = if forward {
+            (current + 1).min(len - 1)
+        } else {
+            current.saturating_sub(1)
+        };
+        self.hotspot_state.select(Some(next));
+    }
+
+    /// Jump to the step of the selected hotspot.
+    fn jump_to_hotspot(&mut self) {
+        let target = self.stats.as_ref().and_then(|stats| {
+            let selected = self.hotspot_state.selected()?;
+            stats.hotspots.get(selected).map(|(index, _)| *index)
+        });
+        if let Some(index) = target {
+            self.message = None;
+            self.jump_to(index);
+        }
+    }
+
+    /// Apply a navigation function over the loaded steps and jump to its result.
+    fn navigate(&mut self, f: impl Fn(&[TraceStep], usize) -> usize) {
+        if let Some(ref trace) = self.trace {
+            let target = f(&trace.steps, self.current_step);
+            self.jump_to(target);
+        }
+    }
+
+    /// Jump to the next (or previous) step matching `pred`, reporting `what`
+    /// when there is none.
+    fn seek(&mut self, forward: bool, what: &str, pred: impl Fn(&TraceStep) -> bool) {
+        let Some(ref trace) = self.trace else {
+            return;
+        };
+        let found = if forward {
+            trace::find_next(&trace.steps, self.current_step, pred)
+        } else {
+            trace::find_prev(&trace.steps, self.current_step, pred)
+        };
+        match found {
+            Some(index) => {
+                self.message = None;
+                self.jump_to(index);
+            }
+            None => self.message = Some(format!("No {what} found")),
+        }
+    }
+
+    fn continue_to_breakpoint(&mut self, forward: bool) {
+        if self.breakpoints.is_empty() {
+            self.message = Some("No breakpoints set (b: toggle at pc, B: add)".to_string());
+            return;
+        }
+        let breakpoints = self.breakpoints.clone();
+        let direction = if forward { "next" } else { "previous" };
+        self.seek(forward, &format!("{direction} breakpoint hit"), |step| {
+            breakpoints.iter().any(|bp| bp.matches(step))
+        });
+    }
+
+    /// Toggle a pc breakpoint scoped to the contract executing the current step.
+    fn toggle_pc_breakpoint(&mut self) {
+        let Some(step) = self.trace.as_ref().and_then(|t| t.steps.get(self.current_step)) else {
+            return;
+        };
+        let bp = Breakpoint::Pc {
+            pc: step.pc,
+            address: Some(step.address),
+        };
+        if let Some(pos) = self.breakpoints.iter().position(|b| *b == bp) {
+            self.breakpoints.remove(pos);
+            self.message = Some(format!("Removed breakpoint {bp}"));
+        } else {
+            self.message = Some(format!("Added breakpoint {bp}"));
+            self.breakpoints.push(bp);
+        }
+    }
+
+    fn submit_breakpoint_input(&mut self) {
+        self.input_mode = false;
+        match Breakpoint::parse(&self.bp_input) {
+            Some(bp) => {
+                self.message = Some(format!("Added breakpoint {bp}"));
+                if !self.breakpoints.contains(&bp) {
+                    self.breakpoints.push(bp);
+                }
+            }
+            None => {
+                self.message = Some(format!("Invalid breakpoint: {}", self.bp_input));
+            }
+        }
+        self.bp_input.clear();
+    }
+
+    /// Source location of a step, if its contract has verified sources.
+    /// Init code is left unmapped: the source map only covers runtime code.
+    fn locate(&self, step: &TraceStep) -> Option<(&ContractSource, SourceLocation)> {
+        if step.in_create {
+            return None;
+        }
+        let source = self.sources.get(&step.address)?;
+        let loc = source.locate(step.pc)?;
+        Some((source, loc))
+    }
+
+    /// Step forward until execution reaches a different Solidity line.
+    fn next_source_line(&mut self) {
+        let Some(ref trace) = self.trace else {
+            return;
+        };
+        let key = |step: &TraceStep| {
+            self.locate(step)
+                .map(|(_, loc)| (step.address, loc.file, loc.line))
+        };
+        let current = trace.steps.get(self.current_step).and_then(key);
+        let found = trace::find_next(&trace.steps, self.current_step, |step| {
+            key(step).is_some_and(|k| Some(k) != current)
+        });
+        match found {
+            Some(index) => {
+                self.message = None;
+                self.jump_to(index);
+            }
+            None => self.message = Some("No further source lines".to_string()),
+        }
+    }
+
+    fn is_breakpoint(&self, step: &TraceStep) -> bool {
+        self.breakpoints.iter().any(|bp| bp.matches(step))
+    }
+
+    /// Re-request the trace after toggling a capture option.
+    fn reload_with(&mut self, options: TraceOptions) -> Option<AppEvent> {
+        self.options = options;
+        let tx_hash = self.tx_hash?;
+        self.set_trace(None);
+        self.error = None;
+        self.loading = true;
+        Some(AppEvent::RequestTrace { tx_hash, options })
+    }
+
     fn op_style(op: &str) -> Style {
         if CALL_OPS.contains(&op) {
             Style::default()
@@ -122,8 +331,49 @@ impl TxDebugger {
 }
 
 impl Component for TxDebugger {
+    fn captures_input(&self) -> bool {
+        self.input_mode
+    }
+
     fn handle_key(&mut self, key: KeyEvent) -> Option<AppEvent> {
+        if self.input_mode {
+            match key.code {
+                KeyCode::Esc => {
+                    self.input_mode = false;
+                    self.bp_input.clear();
+                }
+                KeyCode::Enter => self.submit_breakpoint_input(),
+                KeyCode::Backspace => {
+                    self.bp_input.pop();
+                }
+                KeyCode::Char(c) => self.bp_input.push(c),
+                _ => {}
+            }
+            return None;
+        }
+
         match (key.code, key.modifiers) {
+            (KeyCode::Char('h'), _) => {
+                self.show_summary = !self.show_summary;
+                self.hotspot_focus = false;
+                None
+            }
+            (KeyCode::Tab, _) if self.show_summary => {
+                self.hotspot_focus = !self.hotspot_focus;
+                None
+            }
+            (KeyCode::Char('j'), _) | (KeyCode::Down, _) if self.hotspot_focus => {
+                self.select_hotspot(true);
+                None
+            }
+            (KeyCode::Char('k'), _) | (KeyCode::Up, _) if self.hotspot_focus => {
+                self.select_hotspot(false);
+                None
+            }
+            (KeyCode::Enter, _) if self.hotspot_focus => {
+                self.jump_to_hotspot();
+                None
+            }
             (KeyCode::Char('j'), _) | (KeyCode::Down, _) => {
                 self.select_next();
                 None
@@ -148,14 +398,102 @@ impl Component for TxDebugger {
                 self.page_up();
                 None
             }
+            (KeyCode::Char('i'), _) => {
+                self.select_next();
+                None
+            }
+            (KeyCode::Char('n'), _) => {
+                self.next_source_line();
+                None
+            }
+            (KeyCode::Char('o'), _) => {
+                self.navigate(trace::step_over);
+                None
+            }
+            (KeyCode::Char('O'), _) => {
+                self.navigate(trace::step_out);
+                None
+            }
+            (KeyCode::Char('w'), _) => {
+                self.seek(true, "SSTORE", |s| s.op == "SSTORE");
+                None
+            }
+            (KeyCode::Char('l'), _) => {
+                self.seek(true, "LOG", |s| s.op.starts_with("LOG"));
+                None
+            }
+            (KeyCode::Char('r'), _) => {
+                self.seek(true, "REVERT", |s| s.op == "REVERT");
+                None
+            }
+            (KeyCode::Char('E'), _) => {
+                match self.trace.as_ref().and_then(|t| trace::first_error(&t.steps)) {
+                    Some(index) => {
+                        self.message = None;
+                        self.jump_to(index);
+                    }
+                    None => self.message = Some("Trace has no errors".to_string()),
+                }
+                None
+            }
+            (KeyCode::Char('b'), _) => {
+                self.toggle_pc_breakpoint();
+                None
+            }
+            (KeyCode::Char('B'), _) => {
+                self.input_mode = true;
+                self.bp_input.clear();
+                None
+            }
+            (KeyCode::Char('c'), _) => {
+                self.continue_to_breakpoint(true);
+                None
+            }
+            (KeyCode::Char('C'), _) => {
+                self.continue_to_breakpoint(false);
+                None
+            }
+            (KeyCode::Char('x'), _) => {
+                self.breakpoints.clear();
+                self.message = Some("Cleared all breakpoints".to_string());
+                None
+            }
+            (KeyCode::Char('M'), _) => self.reload_with(TraceOptions {
+                enable_memory: !self.options.enable_memory,
+                ..self.options
+            }),
+            (KeyCode::Char('S'), _) => self.reload_with(TraceOptions {
+                enable_storage: !self.options.enable_storage,
+                ..self.options
+            }),
             (KeyCode::Esc, _) | (KeyCode::Backspace, _) => Some(AppEvent::Back),
             _ => None,
         }
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let footer = if self.input_mode {
+            Line::from(vec![
+                Span::styled(" Breakpoint (pc, pc@addr, 0xaddr, OPCODE): ", THEME.muted_style()),
+                Span::raw(format!("{}_ ", self.bp_input)),
+            ])
+        } else if let Some(ref msg) = self.message {
+            Line::from(Span::styled(format!(" {msg} "), THEME.muted_style()))
+        } else if self.hotspot_focus {
+            Line::from(Span::styled(
+                " j/k: select hotspot | Enter: jump to step | Tab: back to steps | h: hide summary ",
+                THEME.muted_style(),
+            ))
+        } else {
+            Line::from(Span::styled(
+                " i/o/O: into/over/out | w/l/r: SSTORE/LOG/REVERT | E: error | b/B: break | c/C: continue | h: summary ",
+                THEME.muted_style(),
+            ))
+        };
+
         let outer_block = Block::default()
             .title(" Transaction Debugger ")
+            .title_bottom(footer)
             .borders(Borders::ALL)
             .border_style(THEME.border_focused_style());
 
@@ -171,6 +509,15 @@ impl Component for TxDebugger {
             return;
         }
 
+        if let Some(ref err) = self.error {
+            let text = Paragraph::new(err.clone())
+                .style(THEME.error_style())
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+            frame.render_widget(text, inner);
+            return;
+        }
+
         let trace = match &self.trace {
             Some(t) => t,
             None => {
@@ -190,19 +537,54 @@ impl Component for TxDebugger {
             return;
         }
 
-        // Layout: opcode table (left) | stack display (right)
-        let h_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        // Layout: opcode table | [source |] stack on top, memory | storage + returndata
+        // (or the trace summary) below
+        let bottom_height = if self.show_summary { 14 } else { 12 };
+        let v_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(10), Constraint::Length(bottom_height)])
             .split(inner);
 
+        // The source pane only appears while stepping through verified code
+        let has_source = trace
+            .steps
+            .get(self.current_step)
+            .is_some_and(|step| self.sources.contains_key(&step.address));
+        let h_chunks = if has_source {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(40),
+                    Constraint::Percentage(35),
+                    Constraint::Percentage(25),
+                ])
+                .split(v_chunks[0])
+        } else {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .split(v_chunks[0])
+        };
+        let stack_area = h_chunks[h_chunks.len() - 1];
+
+        let bottom_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(v_chunks[1]);
+
+        let side_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(4), Constraint::Length(4)])
+            .split(bottom_chunks[1]);
+
         // -- Left: Opcode table --
         let step_count = trace.steps.len();
         let title = format!(
-            " Steps ({}/{}) | Gas Used: {} ",
+            " Steps ({}/{}) | Gas Used: {}{} ",
             self.current_step + 1,
             step_count,
             utils::format_number(trace.gas_used),
+            if trace.failed { " | REVERTED" } else { "" },
         );
 
         let table_block = Block::default()
@@ -229,9 +611,10 @@ impl Component for TxDebugger {
                 let op_style = Self::op_style(&step.op);
                 let depth_indent = "  ".repeat(step.depth.saturating_sub(1));
                 let has_error = step.error.is_some();
+                let marker = if self.is_breakpoint(step) { "●" } else { " " };
 
                 let mut row = Row::new(vec![
-                    Cell::from(format!("{}", i)),
+                    Cell::from(format!("{marker}{i}")),
                     Cell::from(format!("{}", step.pc)),
                     Cell::from(format!("{}{}", depth_indent, step.op)).style(op_style),
                     Cell::from(utils::format_number(step.gas)),
@@ -271,8 +654,8 @@ impl Component for TxDebugger {
             .title(" Stack ")
             .borders(Borders::ALL)
             .border_style(THEME.border_style());
-        let stack_inner = stack_block.inner(h_chunks[1]);
-        frame.render_widget(stack_block, h_chunks[1]);
+        let stack_inner = stack_block.inner(stack_area);
+        frame.render_widget(stack_block, stack_area);
 
         let current = &trace.steps[self.current_step];
         let mut stack_lines: Vec<Line> = Vec::new();
@@ -286,6 +669,30 @@ impl Component for TxDebugger {
             Span::styled("  PC: ", THEME.muted_style()),
             Span::raw(format!("{}", current.pc)),
         ]));
+        stack_lines.push(Line::from(vec![
+            Span::styled("  In: ", THEME.muted_style()),
+            Span::styled(utils::truncate_address(&current.address), THEME.address_style()),
+        ]));
+
+        if !trace.return_value.is_empty() {
+            let ret_hex = format!("{}", trace.return_value);
+            let display = if ret_hex.len() > 26 {
+                format!("{}...", &ret_hex[..26])
+            } else {
+                ret_hex
+            };
+            stack_lines.push(Line::from(vec![
+                Span::styled("  Ret: ", THEME.muted_style()),
+                Span::styled(display, THEME.hash_style()),
+            ]));
+        }
+
+        if let Some(ref reason) = trace.revert_reason {
+            stack_lines.push(Line::from(vec![
+                Span::styled("  Revert: ", Style::default().fg(THEME.error)),
+                Span::styled(reason.clone(), THEME.error_style()),
+            ]));
+        }
 
         if let Some(ref err) = current.error {
             stack_lines.push(Line::from(vec![
@@ -303,7 +710,7 @@ impl Component for TxDebugger {
         )));
         stack_lines.push(Line::from(""));
 
-        // Show top 8 stack items (stack is stored top-first)
+        // Show top 8 stack items (stack is stored bottom-first)
         let stack_items: Vec<_> = current.stack.iter().rev().take(8).collect();
         if stack_items.is_empty() {
             stack_lines.push(Line::from(Span::styled(
@@ -325,6 +732,22 @@ impl Component for TxDebugger {
             }
         }
 
+        if !self.breakpoints.is_empty() {
+            stack_lines.push(Line::from(""));
+            stack_lines.push(Line::from(Span::styled(
+                format!("  Breakpoints ({}):", self.breakpoints.len()),
+                Style::default()
+                    .fg(THEME.text)
+                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            )));
+            for bp in &self.breakpoints {
+                stack_lines.push(Line::from(Span::styled(
+                    format!("  ● {bp}"),
+                    Style::default().fg(THEME.warning),
+                )));
+            }
+        }
+
         let stack_paragraph =
             Paragraph::new(stack_lines).style(Style::default().fg(THEME.text));
         frame.render_widget(stack_paragraph, stack_inner);
@@ -344,5 +767,393 @@ impl Component for TxDebugger {
 
             frame.render_stateful_widget(scrollbar, scrollbar_area, &mut self.scroll_state);
         }
+
+        // -- Bottom: memory, storage and returndata panes --
+        let options = self.options;
+        let trace = match &self.trace {
+            Some(t) => t,
+            None => return,
+        };
+        let current = &trace.steps[self.current_step];
+        match self.stats {
+            Some(ref stats) if self.show_summary => render_summary(
+                frame,
+                v_chunks[1],
+                trace,
+                stats,
+                &mut self.hotspot_state,
+                self.hotspot_focus,
+            ),
+            _ => {
+                render_memory(frame, bottom_chunks[0], current, options);
+                render_storage(frame, side_chunks[0], trace, self.current_step, options);
+                render_return_data(frame, side_chunks[1], &current.return_data);
+            }
+        }
+
+        if let Some(source) = self.sources.get(&current.address) {
+            let location = self.locate(current).map(|(_, loc)| loc);
+            render_source(frame, h_chunks[1], source, location, current.in_create);
+        }
     }
 }
+
+/// Render the trace summary: an opcode frequency histogram, gas per opcode
+/// class with storage access temperatures, and the most expensive steps.
+fn render_summary(
+    frame: &mut Frame,
+    area: Rect,
+    trace: &ExecutionTrace,
+    stats: &TraceStats,
+    hotspot_state: &mut TableState,
+    focused: bool,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(35),
+            Constraint::Percentage(30),
+            Constraint::Percentage(35),
+        ])
+        .split(area);
+
+    // -- Opcode frequency histogram, bars scaled to the most frequent opcode --
+    let block = Block::default()
+        .title(format!(" Opcodes ({} distinct) ", stats.opcode_counts.len()))
+        .borders(Borders::ALL)
+        .border_style(THEME.border_style());
+    let inner = block.inner(chunks[0]);
+    frame.render_widget(block, chunks[0]);
+
+    let max_count = stats.opcode_counts.first().map(|(_, n)| *n).unwrap_or(0).max(1);
+    let bar_width = (inner.width as usize).saturating_sub(24).max(1);
+    let lines: Vec<Line> = stats
+        .opcode_counts
+        .iter()
+        .take(inner.height as usize)
+        .map(|(op, count)| {
+            let filled = ((*count as u128 * bar_width as u128) / max_count as u128).max(1) as usize;
+            Line::from(vec![
+                Span::styled(format!(" {op:<14}"), TxDebugger::op_style(op)),
+                Span::styled("\u{2588}".repeat(filled), THEME.accent_style()),
+                Span::raw(" ".repeat(bar_width - filled)),
+                Span::styled(format!(" {:>7}", utils::format_number(*count)), THEME.muted_style()),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+
+    // -- Gas per class, storage access temperatures and call depth --
+    let block = Block::default()
+        .title(" Gas by Class ")
+        .borders(Borders::ALL)
+        .border_style(THEME.border_style());
+    let inner = block.inner(chunks[1]);
+    frame.render_widget(block, chunks[1]);
+
+    let execution: u64 = stats.class_gas.iter().map(|(_, gas)| gas).sum();
+    let mut lines: Vec<Line> = stats
+        .class_gas
+        .iter()
+        .map(|(class, gas)| {
+            let pct = if execution > 0 {
+                *gas as f64 * 100.0 / execution as f64
+            } else {
+                0.0
+            };
+            Line::from(vec![
+                Span::styled(format!(" {:<9}", class.to_string()), THEME.muted_style()),
+                Span::raw(format!("{:>12}", utils::format_number(*gas))),
+                Span::styled(format!(" {pct:>5.1}%"), THEME.muted_style()),
+            ])
+        })
+        .collect();
+
+    let access_line = |label: &'static str, counts: AccessCounts| {
+        let mut spans = vec![
+            Span::styled(format!(" {label:<9}"), THEME.muted_style()),
+            Span::styled(format!("{} cold", counts.cold), Style::default().fg(THEME.warning)),
+            Span::styled(" / ", THEME.muted_style()),
+            Span::styled(format!("{} warm", counts.warm), Style::default().fg(THEME.success)),
+        ];
+        if counts.unknown > 0 {
+            spans.push(Span::styled(format!(" / {} ?", counts.unknown), THEME.muted_style()));
+        }
+        Line::from(spans)
+    };
+    lines.push(Line::from(""));
+    lines.push(access_line("SLOAD", stats.sload));
+    lines.push(access_line("SSTORE", stats.sstore));
+    lines.push(Line::from(vec![
+        Span::styled(" Max depth ", THEME.muted_style()),
+        Span::raw(stats.max_depth.to_string()),
+    ]));
+    frame.render_widget(Paragraph::new(lines), inner);
+
+    // -- Most expensive steps by their own cost --
+    let border_style = if focused {
+        THEME.border_focused_style()
+    } else {
+        THEME.border_style()
+    };
+    let title = if focused {
+        " Hotspots | Enter: jump "
+    } else {
+        " Hotspots | Tab: select "
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(border_style);
+
+    let header = Row::new(vec![
+        Cell::from("Step"),
+        Cell::from("Opcode"),
+        Cell::from("Gas"),
+        Cell::from("Depth"),
+    ])
+    .style(THEME.table_header_style());
+
+    let rows: Vec<Row> = stats
+        .hotspots
+        .iter()
+        .filter_map(|(index, gas)| {
+            let step = trace.steps.get(*index)?;
+            Some(Row::new(vec![
+                Cell::from(index.to_string()),
+                Cell::from(step.op.clone()).style(TxDebugger::op_style(&step.op)),
+                Cell::from(utils::format_number(*gas)),
+                Cell::from(step.depth.to_string()),
+            ]))
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(7),
+        Constraint::Min(12),
+        Constraint::Length(10),
+        Constraint::Length(6),
+    ];
+    let mut table = Table::new(rows, widths).header(header).block(block);
+    if focused {
+        table = table
+            .row_highlight_style(THEME.selected_style())
+            .highlight_symbol(" > ");
+    }
+    frame.render_stateful_widget(table, chunks[2], hotspot_state);
+}
+
+/// Render the Solidity source around the current step's location, with the
+/// mapped byte range highlighted. Init code has no mapping to show.
+fn render_source(
+    frame: &mut Frame,
+    area: Rect,
+    source: &ContractSource,
+    location: Option<SourceLocation>,
+    in_create: bool,
+) {
+    let file = location.as_ref().and_then(|loc| source.file(loc.file));
+    let title = match (&location, file) {
+        (Some(loc), Some(file)) => format!(" Source: {}:{} ", file.path, loc.line),
+        _ => format!(" Source: {} ", source.name),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(THEME.border_style());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let (Some(loc), Some(file)) = (location, file) else {
+        let msg = if in_create {
+            "  constructor (no runtime source map)"
+        } else {
+            "  (compiler-generated code)"
+        };
+        let text = Paragraph::new(msg).style(THEME.muted_style());
+        frame.render_widget(text, inner);
+        return;
+    };
+
+    // Center the first mapped line in the pane
+    let height = inner.height as usize;
+    let first_line = loc.line.saturating_sub(height / 2).max(1);
+    let range = loc.offset..loc.offset + loc.length;
+    let highlight = Style::default()
+        .fg(THEME.selected_fg)
+        .bg(THEME.selected_bg)
+        .add_modifier(Modifier::BOLD);
+
+    let mut lines: Vec<Line> = Vec::new();
+    let mut line_start = 0;
+    for (i, text) in file.content.split('\n').enumerate() {
+        let number = i + 1;
+        let line_end = line_start + text.len();
+        if number >= first_line {
+            let in_range = number >= loc.line && number <= loc.end_line;
+            let gutter_style = if in_range {
+                THEME.accent_style()
+            } else {
+                THEME.muted_style()
+            };
+            let mut spans = vec![Span::styled(format!("{number:>5} "), gutter_style)];
+
+            // Split the line into before / highlighted / after by byte offsets
+            let from = range.start.clamp(line_start, line_end) - line_start;
+            let to = range.end.clamp(line_start, line_end) - line_start;
+            match (text.get(..from), text.get(from..to), text.get(to..)) {
+                (Some(before), Some(mid), Some(after)) if in_range && from < to => {
+                    spans.push(Span::raw(before.replace('\t', "    ")));
+                    spans.push(Span::styled(mid.replace('\t', "    "), highlight));
+                    spans.push(Span::raw(after.replace('\t', "    ")));
+                }
+                _ => spans.push(Span::raw(text.replace('\t', "    "))),
+            }
+            lines.push(Line::from(spans));
+            if lines.len() >= height {
+                break;
+            }
+        }
+        line_start = line_end + 1;
+    }
+
+    let paragraph = Paragraph::new(lines).style(Style::default().fg(THEME.text));
+    frame.render_widget(paragraph, inner);
+}
+
+/// Render memory as a grid of 32-byte words, highlighting the bytes the
+/// current op reads or writes and scrolling the first of them into view.
+fn render_memory(frame: &mut Frame, area: Rect, step: &TraceStep, options: TraceOptions) {
+    let block = Block::default()
+        .title(format!(" Memory ({} bytes) ", step.memory.len()))
+        .borders(Borders::ALL)
+        .border_style(THEME.border_style());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if step.memory.is_empty() {
+        let msg = if options.enable_memory {
+            "  (empty)"
+        } else {
+            "  Memory capture disabled. Press M to enable"
+        };
+        frame.render_widget(Paragraph::new(msg).style(THEME.muted_style()), inner);
+        return;
+    }
+
+    let ranges = trace::memory_access_ranges(step);
+    // Offsets and sizes come straight off the stack, so `off + len` may overflow
+    let touched = |i: usize| ranges.iter().any(|(off, len)| i >= *off && i - *off < *len);
+    let highlight = Style::default()
+        .fg(THEME.bg)
+        .bg(THEME.warning)
+        .add_modifier(Modifier::BOLD);
+
+    let row_count = step.memory.len().div_ceil(MEMORY_ROW_BYTES);
+    let visible = inner.height as usize;
+    let first_row = ranges
+        .first()
+        .map(|(off, _)| off / MEMORY_ROW_BYTES)
+        .unwrap_or(0)
+        .min(row_count.saturating_sub(visible));
+
+    let lines: Vec<Line> = (first_row..row_count)
+        .take(visible)
+        .map(|row| {
+            let start = row * MEMORY_ROW_BYTES;
+            let end = (start + MEMORY_ROW_BYTES).min(step.memory.len());
+            let mut spans = vec![Span::styled(format!(" {start:#06x} "), THEME.muted_style())];
+            for i in start..end {
+                let style = if touched(i) {
+                    highlight
+                } else if step.memory[i] == 0 {
+                    THEME.muted_style()
+                } else {
+                    THEME.hash_style()
+                };
+                spans.push(Span::styled(format!("{:02x}", step.memory[i]), style));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Render storage slots read (R) or written (W) so far in the current frame.
+fn render_storage(
+    frame: &mut Frame,
+    area: Rect,
+    trace: &ExecutionTrace,
+    index: usize,
+    options: TraceOptions,
+) {
+    let accesses = trace::frame_storage_accesses(&trace.steps, index);
+    let title = if options.enable_storage {
+        format!(" Storage ({}) ", accesses.len())
+    } else {
+        format!(" Storage ({}) | S: capture ", accesses.len())
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(THEME.border_style());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if accesses.is_empty() {
+        frame.render_widget(
+            Paragraph::new("  No slots accessed in this frame").style(THEME.muted_style()),
+            inner,
+        );
+        return;
+    }
+
+    let lines: Vec<Line> = accesses
+        .iter()
+        .map(|access| {
+            let (marker, marker_style) = if access.written {
+                ("W ", Style::default().fg(THEME.warning).add_modifier(Modifier::BOLD))
+            } else {
+                ("R ", THEME.accent_style())
+            };
+            let value = access
+                .value
+                .map(|v| format!("{v:#x}"))
+                .unwrap_or_else(|| "?".to_string());
+            Line::from(vec![
+                Span::styled(format!(" {marker}"), marker_style),
+                Span::styled(format!("{:#x}", access.slot), THEME.accent_style()),
+                Span::styled(" = ", THEME.muted_style()),
+                Span::styled(value, THEME.hash_style()),
+            ])
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_return_data(frame: &mut Frame, area: Rect, data: &Bytes) {
+    let block = Block::default()
+        .title(format!(" Returndata ({} bytes) ", data.len()))
+        .borders(Borders::ALL)
+        .border_style(THEME.border_style());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    // Revert data from a failed sub-call is decoded when it is Error(string) or Panic(uint256)
+    let text = if data.is_empty() {
+        Paragraph::new("  (empty)").style(THEME.muted_style())
+    } else if let Some(reason) = TxDecoder::decode_revert(data, None) {
+        Paragraph::new(vec![
+            Line::from(Span::styled(reason, THEME.error_style())),
+            Line::from(Span::styled(format!("{data}"), THEME.hash_style())),
+        ])
+        .wrap(Wrap { trim: false })
+    } else {
+        Paragraph::new(format!("{data}"))
+            .style(THEME.hash_style())
+            .wrap(Wrap { trim: false })
+    };
+    frame.render_widget(text, inner);
+}
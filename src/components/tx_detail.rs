@@ -1,10 +1,17 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, U256};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
 use crate::components::Component;
-use crate::data::types::{DecodedLog, InternalCall, TransactionDetail, TxStatus};
-use crate::events::AppEvent;
+use crate::components::value_tree::{RowKind, ValueRow, ValueTree, ValueTreeState};
+use crate::data::types::{
+    DecodedCall, DecodedLog, InnerCall, InternalCall, TokenStandard, TokenTransfer,
+    TransactionDetail, TxStatus,
+};
+use crate::events::{AppEvent, View};
 use crate::theme::THEME;
 use crate::utils;
 
@@ -15,6 +22,12 @@ pub struct TxDetailView {
     pub loading: bool,
     scroll: u16,
     max_scroll: u16,
+    /// Expanded and selected decoded values
+    values: ValueTreeState,
+    /// Selectable values of the last render
+    rows: Vec<ValueRow>,
+    /// Whether j/k move through the values instead of scrolling
+    selecting: bool,
 }
 
 impl TxDetailView {
@@ -26,12 +39,52 @@ impl TxDetailView {
             loading: false,
             scroll: 0,
             max_scroll: 0,
+            values: ValueTreeState::default(),
+            rows: Vec::new(),
+            selecting: false,
         }
     }
 
-    fn build_lines(&self, detail: &TransactionDetail) -> Vec<Line<'static>> {
+    /// Clear the detail and the view state for a new transaction.
+    pub fn reset(&mut self) {
+        self.detail = None;
+        self.internal_calls.clear();
+        self.decoded_logs.clear();
+        self.scroll = 0;
+        self.values = ValueTreeState::default();
+        self.rows.clear();
+        self.selecting = false;
+    }
+
+    /// Move the selection `delta` values up or down, starting from the first.
+    fn move_selection(&mut self, delta: isize) {
+        let current = self
+            .values
+            .selected
+            .as_ref()
+            .and_then(|path| self.rows.iter().position(|row| &row.path == path));
+        let next = match current {
+            Some(i) => i.saturating_add_signed(delta).min(self.rows.len().saturating_sub(1)),
+            None => 0,
+        };
+        self.values.selected = self.rows.get(next).map(|row| row.path.clone());
+    }
+
+    fn selected_row(&self) -> Option<&ValueRow> {
+        let path = self.values.selected.as_ref()?;
+        self.rows.iter().find(|row| &row.path == path)
+    }
+
+    fn build_lines(&self, detail: &TransactionDetail) -> (Vec<Line<'static>>, Vec<ValueRow>) {
         let mut lines: Vec<Line<'static>> = Vec::new();
+        let mut rows = Vec::new();
         let tx = &detail.summary;
+        // Token decimals seen in this transaction, for showing amounts in token units
+        let decimals: HashMap<Address, u8> = detail
+            .token_transfers
+            .iter()
+            .filter_map(|t| Some((t.token_address, t.decimals?)))
+            .collect();
 
         // ---- Section 1: Core Info ----
         lines.push(Line::from(vec![
@@ -76,6 +129,20 @@ impl TxDetailView {
         };
         lines.push(Line::from(vec![status_span, status_value]));
 
+        // Revert reason comes from the top-level call of the trace
+        let revert_reason = self
+            .internal_calls
+            .iter()
+            .find(|c| c.depth == 0)
+            .and_then(|c| c.revert_reason.as_ref())
+            .filter(|_| tx.status == TxStatus::Failed);
+        if let Some(reason) = revert_reason {
+            lines.push(Line::from(vec![
+                Span::styled("  Reason:  ", THEME.muted_style()),
+                Span::styled(reason.clone(), THEME.error_style()),
+            ]));
+        }
+
         if let Some(block_num) = tx.block_number {
             lines.push(Line::from(vec![
                 Span::styled("  Block:  ", THEME.muted_style()),
@@ -199,139 +266,27 @@ impl TxDetailView {
 
         // ---- Section 4: Method / Decoded Input ----
         if let Some(decoded) = &detail.decoded_input {
-            lines.push(Line::from(""));
-            lines.push(Line::from(Span::styled(
-                "  Method",
-                Style::default().fg(THEME.text).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
-            )));
-            lines.push(Line::from(""));
-
-            lines.push(Line::from(vec![
-                Span::styled("  Function:  ", THEME.muted_style()),
-                Span::styled(
-                    decoded.function_name.clone(),
-                    THEME.accent_style(),
-                ),
-            ]));
-
-            if !decoded.params.is_empty() {
-                lines.push(Line::from(Span::styled("  Parameters:", THEME.muted_style())));
-                for (name, value) in &decoded.params {
-                    lines.push(Line::from(vec![
-                        Span::raw("      "),
-                        Span::styled(format!("{name}: "), THEME.muted_style()),
-                        Span::raw(value.clone()),
-                    ]));
-                }
-            }
+            lines.extend(section_title("Method"));
+            let mut tree = ValueTree::new(&self.values, &decimals);
+            method_tree(&mut tree, decoded, tx.to);
+            tree.append_to(&mut lines, &mut rows);
         }
 
-        // ---- Section 5: Token Transfers ----
-        if !detail.token_transfers.is_empty() {
-            lines.push(Line::from(""));
-            lines.push(Line::from(Span::styled(
-                "  Token Transfers",
-                Style::default().fg(THEME.text).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
-            )));
-            lines.push(Line::from(""));
-
-            for transfer in &detail.token_transfers {
-                let symbol = transfer
-                    .token_symbol
-                    .as_deref()
-                    .unwrap_or("TOKEN");
-
-                let decimals = transfer.decimals.unwrap_or(18);
-                let amount = utils::format_u256_as_decimal(transfer.value, decimals);
-
-                lines.push(Line::from(vec![
-                    Span::styled(format!("  {symbol} "), THEME.accent_style()),
-                    Span::styled(
-                        utils::truncate_address(&transfer.from),
-                        THEME.address_style(),
-                    ),
-                    Span::raw(" \u{2192} "),
-                    Span::styled(
-                        utils::truncate_address(&transfer.to),
-                        THEME.address_style(),
-                    ),
-                    Span::raw(format!("  {amount}")),
-                ]));
-            }
-        }
+        // ---- Section 5: Token and NFT Transfers ----
+        lines.extend(transfer_sections(&detail.token_transfers));
 
         // ---- Section 6: Internal Transactions ----
         if !self.internal_calls.is_empty() {
-            lines.push(Line::from(""));
-            lines.push(Line::from(Span::styled(
-                "  Internal Transactions",
-                Style::default().fg(THEME.text).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
-            )));
-            lines.push(Line::from(""));
-
-            for call in &self.internal_calls {
-                let indent = "  ".repeat(call.depth + 1);
-                let value_str = if call.value.is_zero() {
-                    String::new()
-                } else {
-                    format!("  {}", utils::format_eth(call.value))
-                };
-                let error_str = if let Some(ref err) = call.error {
-                    format!(" [ERR: {err}]")
-                } else {
-                    String::new()
-                };
-                lines.push(Line::from(vec![
-                    Span::raw(format!("{indent}")),
-                    Span::styled(
-                        call.call_type.clone(),
-                        Style::default().fg(THEME.warning).add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw(" "),
-                    Span::styled(
-                        utils::truncate_address(&call.from),
-                        THEME.address_style(),
-                    ),
-                    Span::raw(" \u{2192} "),
-                    Span::styled(
-                        utils::truncate_address(&call.to),
-                        THEME.address_style(),
-                    ),
-                    Span::styled(value_str, THEME.eth_style()),
-                    Span::styled(error_str, THEME.error_style()),
-                ]));
-            }
+            lines.extend(section_title("Internal Transactions"));
+            lines.extend(internal_call_lines(&self.internal_calls));
         }
 
         // ---- Section 7: Events (Decoded Logs) ----
         if !self.decoded_logs.is_empty() {
-            lines.push(Line::from(""));
-            lines.push(Line::from(Span::styled(
-                "  Events",
-                Style::default().fg(THEME.text).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
-            )));
-            lines.push(Line::from(""));
-
-            for log in &self.decoded_logs {
-                lines.push(Line::from(vec![
-                    Span::styled(
-                        format!("  {} ", log.event_name),
-                        THEME.accent_style(),
-                    ),
-                    Span::styled(
-                        utils::truncate_address(&log.address),
-                        THEME.address_style(),
-                    ),
-                ]));
-
-                for (name, value) in &log.params {
-                    lines.push(Line::from(vec![
-                        Span::raw("      "),
-                        Span::styled(format!("{name}: "), THEME.muted_style()),
-                        Span::raw(value.clone()),
-                    ]));
-                }
-            }
+            lines.extend(section_title("Events"));
+            let mut tree = ValueTree::new(&self.values, &decimals);
+            event_tree(&mut tree, &self.decoded_logs);
+            tree.append_to(&mut lines, &mut rows);
         }
 
         // ---- Section 8: Raw Input ----
@@ -363,7 +318,252 @@ impl TxDetailView {
 
         lines.push(Line::from(""));
 
-        lines
+        (lines, rows)
+    }
+}
+
+/// Blank-line-padded, underlined section heading.
+pub fn section_title(title: &str) -> Vec<Line<'static>> {
+    vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("  {title}"),
+            Style::default().fg(THEME.text).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        )),
+        Line::from(""),
+    ]
+}
+
+/// Decoded function name and parameters.
+pub fn method_lines(decoded: &DecodedCall) -> Vec<Line<'static>> {
+    let (state, decimals) = (ValueTreeState::default(), HashMap::new());
+    let mut tree = ValueTree::new(&state, &decimals);
+    method_tree(&mut tree, decoded, None);
+    tree.lines
+}
+
+/// Decoded function name, parameters and inner calls of a call to `to`,
+/// with values under the `m` path.
+fn method_tree(tree: &mut ValueTree, decoded: &DecodedCall, to: Option<Address>) {
+    tree.lines.push(Line::from(vec![
+        Span::styled("  Function:  ", THEME.muted_style()),
+        Span::styled(
+            decoded.function_name.clone(),
+            THEME.accent_style(),
+        ),
+    ]));
+
+    if !decoded.params.is_empty() {
+        tree.lines.push(Line::from(Span::styled("  Parameters:", THEME.muted_style())));
+        tree.params(&decoded.params, "m", 6, to);
+    }
+
+    if !decoded.inner.is_empty() {
+        tree.lines.push(Line::from(Span::styled("  Inner Calls:", THEME.muted_style())));
+        inner_call_tree(tree, &decoded.inner, "m", 1);
+    }
+}
+
+/// Calls carried in a call's arguments as a tree: target, function and value,
+/// then parameters and further inner calls, indented one level deeper.
+fn inner_call_tree(tree: &mut ValueTree, calls: &[InnerCall], path: &str, depth: usize) {
+    let indent = "    ".repeat(depth);
+    for (i, call) in calls.iter().enumerate() {
+        let name = match call.decoded {
+            Some(ref d) => format!(".{}()", d.function_name),
+            None => match call.input.get(..4).and_then(|s| <&[u8; 4]>::try_from(s).ok()) {
+                Some(selector) => format!(".{}", utils::format_selector(selector)),
+                None => " (transfer)".to_string(),
+            },
+        };
+        let value = if call.value.is_zero() {
+            String::new()
+        } else {
+            format!("  {}", utils::format_eth(call.value))
+        };
+        tree.lines.push(Line::from(vec![
+            Span::styled(format!("  {indent}{}. ", i + 1), THEME.muted_style()),
+            Span::styled(utils::truncate_address(&call.target), THEME.address_style()),
+            Span::styled(name, THEME.accent_style()),
+            Span::styled(value, THEME.eth_style()),
+        ]));
+
+        let Some(ref decoded) = call.decoded else {
+            continue;
+        };
+        let path = format!("{path}/c{i}");
+        tree.params(&decoded.params, &path, 4 * depth + 6, Some(call.target));
+        inner_call_tree(tree, &decoded.inner, &path, depth + 1);
+    }
+}
+
+/// "Token Transfers" for ERC-20 and "NFT Transfers" for ERC-721 and
+/// ERC-1155, each only when there are any.
+pub fn transfer_sections(transfers: &[TokenTransfer]) -> Vec<Line<'static>> {
+    let (tokens, nfts): (Vec<&TokenTransfer>, Vec<&TokenTransfer>) = transfers
+        .iter()
+        .partition(|t| t.standard == TokenStandard::ERC20);
+    let mut lines = Vec::new();
+    if !tokens.is_empty() {
+        lines.extend(section_title("Token Transfers"));
+        lines.extend(token_transfer_lines(&tokens));
+    }
+    if !nfts.is_empty() {
+        lines.extend(section_title("NFT Transfers"));
+        lines.extend(nft_transfer_lines(&nfts));
+    }
+    lines
+}
+
+/// Token, parties and amount of each ERC-20 transfer. Until the token's
+/// metadata arrives the token is shown by address and the amount in raw units.
+fn token_transfer_lines(transfers: &[&TokenTransfer]) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for transfer in transfers {
+        let token = match transfer.token_symbol {
+            Some(ref symbol) => symbol.clone(),
+            None => utils::truncate_address(&transfer.token_address),
+        };
+        let amount = match (transfer.decimals, transfer.token_symbol.as_deref()) {
+            (Some(decimals), Some(symbol)) => format!(
+                "{} {symbol}",
+                utils::format_u256_as_decimal(transfer.value, decimals)
+            ),
+            (Some(decimals), None) => utils::format_u256_as_decimal(transfer.value, decimals),
+            (None, _) => transfer.value.to_string(),
+        };
+
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {token} "), THEME.accent_style()),
+            Span::styled(
+                utils::truncate_address(&transfer.from),
+                THEME.address_style(),
+            ),
+            Span::raw(" \u{2192} "),
+            Span::styled(
+                utils::truncate_address(&transfer.to),
+                THEME.address_style(),
+            ),
+            Span::raw(format!("  {amount}")),
+        ]));
+    }
+    lines
+}
+
+/// Collection, token id and parties of each NFT moved; ERC-1155 amounts
+/// other than one are shown after the id.
+fn nft_transfer_lines(transfers: &[&TokenTransfer]) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for transfer in transfers {
+        let collection = match transfer.token_symbol {
+            Some(ref symbol) => symbol.clone(),
+            None => transfer.standard.to_string(),
+        };
+        let id = transfer
+            .token_id
+            .map(|id| format!("#{id}"))
+            .unwrap_or_default();
+        let amount = if transfer.standard == TokenStandard::ERC1155 && transfer.value != U256::from(1) {
+            format!("  x{}", transfer.value)
+        } else {
+            String::new()
+        };
+
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {collection} "), THEME.accent_style()),
+            Span::styled(
+                utils::truncate_address(&transfer.token_address),
+                THEME.address_style(),
+            ),
+            Span::raw(format!(" {id}{amount}  ")),
+            Span::styled(
+                utils::truncate_address(&transfer.from),
+                THEME.address_style(),
+            ),
+            Span::raw(" \u{2192} "),
+            Span::styled(
+                utils::truncate_address(&transfer.to),
+                THEME.address_style(),
+            ),
+        ]));
+    }
+    lines
+}
+
+/// Internal calls indented by depth, with errors and revert reasons.
+pub fn internal_call_lines(calls: &[InternalCall]) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for call in calls {
+        let indent = "  ".repeat(call.depth + 1);
+        let value_str = if call.value.is_zero() {
+            String::new()
+        } else {
+            format!("  {}", utils::format_eth(call.value))
+        };
+        let error_str = if let Some(ref err) = call.error {
+            match call.revert_reason {
+                Some(ref reason) => format!(" [ERR: {err}: {reason}]"),
+                None => format!(" [ERR: {err}]"),
+            }
+        } else {
+            String::new()
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!("{indent}")),
+            Span::styled(
+                call.call_type.clone(),
+                Style::default().fg(THEME.warning).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" "),
+            Span::styled(
+                utils::truncate_address(&call.from),
+                THEME.address_style(),
+            ),
+            Span::raw(" \u{2192} "),
+            Span::styled(
+                utils::truncate_address(&call.to),
+                THEME.address_style(),
+            ),
+            Span::styled(
+                call.decoded
+                    .as_ref()
+                    .map(|d| format!(".{}()", d.function_name))
+                    .unwrap_or_default(),
+                THEME.accent_style(),
+            ),
+            Span::styled(value_str, THEME.eth_style()),
+            Span::styled(error_str, THEME.error_style()),
+        ]));
+    }
+    lines
+}
+
+/// Event name and emitter, followed by the decoded parameters.
+pub fn event_lines(logs: &[DecodedLog]) -> Vec<Line<'static>> {
+    let (state, decimals) = (ValueTreeState::default(), HashMap::new());
+    let mut tree = ValueTree::new(&state, &decimals);
+    event_tree(&mut tree, logs);
+    tree.lines
+}
+
+/// Events with their values under `l{i}`, scaled by the emitter's decimals.
+fn event_tree(tree: &mut ValueTree, logs: &[DecodedLog]) {
+    for (i, log) in logs.iter().enumerate() {
+        let mut header = vec![
+            Span::styled(
+                format!("  {} ", log.event_name),
+                THEME.accent_style(),
+            ),
+            Span::styled(
+                utils::truncate_address(&log.address),
+                THEME.address_style(),
+            ),
+        ];
+        if log.heuristic {
+            header.push(Span::styled("  (guessed indexed inputs)", THEME.muted_style()));
+        }
+        tree.lines.push(Line::from(header));
+        tree.params(&log.params, &format!("l{i}"), 6, Some(log.address));
     }
 }
 
@@ -371,6 +571,41 @@ impl Component for TxDetailView {
     fn handle_key(&mut self, key: KeyEvent) -> Option<AppEvent> {
         match (key.code, key.modifiers) {
             (KeyCode::Esc, _) | (KeyCode::Backspace, _) => Some(AppEvent::Back),
+            (KeyCode::Tab, _) => {
+                // Toggle selecting decoded values
+                self.selecting = !self.selecting && !self.rows.is_empty();
+                self.values.selected = None;
+                if self.selecting {
+                    self.move_selection(0);
+                }
+                None
+            }
+            (KeyCode::Char('j'), _) | (KeyCode::Down, _) if self.selecting => {
+                self.move_selection(1);
+                None
+            }
+            (KeyCode::Char('k'), _) | (KeyCode::Up, _) if self.selecting => {
+                self.move_selection(-1);
+                None
+            }
+            (KeyCode::Enter, _) => {
+                let row = self.selected_row()?;
+                let path = row.path.clone();
+                match row.kind {
+                    RowKind::Branch => self.values.toggle_expanded(&path),
+                    RowKind::LongBytes => self.values.toggle_full_bytes(&path),
+                    RowKind::Address(address) => {
+                        return Some(AppEvent::Navigate(View::AddressView(address)));
+                    }
+                    RowKind::Other => {}
+                }
+                None
+            }
+            (KeyCode::Char('u'), KeyModifiers::NONE) => {
+                // Show uint values in token units
+                self.values.token_units = !self.values.token_units;
+                None
+            }
             (KeyCode::Char('j'), _) | (KeyCode::Down, _) => {
                 if self.scroll < self.max_scroll {
                     self.scroll += 1;
@@ -399,6 +634,40 @@ impl Component for TxDetailView {
                 self.scroll = self.max_scroll;
                 None
             }
+            (KeyCode::Char('d'), KeyModifiers::NONE) => {
+                // Open the opcode-level debugger for this transaction
+                self.detail
+                    .as_ref()
+                    .map(|d| AppEvent::Navigate(View::TxDebugger(d.summary.hash)))
+            }
+            (KeyCode::Char('t'), KeyModifiers::NONE) => {
+                // Open the call tree for this transaction
+                self.detail
+                    .as_ref()
+                    .map(|d| AppEvent::Navigate(View::CallTree(d.summary.hash)))
+            }
+            (KeyCode::Char('p'), KeyModifiers::NONE) => {
+                // Open the gas profile for this transaction
+                self.detail
+                    .as_ref()
+                    .map(|d| AppEvent::Navigate(View::GasProfile(d.summary.hash)))
+            }
+            (KeyCode::Char('x'), KeyModifiers::NONE) => {
+                // Re-run this transaction as a simulation on the parent block's state
+                self.detail.as_ref().map(|d| AppEvent::OpenSimulation {
+                    from: Some(d.summary.from),
+                    to: d.summary.to,
+                    value: d.summary.value,
+                    input: d.input_data.clone(),
+                    block: d.summary.block_number.map(|n| n.saturating_sub(1)),
+                })
+            }
+            (KeyCode::Char('D'), _) => {
+                // Open the state diff for this transaction
+                self.detail
+                    .as_ref()
+                    .map(|d| AppEvent::Navigate(View::StateDiff(d.summary.hash)))
+            }
             _ => None,
         }
     }
@@ -426,10 +695,20 @@ impl Component for TxDetailView {
             None => return,
         };
 
-        let lines = self.build_lines(detail);
+        let (lines, rows) = self.build_lines(detail);
+        self.rows = rows;
         let total_lines = lines.len() as u16;
         self.max_scroll = total_lines.saturating_sub(inner.height);
 
+        // Keep the selected value on screen
+        if let Some(line) = self.selected_row().map(|row| row.line as u16) {
+            if line < self.scroll {
+                self.scroll = line;
+            } else if line >= self.scroll + inner.height {
+                self.scroll = line + 1 - inner.height;
+            }
+        }
+
         // Clamp scroll
         if self.scroll > self.max_scroll {
             self.scroll = self.max_scroll;
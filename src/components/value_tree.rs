@@ -0,0 +1,489 @@
+use std::collections::{HashMap, HashSet};
+
+use alloy::dyn_abi::DynSolValue;
+use alloy::json_abi::{InternalType, Param};
+use alloy::primitives::Address;
+use ratatui::prelude::*;
+
+use crate::data::decoder::format_sol_value;
+use crate::data::types::DecodedParam;
+use crate::theme::THEME;
+use crate::utils;
+
+/// Bytes shown before a long value is cut off, unless expanded.
+const BYTES_PREVIEW: usize = 32;
+
+/// Hex characters per line of an expanded bytes value.
+const HEX_LINE_WIDTH: usize = 64;
+
+/// Characters of a collapsed tuple or array summary.
+const SUMMARY_WIDTH: usize = 80;
+
+/// Name fragments of params holding token amounts, the only uint values
+/// scaled by token decimals.
+const AMOUNT_NAMES: &[&str] = &[
+    "amount",
+    "value",
+    "wad",
+    "balance",
+    "supply",
+    "allowance",
+    "fee",
+    "reserve",
+];
+
+/// What a selectable row holds, for deciding what a key press does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowKind {
+    /// Tuple or array that expands into its elements
+    Branch,
+    Address(Address),
+    /// Bytes too long to show in full by default
+    LongBytes,
+    Other,
+}
+
+/// A selectable value: the line it starts on and its path in the tree.
+#[derive(Debug, Clone)]
+pub struct ValueRow {
+    pub line: usize,
+    pub path: String,
+    pub kind: RowKind,
+}
+
+/// Expanded branches, fully shown bytes and the selected value of a tree.
+/// Values are keyed by path, e.g. `m/0/1` for the second field of the first
+/// argument of the method.
+#[derive(Debug, Default)]
+pub struct ValueTreeState {
+    expanded: HashSet<String>,
+    full_bytes: HashSet<String>,
+    /// Scale amount-like uint values by the token decimals of the contract
+    /// they belong to
+    pub token_units: bool,
+    pub selected: Option<String>,
+}
+
+impl ValueTreeState {
+    pub fn toggle_expanded(&mut self, path: &str) {
+        if !self.expanded.remove(path) {
+            self.expanded.insert(path.to_string());
+        }
+    }
+
+    pub fn toggle_full_bytes(&mut self, path: &str) {
+        if !self.full_bytes.remove(path) {
+            self.full_bytes.insert(path.to_string());
+        }
+    }
+}
+
+/// Builds the lines of decoded values and records which are selectable.
+pub struct ValueTree<'a> {
+    state: &'a ValueTreeState,
+    /// Known token decimals by contract; values of others are never scaled
+    decimals: &'a HashMap<Address, u8>,
+    /// Decimals of the contract whose params are being built
+    token: Option<u8>,
+    pub lines: Vec<Line<'static>>,
+    pub rows: Vec<ValueRow>,
+}
+
+impl<'a> ValueTree<'a> {
+    pub fn new(state: &'a ValueTreeState, decimals: &'a HashMap<Address, u8>) -> Self {
+        Self {
+            state,
+            decimals,
+            token: None,
+            lines: Vec::new(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Move the built lines onto `lines`, offsetting the rows to match.
+    pub fn append_to(self, lines: &mut Vec<Line<'static>>, rows: &mut Vec<ValueRow>) {
+        let offset = lines.len();
+        lines.extend(self.lines);
+        rows.extend(self.rows.into_iter().map(|row| ValueRow {
+            line: row.line + offset,
+            ..row
+        }));
+    }
+
+    /// One row per parameter under `path`, indented by `indent` columns.
+    /// `contract` is the called or emitting contract, whose token decimals,
+    /// when known, scale the uint values of amount-like params.
+    pub fn params(
+        &mut self,
+        params: &[DecodedParam],
+        path: &str,
+        indent: usize,
+        contract: Option<Address>,
+    ) {
+        self.token = contract.and_then(|c| self.decimals.get(&c).copied());
+        for (i, p) in params.iter().enumerate() {
+            let path = format!("{path}/{i}");
+            let Some(value) = &p.value else {
+                let spans = vec![Span::styled("?", THEME.muted_style())];
+                self.leaf(p.name(), spans, path, indent, RowKind::Other);
+                continue;
+            };
+            if p.is_topic_hash() {
+                let spans = vec![
+                    Span::raw(format_sol_value(value)),
+                    Span::styled(" (hash)", THEME.muted_style()),
+                ];
+                self.leaf(p.name(), spans, path, indent, RowKind::Other);
+            } else {
+                let decimals = self.amount_decimals(p.name());
+                self.value(p.name(), &p.param, value, path, indent, decimals);
+            }
+        }
+    }
+
+    /// The token decimals to scale a value named `name` by, if it looks like
+    /// an amount and the contract is a known token.
+    fn amount_decimals(&self, name: &str) -> Option<u8> {
+        let name = name.to_lowercase();
+        let amount_like = AMOUNT_NAMES.iter().any(|n| name.contains(n));
+        self.token.filter(|_| amount_like)
+    }
+
+    fn value(
+        &mut self,
+        name: &str,
+        param: &Param,
+        value: &DynSolValue,
+        path: String,
+        indent: usize,
+        decimals: Option<u8>,
+    ) {
+        if let (DynSolValue::Uint(amount, _), Some(decimals)) = (value, decimals)
+            && self.state.token_units
+        {
+            let spans = vec![
+                Span::raw(utils::format_u256_as_decimal(*amount, decimals)),
+                Span::styled(format!("  ({decimals} decimals)"), THEME.muted_style()),
+            ];
+            self.leaf(name, spans, path, indent, RowKind::Other);
+            return;
+        }
+        match value {
+            DynSolValue::Tuple(_) | DynSolValue::Array(_) | DynSolValue::FixedArray(_) => {
+                self.branch(name, param, value, path, indent, decimals)
+            }
+            DynSolValue::Address(address) => {
+                let spans = vec![Span::styled(format!("{address}"), THEME.address_style())];
+                self.leaf(name, spans, path, indent, RowKind::Address(*address));
+            }
+            DynSolValue::Bytes(bytes) if bytes.len() > BYTES_PREVIEW => {
+                self.long_bytes(name, bytes, path, indent)
+            }
+            other => {
+                let spans = vec![Span::raw(format_sol_value(other))];
+                self.leaf(name, spans, path, indent, RowKind::Other);
+            }
+        }
+    }
+
+    /// A tuple or array: its summary when collapsed, its elements when expanded.
+    fn branch(
+        &mut self,
+        name: &str,
+        param: &Param,
+        value: &DynSolValue,
+        path: String,
+        indent: usize,
+        decimals: Option<u8>,
+    ) {
+        let items = match value {
+            DynSolValue::Tuple(items)
+            | DynSolValue::Array(items)
+            | DynSolValue::FixedArray(items) => items.as_slice(),
+            _ => &[],
+        };
+        let expanded = self.state.expanded.contains(&path);
+        let is_tuple = matches!(value, DynSolValue::Tuple(_));
+        let summary = if expanded {
+            let kind = match param.internal_type {
+                Some(InternalType::Struct { ref ty, .. }) if is_tuple => format!("struct {ty}"),
+                _ if is_tuple => "tuple".to_string(),
+                _ => param.selector_type().into_owned(),
+            };
+            let count = match (is_tuple, items.len()) {
+                (true, 1) => "1 field".to_string(),
+                (true, n) => format!("{n} fields"),
+                (false, 1) => "1 item".to_string(),
+                (false, n) => format!("{n} items"),
+            };
+            Span::styled(format!("{kind} ({count})"), THEME.muted_style())
+        } else {
+            Span::raw(utils::shorten(&format_sol_value(value), SUMMARY_WIDTH))
+        };
+        let marker = if expanded { "\u{25be} " } else { "\u{25b8} " };
+        self.row(
+            name,
+            marker,
+            vec![summary],
+            path.clone(),
+            indent,
+            RowKind::Branch,
+        );
+        if !expanded {
+            return;
+        }
+
+        for (i, item) in items.iter().enumerate() {
+            // Array elements share the array's name; struct fields have their own
+            let (child, child_decimals) = if is_tuple {
+                let mut child = param.components.get(i).cloned().unwrap_or_else(|| Param {
+                    ty: item.sol_type_name().unwrap_or_default().into_owned(),
+                    name: String::new(),
+                    components: Vec::new(),
+                    internal_type: None,
+                });
+                if child.name.is_empty() {
+                    child.name = format!("[{i}]");
+                }
+                let child_decimals = self.amount_decimals(&child.name);
+                (child, child_decimals)
+            } else {
+                let child = Param {
+                    ty: element_type(&param.ty).to_string(),
+                    name: format!("[{i}]"),
+                    components: param.components.clone(),
+                    internal_type: None,
+                };
+                (child, decimals)
+            };
+            self.value(
+                &child.name,
+                &child,
+                item,
+                format!("{path}/{i}"),
+                indent + 4,
+                child_decimals,
+            );
+        }
+    }
+
+    /// Bytes longer than the preview, cut off unless expanded; expanded
+    /// bytes continue over as many lines as they need.
+    fn long_bytes(&mut self, name: &str, bytes: &[u8], path: String, indent: usize) {
+        let size = Span::styled(format!("  ({} bytes)", bytes.len()), THEME.muted_style());
+        if !self.state.full_bytes.contains(&path) {
+            let preview = format!("0x{}\u{2026}", alloy::hex::encode(&bytes[..BYTES_PREVIEW]));
+            self.leaf(
+                name,
+                vec![Span::raw(preview), size],
+                path,
+                indent,
+                RowKind::LongBytes,
+            );
+            return;
+        }
+
+        let hex = format!("0x{}", alloy::hex::encode(bytes));
+        let mut chunks = hex
+            .as_bytes()
+            .chunks(HEX_LINE_WIDTH)
+            .map(|chunk| String::from_utf8_lossy(chunk).into_owned());
+        let first = chunks.next().unwrap_or_default();
+        self.leaf(
+            name,
+            vec![Span::raw(first), size],
+            path,
+            indent,
+            RowKind::LongBytes,
+        );
+        for chunk in chunks {
+            self.lines
+                .push(Line::from(format!("{}      {chunk}", " ".repeat(indent))));
+        }
+    }
+
+    fn leaf(
+        &mut self,
+        name: &str,
+        spans: Vec<Span<'static>>,
+        path: String,
+        indent: usize,
+        kind: RowKind,
+    ) {
+        self.row(name, "  ", spans, path, indent, kind);
+    }
+
+    fn row(
+        &mut self,
+        name: &str,
+        marker: &str,
+        value: Vec<Span<'static>>,
+        path: String,
+        indent: usize,
+        kind: RowKind,
+    ) {
+        let mut spans = vec![
+            Span::raw(format!("{}{marker}", " ".repeat(indent))),
+            Span::styled(format!("{name}: "), THEME.muted_style()),
+        ];
+        spans.extend(value);
+        let mut line = Line::from(spans);
+        if self.state.selected.as_deref() == Some(path.as_str()) {
+            line = line.style(THEME.selected_style());
+        }
+        self.rows.push(ValueRow {
+            line: self.lines.len(),
+            path,
+            kind,
+        });
+        self.lines.push(line);
+    }
+}
+
+/// Element type of an array type, e.g. `uint256` for `uint256[3]`.
+fn element_type(ty: &str) -> &str {
+    ty.rfind('[').map_or(ty, |i| &ty[..i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U256;
+
+    const TOKEN: Address = Address::repeat_byte(0x77);
+
+    fn order() -> DecodedParam {
+        let param: Param = serde_json::from_value(serde_json::json!({
+            "name": "order",
+            "type": "tuple",
+            "internalType": "struct Order",
+            "components": [
+                {"name": "maker", "type": "address"},
+                {"name": "amounts", "type": "uint256[]"},
+                {"name": "fee", "type": "tuple", "components": [
+                    {"name": "bps", "type": "uint16"},
+                ]},
+            ],
+        }))
+        .unwrap();
+        DecodedParam {
+            param,
+            value: Some(DynSolValue::Tuple(vec![
+                DynSolValue::Address(Address::repeat_byte(0x44)),
+                DynSolValue::Array(vec![
+                    DynSolValue::Uint(U256::from(1_500_000), 256),
+                    DynSolValue::Uint(U256::from(2), 256),
+                ]),
+                DynSolValue::Tuple(vec![DynSolValue::Uint(U256::from(30), 16)]),
+            ])),
+        }
+    }
+
+    fn build(
+        state: &ValueTreeState,
+        decimals: &HashMap<Address, u8>,
+        params: &[DecodedParam],
+    ) -> (Vec<String>, Vec<ValueRow>) {
+        let mut tree = ValueTree::new(state, decimals);
+        tree.params(params, "m", 0, Some(TOKEN));
+        let text = tree
+            .lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        (text, tree.rows)
+    }
+
+    #[test]
+    fn test_expand_nested_tuples_and_arrays() {
+        let params = [order()];
+        let decimals = HashMap::new();
+        let mut state = ValueTreeState::default();
+
+        // Collapsed, the tuple is one summary row
+        let (text, rows) = build(&state, &decimals, &params);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].kind, RowKind::Branch);
+        assert!(text[0].contains("order: ("));
+
+        state.toggle_expanded("m/0");
+        let (text, rows) = build(&state, &decimals, &params);
+        let paths: Vec<_> = rows.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, ["m/0", "m/0/0", "m/0/1", "m/0/2"]);
+        assert!(text[0].contains("struct Order (3 fields)"));
+        assert_eq!(rows[1].kind, RowKind::Address(Address::repeat_byte(0x44)));
+        assert_eq!(rows[2].kind, RowKind::Branch);
+
+        // Array items take the array's name with their index; struct fields keep theirs
+        state.toggle_expanded("m/0/1");
+        state.toggle_expanded("m/0/2");
+        let (text, rows) = build(&state, &decimals, &params);
+        let paths: Vec<_> = rows.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["m/0", "m/0/0", "m/0/1", "m/0/1/0", "m/0/1/1", "m/0/2", "m/0/2/0"]
+        );
+        assert!(text[2].contains("uint256[] (2 items)"));
+        assert!(text[3].contains("[0]: 1500000"));
+        assert!(text[6].contains("bps: 30"));
+        assert!(text[6].starts_with("          "));
+
+        // Collapsing the parent hides expanded children too
+        state.toggle_expanded("m/0");
+        assert_eq!(build(&state, &decimals, &params).1.len(), 1);
+    }
+
+    #[test]
+    fn test_token_units_scale_amounts() {
+        let amount = |name: &str| {
+            DecodedParam::new(name, "uint256", DynSolValue::Uint(U256::from(1_500_000), 256))
+        };
+        let params = [amount("amount"), amount("id")];
+        let decimals = HashMap::from([(TOKEN, 6)]);
+        let mut state = ValueTreeState::default();
+
+        // Raw values until token units are switched on
+        let (text, _) = build(&state, &decimals, &params);
+        assert!(text[0].ends_with("amount: 1500000"));
+
+        state.token_units = true;
+        let (text, _) = build(&state, &decimals, &params);
+        assert!(text[0].ends_with("amount: 1.5  (6 decimals)"));
+        // Only amount-like names are scaled
+        assert!(text[1].ends_with("id: 1500000"));
+
+        // Elements of an amount array are scaled like the array; other fields are not
+        let params = [order()];
+        state.toggle_expanded("m/0");
+        state.toggle_expanded("m/0/1");
+        state.toggle_expanded("m/0/2");
+        let (text, _) = build(&state, &decimals, &params);
+        assert!(text[3].ends_with("[0]: 1.5  (6 decimals)"));
+        assert!(text[4].ends_with("[1]: 0.000002  (6 decimals)"));
+        assert!(text[6].ends_with("bps: 30"));
+
+        // Contracts without known decimals are never scaled
+        let (text, _) = build(&state, &HashMap::new(), &[amount("amount")]);
+        assert!(text[0].ends_with("amount: 1500000"));
+    }
+
+    #[test]
+    fn test_long_bytes_preview_and_full() {
+        let params = [DecodedParam::new("data", "bytes", DynSolValue::Bytes(vec![0xab; 40]))];
+        let decimals = HashMap::new();
+        let mut state = ValueTreeState::default();
+
+        let (text, rows) = build(&state, &decimals, &params);
+        assert_eq!(rows[0].kind, RowKind::LongBytes);
+        assert_eq!(text.len(), 1);
+        assert!(text[0].ends_with(&format!("data: 0x{}\u{2026}  (40 bytes)", "ab".repeat(32))));
+
+        // In full, the hex wraps onto a continuation line
+        state.toggle_full_bytes("m/0");
+        let (text, rows) = build(&state, &decimals, &params);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(text.len(), 2);
+        assert!(text[0].ends_with(&format!("data: 0x{}  (40 bytes)", "ab".repeat(31))));
+        assert_eq!(text[1].trim_start(), "ab".repeat(9));
+    }
+}
@@ -1,4 +1,7 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use alloy::primitives::Address;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[command(name = "eth-tui", about = "Terminal Ethereum blockchain explorer")]
@@ -7,10 +10,15 @@ pub struct Config {
     #[arg(short, long, default_value = "https://eth.merkle.io")]
     pub rpc_url: String,
 
-    /// Etherscan API key (optional, for ABI resolution)
+    /// Etherscan API key (optional, for ABIs, verified sources and address
+    /// history on any chain Etherscan v2 covers)
     #[arg(long, env = "ETHERSCAN_API_KEY")]
     pub etherscan_api_key: Option<String>,
 
+    /// Blockscout instance to use instead of Etherscan, e.g. https://eth.blockscout.com
+    #[arg(long, env = "BLOCKSCOUT_URL")]
+    pub blockscout_url: Option<String>,
+
     /// Start with a specific search query
     #[arg(short, long)]
     pub search: Option<String>,
@@ -24,6 +32,68 @@ pub struct Config {
     pub ws_url: Option<String>,
 
     /// Chain preset (ethereum, arbitrum, optimism, base, polygon)
-    #[arg(long, default_value = "ethereum")]
+    #[arg(long, default_value = "ethereum", global = true)]
     pub chain: String,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Manage the local ABI library
+    #[command(subcommand)]
+    Abi(AbiCommand),
+    /// Manage the offline signature database
+    #[command(subcommand)]
+    Sig(SigCommand),
+    /// Manage the on-disk cache of fetched ABIs and signature lookups
+    #[command(subcommand)]
+    Cache(CacheCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AbiCommand {
+    /// Import deployed contracts from a Foundry or Hardhat project
+    Import {
+        /// Project root, or its out/ or artifacts/ directory
+        path: PathBuf,
+    },
+    /// Save the ABI of one contract from an ABI or artifact JSON file
+    Add {
+        address: Address,
+        file: PathBuf,
+        /// Chain the contract is deployed on [default: the --chain preset's]
+        #[arg(long)]
+        chain_id: Option<u64>,
+    },
+    /// List the contracts in the local library
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SigCommand {
+    /// Import declarations from a text file (one per line) or a JSON ABI
+    Import { file: PathBuf },
+    /// Look up a 4-byte selector or 32-byte event topic
+    Lookup { selector: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    /// Forget the cached ABI of an address, or the cached lookup of a
+    /// 4-byte selector or 32-byte event topic
+    Purge {
+        target: String,
+        /// Chain of the address [default: the --chain preset's]
+        #[arg(long)]
+        chain_id: Option<u64>,
+    },
+    /// Fetch the ABI of an address again, replacing the cached one
+    Refresh {
+        address: Address,
+        /// Chain of the address [default: the --chain preset's]
+        #[arg(long)]
+        chain_id: Option<u64>,
+    },
 }
@@ -1,24 +1,131 @@
 use std::num::NonZeroUsize;
-use std::sync::{Mutex, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock};
 
-use alloy::json_abi::JsonAbi;
-use alloy::primitives::Address;
+use alloy::json_abi::{Function, JsonAbi};
+use alloy::primitives::{Address, Bytes, B256};
+use futures::StreamExt;
 use lru::LruCache;
+use serde::{Deserialize, Serialize};
+
+use crate::data::abi_cache::AbiCache;
+use crate::data::abi_store::AbiStore;
+use crate::data::explorer::ExplorerClient;
+use crate::data::interface;
+use crate::data::provider::EthProvider;
+use crate::data::proxy;
+use crate::data::signatures;
+use crate::data::sourcemap::{self, ContractSource};
+use crate::data::storage_layout::{self, StorageLayout};
+use crate::data::types::ProxyInfo;
+
+/// Source of an ABI assembled from the selectors in a contract's bytecode.
+pub const INFERRED_SOURCE: &str = "inferred";
+
+/// Source of the ERC-20 ABI returned when nothing better is known.
+pub const FALLBACK_SOURCE: &str = "built-in ERC-20";
+
+/// Selectors looked up at once while inferring an interface, so a large
+/// dispatcher doesn't fire hundreds of 4byte requests together.
+const INFER_CONCURRENCY: usize = 8;
+
+/// The answer of one remote source to a lookup. Only `NotFound` is worth
+/// remembering as a failure; a `Transient` one may succeed when asked again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lookup<T> {
+    Found(T),
+    /// The source answered and has nothing
+    NotFound,
+    /// The source could not answer: a timeout, rate limit or server error
+    Transient,
+}
+
+impl<T> Lookup<T> {
+    /// The outcome of an unsuccessful HTTP status: 404 says the source has
+    /// nothing, anything else (429, 5xx) may pass.
+    pub fn from_status(status: reqwest::StatusCode) -> Self {
+        if status == reqwest::StatusCode::NOT_FOUND {
+            Self::NotFound
+        } else {
+            Self::Transient
+        }
+    }
+
+    /// Extract from what was found; `None` from `f` means the answer holds
+    /// nothing usable.
+    pub fn and_then<U>(self, f: impl FnOnce(T) -> Option<U>) -> Lookup<U> {
+        match self {
+            Self::Found(value) => f(value).map_or(Lookup::NotFound, Lookup::Found),
+            Self::NotFound => Lookup::NotFound,
+            Self::Transient => Lookup::Transient,
+        }
+    }
+
+    /// This lookup if it found something, else `other`, staying transient
+    /// if either was.
+    pub fn or(self, other: Self) -> Self {
+        match (self, other) {
+            (found @ Self::Found(_), _) | (_, found @ Self::Found(_)) => found,
+            (Self::NotFound, Self::NotFound) => Self::NotFound,
+            _ => Self::Transient,
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Transient)
+    }
+
+    pub fn found(self) -> Option<T> {
+        match self {
+            Self::Found(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// GET a JSON document. Unreachable sources and unparsable bodies are
+/// transient, as are statuses other than 404.
+pub async fn get_json(client: &reqwest::Client, url: &str) -> Lookup<serde_json::Value> {
+    let response = match client.get(url).send().await {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => return Lookup::from_status(response.status()),
+        Err(_) => return Lookup::Transient,
+    };
+    match response.json().await {
+        Ok(body) => Lookup::Found(body),
+        Err(_) => Lookup::Transient,
+    }
+}
 
 /// A resolved ABI along with the source it was obtained from.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolvedAbi {
     pub abi: JsonAbi,
     pub source: String,
 }
 
-/// Cascading ABI resolver: Sourcify -> Etherscan -> built-in ERC ABIs.
-/// Also resolves 4-byte function selectors via the 4byte.directory API.
+/// Cascading ABI resolver: local library -> Sourcify -> block explorer ->
+/// bytecode inference -> built-in ERC ABIs. Proxies get the ABIs of their
+/// implementation or facets merged in.
+/// Also resolves 4-byte function selectors and event topics via the
+/// 4byte.directory API.
 pub struct AbiResolver {
     client: reqwest::Client,
-    etherscan_api_key: Option<String>,
+    /// Etherscan or Blockscout for the current chain, if configured
+    explorer: Option<ExplorerClient>,
+    /// Node used to detect proxies; without one, ABIs are never merged
+    provider: Option<Arc<EthProvider>>,
+    /// ABIs imported from local projects, consulted before any remote source
+    store: Option<AbiStore>,
+    /// Remote lookups from earlier runs, consulted before asking again
+    disk_cache: Option<AbiCache>,
     cache: Mutex<LruCache<Address, Option<ResolvedAbi>>>,
     selector_cache: Mutex<LruCache<[u8; 4], Option<String>>>,
+    event_cache: Mutex<LruCache<B256, Option<String>>>,
+    source_cache: Mutex<LruCache<Address, Option<ContractSource>>>,
+    proxy_cache: Mutex<LruCache<Address, Option<ProxyInfo>>>,
+    inferred_cache: Mutex<LruCache<Address, Option<ResolvedAbi>>>,
+    code_cache: Mutex<LruCache<Address, Bytes>>,
+    layout_cache: Mutex<LruCache<Address, Option<StorageLayout>>>,
 }
 
 // --- Built-in ABI singletons ---
@@ -49,24 +156,73 @@ fn get_erc1155_abi() -> &'static JsonAbi {
 }
 
 impl AbiResolver {
-    pub fn new(etherscan_api_key: Option<String>) -> Self {
+    pub fn new(explorer: Option<ExplorerClient>) -> Self {
         Self {
             client: reqwest::Client::builder()
                 .timeout(std::time::Duration::from_secs(10))
                 .build()
                 .unwrap_or_default(),
-            etherscan_api_key,
+            explorer,
+            provider: None,
+            store: AbiStore::open(),
+            disk_cache: AbiCache::open(),
             cache: Mutex::new(LruCache::new(NonZeroUsize::new(500).unwrap())),
             selector_cache: Mutex::new(LruCache::new(NonZeroUsize::new(2000).unwrap())),
+            event_cache: Mutex::new(LruCache::new(NonZeroUsize::new(2000).unwrap())),
+            source_cache: Mutex::new(LruCache::new(NonZeroUsize::new(50).unwrap())),
+            proxy_cache: Mutex::new(LruCache::new(NonZeroUsize::new(500).unwrap())),
+            inferred_cache: Mutex::new(LruCache::new(NonZeroUsize::new(200).unwrap())),
+            code_cache: Mutex::new(LruCache::new(NonZeroUsize::new(200).unwrap())),
+            layout_cache: Mutex::new(LruCache::new(NonZeroUsize::new(50).unwrap())),
         }
     }
 
+    /// Detect proxies through `provider` and merge their targets' ABIs.
+    pub fn with_provider(mut self, provider: Arc<EthProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
     /// Resolve an ABI for a contract address using a cascading strategy:
     /// 1. In-memory cache
-    /// 2. Sourcify full-match metadata
-    /// 3. Etherscan (if API key is configured)
-    /// 4. Built-in ERC-20/721/1155 ABIs (returned as fallback)
+    /// 2. Local ABI library (~/.config/eth-tui/abis)
+    /// 3. On-disk cache of earlier remote lookups, including recent failures
+    /// 4. Sourcify full-match metadata
+    /// 5. The chain's block explorer (Etherscan with an API key, or Blockscout)
+    /// 6. Functions inferred from the dispatcher selectors in the bytecode
+    /// 7. Built-in ERC-20/721/1155 ABIs (returned as fallback)
+    ///
+    /// For a proxy, steps 1-5 also run for its implementation or facets and
+    /// the ABIs found are merged into the proxy's own.
     pub async fn resolve(&self, chain_id: u64, address: Address) -> Option<ResolvedAbi> {
+        let own = self.resolve_verified(chain_id, address).await;
+        let proxy = self.proxy(address).await;
+        let resolved = match proxy {
+            Some(ref proxy) => self.merge_targets(chain_id, own, proxy).await,
+            None => own,
+        };
+        if resolved.is_some() {
+            return resolved;
+        }
+
+        // 6. Infer an interface from the selectors the code dispatches on
+        if let Some(inferred) = self.infer(address, proxy.as_ref()).await {
+            return Some(inferred);
+        }
+
+        // 7. Fall back to built-in ABIs: try each to see if any function matches
+        //    We return the ERC-20 ABI as the most common fallback for contracts.
+        //    The caller can attempt decoding and see if it succeeds.
+        let fallback = ResolvedAbi {
+            abi: get_erc20_abi().clone(),
+            source: FALLBACK_SOURCE.to_string(),
+        };
+        // Don't cache the fallback so we can retry external sources later
+        Some(fallback)
+    }
+
+    /// Steps 1-5 of `resolve`: an ABI published for this very address.
+    async fn resolve_verified(&self, chain_id: u64, address: Address) -> Option<ResolvedAbi> {
         // 1. Check cache
         {
             let mut cache = self.cache.lock().ok()?;
@@ -75,27 +231,189 @@ impl AbiResolver {
             }
         }
 
-        // 2. Try Sourcify
-        if let Some(resolved) = self.try_sourcify(chain_id, address).await {
+        // 2. Try the local library
+        if let Some(resolved) = self.try_local(chain_id, address) {
             self.cache_abi(address, Some(resolved.clone()));
             return Some(resolved);
         }
 
-        // 3. Try Etherscan
-        if let Some(resolved) = self.try_etherscan(address).await {
+        // 3-5. Try the disk cache, then Sourcify and the block explorer
+        let cached = self
+            .disk_cache
+            .as_ref()
+            .and_then(|disk| disk.abi(chain_id, address));
+        let remote = match cached {
+            Some(cached) => cached,
+            None => self.fetch_remote(chain_id, address).await,
+        };
+        if let Some(ref resolved) = remote {
             self.cache_abi(address, Some(resolved.clone()));
-            return Some(resolved);
         }
+        remote
+    }
 
-        // 4. Fall back to built-in ABIs: try each to see if any function matches
-        //    We return the ERC-20 ABI as the most common fallback for contracts.
-        //    The caller can attempt decoding and see if it succeeds.
-        let fallback = ResolvedAbi {
-            abi: get_erc20_abi().clone(),
-            source: "built-in ERC-20".to_string(),
-        };
-        // Don't cache the fallback so we can retry external sources later
-        Some(fallback)
+    /// Step 6 of `resolve`: an ABI of the functions whose selectors the code
+    /// of the contract, and of its implementation behind a proxy, dispatches
+    /// on. Selectors are named from the signature database or 4byte; those
+    /// nobody knows are left out. Remembered, including failures, unless
+    /// some code could not be fetched.
+    async fn infer(&self, address: Address, proxy: Option<&ProxyInfo>) -> Option<ResolvedAbi> {
+        {
+            let mut cache = self.inferred_cache.lock().ok()?;
+            if let Some(cached) = cache.get(&address) {
+                return cached.clone();
+            }
+        }
+
+        let mut selectors: Vec<[u8; 4]> = Vec::new();
+        let mut complete = true;
+        let targets = proxy.map(ProxyInfo::targets).unwrap_or_default();
+        for code_address in std::iter::once(address).chain(targets) {
+            let Some(code) = self.code(code_address).await else {
+                complete = false;
+                continue;
+            };
+            for selector in interface::dispatch_selectors(&code) {
+                if !selectors.contains(&selector) {
+                    selectors.push(selector);
+                }
+            }
+        }
+
+        let functions: Vec<Option<Function>> = futures::stream::iter(
+            selectors
+                .into_iter()
+                .map(|selector| self.resolve_function(selector)),
+        )
+        .buffer_unordered(INFER_CONCURRENCY)
+        .collect()
+        .await;
+        let mut abi = JsonAbi::new();
+        for function in functions.into_iter().flatten() {
+            abi.functions
+                .entry(function.name.clone())
+                .or_default()
+                .push(function);
+        }
+
+        let inferred = abi.functions().next().is_some().then(|| ResolvedAbi {
+            abi,
+            source: INFERRED_SOURCE.to_string(),
+        });
+        if complete && let Ok(mut cache) = self.inferred_cache.lock() {
+            cache.put(address, inferred.clone());
+        }
+        inferred
+    }
+
+    /// Runtime code of a contract, fetched once for proxy detection and
+    /// interface inference alike. RPC failures are not remembered.
+    async fn code(&self, address: Address) -> Option<Bytes> {
+        let provider = self.provider.as_ref()?;
+        {
+            let mut cache = self.code_cache.lock().ok()?;
+            if let Some(cached) = cache.get(&address) {
+                return Some(cached.clone());
+            }
+        }
+
+        let code = provider.get_code(address).await.ok()?;
+        if let Ok(mut cache) = self.code_cache.lock() {
+            cache.put(address, code.clone());
+        }
+        Some(code)
+    }
+
+    /// A function for a selector: the full declaration from the signature
+    /// database, or one parsed from the text signature 4byte knows, which
+    /// says nothing of mutability or outputs.
+    async fn resolve_function(&self, selector: [u8; 4]) -> Option<Function> {
+        if let Some(function) = signatures::global().function(selector) {
+            return Some(function);
+        }
+        let signature = self.resolve_selector(selector).await?;
+        Function::parse(&signature)
+            .ok()
+            .filter(|f| f.selector() == selector)
+    }
+
+    /// Add the ABIs of a proxy's implementation or facets to its own.
+    async fn merge_targets(
+        &self,
+        chain_id: u64,
+        own: Option<ResolvedAbi>,
+        proxy: &ProxyInfo,
+    ) -> Option<ResolvedAbi> {
+        let mut merged = own.clone();
+        let mut target_sources: Vec<String> = Vec::new();
+        for target in proxy.targets() {
+            let Some(resolved) = self.resolve_verified(chain_id, target).await else {
+                continue;
+            };
+            match merged {
+                Some(ref mut merged) => merge_abi(&mut merged.abi, &resolved.abi),
+                None => merged = Some(resolved.clone()),
+            }
+            if !target_sources.contains(&resolved.source) {
+                target_sources.push(resolved.source);
+            }
+        }
+
+        let mut merged = merged?;
+        if !target_sources.is_empty() {
+            let targets = target_sources.join(", ");
+            merged.source = match own {
+                Some(own) => format!("{} + {} ({targets})", own.source, proxy.kind),
+                None => format!("{} ({targets})", proxy.kind),
+            };
+        }
+        Some(merged)
+    }
+
+    /// The proxy a contract is, if any, detected once and remembered.
+    pub async fn proxy(&self, address: Address) -> Option<ProxyInfo> {
+        let provider = self.provider.as_ref()?;
+        {
+            let mut cache = self.proxy_cache.lock().ok()?;
+            if let Some(cached) = cache.get(&address) {
+                return cached.clone();
+            }
+        }
+
+        let code = self.code(address).await?;
+        let detected = proxy::detect(provider, address, &code).await;
+        if let Ok(mut cache) = self.proxy_cache.lock() {
+            cache.put(address, detected.clone());
+        }
+        detected
+    }
+
+    /// Look a contract's ABI up on Sourcify and the block explorer again,
+    /// replacing what the caches hold.
+    pub async fn refresh(&self, chain_id: u64, address: Address) -> Option<ResolvedAbi> {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.pop(&address);
+        }
+        let resolved = self.fetch_remote(chain_id, address).await;
+        if resolved.is_some() {
+            self.cache_abi(address, resolved.clone());
+        }
+        resolved
+    }
+
+    /// Ask Sourcify, then the block explorer, recording the outcome on disk
+    /// unless a source could not answer.
+    async fn fetch_remote(&self, chain_id: u64, address: Address) -> Option<ResolvedAbi> {
+        let mut lookup = self.try_sourcify(chain_id, address).await;
+        if lookup.clone().found().is_none() {
+            lookup = lookup.or(self.try_explorer(address).await);
+        }
+        if let Some(ref disk) = self.disk_cache
+            && !lookup.is_transient()
+        {
+            let _ = disk.put_abi(chain_id, address, lookup.clone().found());
+        }
+        lookup.found()
     }
 
     /// Resolve a 4-byte function selector to a human-readable signature.
@@ -108,21 +426,60 @@ impl AbiResolver {
             }
         }
 
-        let result = self.try_4byte(selector).await;
+        // The bundled and imported signatures answer offline
+        if let Some(signature) = signatures::global().function_signature(selector) {
+            return Some(signature);
+        }
+
+        let lookup = match self.disk_cache.as_ref().and_then(|disk| disk.signature(&selector)) {
+            Some(Some(cached)) => Lookup::Found(cached),
+            Some(None) => Lookup::NotFound,
+            None => {
+                let lookup = self.try_4byte(selector).await;
+                if let Some(ref disk) = self.disk_cache
+                    && !lookup.is_transient()
+                {
+                    let _ = disk.put_signature(&selector, lookup.clone().found());
+                }
+                lookup
+            }
+        };
+
+        // Cache the result (including None to avoid repeated lookups), but
+        // ask again next time if 4byte could not answer
+        if !lookup.is_transient()
+            && let Ok(mut cache) = self.selector_cache.lock()
+        {
+            cache.put(selector, lookup.clone().found());
+        }
+
+        lookup.found()
+    }
+
+    /// Resolve verified sources and the deployed source map for a contract.
+    /// Only Sourcify publishes compiler output, so there is no fallback.
+    pub async fn resolve_source(&self, chain_id: u64, address: Address) -> Option<ContractSource> {
+        {
+            let mut cache = self.source_cache.lock().ok()?;
+            if let Some(cached) = cache.get(&address) {
+                return cached.clone();
+            }
+        }
+
+        let result = self.try_sourcify_source(chain_id, address).await;
 
-        // Cache the result (including None to avoid repeated lookups)
-        if let Ok(mut cache) = self.selector_cache.lock() {
-            cache.put(selector, result.clone());
+        if let Ok(mut cache) = self.source_cache.lock() {
+            cache.put(address, result.clone());
         }
 
         result
     }
 
-    /// Try resolving ABI from Sourcify's repository.
-    /// GET https://repo.sourcify.dev/contracts/full_match/{chainId}/{address}/metadata.json
-    async fn try_sourcify(&self, chain_id: u64, address: Address) -> Option<ResolvedAbi> {
+    /// Try fetching sources and compiler output from Sourcify.
+    /// GET https://sourcify.dev/server/v2/contract/{chainId}/{address}?fields=...
+    async fn try_sourcify_source(&self, chain_id: u64, address: Address) -> Option<ContractSource> {
         let url = format!(
-            "https://repo.sourcify.dev/contracts/full_match/{chain_id}/{address}/metadata.json"
+            "https://sourcify.dev/server/v2/contract/{chain_id}/{address}?fields=sources,sourceIds,runtimeBytecode,compilation"
         );
 
         let response = self.client.get(&url).send().await.ok()?;
@@ -131,22 +488,55 @@ impl AbiResolver {
         }
 
         let body: serde_json::Value = response.json().await.ok()?;
-        let abi_value = body.get("output")?.get("abi")?;
-        let abi: JsonAbi = serde_json::from_value(abi_value.clone()).ok()?;
+        sourcemap::parse_sourcify_contract(&body)
+    }
 
-        Some(ResolvedAbi {
-            abi,
-            source: "Sourcify".to_string(),
-        })
+    /// Resolve the storage layout solc emitted for a contract. A proxy's
+    /// storage follows its implementation's layout, so that is tried first.
+    /// Only Sourcify publishes layouts, so there is no fallback.
+    pub async fn resolve_storage_layout(
+        &self,
+        chain_id: u64,
+        address: Address,
+    ) -> Option<StorageLayout> {
+        let implementation = self.proxy(address).await.and_then(|p| p.implementation);
+        let from_implementation = match implementation {
+            Some(implementation) => self.storage_layout(chain_id, implementation).await,
+            None => None,
+        };
+        if from_implementation.is_some() {
+            return from_implementation;
+        }
+        self.storage_layout(chain_id, address).await
     }
 
-    /// Try resolving ABI from Etherscan.
-    /// GET https://api.etherscan.io/api?module=contract&action=getabi&address={address}&apikey={key}
-    async fn try_etherscan(&self, address: Address) -> Option<ResolvedAbi> {
-        let api_key = self.etherscan_api_key.as_ref()?;
+    async fn storage_layout(&self, chain_id: u64, address: Address) -> Option<StorageLayout> {
+        {
+            let mut cache = self.layout_cache.lock().ok()?;
+            if let Some(cached) = cache.get(&address) {
+                return cached.clone();
+            }
+        }
+
+        let result = self.try_sourcify_storage_layout(chain_id, address).await;
+
+        if let Ok(mut cache) = self.layout_cache.lock() {
+            cache.put(address, result.clone());
+        }
+
+        result
+    }
 
+    /// Try fetching the storage layout from Sourcify, which has it for
+    /// contracts compiled with it requested.
+    /// GET https://sourcify.dev/server/v2/contract/{chainId}/{address}?fields=storageLayout
+    async fn try_sourcify_storage_layout(
+        &self,
+        chain_id: u64,
+        address: Address,
+    ) -> Option<StorageLayout> {
         let url = format!(
-            "https://api.etherscan.io/api?module=contract&action=getabi&address={address}&apikey={api_key}"
+            "https://sourcify.dev/server/v2/contract/{chain_id}/{address}?fields=storageLayout"
         );
 
         let response = self.client.get(&url).send().await.ok()?;
@@ -155,25 +545,52 @@ impl AbiResolver {
         }
 
         let body: serde_json::Value = response.json().await.ok()?;
+        storage_layout::parse_storage_layout(body.get("storageLayout")?)
+    }
 
-        // Etherscan returns status "1" on success, the ABI as a JSON string in "result"
-        let status = body.get("status")?.as_str()?;
-        if status != "1" {
-            return None;
-        }
+    /// Try the local ABI library populated by `eth-tui abi import` / `abi add`.
+    fn try_local(&self, chain_id: u64, address: Address) -> Option<ResolvedAbi> {
+        let stored = self.store.as_ref()?.get(chain_id, address)?;
+        Some(ResolvedAbi {
+            abi: stored.abi,
+            source: format!("Local ({})", stored.name),
+        })
+    }
 
-        let abi_str = body.get("result")?.as_str()?;
-        let abi: JsonAbi = serde_json::from_str(abi_str).ok()?;
+    /// Try resolving ABI from Sourcify's repository.
+    /// GET https://repo.sourcify.dev/contracts/full_match/{chainId}/{address}/metadata.json
+    async fn try_sourcify(&self, chain_id: u64, address: Address) -> Lookup<ResolvedAbi> {
+        let url = format!(
+            "https://repo.sourcify.dev/contracts/full_match/{chain_id}/{address}/metadata.json"
+        );
 
-        Some(ResolvedAbi {
-            abi,
-            source: "Etherscan".to_string(),
+        get_json(&self.client, &url).await.and_then(|body| {
+            let abi_value = body.get("output")?.get("abi")?;
+            let abi: JsonAbi = serde_json::from_value(abi_value.clone()).ok()?;
+            Some(ResolvedAbi {
+                abi,
+                source: "Sourcify".to_string(),
+            })
+        })
+    }
+
+    /// Try resolving ABI from the chain's block explorer. Without one
+    /// configured there is nothing to find.
+    async fn try_explorer(&self, address: Address) -> Lookup<ResolvedAbi> {
+        let Some(explorer) = self.explorer.as_ref() else {
+            return Lookup::NotFound;
+        };
+        explorer.abi(address).await.and_then(|abi| {
+            Some(ResolvedAbi {
+                abi,
+                source: explorer.name().to_string(),
+            })
         })
     }
 
     /// Try resolving a 4-byte selector from 4byte.directory.
     /// GET https://www.4byte.directory/api/v1/signatures/?hex_signature=0x{selector_hex}
-    async fn try_4byte(&self, selector: [u8; 4]) -> Option<String> {
+    async fn try_4byte(&self, selector: [u8; 4]) -> Lookup<String> {
         let hex = selector
             .iter()
             .map(|b| format!("{b:02x}"))
@@ -182,18 +599,60 @@ impl AbiResolver {
             "https://www.4byte.directory/api/v1/signatures/?hex_signature=0x{hex}"
         );
 
-        let response = self.client.get(&url).send().await.ok()?;
-        if !response.status().is_success() {
-            return None;
+        // Return the first (most popular) text signature
+        get_json(&self.client, &url)
+            .await
+            .and_then(first_text_signature)
+    }
+
+    /// Resolve an event topic to a declaration or text signature, which may
+    /// not say which inputs are indexed.
+    pub async fn resolve_event_signature(&self, topic: B256) -> Option<String> {
+        if let Some(declaration) = signatures::global().event_declaration(topic) {
+            return Some(declaration);
         }
 
-        let body: serde_json::Value = response.json().await.ok()?;
-        let results = body.get("results")?.as_array()?;
+        {
+            let mut cache = self.event_cache.lock().ok()?;
+            if let Some(cached) = cache.get(&topic) {
+                return cached.clone();
+            }
+        }
 
-        // Return the first (most popular) text signature
-        let first = results.first()?;
-        let sig = first.get("text_signature")?.as_str()?;
-        Some(sig.to_string())
+        let lookup = match self.disk_cache.as_ref().and_then(|disk| disk.signature(topic.as_slice())) {
+            Some(Some(cached)) => Lookup::Found(cached),
+            Some(None) => Lookup::NotFound,
+            None => {
+                let lookup = self.try_4byte_event(topic).await;
+                if let Some(ref disk) = self.disk_cache
+                    && !lookup.is_transient()
+                {
+                    let _ = disk.put_signature(topic.as_slice(), lookup.clone().found());
+                }
+                lookup
+            }
+        };
+
+        if !lookup.is_transient()
+            && let Ok(mut cache) = self.event_cache.lock()
+        {
+            cache.put(topic, lookup.clone().found());
+        }
+
+        lookup.found()
+    }
+
+    /// Try resolving an event topic from 4byte.directory.
+    /// GET https://www.4byte.directory/api/v1/event-signatures/?hex_signature={topic}
+    async fn try_4byte_event(&self, topic: B256) -> Lookup<String> {
+        let url = format!(
+            "https://www.4byte.directory/api/v1/event-signatures/?hex_signature={topic}"
+        );
+
+        // Topics are full hashes, so any result is an exact match
+        get_json(&self.client, &url)
+            .await
+            .and_then(first_text_signature)
     }
 
     /// Try to match a selector against built-in ERC ABIs.
@@ -215,3 +674,86 @@ impl AbiResolver {
         }
     }
 }
+
+/// The text signature of the first result of a 4byte.directory search.
+fn first_text_signature(body: serde_json::Value) -> Option<String> {
+    let results = body.get("results")?.as_array()?;
+    let sig = results.first()?.get("text_signature")?.as_str()?;
+    Some(sig.to_string())
+}
+
+/// Add the functions, events and errors of `other` that `abi` lacks,
+/// matching them by selector.
+pub fn merge_abi(abi: &mut JsonAbi, other: &JsonAbi) {
+    for function in other.functions() {
+        if !abi.functions().any(|f| f.selector() == function.selector()) {
+            abi.functions
+                .entry(function.name.clone())
+                .or_default()
+                .push(function.clone());
+        }
+    }
+    for event in other.events() {
+        if !abi.events().any(|e| e.selector() == event.selector()) {
+            abi.events
+                .entry(event.name.clone())
+                .or_default()
+                .push(event.clone());
+        }
+    }
+    for error in other.errors() {
+        if !abi.errors().any(|e| e.selector() == error.selector()) {
+            abi.errors
+                .entry(error.name.clone())
+                .or_default()
+                .push(error.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_or_stays_transient() {
+        use Lookup::*;
+        assert_eq!(Transient.or(Found(1)), Found(1));
+        assert_eq!(Found(1).or(Transient), Found(1));
+        assert_eq!(NotFound::<u8>.or(NotFound), NotFound);
+        assert_eq!(NotFound::<u8>.or(Transient), Transient);
+        assert_eq!(Transient::<u8>.or(NotFound), Transient);
+        assert_eq!(Found(1).and_then(|_| None::<u8>), NotFound);
+        assert_eq!(
+            Lookup::<u8>::from_status(reqwest::StatusCode::TOO_MANY_REQUESTS),
+            Transient
+        );
+        assert_eq!(
+            Lookup::<u8>::from_status(reqwest::StatusCode::NOT_FOUND),
+            NotFound
+        );
+    }
+
+    #[test]
+    fn test_merge_abi_adds_missing_items() {
+        let mut proxy = JsonAbi::parse([
+            "function upgradeTo(address newImplementation)",
+            "event Upgraded(address indexed implementation)",
+        ])
+        .unwrap();
+        let implementation = JsonAbi::parse([
+            "function transfer(address to, uint256 amount) returns (bool)",
+            "function transfer(address to, uint256 amount, bytes data) returns (bool)",
+            "function upgradeTo(address newImplementation)",
+            "event Upgraded(address indexed implementation)",
+            "error Unauthorized()",
+        ])
+        .unwrap();
+
+        merge_abi(&mut proxy, &implementation);
+        assert_eq!(proxy.functions().count(), 3);
+        assert_eq!(proxy.function("transfer").map(Vec::len), Some(2));
+        assert_eq!(proxy.events().count(), 1);
+        assert_eq!(proxy.errors().count(), 1);
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::Address;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::data::abi::ResolvedAbi;
+
+const APP_DIR: &str = "eth-tui";
+const CACHE_DIR: &str = "abi-cache";
+const SIGNATURES_FILE: &str = "signatures.jsonl";
+
+/// How long a failed lookup is trusted before the remote sources are asked
+/// again, so contracts verified since are picked up.
+const NEGATIVE_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// A remote ABI lookup: the ABI and its source, or None if no source had one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedAbi {
+    resolved: Option<ResolvedAbi>,
+    fetched_at: u64,
+}
+
+/// A remote selector or event topic lookup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSignature {
+    signature: Option<String>,
+    fetched_at: u64,
+}
+
+/// One line of the signature log: a lookup and the hex it was keyed by.
+#[derive(Debug, Serialize, Deserialize)]
+struct SignatureEntry {
+    key: String,
+    #[serde(flatten)]
+    cached: CachedSignature,
+}
+
+/// Remote ABI and signature lookups kept across runs, so restarts don't
+/// hit Sourcify, the block explorer and 4byte again. ABIs live at
+/// ~/.cache/eth-tui/abi-cache/<chain id>/<address>.json, selector and event
+/// topic lookups in signatures.jsonl, a log appended to one line per lookup
+/// where later lines win.
+pub struct AbiCache {
+    root: PathBuf,
+    signatures: Mutex<HashMap<String, CachedSignature>>,
+}
+
+impl AbiCache {
+    /// Open the cache in the user's cache directory.
+    pub fn open() -> Option<Self> {
+        let cache_dir = dirs::cache_dir()?;
+        Some(Self::at(cache_dir.join(APP_DIR).join(CACHE_DIR)))
+    }
+
+    /// Open a cache rooted at `root`.
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let signatures = fs::read_to_string(root.join(SIGNATURES_FILE))
+            .map(|data| parse_signature_log(&data))
+            .unwrap_or_default();
+        Self {
+            root,
+            signatures: Mutex::new(signatures),
+        }
+    }
+
+    fn abi_path(&self, chain_id: u64, address: Address) -> PathBuf {
+        self.root
+            .join(chain_id.to_string())
+            .join(format!("{address:#x}.json"))
+    }
+
+    /// The cached lookup for a contract: Some(None) if no source had an ABI
+    /// recently, None if it was never looked up or the failure has expired.
+    pub fn abi(&self, chain_id: u64, address: Address) -> Option<Option<ResolvedAbi>> {
+        let data = fs::read_to_string(self.abi_path(chain_id, address)).ok()?;
+        let cached: CachedAbi = serde_json::from_str(&data).ok()?;
+        is_fresh(cached.resolved.is_some(), cached.fetched_at, now()).then_some(cached.resolved)
+    }
+
+    /// Record the result of a remote ABI lookup.
+    pub fn put_abi(
+        &self,
+        chain_id: u64,
+        address: Address,
+        resolved: Option<ResolvedAbi>,
+    ) -> Result<()> {
+        self.write_abi(
+            chain_id,
+            address,
+            &CachedAbi {
+                resolved,
+                fetched_at: now(),
+            },
+        )
+    }
+
+    fn write_abi(&self, chain_id: u64, address: Address, cached: &CachedAbi) -> Result<()> {
+        let path = self.abi_path(chain_id, address);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let json = serde_json::to_string(cached)?;
+        fs::write(&path, json).wrap_err_with(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Forget the cached lookup for a contract. Returns whether there was one.
+    pub fn purge_abi(&self, chain_id: u64, address: Address) -> Result<bool> {
+        let path = self.abi_path(chain_id, address);
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&path).wrap_err_with(|| format!("Failed to remove {}", path.display()))?;
+        Ok(true)
+    }
+
+    /// The cached lookup for a 4-byte selector or 32-byte event topic, with
+    /// the same expiry of failed lookups as ABIs.
+    pub fn signature(&self, key: &[u8]) -> Option<Option<String>> {
+        let signatures = self.signatures.lock().ok()?;
+        let cached = signatures.get(&alloy::hex::encode(key))?;
+        is_fresh(cached.signature.is_some(), cached.fetched_at, now())
+            .then(|| cached.signature.clone())
+    }
+
+    /// Record the result of a remote selector or event topic lookup by
+    /// appending it to the log, rather than rewriting every lookup.
+    pub fn put_signature(&self, key: &[u8], signature: Option<String>) -> Result<()> {
+        let entry = SignatureEntry {
+            key: alloy::hex::encode(key),
+            cached: CachedSignature {
+                signature,
+                fetched_at: now(),
+            },
+        };
+        // Held while appending so lines land in the order of the map updates
+        let mut signatures = self
+            .signatures
+            .lock()
+            .map_err(|_| eyre!("Signature cache lock poisoned"))?;
+        signatures.insert(entry.key.clone(), entry.cached.clone());
+
+        fs::create_dir_all(&self.root)
+            .wrap_err_with(|| format!("Failed to create {}", self.root.display()))?;
+        let path = self.root.join(SIGNATURES_FILE);
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .wrap_err_with(|| format!("Failed to open {}", path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)
+            .wrap_err_with(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Forget the cached lookup for a selector or topic. Returns whether
+    /// there was one. The log is rewritten without it, through a temporary
+    /// file so a failed write leaves the old log in place.
+    pub fn purge_signature(&self, key: &[u8]) -> Result<bool> {
+        let mut signatures = self
+            .signatures
+            .lock()
+            .map_err(|_| eyre!("Signature cache lock poisoned"))?;
+        if signatures.remove(&alloy::hex::encode(key)).is_none() {
+            return Ok(false);
+        }
+
+        let mut log = String::new();
+        for (key, cached) in signatures.iter() {
+            let entry = SignatureEntry {
+                key: key.clone(),
+                cached: cached.clone(),
+            };
+            log.push_str(&serde_json::to_string(&entry)?);
+            log.push('\n');
+        }
+        let path = self.root.join(SIGNATURES_FILE);
+        let tmp = path.with_extension("jsonl.tmp");
+        fs::write(&tmp, log).wrap_err_with(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &path)
+            .wrap_err_with(|| format!("Failed to replace {}", path.display()))?;
+        Ok(true)
+    }
+}
+
+/// The latest lookup per key in a signature log. Lines that don't parse,
+/// such as one cut off by a crash mid-append, are skipped.
+fn parse_signature_log(data: &str) -> HashMap<String, CachedSignature> {
+    data.lines()
+        .filter_map(|line| serde_json::from_str::<SignatureEntry>(line).ok())
+        .map(|entry| (entry.key, entry.cached))
+        .collect()
+}
+
+/// Found results are kept for good; failed lookups for `NEGATIVE_TTL_SECS`.
+fn is_fresh(found: bool, fetched_at: u64, now: u64) -> bool {
+    found || now.saturating_sub(fetched_at) < NEGATIVE_TTL_SECS
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::scratch;
+    use alloy::json_abi::JsonAbi;
+
+    fn resolved() -> ResolvedAbi {
+        ResolvedAbi {
+            abi: JsonAbi::parse(["function increment()"]).unwrap(),
+            source: "Sourcify".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_abi_roundtrip_per_chain() {
+        let dir = scratch("abi-cache");
+        let cache = AbiCache::at(&dir);
+        let address = Address::repeat_byte(1);
+        assert!(cache.abi(1, address).is_none());
+
+        cache.put_abi(1, address, Some(resolved())).unwrap();
+        let cached = AbiCache::at(&dir).abi(1, address).unwrap().unwrap();
+        assert_eq!(cached.source, "Sourcify");
+        assert_eq!(cached.abi.functions().count(), 1);
+        assert!(cache.abi(10, address).is_none());
+
+        assert!(cache.purge_abi(1, address).unwrap());
+        assert!(!cache.purge_abi(1, address).unwrap());
+        assert!(cache.abi(1, address).is_none());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_failed_abi_lookup_expires() {
+        let dir = scratch("abi-cache-expiry");
+        let cache = AbiCache::at(&dir);
+        let address = Address::repeat_byte(2);
+
+        cache.put_abi(1, address, None).unwrap();
+        assert!(matches!(cache.abi(1, address), Some(None)));
+
+        let stale = CachedAbi {
+            resolved: None,
+            fetched_at: now() - NEGATIVE_TTL_SECS - 1,
+        };
+        cache.write_abi(1, address, &stale).unwrap();
+        assert!(cache.abi(1, address).is_none());
+
+        // Found ABIs don't expire
+        let old = CachedAbi {
+            resolved: Some(resolved()),
+            fetched_at: 0,
+        };
+        cache.write_abi(1, address, &old).unwrap();
+        assert!(matches!(cache.abi(1, address), Some(Some(_))));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_signature_roundtrip() {
+        let dir = scratch("sig-cache");
+        let cache = AbiCache::at(&dir);
+        let selector = [0xa9, 0x05, 0x9c, 0xbb];
+        let unknown = [0xde, 0xad, 0xbe, 0xef];
+
+        cache
+            .put_signature(&selector, Some("transfer(address,uint256)".to_string()))
+            .unwrap();
+        cache.put_signature(&unknown, None).unwrap();
+
+        let reopened = AbiCache::at(&dir);
+        assert_eq!(
+            reopened.signature(&selector),
+            Some(Some("transfer(address,uint256)".to_string()))
+        );
+        assert_eq!(reopened.signature(&unknown), Some(None));
+        assert!(reopened.purge_signature(&unknown).unwrap());
+        assert_eq!(AbiCache::at(&dir).signature(&unknown), None);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_signature_log_later_lines_win() {
+        let log = concat!(
+            r#"{"key":"deadbeef","signature":null,"fetched_at":1}"#,
+            "\n",
+            r#"{"key":"deadbeef","signature":"foo()","fetched_at":2}"#,
+            "\n",
+            r#"{"key":"a9059cbb","signa"#,
+        );
+        let signatures = parse_signature_log(log);
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures["deadbeef"].signature.as_deref(), Some("foo()"));
+    }
+
+    #[test]
+    fn test_is_fresh() {
+        assert!(is_fresh(true, 0, u64::MAX));
+        assert!(is_fresh(false, 100, 100 + NEGATIVE_TTL_SECS - 1));
+        assert!(!is_fresh(false, 100, 100 + NEGATIVE_TTL_SECS));
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use alloy::json_abi::JsonAbi;
+use alloy::primitives::Address;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+const APP_DIR: &str = "eth-tui";
+const ABI_DIR: &str = "abis";
+
+/// An ABI in the local library, with the name of the contract it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredAbi {
+    pub name: String,
+    pub abi: JsonAbi,
+}
+
+/// A contract deployment recorded by a Foundry broadcast or a Hardhat
+/// deployment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deployment {
+    pub chain_id: u64,
+    pub address: Address,
+    pub name: String,
+}
+
+/// Outcome of importing a Foundry or Hardhat project.
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    /// Compiled contracts found in the project
+    pub artifacts: usize,
+    /// Deployments whose ABI was saved
+    pub imported: Vec<Deployment>,
+    /// Deployments with no compiled artifact of the same name
+    pub missing: Vec<Deployment>,
+}
+
+/// Local ABI library, one file per contract at
+/// ~/.config/eth-tui/abis/<chain id>/<address>.json.
+pub struct AbiStore {
+    root: PathBuf,
+}
+
+impl AbiStore {
+    /// Open the library in the user's config directory.
+    pub fn open() -> Option<Self> {
+        let config_dir = dirs::config_dir()?;
+        Some(Self::at(config_dir.join(APP_DIR).join(ABI_DIR)))
+    }
+
+    /// Open a library rooted at `root`.
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, chain_id: u64, address: Address) -> PathBuf {
+        self.root
+            .join(chain_id.to_string())
+            .join(format!("{address:#x}.json"))
+    }
+
+    /// Look up the ABI saved for a contract.
+    pub fn get(&self, chain_id: u64, address: Address) -> Option<StoredAbi> {
+        let data = fs::read_to_string(self.path(chain_id, address)).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    /// Save the ABI of a contract, replacing any previous one.
+    pub fn put(&self, chain_id: u64, address: Address, abi: &StoredAbi) -> Result<()> {
+        let path = self.path(chain_id, address);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(abi)?;
+        fs::write(&path, json).wrap_err_with(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Every saved contract as (chain id, address, name), sorted.
+    pub fn list(&self) -> Vec<(u64, Address, String)> {
+        let mut entries = Vec::new();
+        for chain_dir in sorted_entries(&self.root) {
+            let Some(chain_id) = file_name(&chain_dir).and_then(|n| n.parse::<u64>().ok()) else {
+                continue;
+            };
+            for file in sorted_entries(&chain_dir) {
+                let address = file
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| s.parse::<Address>().ok());
+                if let Some(address) = address {
+                    let name = self
+                        .get(chain_id, address)
+                        .map(|stored| stored.name)
+                        .unwrap_or_default();
+                    entries.push((chain_id, address, name));
+                }
+            }
+        }
+        entries
+    }
+}
+
+/// Import the ABIs of every deployed contract of a Foundry or Hardhat
+/// project. `path` is the project root or its `out/` / `artifacts/` directory.
+///
+/// Deployed addresses come from Foundry `broadcast/*/<chain id>/run-latest.json`
+/// files, hardhat-deploy `deployments/<network>/` directories and Hardhat
+/// Ignition `ignition/deployments/chain-<id>/deployed_addresses.json` files.
+pub fn import_project(store: &AbiStore, path: &Path) -> Result<ImportSummary> {
+    let root = match file_name(path) {
+        Some("out") | Some("artifacts") => path.parent().unwrap_or(path),
+        _ => path,
+    };
+
+    let mut artifacts: HashMap<String, JsonAbi> = HashMap::new();
+    for dir in ["out", "artifacts"] {
+        collect_artifacts(&root.join(dir), &mut artifacts);
+    }
+
+    let mut deployments = Vec::new();
+    deployments.extend(foundry_deployments(root));
+    deployments.extend(ignition_deployments(root));
+    // hardhat-deploy records the ABI alongside the address
+    for (deployment, abi) in hardhat_deploy_deployments(root) {
+        artifacts.entry(deployment.name.clone()).or_insert(abi);
+        deployments.push(deployment);
+    }
+
+    if artifacts.is_empty() {
+        return Err(eyre!(
+            "No Foundry out/ or Hardhat artifacts/ found in {}",
+            root.display()
+        ));
+    }
+
+    let mut summary = ImportSummary {
+        artifacts: artifacts.len(),
+        ..Default::default()
+    };
+    for deployment in deployments {
+        match artifacts.get(&deployment.name) {
+            Some(abi) => {
+                let stored = StoredAbi {
+                    name: deployment.name.clone(),
+                    abi: abi.clone(),
+                };
+                store.put(deployment.chain_id, deployment.address, &stored)?;
+                summary.imported.push(deployment);
+            }
+            None => summary.missing.push(deployment),
+        }
+    }
+    Ok(summary)
+}
+
+/// Read an ABI from a file holding either a bare ABI array or a compiler
+/// artifact with an `abi` field.
+pub fn load_abi_file(path: &Path) -> Result<StoredAbi> {
+    let data =
+        fs::read_to_string(path).wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_str(&data)?;
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let (name, abi) =
+        parse_artifact(&value, stem).ok_or_else(|| eyre!("No ABI in {}", path.display()))?;
+    Ok(StoredAbi { name, abi })
+}
+
+/// Contract name and ABI of an artifact, or of a bare ABI array named after
+/// its file. Foundry names artifacts `Name.json` (or `Name.0.8.20.json` when
+/// built with several compilers), Hardhat adds a `contractName`.
+fn parse_artifact(value: &serde_json::Value, stem: &str) -> Option<(String, JsonAbi)> {
+    let abi_value = if value.is_array() {
+        value
+    } else {
+        value.get("abi")?
+    };
+    let abi: JsonAbi = serde_json::from_value(abi_value.clone()).ok()?;
+    let name = value
+        .get("contractName")
+        .and_then(|n| n.as_str())
+        .unwrap_or_else(|| stem.split('.').next().unwrap_or(stem));
+    Some((name.to_string(), abi))
+}
+
+/// Collect the ABIs of every artifact under `dir`, keeping the first of
+/// several contracts sharing a name.
+fn collect_artifacts(dir: &Path, artifacts: &mut HashMap<String, JsonAbi>) {
+    for entry in sorted_entries(dir) {
+        let name = file_name(&entry).unwrap_or_default();
+        if entry.is_dir() {
+            if name != "build-info" {
+                collect_artifacts(&entry, artifacts);
+            }
+            continue;
+        }
+        if !name.ends_with(".json") || name.ends_with(".dbg.json") {
+            continue;
+        }
+        let value = fs::read_to_string(&entry)
+            .ok()
+            .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok());
+        let stem = name.trim_end_matches(".json");
+        let parsed = value
+            .and_then(|v| parse_artifact(&v, stem))
+            .filter(|(_, abi)| !abi.is_empty());
+        if let Some((name, abi)) = parsed {
+            artifacts.entry(name).or_insert(abi);
+        }
+    }
+}
+
+/// Deployments from Foundry's `broadcast/<script>/<chain id>/run-latest.json`.
+fn foundry_deployments(root: &Path) -> Vec<Deployment> {
+    let mut deployments = Vec::new();
+    for script in sorted_entries(&root.join("broadcast")) {
+        for chain_dir in sorted_entries(&script) {
+            let chain_id = file_name(&chain_dir).and_then(|n| n.parse::<u64>().ok());
+            let value = fs::read_to_string(chain_dir.join("run-latest.json"))
+                .ok()
+                .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok());
+            if let (Some(chain_id), Some(value)) = (chain_id, value) {
+                deployments.extend(parse_foundry_broadcast(&value, chain_id));
+            }
+        }
+    }
+    deployments
+}
+
+/// Contracts created by a Foundry broadcast. `chain_id` is used when the file
+/// does not record one.
+pub fn parse_foundry_broadcast(value: &serde_json::Value, chain_id: u64) -> Vec<Deployment> {
+    let chain_id = value["chain"].as_u64().unwrap_or(chain_id);
+    let Some(transactions) = value["transactions"].as_array() else {
+        return Vec::new();
+    };
+    transactions
+        .iter()
+        .filter(|tx| matches!(tx["transactionType"].as_str(), Some("CREATE" | "CREATE2")))
+        .filter_map(|tx| {
+            Some(Deployment {
+                chain_id,
+                address: tx["contractAddress"].as_str()?.parse().ok()?,
+                name: tx["contractName"].as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Deployments from Hardhat Ignition's per-chain `deployed_addresses.json`.
+fn ignition_deployments(root: &Path) -> Vec<Deployment> {
+    let mut deployments = Vec::new();
+    for chain_dir in sorted_entries(&root.join("ignition").join("deployments")) {
+        let chain_id = file_name(&chain_dir)
+            .and_then(|n| n.strip_prefix("chain-"))
+            .and_then(|n| n.parse::<u64>().ok());
+        let value = fs::read_to_string(chain_dir.join("deployed_addresses.json"))
+            .ok()
+            .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok());
+        if let (Some(chain_id), Some(value)) = (chain_id, value) {
+            deployments.extend(parse_ignition_addresses(&value, chain_id));
+        }
+    }
+    deployments
+}
+
+/// Parse Ignition's `{"Module#Contract": "0x..."}` address map.
+pub fn parse_ignition_addresses(value: &serde_json::Value, chain_id: u64) -> Vec<Deployment> {
+    let Some(map) = value.as_object() else {
+        return Vec::new();
+    };
+    map.iter()
+        .filter_map(|(future, address)| {
+            let name = future.rsplit('#').next()?;
+            Some(Deployment {
+                chain_id,
+                address: address.as_str()?.parse().ok()?,
+                name: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Deployments and their ABIs from hardhat-deploy's
+/// `deployments/<network>/<Contract>.json`, with the chain id in `.chainId`.
+fn hardhat_deploy_deployments(root: &Path) -> Vec<(Deployment, JsonAbi)> {
+    let mut deployments = Vec::new();
+    for network in sorted_entries(&root.join("deployments")) {
+        let Some(chain_id) = fs::read_to_string(network.join(".chainId"))
+            .ok()
+            .and_then(|id| id.trim().parse::<u64>().ok())
+        else {
+            continue;
+        };
+        for file in sorted_entries(&network) {
+            let Some(name) = file_name(&file).and_then(|n| n.strip_suffix(".json")) else {
+                continue;
+            };
+            let value = fs::read_to_string(&file)
+                .ok()
+                .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok());
+            let deployed = value.and_then(|value| {
+                let address = value["address"].as_str()?.parse().ok()?;
+                let abi = serde_json::from_value(value["abi"].clone()).ok()?;
+                Some((address, abi))
+            });
+            if let Some((address, abi)) = deployed {
+                let deployment = Deployment {
+                    chain_id,
+                    address,
+                    name: name.to_string(),
+                };
+                deployments.push((deployment, abi));
+            }
+        }
+    }
+    deployments
+}
+
+/// Entries of a directory in name order, or none if it cannot be read.
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<PathBuf> = read.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    entries.sort();
+    entries
+}
+
+fn file_name(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|n| n.to_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::scratch;
+    use serde_json::json;
+
+    const COUNTER: &str = "0x5fbdb2315678afecb367f032d93f642f64180aa3";
+    const TOKEN: &str = "0xe7f1725e7734ce288f8367e1bb143e90bb3f0512";
+
+    fn abi_json() -> serde_json::Value {
+        json!([{
+            "type": "function", "name": "increment", "inputs": [], "outputs": [],
+            "stateMutability": "nonpayable"
+        }])
+    }
+
+    fn write(path: PathBuf, value: &serde_json::Value) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, value.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let dir = scratch("abi-store");
+        let store = AbiStore::at(&dir);
+        let address: Address = COUNTER.parse().unwrap();
+        assert!(store.get(1, address).is_none());
+
+        let abi: JsonAbi = serde_json::from_value(abi_json()).unwrap();
+        let stored = StoredAbi {
+            name: "Counter".to_string(),
+            abi,
+        };
+        store.put(1, address, &stored).unwrap();
+        assert_eq!(store.get(1, address).unwrap().name, "Counter");
+        assert!(store.get(10, address).is_none());
+        assert_eq!(store.list(), vec![(1, address, "Counter".to_string())]);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_parse_foundry_broadcast() {
+        let broadcast = json!({
+            "transactions": [
+                {"transactionType": "CREATE", "contractName": "Counter", "contractAddress": COUNTER},
+                {"transactionType": "CALL", "contractName": "Counter", "contractAddress": COUNTER},
+                {"transactionType": "CREATE2", "contractName": null, "contractAddress": TOKEN}
+            ],
+            "chain": 31337
+        });
+        let deployments = parse_foundry_broadcast(&broadcast, 1);
+        assert_eq!(
+            deployments,
+            vec![Deployment {
+                chain_id: 31337,
+                address: COUNTER.parse().unwrap(),
+                name: "Counter".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_ignition_addresses() {
+        let addresses = json!({"TokenModule#Token": TOKEN});
+        let deployments = parse_ignition_addresses(&addresses, 5);
+        assert_eq!(deployments[0].name, "Token");
+        assert_eq!(deployments[0].chain_id, 5);
+        assert_eq!(deployments[0].address, TOKEN.parse::<Address>().unwrap());
+    }
+
+    #[test]
+    fn test_parse_artifact() {
+        let (name, abi) = parse_artifact(&json!({"abi": abi_json()}), "Counter.0.8.20").unwrap();
+        assert_eq!(name, "Counter");
+        assert_eq!(abi.functions().count(), 1);
+
+        let hardhat = json!({"contractName": "Token", "abi": abi_json()});
+        assert_eq!(parse_artifact(&hardhat, "Other").unwrap().0, "Token");
+        assert_eq!(parse_artifact(&abi_json(), "Bare").unwrap().0, "Bare");
+        assert!(parse_artifact(&json!({"bytecode": "0x"}), "None").is_none());
+    }
+
+    #[test]
+    fn test_import_foundry_project() {
+        let project = scratch("foundry-project");
+        write(
+            project.join("out/Counter.sol/Counter.json"),
+            &json!({"abi": abi_json()}),
+        );
+        write(
+            project.join("out/build-info/abc.json"),
+            &json!({"abi": abi_json()}),
+        );
+        write(
+            project.join("broadcast/Deploy.s.sol/31337/run-latest.json"),
+            &json!({"transactions": [
+                {"transactionType": "CREATE", "contractName": "Counter", "contractAddress": COUNTER},
+                {"transactionType": "CREATE", "contractName": "Unknown", "contractAddress": TOKEN}
+            ]}),
+        );
+
+        let store = AbiStore::at(project.join("store"));
+        let summary = import_project(&store, &project.join("out")).unwrap();
+        assert_eq!(summary.artifacts, 1);
+        assert_eq!(summary.imported.len(), 1);
+        assert_eq!(summary.missing[0].name, "Unknown");
+
+        let stored = store.get(31337, COUNTER.parse().unwrap()).unwrap();
+        assert_eq!(stored.name, "Counter");
+        assert!(import_project(&store, &project.join("store")).is_err());
+
+        let _ = fs::remove_dir_all(project);
+    }
+
+    #[test]
+    fn test_import_hardhat_deploy_project() {
+        let project = scratch("hardhat-project");
+        write(
+            project.join("artifacts/contracts/Token.sol/Token.json"),
+            &json!({"_format": "hh-sol-artifact-1", "contractName": "Token", "abi": abi_json()}),
+        );
+        write(
+            project.join("artifacts/contracts/Token.sol/Token.dbg.json"),
+            &json!({"buildInfo": "../../build-info/x.json"}),
+        );
+        fs::create_dir_all(project.join("deployments/sepolia")).unwrap();
+        fs::write(project.join("deployments/sepolia/.chainId"), "11155111\n").unwrap();
+        write(
+            project.join("deployments/sepolia/Token.json"),
+            &json!({"address": TOKEN, "abi": abi_json()}),
+        );
+
+        let store = AbiStore::at(project.join("store"));
+        let summary = import_project(&store, &project).unwrap();
+        assert_eq!(summary.imported.len(), 1);
+        assert!(store.get(11155111, TOKEN.parse().unwrap()).is_some());
+
+        let _ = fs::remove_dir_all(project);
+    }
+}
@@ -0,0 +1,266 @@
+use alloy::primitives::{B256, U256};
+use color_eyre::eyre::{eyre, Result};
+
+use crate::data::provider::EthProvider;
+use crate::data::types::{BlockTrace, InternalCall, InternalTransfer, TxTraceStats};
+use crate::data::{parse_call_frame, parse_parity_trace};
+
+/// Internal calls of each transaction in a block, in block order.
+pub type BlockCalls = Vec<(B256, Vec<InternalCall>)>;
+
+/// Trace every transaction of a block with Parity-style `trace_block`.
+pub async fn fetch_trace_block(provider: &EthProvider, number: u64) -> Result<BlockCalls> {
+    let params = serde_json::json!([format!("{number:#x}")]);
+    let result = provider.raw_request("trace_block", params).await?;
+    parse_trace_block(&result)
+}
+
+/// Trace every transaction of a block with Geth's `callTracer` via
+/// `debug_traceBlockByNumber`. `tx_hashes` are the block's transactions in
+/// order, for nodes that do not echo the hash with each result.
+pub async fn fetch_debug_trace_block(
+    provider: &EthProvider,
+    number: u64,
+    tx_hashes: &[B256],
+) -> Result<BlockCalls> {
+    let params = serde_json::json!([format!("{number:#x}"), {"tracer": "callTracer"}]);
+    let result = provider
+        .raw_request("debug_traceBlockByNumber", params)
+        .await?;
+    parse_debug_trace_block(&result, tx_hashes)
+}
+
+/// Group the flat `trace_block` list by transaction, skipping block rewards.
+pub fn parse_trace_block(result: &serde_json::Value) -> Result<BlockCalls> {
+    let traces = result
+        .as_array()
+        .ok_or_else(|| eyre!("Expected array from trace_block"))?;
+
+    let mut blocks: BlockCalls = Vec::new();
+    for trace in traces {
+        let Some(tx_hash) = trace["transactionHash"]
+            .as_str()
+            .and_then(|s| s.parse::<B256>().ok())
+        else {
+            continue;
+        };
+        let Some(call) = parse_parity_trace(trace) else {
+            continue;
+        };
+        match blocks.last_mut() {
+            Some((hash, calls)) if *hash == tx_hash => calls.push(call),
+            _ => blocks.push((tx_hash, vec![call])),
+        }
+    }
+    Ok(blocks)
+}
+
+/// Parse `debug_traceBlockByNumber` results (`[{txHash?, result}]`).
+pub fn parse_debug_trace_block(
+    result: &serde_json::Value,
+    tx_hashes: &[B256],
+) -> Result<BlockCalls> {
+    let results = result
+        .as_array()
+        .ok_or_else(|| eyre!("Expected array from debug_traceBlockByNumber"))?;
+
+    let mut blocks = Vec::with_capacity(results.len());
+    for (i, entry) in results.iter().enumerate() {
+        let tx_hash = entry["txHash"]
+            .as_str()
+            .and_then(|s| s.parse::<B256>().ok())
+            .or_else(|| tx_hashes.get(i).copied())
+            .ok_or_else(|| eyre!("No transaction hash for trace {i}"))?;
+        if let Some(error) = entry["error"].as_str() {
+            return Err(eyre!("Tracing transaction {tx_hash} failed: {error}"));
+        }
+        let mut calls = Vec::new();
+        parse_call_frame(&entry["result"], 0, &mut calls);
+        blocks.push((tx_hash, calls));
+    }
+    Ok(blocks)
+}
+
+/// Count internal calls and collect the ETH they moved and the contracts
+/// they created.
+pub fn summarize_block_trace(number: u64, blocks: &BlockCalls) -> BlockTrace {
+    let mut trace = BlockTrace {
+        number,
+        ..Default::default()
+    };
+
+    for (tx_hash, calls) in blocks {
+        let mut stats = TxTraceStats::default();
+        // Depth of the outermost call that failed; everything beneath it reverted
+        let mut failed_depth: Option<usize> = None;
+
+        for call in calls {
+            if failed_depth.is_some_and(|depth| call.depth <= depth) {
+                failed_depth = None;
+            }
+            if failed_depth.is_none() && call.error.is_some() {
+                failed_depth = Some(call.depth);
+            }
+            if call.depth == 0 {
+                continue;
+            }
+
+            let reverted = failed_depth.is_some();
+            stats.call_count += 1;
+            if !reverted {
+                stats.internal_value += call.value;
+            }
+            let is_create = call.call_type.starts_with("CREATE");
+            if call.value > U256::ZERO || is_create {
+                trace.transfers.push(InternalTransfer {
+                    tx_hash: *tx_hash,
+                    call_type: call.call_type.clone(),
+                    from: call.from,
+                    to: call.to,
+                    value: call.value,
+                    depth: call.depth,
+                    reverted,
+                });
+            }
+        }
+        trace.stats.insert(*tx_hash, stats);
+    }
+    trace
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::Address;
+    use serde_json::json;
+
+    const TX1: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+    const TX2: &str = "0x2222222222222222222222222222222222222222222222222222222222222222";
+    const A: &str = "0x00000000000000000000000000000000000000aa";
+    const B: &str = "0x00000000000000000000000000000000000000bb";
+
+    fn call(depth: usize, value: u64, call_type: &str, error: Option<&str>) -> InternalCall {
+        InternalCall {
+            from: A.parse().unwrap(),
+            to: B.parse().unwrap(),
+            value: U256::from(value),
+            call_type: call_type.to_string(),
+            gas_used: 0,
+            input: Default::default(),
+            output: Default::default(),
+            depth,
+            error: error.map(|e| e.to_string()),
+            decoded: None,
+            decoded_output: Vec::new(),
+            revert_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_trace_block_groups_by_transaction() {
+        let result = json!([
+            {
+                "type": "call", "transactionHash": TX1, "traceAddress": [],
+                "action": {"callType": "call", "from": A, "to": B, "value": "0x0", "input": "0x"},
+                "result": {"gasUsed": "0x5208", "output": "0x"}
+            },
+            {
+                "type": "create", "transactionHash": TX1, "traceAddress": [0],
+                "action": {"from": B, "value": "0x5", "init": "0x6000"},
+                "result": {"gasUsed": "0x10", "address": A, "code": "0x00"}
+            },
+            {
+                "type": "suicide", "transactionHash": TX2, "traceAddress": [0],
+                "action": {"address": A, "refundAddress": B, "balance": "0x7"}
+            },
+            {
+                "type": "reward", "transactionHash": null,
+                "action": {"author": A, "value": "0x1", "rewardType": "block"}
+            }
+        ]);
+        let blocks = parse_trace_block(&result).unwrap();
+        assert_eq!(blocks.len(), 2);
+        let (hash, calls) = &blocks[0];
+        assert_eq!(*hash, TX1.parse::<B256>().unwrap());
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].call_type, "CALL");
+        assert_eq!(calls[1].call_type, "CREATE");
+        assert_eq!(calls[1].to, A.parse::<Address>().unwrap());
+        assert_eq!(calls[1].depth, 1);
+
+        let selfdestruct = &blocks[1].1[0];
+        assert_eq!(selfdestruct.call_type, "SELFDESTRUCT");
+        assert_eq!(selfdestruct.to, B.parse::<Address>().unwrap());
+        assert_eq!(selfdestruct.value, U256::from(7));
+    }
+
+    #[test]
+    fn test_parse_debug_trace_block_uses_known_hashes() {
+        let frame = json!({"type": "CALL", "from": A, "to": B, "value": "0x0",
+            "calls": [{"type": "CALL", "from": B, "to": A, "value": "0x3"}]});
+        let tx1 = TX1.parse::<B256>().unwrap();
+        let tx2 = TX2.parse::<B256>().unwrap();
+
+        let result = json!([{"result": frame}, {"txHash": TX2, "result": frame}]);
+        let blocks = parse_debug_trace_block(&result, &[tx1]).unwrap();
+        assert_eq!(blocks[0].0, tx1);
+        assert_eq!(blocks[1].0, tx2);
+        assert_eq!(blocks[0].1.len(), 2);
+        assert_eq!(blocks[0].1[1].depth, 1);
+
+        // Without a hash from either side the results cannot be attributed
+        assert!(parse_debug_trace_block(&json!([{"result": frame}]), &[]).is_err());
+        let failed = json!([{"txHash": TX1, "error": "execution timeout"}]);
+        assert!(parse_debug_trace_block(&failed, &[]).is_err());
+    }
+
+    #[test]
+    fn test_summarize_block_trace() {
+        let tx1 = TX1.parse::<B256>().unwrap();
+        let blocks = vec![(
+            tx1,
+            vec![
+                call(0, 100, "CALL", None),
+                call(1, 5, "CALL", None),
+                call(1, 0, "STATICCALL", None),
+                // A reverted subtree moves nothing
+                call(1, 9, "CALL", Some("execution reverted")),
+                call(2, 4, "CALL", None),
+                call(1, 0, "CREATE2", None),
+            ],
+        )];
+        let trace = summarize_block_trace(7, &blocks);
+        assert_eq!(trace.number, 7);
+        assert_eq!(
+            trace.stats[&tx1],
+            TxTraceStats {
+                call_count: 5,
+                internal_value: U256::from(5),
+            }
+        );
+
+        let moved: Vec<(u64, bool)> = trace
+            .transfers
+            .iter()
+            .map(|t| (t.value.to::<u64>(), t.reverted))
+            .collect();
+        assert_eq!(moved, vec![(5, false), (9, true), (4, true), (0, false)]);
+        assert_eq!(trace.transfers[3].call_type, "CREATE2");
+    }
+
+    #[test]
+    fn test_summarize_failed_transaction() {
+        let tx1 = TX1.parse::<B256>().unwrap();
+        let blocks = vec![(
+            tx1,
+            vec![
+                call(0, 0, "CALL", Some("execution reverted")),
+                call(1, 5, "CALL", None),
+            ],
+        )];
+        let trace = summarize_block_trace(1, &blocks);
+        assert_eq!(trace.stats[&tx1].call_count, 1);
+        assert_eq!(trace.stats[&tx1].internal_value, U256::ZERO);
+        assert!(trace.transfers[0].reverted);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::data::explorer::ExplorerApi;
 use crate::data::types::ChainConfig;
 
 /// Get a chain configuration preset by name.
@@ -9,6 +10,7 @@ pub fn get_chain_config(name: &str) -> Option<ChainConfig> {
             rpc_url: "https://eth.merkle.io".to_string(),
             symbol: "ETH".to_string(),
             explorer_url: Some("https://etherscan.io".to_string()),
+            explorer_api: Some(ExplorerApi::EtherscanV2),
             explorer_api_key: None,
         }),
         "arbitrum" | "arb" => Some(ChainConfig {
@@ -17,6 +19,7 @@ pub fn get_chain_config(name: &str) -> Option<ChainConfig> {
             rpc_url: "https://arb1.arbitrum.io/rpc".to_string(),
             symbol: "ETH".to_string(),
             explorer_url: Some("https://arbiscan.io".to_string()),
+            explorer_api: Some(ExplorerApi::EtherscanV2),
             explorer_api_key: None,
         }),
         "optimism" | "op" => Some(ChainConfig {
@@ -25,6 +28,7 @@ pub fn get_chain_config(name: &str) -> Option<ChainConfig> {
             rpc_url: "https://mainnet.optimism.io".to_string(),
             symbol: "ETH".to_string(),
             explorer_url: Some("https://optimistic.etherscan.io".to_string()),
+            explorer_api: Some(ExplorerApi::EtherscanV2),
             explorer_api_key: None,
         }),
         "base" => Some(ChainConfig {
@@ -33,6 +37,7 @@ pub fn get_chain_config(name: &str) -> Option<ChainConfig> {
             rpc_url: "https://mainnet.base.org".to_string(),
             symbol: "ETH".to_string(),
             explorer_url: Some("https://basescan.org".to_string()),
+            explorer_api: Some(ExplorerApi::EtherscanV2),
             explorer_api_key: None,
         }),
         "polygon" | "matic" => Some(ChainConfig {
@@ -41,12 +46,27 @@ pub fn get_chain_config(name: &str) -> Option<ChainConfig> {
             rpc_url: "https://polygon-rpc.com".to_string(),
             symbol: "MATIC".to_string(),
             explorer_url: Some("https://polygonscan.com".to_string()),
+            explorer_api: Some(ExplorerApi::EtherscanV2),
             explorer_api_key: None,
         }),
         _ => None,
     }
 }
 
+/// Configuration for a chain without a preset, identified by its chain id.
+/// Etherscan v2 covers most EVM chains, so it is tried by default.
+pub fn custom_chain_config(chain_id: u64, rpc_url: &str) -> ChainConfig {
+    ChainConfig {
+        name: format!("Chain {chain_id}"),
+        chain_id,
+        rpc_url: rpc_url.to_string(),
+        symbol: "ETH".to_string(),
+        explorer_url: None,
+        explorer_api: Some(ExplorerApi::EtherscanV2),
+        explorer_api_key: None,
+    }
+}
+
 /// Return a list of all supported chain names.
 pub fn supported_chains() -> Vec<&'static str> {
     vec!["ethereum", "arbitrum", "optimism", "base", "polygon"]
@@ -103,6 +123,21 @@ mod tests {
         assert!(get_chain_config("matic").is_some());
     }
 
+    #[test]
+    fn test_presets_use_etherscan_v2() {
+        for name in supported_chains() {
+            let config = get_chain_config(name).unwrap();
+            assert_eq!(config.explorer_api, Some(ExplorerApi::EtherscanV2));
+        }
+    }
+
+    #[test]
+    fn test_custom_chain_config() {
+        let config = custom_chain_config(31337, "http://localhost:8545");
+        assert_eq!(config.chain_id, 31337);
+        assert_eq!(config.name, "Chain 31337");
+    }
+
     #[test]
     fn test_unknown_chain() {
         assert!(get_chain_config("unknown").is_none());
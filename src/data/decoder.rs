@@ -1,14 +1,17 @@
-use alloy::dyn_abi::{DynSolValue, JsonAbiExt};
-use alloy::json_abi::JsonAbi;
-use alloy::primitives::{Address, Bytes, B256, U256};
+use alloy::dyn_abi::{DynSolType, DynSolValue, FunctionExt, JsonAbiExt, Specifier};
+use alloy::json_abi::{Event, EventParam, JsonAbi, Param};
+use alloy::primitives::{b256, Address, Bytes, B256, U256};
+use alloy::sol_types::SolError;
 
 // EventExt provides decode_log_parts on json_abi::Event
 use alloy::dyn_abi::EventExt;
 
-use crate::data::types::{DecodedCall, DecodedLog, TokenTransfer};
+use crate::data::types::{
+    DecodedCall, DecodedLog, DecodedParam, TokenMetadata, TokenStandard, TokenTransfer,
+};
 
 /// The keccak256 hash of `Transfer(address,address,uint256)`.
-/// This is the topic0 for ERC-20 Transfer events.
+/// This is the topic0 for ERC-20 and ERC-721 Transfer events.
 const TRANSFER_EVENT_TOPIC: B256 = {
     // 0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef
     B256::new([
@@ -18,6 +21,37 @@ const TRANSFER_EVENT_TOPIC: B256 = {
     ])
 };
 
+/// Topic0 of ERC-1155 `TransferSingle(address,address,address,uint256,uint256)`.
+const TRANSFER_SINGLE_TOPIC: B256 =
+    b256!("c3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62");
+
+/// Topic0 of ERC-1155 `TransferBatch(address,address,address,uint256[],uint256[])`.
+const TRANSFER_BATCH_TOPIC: B256 =
+    b256!("4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb");
+
+/// Selector of the `Panic(uint256)` error emitted by failed compiler checks.
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Selector of the `Error(string)` error emitted by `require` and `revert("...")`.
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Human-readable meaning of a Solidity panic code.
+pub fn panic_reason(code: U256) -> &'static str {
+    match code.saturating_to::<u64>() {
+        0x00 => "generic compiler panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "invalid storage byte array encoding",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory or array too large",
+        0x51 => "call to uninitialized internal function",
+        _ => "unknown panic code",
+    }
+}
+
 pub struct TxDecoder;
 
 impl TxDecoder {
@@ -37,18 +71,20 @@ impl TxDecoder {
                 // Try to decode the calldata parameters
                 match func.abi_decode_input(&input[4..], false) {
                     Ok(values) => {
-                        let params: Vec<(String, String)> = func
+                        let params: Vec<DecodedParam> = func
                             .inputs
                             .iter()
-                            .zip(values.iter())
-                            .map(|(param, value): (&alloy::json_abi::Param, &DynSolValue)| {
-                                (param.name.clone(), format_sol_value(value))
+                            .zip(values)
+                            .map(|(param, value)| DecodedParam {
+                                param: param.clone(),
+                                value: Some(value),
                             })
                             .collect();
 
                         return Some(DecodedCall {
                             function_name: func.name.clone(),
                             params,
+                            inner: vec![],
                         });
                     }
                     Err(_) => {
@@ -57,6 +93,7 @@ impl TxDecoder {
                         return Some(DecodedCall {
                             function_name: func.name.clone(),
                             params: vec![],
+                            inner: vec![],
                         });
                     }
                 }
@@ -66,57 +103,160 @@ impl TxDecoder {
         None
     }
 
-    /// Extract ERC-20 `Transfer` events from raw transaction logs.
+    /// Decode the return data of a call using a known ABI.
+    ///
+    /// The function is looked up by the selector in `input`, and its declared
+    /// outputs are decoded from `output`. Unnamed outputs are labelled by position.
+    pub fn decode_output(abi: &JsonAbi, input: &Bytes, output: &Bytes) -> Option<Vec<DecodedParam>> {
+        if input.len() < 4 {
+            return None;
+        }
+        let selector: [u8; 4] = input[..4].try_into().ok()?;
+        let func = abi.functions().find(|f| f.selector() == selector)?;
+        let values = func.abi_decode_output(output, false).ok()?;
+
+        Some(
+            func.outputs
+                .iter()
+                .zip(values)
+                .enumerate()
+                .map(|(i, (param, value))| {
+                    let mut param = param.clone();
+                    if param.name.is_empty() {
+                        param.name = format!("ret{i}");
+                    }
+                    DecodedParam {
+                        param,
+                        value: Some(value),
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Decode revert data into a readable reason.
+    ///
+    /// Handles `Error(string)` and `Panic(uint256)`, then custom errors
+    /// declared in `abi`. Returns None for empty data or an unknown selector;
+    /// callers can then look the selector up and use `decode_error_signature`.
+    pub fn decode_revert(output: &[u8], abi: Option<&JsonAbi>) -> Option<String> {
+        if output.len() < 4 {
+            return None;
+        }
+        let selector: [u8; 4] = output[..4].try_into().ok()?;
+
+        if selector == ERROR_STRING_SELECTOR {
+            let revert = alloy::sol_types::Revert::abi_decode(output, false).ok()?;
+            return Some(revert.reason);
+        }
+        if selector == PANIC_SELECTOR {
+            let panic = alloy::sol_types::Panic::abi_decode(output, false).ok()?;
+            return Some(format!("Panic({:#x}): {}", panic.code, panic_reason(panic.code)));
+        }
+
+        let error = abi?.errors().find(|e| e.selector() == selector)?;
+        Some(format_error(error, &output[4..]))
+    }
+
+    /// Decode a custom error using a text signature such as
+    /// `InsufficientBalance(uint256,uint256)` from a selector lookup.
+    pub fn decode_error_signature(signature: &str, output: &[u8]) -> Option<String> {
+        let error = alloy::json_abi::Error::parse(signature).ok()?;
+        if output.len() < 4 || error.selector().as_slice() != &output[..4] {
+            return None;
+        }
+        Some(format_error(&error, &output[4..]))
+    }
+
+    /// Extract token transfers from raw transaction logs.
+    ///
+    /// ERC-20 and ERC-721 share the `Transfer(address,address,uint256)` topic
+    /// and differ in what is indexed:
+    /// - ERC-20:  topics [sig, from, to],          data = value
+    /// - ERC-721: topics [sig, from, to, tokenId], no data
     ///
-    /// A standard ERC-20 Transfer log has:
-    /// - topic[0] = keccak256("Transfer(address,address,uint256)")
-    /// - topic[1] = from address (zero-padded to 32 bytes)
-    /// - topic[2] = to address (zero-padded to 32 bytes)
-    /// - data     = value (uint256, 32 bytes)
+    /// ERC-1155 `TransferSingle` and `TransferBatch` index
+    /// [sig, operator, from, to] and carry the ids and amounts as data.
     pub fn extract_token_transfers(logs: &[alloy::rpc::types::Log]) -> Vec<TokenTransfer> {
         let mut transfers = Vec::new();
 
         for log in logs {
             let topics = log.inner.data.topics();
             let data = log.inner.data.data.as_ref();
-
-            // Must have exactly 3 topics for ERC-20 Transfer
-            if topics.len() != 3 {
-                continue;
-            }
-
-            // Check the event signature
-            if topics[0] != TRANSFER_EVENT_TOPIC {
+            if topics.len() < 3 {
                 continue;
             }
 
-            // Parse from and to addresses from topics (last 20 bytes of each 32-byte topic)
-            let from = Address::from_slice(&topics[1].as_slice()[12..]);
-            let to = Address::from_slice(&topics[2].as_slice()[12..]);
-
-            // Parse value from data (first 32 bytes)
-            let value = if data.len() >= 32 {
-                U256::from_be_slice(&data[..32])
-            } else {
-                U256::ZERO
-            };
-
-            let token_address = log.inner.address;
-
-            transfers.push(TokenTransfer {
-                token_address,
-                from,
-                to,
+            let transfer = |standard, from: &B256, to: &B256, value, token_id| TokenTransfer {
+                token_address: log.inner.address,
+                standard,
+                from: topic_address(from),
+                to: topic_address(to),
                 value,
+                token_id,
                 token_name: None,
                 token_symbol: None,
                 decimals: None,
-            });
+            };
+
+            if topics[0] == TRANSFER_EVENT_TOPIC && topics.len() == 3 {
+                // Parse value from data (first 32 bytes)
+                let value = if data.len() >= 32 {
+                    U256::from_be_slice(&data[..32])
+                } else {
+                    U256::ZERO
+                };
+                transfers.push(transfer(TokenStandard::ERC20, &topics[1], &topics[2], value, None));
+            } else if topics[0] == TRANSFER_EVENT_TOPIC && topics.len() == 4 {
+                let token_id = U256::from_be_bytes(topics[3].0);
+                transfers.push(transfer(
+                    TokenStandard::ERC721,
+                    &topics[1],
+                    &topics[2],
+                    U256::from(1),
+                    Some(token_id),
+                ));
+            } else if topics[0] == TRANSFER_SINGLE_TOPIC && topics.len() == 4 && data.len() >= 64 {
+                let token_id = U256::from_be_slice(&data[..32]);
+                let value = U256::from_be_slice(&data[32..64]);
+                transfers.push(transfer(
+                    TokenStandard::ERC1155,
+                    &topics[2],
+                    &topics[3],
+                    value,
+                    Some(token_id),
+                ));
+            } else if topics[0] == TRANSFER_BATCH_TOPIC && topics.len() == 4 {
+                for (token_id, value) in decode_batch(data) {
+                    transfers.push(transfer(
+                        TokenStandard::ERC1155,
+                        &topics[2],
+                        &topics[3],
+                        value,
+                        Some(token_id),
+                    ));
+                }
+            }
         }
 
         transfers
     }
 
+    /// Fill in the name and symbol of each transfer's token, and the
+    /// decimals of ERC-20 amounts, from looked-up token metadata.
+    pub fn apply_token_metadata(transfers: &mut [TokenTransfer], metadata: &[TokenMetadata]) {
+        for transfer in transfers {
+            let Some(token) = metadata.iter().find(|m| m.address == transfer.token_address) else {
+                continue;
+            };
+            transfer.token_name = Some(token.name.clone());
+            transfer.token_symbol = Some(token.symbol.clone());
+            if transfer.standard == TokenStandard::ERC20 {
+                transfer.decimals = Some(token.decimals);
+            }
+        }
+    }
+
     /// Decode event logs using a known ABI.
     ///
     /// For each log, matches topic[0] against all ABI event signatures.
@@ -140,12 +280,12 @@ impl TxDecoder {
                     continue;
                 }
 
-                // Try to decode the log using decode_log_parts
-                // topics[1..] are indexed params, log data is non-indexed
-                let remaining_topics = topics[1..].iter().copied();
+                // Try to decode the log using decode_log_parts, which skips
+                // topic0; the remaining topics are indexed params and log data
+                // is non-indexed
                 let data = log.inner.data.data.as_ref();
 
-                let params = match event.decode_log_parts(remaining_topics, data, false) {
+                let params = match event.decode_log_parts(topics.iter().copied(), data, false) {
                     Ok(decoded_event) => {
                         // Combine indexed and non-indexed params
                         let mut params = Vec::new();
@@ -159,11 +299,10 @@ impl TxDecoder {
                                 body_iter.next()
                             };
 
-                            let formatted = value
-                                .map(|v| format_sol_value(v))
-                                .unwrap_or_else(|| "?".to_string());
-
-                            params.push((input.name.clone(), formatted));
+                            params.push(DecodedParam {
+                                param: event_param(input),
+                                value: value.cloned(),
+                            });
                         }
 
                         params
@@ -179,6 +318,7 @@ impl TxDecoder {
                     event_name: event.name.clone(),
                     params,
                     topic0,
+                    heuristic: false,
                 });
 
                 break; // Found matching event, no need to check more
@@ -188,6 +328,86 @@ impl TxDecoder {
         decoded
     }
 
+    /// Decode a log from an event declaration or bare text signature such as
+    /// `Transfer(address,address,uint256)` without trusting its `indexed`
+    /// markers. Each split of the inputs that fits the log's topic count is
+    /// tried, earliest inputs indexed first, and the first whose topics and
+    /// data are canonical encodings of their types wins.
+    pub fn decode_log_heuristic(
+        signature: &str,
+        log: &alloy::rpc::types::Log,
+    ) -> Option<DecodedLog> {
+        let signature = signature.trim();
+        let event = match signature.strip_prefix("event ") {
+            Some(_) => Event::parse(signature),
+            None => Event::parse(&format!("event {signature}")),
+        }
+        .ok()?;
+        let topics = log.inner.data.topics();
+        let topic0 = *topics.first()?;
+        if event.selector() != topic0 {
+            return None;
+        }
+
+        let types = event
+            .inputs
+            .iter()
+            .map(|input| input.resolve())
+            .collect::<Result<Vec<DynSolType>, _>>()
+            .ok()?;
+        let data = log.inner.data.data.as_ref();
+
+        'split: for indexed in combinations(types.len(), topics.len() - 1) {
+            let mut values = vec![DynSolValue::Bool(false); types.len()];
+            for (&i, topic) in indexed.iter().zip(&topics[1..]) {
+                match topic_value(&types[i], topic) {
+                    Some(value) => values[i] = value,
+                    None => continue 'split,
+                }
+            }
+
+            let body: Vec<usize> = (0..types.len()).filter(|i| !indexed.contains(i)).collect();
+            let body_type = DynSolType::Tuple(body.iter().map(|&i| types[i].clone()).collect());
+            let Ok(decoded) = body_type.abi_decode_sequence(data) else {
+                continue;
+            };
+            // Trailing or non-canonical data means the split is wrong
+            if decoded.abi_encode_params() != data {
+                continue;
+            }
+            let DynSolValue::Tuple(body_values) = decoded else {
+                continue;
+            };
+            for (i, value) in body.into_iter().zip(body_values) {
+                values[i] = value;
+            }
+
+            let params = event
+                .inputs
+                .iter()
+                .zip(values)
+                .map(|(input, value)| {
+                    let mut param = event_param(input);
+                    if param.name.is_empty() {
+                        param.name = param.ty.clone();
+                    }
+                    DecodedParam {
+                        param,
+                        value: Some(value),
+                    }
+                })
+                .collect();
+            return Some(DecodedLog {
+                address: log.inner.address,
+                event_name: event.name.clone(),
+                params,
+                topic0,
+                heuristic: true,
+            });
+        }
+        None
+    }
+
     /// Extract the 4-byte method selector from transaction input data.
     pub fn extract_selector(input: &Bytes) -> Option<[u8; 4]> {
         if input.len() < 4 {
@@ -199,6 +419,98 @@ impl TxDecoder {
     }
 }
 
+/// Format a custom error as `Name(arg: value, ...)`, or just the name when
+/// the arguments do not decode.
+fn format_error(error: &alloy::json_abi::Error, data: &[u8]) -> String {
+    let Ok(values) = error.abi_decode_input(data, false) else {
+        return error.name.clone();
+    };
+    let args = error
+        .inputs
+        .iter()
+        .zip(values.iter())
+        .map(|(param, value)| {
+            if param.name.is_empty() {
+                format_sol_value(value)
+            } else {
+                format!("{}: {}", param.name, format_sol_value(value))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{}({args})", error.name)
+}
+
+/// The value of an indexed input from its topic. Word types must be
+/// canonically encoded; other types are indexed as a hash of their value.
+fn topic_value(ty: &DynSolType, topic: &B256) -> Option<DynSolValue> {
+    match ty {
+        DynSolType::Address
+        | DynSolType::Function
+        | DynSolType::Bool
+        | DynSolType::FixedBytes(_)
+        | DynSolType::Int(_)
+        | DynSolType::Uint(_) => {
+            let value = ty.abi_decode(topic.as_slice()).ok()?;
+            (value.abi_encode() == topic.as_slice()).then_some(value)
+        }
+        _ => Some(DynSolValue::FixedBytes(*topic, 32)),
+    }
+}
+
+/// An event input as a plain ABI parameter.
+fn event_param(input: &EventParam) -> Param {
+    Param {
+        ty: input.ty.clone(),
+        name: input.name.clone(),
+        components: input.components.clone(),
+        internal_type: input.internal_type.clone(),
+    }
+}
+
+/// Every choice of `k` of the positions `0..n`, in lexicographic order.
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+    let mut all = Vec::new();
+    for first in 0..n.saturating_sub(k - 1) {
+        for rest in combinations(n - first - 1, k - 1) {
+            let mut combination = vec![first];
+            combination.extend(rest.into_iter().map(|i| i + first + 1));
+            all.push(combination);
+        }
+    }
+    all
+}
+
+/// The address in the low 20 bytes of an indexed address topic.
+fn topic_address(topic: &B256) -> Address {
+    Address::from_slice(&topic.as_slice()[12..])
+}
+
+/// (id, amount) pairs of a `TransferBatch`, whose data is `(uint256[], uint256[])`.
+/// Malformed data or mismatched lengths yield nothing.
+fn decode_batch(data: &[u8]) -> Vec<(U256, U256)> {
+    let uints = DynSolType::Array(Box::new(DynSolType::Uint(256)));
+    let Ok(DynSolValue::Tuple(arrays)) =
+        DynSolType::Tuple(vec![uints.clone(), uints]).abi_decode_params(data)
+    else {
+        return Vec::new();
+    };
+    let column = |value: &DynSolValue| -> Vec<U256> {
+        value
+            .as_array()
+            .map(|items| items.iter().filter_map(|v| v.as_uint().map(|(u, _)| u)).collect())
+            .unwrap_or_default()
+    };
+    let (ids, amounts) = (column(&arrays[0]), column(&arrays[1]));
+    if ids.len() != amounts.len() {
+        return Vec::new();
+    }
+    ids.into_iter().zip(amounts).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,9 +586,9 @@ mod tests {
         let decoded = TxDecoder::decode_input(&abi, &input).unwrap();
         assert_eq!(decoded.function_name, "transfer");
         assert_eq!(decoded.params.len(), 2);
-        assert_eq!(decoded.params[0].0, "to");
-        assert_eq!(decoded.params[1].0, "amount");
-        assert_eq!(decoded.params[1].1, "1000");
+        assert_eq!(decoded.params[0].name(), "to");
+        assert_eq!(decoded.params[1].name(), "amount");
+        assert_eq!(decoded.params[1].formatted(), "1000");
     }
 
     #[test]
@@ -288,6 +600,98 @@ mod tests {
         assert!(TxDecoder::decode_input(&abi, &input).is_none());
     }
 
+    #[test]
+    fn test_decode_output_unnamed_return() {
+        let abi = erc20_functions_abi();
+        let input = Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb]);
+        let mut ret = vec![0u8; 32];
+        ret[31] = 1;
+
+        let decoded = TxDecoder::decode_output(&abi, &input, &Bytes::from(ret)).unwrap();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].name(), "ret0");
+        assert_eq!(decoded[0].value, Some(DynSolValue::Bool(true)));
+    }
+
+    #[test]
+    fn test_decode_input_keeps_typed_values() {
+        let abi: JsonAbi = serde_json::from_str(
+            r#"[{"type":"function","name":"fill","stateMutability":"nonpayable","outputs":[],
+                "inputs":[{"name":"order","type":"tuple","internalType":"struct Order",
+                    "components":[{"name":"maker","type":"address"},{"name":"data","type":"bytes"}]}]}]"#,
+        )
+        .unwrap();
+        let data = vec![0xab; 40];
+        let order = DynSolValue::Tuple(vec![
+            DynSolValue::Address(Address::repeat_byte(1)),
+            DynSolValue::Bytes(data.clone()),
+        ]);
+        let func = abi.functions().next().unwrap();
+        let input = func.abi_encode_input(std::slice::from_ref(&order)).unwrap();
+
+        let decoded = TxDecoder::decode_input(&abi, &Bytes::from(input)).unwrap();
+        let param = &decoded.params[0];
+        assert_eq!(param.value, Some(order));
+        let fields: Vec<&str> = param.param.components.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(fields, ["maker", "data"]);
+        // Only the one-line form truncates bytes
+        assert!(param.formatted().contains("... (40 bytes)"));
+    }
+
+    #[test]
+    fn test_decode_output_malformed() {
+        let abi = erc20_functions_abi();
+        let input = Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb]);
+        assert!(TxDecoder::decode_output(&abi, &input, &Bytes::new()).is_none());
+    }
+
+    #[test]
+    fn test_decode_revert_error_string() {
+        let data = alloy::sol_types::Revert::from("insufficient balance").abi_encode();
+        assert_eq!(
+            TxDecoder::decode_revert(&data, None).as_deref(),
+            Some("insufficient balance")
+        );
+    }
+
+    #[test]
+    fn test_decode_revert_panic() {
+        let data = alloy::sol_types::Panic::from(0x11).abi_encode();
+        assert_eq!(
+            TxDecoder::decode_revert(&data, None).as_deref(),
+            Some("Panic(0x11): arithmetic overflow or underflow")
+        );
+    }
+
+    #[test]
+    fn test_decode_revert_custom_error() {
+        let abi: JsonAbi = serde_json::from_str(
+            r#"[{"type":"error","name":"InsufficientBalance","inputs":[{"name":"available","type":"uint256"},{"name":"required","type":"uint256"}]}]"#,
+        )
+        .unwrap();
+        let error = abi.errors().next().unwrap();
+        let mut data = error.selector().to_vec();
+        data.extend_from_slice(&U256::from(1).to_be_bytes::<32>());
+        data.extend_from_slice(&U256::from(2).to_be_bytes::<32>());
+
+        assert_eq!(
+            TxDecoder::decode_revert(&data, Some(&abi)).as_deref(),
+            Some("InsufficientBalance(available: 1, required: 2)")
+        );
+        // Unknown without the ABI, but decodable from a looked-up signature
+        assert!(TxDecoder::decode_revert(&data, None).is_none());
+        assert_eq!(
+            TxDecoder::decode_error_signature("InsufficientBalance(uint256,uint256)", &data)
+                .as_deref(),
+            Some("InsufficientBalance(1, 2)")
+        );
+    }
+
+    #[test]
+    fn test_decode_revert_empty() {
+        assert!(TxDecoder::decode_revert(&[], None).is_none());
+    }
+
     fn make_transfer_log(
         token: Address,
         from: Address,
@@ -396,10 +800,248 @@ mod tests {
         let transfers = TxDecoder::extract_token_transfers(&[]);
         assert!(transfers.is_empty());
     }
+
+    fn make_log(topics: Vec<B256>, data: Vec<u8>) -> alloy::rpc::types::Log {
+        alloy::rpc::types::Log {
+            inner: PrimitiveLog {
+                address: Address::ZERO,
+                data: LogData::new(topics, Bytes::from(data)).unwrap(),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_apply_token_metadata() {
+        let usdc = Address::repeat_byte(0x01);
+        let nft = Address::repeat_byte(0x04);
+        let from = Address::repeat_byte(0x02);
+        let to = Address::repeat_byte(0x03);
+        let token_id = B256::from(U256::from(1));
+        let mut transfers = TxDecoder::extract_token_transfers(&[
+            make_transfer_log(usdc, from, to, U256::from(1_500_000)),
+            make_log(
+                vec![TRANSFER_EVENT_TOPIC, from.into_word(), to.into_word(), token_id],
+                Vec::new(),
+            ),
+        ]);
+        transfers[1].token_address = nft;
+        let metadata = [
+            TokenMetadata {
+                address: usdc,
+                name: "USD Coin".to_string(),
+                symbol: "USDC".to_string(),
+                decimals: 6,
+            },
+            TokenMetadata {
+                address: nft,
+                name: "Punks".to_string(),
+                symbol: "PUNK".to_string(),
+                decimals: 18,
+            },
+        ];
+
+        TxDecoder::apply_token_metadata(&mut transfers, &metadata);
+        assert_eq!(transfers[0].token_symbol.as_deref(), Some("USDC"));
+        assert_eq!(transfers[0].decimals, Some(6));
+        assert_eq!(transfers[1].token_name.as_deref(), Some("Punks"));
+        // NFTs have no decimals, whatever the lookup defaulted to
+        assert_eq!(transfers[1].decimals, None);
+    }
+
+    #[test]
+    fn test_apply_token_metadata_decimals() {
+        let from = Address::repeat_byte(0x02);
+        let to = Address::repeat_byte(0x03);
+        let whole = Address::repeat_byte(0x05);
+        let fine = Address::repeat_byte(0x06);
+        let fine_value = U256::from(15u64) * U256::from(10u64).pow(U256::from(23));
+        let mut transfers = TxDecoder::extract_token_transfers(&[
+            make_transfer_log(whole, from, to, U256::from(42)),
+            make_transfer_log(fine, from, to, fine_value),
+        ]);
+        let metadata = [
+            TokenMetadata {
+                address: whole,
+                name: "Whole".to_string(),
+                symbol: "WHL".to_string(),
+                decimals: 0,
+            },
+            TokenMetadata {
+                address: fine,
+                name: "Fine".to_string(),
+                symbol: "FIN".to_string(),
+                decimals: 24,
+            },
+        ];
+
+        TxDecoder::apply_token_metadata(&mut transfers, &metadata);
+        assert_eq!(transfers[0].decimals, Some(0));
+        assert_eq!(transfers[1].decimals, Some(24));
+        let amounts: Vec<_> = transfers
+            .iter()
+            .map(|t| crate::utils::format_u256_as_decimal(t.value, t.decimals.unwrap()))
+            .collect();
+        assert_eq!(amounts, ["42.0", "1.5"]);
+    }
+
+    #[test]
+    fn test_apply_token_metadata_missing() {
+        let known = Address::repeat_byte(0x01);
+        let unknown = Address::repeat_byte(0x07);
+        let from = Address::repeat_byte(0x02);
+        let to = Address::repeat_byte(0x03);
+        let mut transfers = TxDecoder::extract_token_transfers(&[
+            make_transfer_log(unknown, from, to, U256::from(1_500_000)),
+            make_transfer_log(known, from, to, U256::from(1_500_000)),
+        ]);
+        let metadata = [TokenMetadata {
+            address: known,
+            name: "USD Coin".to_string(),
+            symbol: "USDC".to_string(),
+            decimals: 6,
+        }];
+
+        TxDecoder::apply_token_metadata(&mut transfers, &metadata);
+        // A token without metadata keeps its raw value and no labels
+        assert_eq!(transfers[0].token_name, None);
+        assert_eq!(transfers[0].token_symbol, None);
+        assert_eq!(transfers[0].decimals, None);
+        assert_eq!(transfers[0].value, U256::from(1_500_000));
+        assert_eq!(transfers[1].decimals, Some(6));
+
+        // No metadata at all leaves every transfer untouched
+        let mut untouched = transfers[..1].to_vec();
+        TxDecoder::apply_token_metadata(&mut untouched, &[]);
+        assert_eq!(untouched[0].token_symbol, None);
+    }
+
+    #[test]
+    fn test_extract_erc721_transfer() {
+        let from = Address::repeat_byte(0x02);
+        let to = Address::repeat_byte(0x03);
+        let token_id = B256::from(U256::from(7777));
+        let log = make_log(
+            vec![TRANSFER_EVENT_TOPIC, from.into_word(), to.into_word(), token_id],
+            Vec::new(),
+        );
+
+        let transfers = TxDecoder::extract_token_transfers(&[log]);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].standard, TokenStandard::ERC721);
+        assert_eq!(transfers[0].from, from);
+        assert_eq!(transfers[0].to, to);
+        assert_eq!(transfers[0].token_id, Some(U256::from(7777)));
+        assert_eq!(transfers[0].value, U256::from(1));
+    }
+
+    #[test]
+    fn test_extract_erc1155_transfer_single() {
+        let operator = Address::repeat_byte(0x01).into_word();
+        let from = Address::repeat_byte(0x02);
+        let to = Address::repeat_byte(0x03);
+        let mut data = U256::from(42).to_be_bytes::<32>().to_vec();
+        data.extend(U256::from(5).to_be_bytes::<32>());
+        let log = make_log(
+            vec![TRANSFER_SINGLE_TOPIC, operator, from.into_word(), to.into_word()],
+            data,
+        );
+
+        let transfers = TxDecoder::extract_token_transfers(&[log]);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].standard, TokenStandard::ERC1155);
+        assert_eq!(transfers[0].from, from);
+        assert_eq!(transfers[0].to, to);
+        assert_eq!(transfers[0].token_id, Some(U256::from(42)));
+        assert_eq!(transfers[0].value, U256::from(5));
+    }
+
+    #[test]
+    fn test_extract_erc1155_transfer_batch() {
+        let operator = Address::repeat_byte(0x01).into_word();
+        let from = Address::repeat_byte(0x02).into_word();
+        let to = Address::repeat_byte(0x03).into_word();
+        let uints = |values: &[u64]| {
+            DynSolValue::Array(values.iter().map(|v| DynSolValue::Uint(U256::from(*v), 256)).collect())
+        };
+        let data = DynSolValue::Tuple(vec![uints(&[1, 2]), uints(&[10, 20])]).abi_encode_params();
+        let log = make_log(vec![TRANSFER_BATCH_TOPIC, operator, from, to], data);
+
+        let transfers = TxDecoder::extract_token_transfers(&[log]);
+        let moved: Vec<(Option<U256>, U256)> =
+            transfers.iter().map(|t| (t.token_id, t.value)).collect();
+        assert_eq!(
+            moved,
+            [
+                (Some(U256::from(1)), U256::from(10)),
+                (Some(U256::from(2)), U256::from(20)),
+            ]
+        );
+
+        // Mismatched id and amount counts are not guessed at
+        let data = DynSolValue::Tuple(vec![uints(&[1, 2]), uints(&[10])]).abi_encode_params();
+        let log = make_log(vec![TRANSFER_BATCH_TOPIC, operator, from, to], data);
+        assert!(TxDecoder::extract_token_transfers(&[log]).is_empty());
+    }
+
+    #[test]
+    fn test_decode_log_heuristic_fits_topic_count() {
+        let from = Address::repeat_byte(0x02).into_word();
+        let to = Address::repeat_byte(0x03).into_word();
+        let amount = U256::from(9).to_be_bytes::<32>();
+
+        // ERC-20 layout: two indexed addresses, value in data
+        let log = make_log(vec![TRANSFER_EVENT_TOPIC, from, to], amount.to_vec());
+        let decoded =
+            TxDecoder::decode_log_heuristic("Transfer(address,address,uint256)", &log).unwrap();
+        assert!(decoded.heuristic);
+        assert_eq!(decoded.event_name, "Transfer");
+        assert_eq!(decoded.params[0].name(), "address");
+        assert_eq!(decoded.params[2].name(), "uint256");
+        assert_eq!(decoded.params[2].value, Some(DynSolValue::Uint(U256::from(9), 256)));
+
+        // ERC-721 layout: everything indexed, names kept from a declaration
+        let log = make_log(vec![TRANSFER_EVENT_TOPIC, from, to, B256::from(amount)], Vec::new());
+        let decoded = TxDecoder::decode_log_heuristic(
+            "event Transfer(address from, address to, uint256 tokenId)",
+            &log,
+        )
+        .unwrap();
+        assert_eq!(decoded.params[2].name(), "tokenId");
+        assert_eq!(decoded.params[2].formatted(), "9");
+
+        // Signature that does not hash to topic0
+        assert!(TxDecoder::decode_log_heuristic("Approval(address,address,uint256)", &log).is_none());
+    }
+
+    #[test]
+    fn test_decode_log_heuristic_rejects_non_canonical_splits() {
+        let event = Event::parse("event Flag(bool,address)").unwrap();
+        let account = Address::repeat_byte(0x04).into_word();
+        let flag = U256::from(1).to_be_bytes::<32>().to_vec();
+
+        // An address is not a valid bool topic, so the address must be the indexed input
+        let log = make_log(vec![event.selector(), account], flag);
+        let decoded = TxDecoder::decode_log_heuristic("Flag(bool,address)", &log).unwrap();
+        assert_eq!(decoded.params[0].value, Some(DynSolValue::Bool(true)));
+
+        // Trailing data fits no split
+        let mut data = account.to_vec();
+        data.extend_from_slice(&[0; 32]);
+        let log = make_log(vec![event.selector(), B256::with_last_byte(1)], data);
+        assert!(TxDecoder::decode_log_heuristic("Flag(bool,address)", &log).is_none());
+    }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(combinations(3, 0), vec![Vec::<usize>::new()]);
+        assert_eq!(combinations(3, 2), vec![vec![0, 1], vec![0, 2], vec![1, 2]]);
+        assert!(combinations(2, 3).is_empty());
+    }
 }
 
 /// Format a dynamic Solidity value to a human-readable string.
-fn format_sol_value(value: &DynSolValue) -> String {
+pub fn format_sol_value(value: &DynSolValue) -> String {
     match value {
         DynSolValue::Bool(b) => b.to_string(),
         DynSolValue::Int(i, _) => i.to_string(),
@@ -0,0 +1,536 @@
+use revm::bytecode::OpCode;
+
+const PUSH1: u8 = 0x60;
+const PUSH3: u8 = 0x62;
+const PUSH4: u8 = 0x63;
+const PUSH32: u8 = 0x7f;
+const DUP1: u8 = 0x80;
+const DUP2: u8 = 0x81;
+const EQ: u8 = 0x14;
+const JUMPDEST: u8 = 0x5b;
+const JUMPI: u8 = 0x57;
+
+/// Arrays nested in a metadata value; compilers emit at most one level
+const MAX_CBOR_DEPTH: usize = 1;
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// One decoded instruction of runtime bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub pc: usize,
+    pub opcode: u8,
+    /// Immediate bytes of a PUSH, cut short if the code ends first
+    pub push_data: Vec<u8>,
+}
+
+impl Instruction {
+    /// Mnemonic, or UNKNOWN for bytes that are not an opcode.
+    pub fn name(&self) -> &'static str {
+        OpCode::new(self.opcode).map_or("UNKNOWN", |op| op.as_str())
+    }
+
+    pub fn is_jumpdest(&self) -> bool {
+        self.opcode == JUMPDEST
+    }
+
+    /// The pushed value as a program counter, if it fits one.
+    pub fn push_value(&self) -> Option<usize> {
+        if self.push_data.is_empty() || self.push_data.len() > 8 {
+            return None;
+        }
+        Some(
+            self.push_data
+                .iter()
+                .fold(0usize, |acc, b| (acc << 8) | *b as usize),
+        )
+    }
+}
+
+/// Solidity or Vyper metadata appended to runtime code as CBOR.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodeMetadata {
+    /// Bytes of the trailer, including its 2-byte length
+    pub len: usize,
+    /// e.g. `solc 0.8.24` or `vyper 0.3.10`
+    pub compiler: Option<String>,
+    /// IPFS CID of the metadata file
+    pub ipfs: Option<String>,
+    /// Swarm hash of the metadata file, with its version (`bzzr0`/`bzzr1`)
+    pub swarm: Option<(String, String)>,
+    /// Compiled with experimental features enabled
+    pub experimental: bool,
+}
+
+/// A branch of the Solidity function dispatcher: the selector compared
+/// against the calldata and the function body it jumps to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchEntry {
+    pub selector: [u8; 4],
+    /// Program counters of the selector's PUSH and of the JUMPI
+    pub pc: usize,
+    pub jumpi_pc: usize,
+    pub target: usize,
+    /// Function name from the contract's ABI or the signature database
+    pub name: Option<String>,
+}
+
+/// Disassembled runtime code of a contract.
+#[derive(Debug, Clone, Default)]
+pub struct Disassembly {
+    pub code_size: usize,
+    pub instructions: Vec<Instruction>,
+    pub metadata: Option<CodeMetadata>,
+    pub dispatch: Vec<DispatchEntry>,
+}
+
+impl Disassembly {
+    /// Index of the instruction starting at `pc`.
+    pub fn index_of_pc(&self, pc: usize) -> Option<usize> {
+        self.instructions.binary_search_by_key(&pc, |i| i.pc).ok()
+    }
+
+    /// The dispatcher branch an instruction belongs to.
+    pub fn dispatch_at(&self, pc: usize) -> Option<&DispatchEntry> {
+        self.dispatch
+            .iter()
+            .find(|entry| (entry.pc..=entry.jumpi_pc).contains(&pc))
+    }
+
+    /// The dispatcher branch jumping to a function body at `pc`.
+    pub fn dispatch_to(&self, pc: usize) -> Option<&DispatchEntry> {
+        self.dispatch.iter().find(|entry| entry.target == pc)
+    }
+
+    /// Index of the JUMPDEST a PUSH at `index` names, if it names one.
+    pub fn jump_target(&self, index: usize) -> Option<usize> {
+        let pc = self.instructions.get(index)?.push_value()?;
+        let target = self.index_of_pc(pc)?;
+        self.instructions[target].is_jumpdest().then_some(target)
+    }
+
+    /// Next instruction after `from` (or before, going backwards) matching
+    /// `query`, wrapping around the code.
+    pub fn find(&self, query: &str, from: usize, forward: bool) -> Option<usize> {
+        let len = self.instructions.len();
+        if len == 0 {
+            return None;
+        }
+        (1..=len)
+            .map(|step| {
+                if forward {
+                    (from + step) % len
+                } else {
+                    (from + len - step % len) % len
+                }
+            })
+            .find(|&i| matches_query(&self.instructions[i], query))
+    }
+}
+
+/// Whether an instruction matches a search: an opcode name, a family like
+/// `LOG` or `PUSH`, or `0x` hex found in the push data.
+pub fn matches_query(instruction: &Instruction, query: &str) -> bool {
+    let query = query.trim();
+    if let Some(hex) = query.strip_prefix("0x") {
+        let hex = hex.to_ascii_lowercase();
+        return !hex.is_empty() && alloy::hex::encode(&instruction.push_data).contains(&hex);
+    }
+    let query = query.to_ascii_uppercase();
+    let name = instruction.name();
+    match name.strip_prefix(query.as_str()) {
+        Some(rest) => !query.is_empty() && rest.chars().all(|c| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+/// Split off the metadata trailer and decode the rest into instructions,
+/// marking the function dispatcher.
+pub fn disassemble(code: &[u8]) -> Disassembly {
+    let metadata = metadata_trailer(code);
+    let body_len = code.len() - metadata.as_ref().map_or(0, |m| m.len);
+    let instructions = decode(&code[..body_len]);
+    let dispatch = find_dispatch(&instructions);
+    Disassembly {
+        code_size: code.len(),
+        instructions,
+        metadata,
+        dispatch,
+    }
+}
+
+pub(crate) fn decode(code: &[u8]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        let push_len = match opcode {
+            PUSH1..=PUSH32 => (opcode - PUSH1 + 1) as usize,
+            _ => 0,
+        };
+        let end = (pc + 1 + push_len).min(code.len());
+        instructions.push(Instruction {
+            pc,
+            opcode,
+            push_data: code[pc + 1..end].to_vec(),
+        });
+        pc += 1 + push_len;
+    }
+    instructions
+}
+
+/// Selector comparisons of the dispatcher: `PUSH4 selector`, optionally a
+/// DUP, `EQ`, `PUSH target`, `JUMPI`. Selectors with a leading zero byte are
+/// pushed with PUSH3.
+fn find_dispatch(instructions: &[Instruction]) -> Vec<DispatchEntry> {
+    let mut entries = Vec::new();
+    for (i, push) in instructions.iter().enumerate() {
+        if !matches!(push.opcode, PUSH3 | PUSH4)
+            || push.push_data.len() != (push.opcode - PUSH1 + 1) as usize
+        {
+            continue;
+        }
+        let mut next = i + 1;
+        if matches!(instructions.get(next).map(|i| i.opcode), Some(DUP1 | DUP2)) {
+            next += 1;
+        }
+        let (Some(eq), Some(dest), Some(jumpi)) = (
+            instructions.get(next),
+            instructions.get(next + 1),
+            instructions.get(next + 2),
+        ) else {
+            continue;
+        };
+        if eq.opcode != EQ || !matches!(dest.opcode, PUSH1..=PUSH32) || jumpi.opcode != JUMPI {
+            continue;
+        }
+        let Some(target) = dest.push_value() else {
+            continue;
+        };
+        let mut selector = [0u8; 4];
+        selector[4 - push.push_data.len()..].copy_from_slice(&push.push_data);
+        entries.push(DispatchEntry {
+            selector,
+            pc: push.pc,
+            jumpi_pc: jumpi.pc,
+            target,
+            name: None,
+        });
+    }
+    entries
+}
+
+/// The CBOR metadata trailer, whose length is in the code's last two bytes.
+/// Only accepted if it decodes to a map with a compiler or metadata hash.
+pub fn metadata_trailer(code: &[u8]) -> Option<CodeMetadata> {
+    let len = code.len();
+    if len < 2 {
+        return None;
+    }
+    let cbor_len = u16::from_be_bytes([code[len - 2], code[len - 1]]) as usize;
+    if cbor_len == 0 || cbor_len + 2 > len {
+        return None;
+    }
+    let cbor = &code[len - 2 - cbor_len..len - 2];
+
+    let mut pos = 0;
+    let (major, count) = cbor_header(cbor, &mut pos)?;
+    if major != 5 {
+        return None;
+    }
+    let mut metadata = CodeMetadata {
+        len: cbor_len + 2,
+        ..Default::default()
+    };
+    for _ in 0..count {
+        let Cbor::Text(key) = cbor_item(cbor, &mut pos, 0)? else {
+            return None;
+        };
+        let value = cbor_item(cbor, &mut pos, 0)?;
+        match (key.as_str(), value) {
+            ("ipfs", Cbor::Bytes(hash)) => metadata.ipfs = Some(base58(&hash)),
+            (version @ ("bzzr0" | "bzzr1"), Cbor::Bytes(hash)) => {
+                metadata.swarm = Some((
+                    version.to_string(),
+                    format!("0x{}", alloy::hex::encode(hash)),
+                ));
+            }
+            (compiler @ ("solc" | "vyper"), value) => {
+                metadata.compiler = Some(format!("{compiler} {}", version_string(&value)?));
+            }
+            ("experimental", Cbor::Bool(experimental)) => metadata.experimental = experimental,
+            _ => {}
+        }
+    }
+    if pos != cbor.len() {
+        return None;
+    }
+    let known = metadata.compiler.is_some() || metadata.ipfs.is_some() || metadata.swarm.is_some();
+    known.then_some(metadata)
+}
+
+/// The subset of CBOR compilers write into metadata.
+enum Cbor {
+    Uint(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Bool(bool),
+}
+
+/// Major type and argument of the item at `pos`.
+fn cbor_header(data: &[u8], pos: &mut usize) -> Option<(u8, u64)> {
+    let initial = *data.get(*pos)?;
+    *pos += 1;
+    let major = initial >> 5;
+    let info = initial & 0x1f;
+    let size = match info {
+        0..=23 => return Some((major, info as u64)),
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => return None,
+    };
+    let bytes = data.get(*pos..*pos + size)?;
+    *pos += size;
+    Some((
+        major,
+        bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64),
+    ))
+}
+
+/// The item at `pos`, with `depth` arrays already open around it.
+fn cbor_item(data: &[u8], pos: &mut usize, depth: usize) -> Option<Cbor> {
+    let (major, arg) = cbor_header(data, pos)?;
+    match major {
+        0 => Some(Cbor::Uint(arg)),
+        2 | 3 => {
+            // Lengths come from arbitrary bytecode; overflow means malformed
+            let end = pos.checked_add(usize::try_from(arg).ok()?)?;
+            let bytes = data.get(*pos..end)?.to_vec();
+            *pos = end;
+            if major == 2 {
+                Some(Cbor::Bytes(bytes))
+            } else {
+                String::from_utf8(bytes).ok().map(Cbor::Text)
+            }
+        }
+        // Nesting comes from arbitrary bytecode too; bound the recursion
+        4 if depth < MAX_CBOR_DEPTH => (0..arg)
+            .map(|_| cbor_item(data, pos, depth + 1))
+            .collect::<Option<Vec<_>>>()
+            .map(Cbor::Array),
+        7 if arg == 20 || arg == 21 => Some(Cbor::Bool(arg == 21)),
+        _ => None,
+    }
+}
+
+/// A compiler version: three bytes or integers, or a full version string.
+fn version_string(value: &Cbor) -> Option<String> {
+    match value {
+        Cbor::Bytes(v) if v.len() == 3 => Some(format!("{}.{}.{}", v[0], v[1], v[2])),
+        Cbor::Text(text) => Some(text.clone()),
+        Cbor::Array(parts) => parts
+            .iter()
+            .map(|part| match part {
+                Cbor::Uint(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|parts| parts.join(".")),
+        _ => None,
+    }
+}
+
+/// Base58 (Bitcoin alphabet), the encoding of CIDv0 IPFS hashes.
+fn base58(bytes: &[u8]) -> String {
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    std::iter::repeat_n(b'1', zeros)
+        .chain(digits.iter().rev().map(|d| BASE58_ALPHABET[*d as usize]))
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dispatcher of a contract with `transfer(address,uint256)` and a
+    /// selector starting with a zero byte, followed by solc 0.8.24 metadata.
+    fn sample_code() -> Vec<u8> {
+        let mut code = alloy::hex::decode(
+            concat!(
+                "6080604052",              // PUSH1 0x80 PUSH1 0x40 MSTORE
+                "60003560e01c",            // PUSH1 0 CALLDATALOAD PUSH1 0xe0 SHR
+                "8063a9059cbb14610023 57", // DUP1 PUSH4 transfer EQ PUSH2 0x0023 JUMPI
+                "80620a0b0c14610025 57",   // DUP1 PUSH3 0x000a0b0c EQ PUSH2 0x0025 JUMPI
+                "5f80fd",                  // PUSH0 DUP1 REVERT
+                "5b00",                    // 0x23: JUMPDEST STOP
+                "5bff",                    // 0x25: JUMPDEST SELFDESTRUCT
+            )
+            .replace(' ', ""),
+        )
+        .unwrap();
+        code.extend(solc_metadata());
+        code
+    }
+
+    /// {"ipfs": <34 bytes>, "solc": 0x000818} with its length suffix.
+    fn solc_metadata() -> Vec<u8> {
+        let mut cbor = vec![0xa2, 0x64];
+        cbor.extend(b"ipfs");
+        cbor.extend([0x58, 0x22, 0x12, 0x20]);
+        cbor.extend([0xab; 32]);
+        cbor.push(0x64);
+        cbor.extend(b"solc");
+        cbor.extend([0x43, 0x00, 0x08, 0x18]);
+        let len = cbor.len() as u16;
+        cbor.extend(len.to_be_bytes());
+        cbor
+    }
+
+    #[test]
+    fn test_decode_push_data() {
+        let code = [0x60, 0x80, 0x61, 0x01, 0x02, 0x5b, 0x7f, 0xaa];
+        let instructions = decode(&code);
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(instructions[0].name(), "PUSH1");
+        assert_eq!(instructions[1].push_value(), Some(0x0102));
+        assert_eq!(instructions[2].pc, 5);
+        assert!(instructions[2].is_jumpdest());
+        // Truncated PUSH32 at the end of the code
+        assert_eq!(instructions[3].push_data, vec![0xaa]);
+    }
+
+    #[test]
+    fn test_unknown_opcode() {
+        let instructions = decode(&[0x0c]);
+        assert_eq!(instructions[0].name(), "UNKNOWN");
+    }
+
+    #[test]
+    fn test_metadata_trailer() {
+        let disassembly = disassemble(&sample_code());
+        let metadata = disassembly.metadata.unwrap();
+        assert_eq!(metadata.compiler.as_deref(), Some("solc 0.8.24"));
+        assert!(metadata.ipfs.unwrap().starts_with("Qm"));
+        assert_eq!(metadata.len, solc_metadata().len());
+        // The trailer is not disassembled
+        let last = disassembly.instructions.last().unwrap();
+        assert_eq!(last.name(), "SELFDESTRUCT");
+    }
+
+    #[test]
+    fn test_vyper_metadata() {
+        // {"vyper": [0, 3, 10]}
+        let mut code = vec![0x00, 0xa1, 0x65];
+        code.extend(b"vyper");
+        code.extend([0x83, 0x00, 0x03, 0x0a, 0x00, 0x0b]);
+        let metadata = metadata_trailer(&code).unwrap();
+        assert_eq!(metadata.compiler.as_deref(), Some("vyper 0.3.10"));
+    }
+
+    #[test]
+    fn test_oversized_cbor_length() {
+        // {"ipfs": bytes of length 2^64 - 1}
+        let mut code = vec![0xa1, 0x64];
+        code.extend(b"ipfs");
+        code.push(0x5b);
+        code.extend([0xff; 8]);
+        code.extend([0x00, 0x0f]);
+        assert_eq!(metadata_trailer(&code), None);
+    }
+
+    #[test]
+    fn test_deeply_nested_cbor() {
+        // {"solc": [[[...]]]} nested far deeper than any compiler emits
+        let mut cbor = vec![0xa1, 0x64];
+        cbor.extend(b"solc");
+        cbor.extend([0x81; 60_000]);
+        cbor.push(0x00);
+        let mut code = cbor.clone();
+        code.extend((cbor.len() as u16).to_be_bytes());
+        assert_eq!(metadata_trailer(&code), None);
+    }
+
+    #[test]
+    fn test_no_metadata() {
+        assert_eq!(metadata_trailer(&[0x60, 0x80, 0x60, 0x40, 0x52]), None);
+        assert_eq!(metadata_trailer(&[]), None);
+        assert_eq!(disassemble(&[0x00, 0x00]).instructions.len(), 2);
+    }
+
+    #[test]
+    fn test_dispatcher() {
+        let disassembly = disassemble(&sample_code());
+        let selectors: Vec<_> = disassembly.dispatch.iter().map(|e| e.selector).collect();
+        assert_eq!(
+            selectors,
+            vec![[0xa9, 0x05, 0x9c, 0xbb], [0x00, 0x0a, 0x0b, 0x0c]]
+        );
+        let transfer = &disassembly.dispatch[0];
+        assert_eq!(transfer.target, 0x23);
+        assert_eq!(
+            disassembly.dispatch_at(transfer.pc + 5).map(|e| e.selector),
+            Some(transfer.selector)
+        );
+        assert_eq!(disassembly.dispatch_to(0x23).map(|e| e.target), Some(0x23));
+    }
+
+    #[test]
+    fn test_jump_target() {
+        let disassembly = disassemble(&sample_code());
+        let push = disassembly
+            .instructions
+            .iter()
+            .position(|i| i.push_value() == Some(0x23) && i.opcode == 0x61)
+            .unwrap();
+        let target = disassembly.jump_target(push).unwrap();
+        assert_eq!(disassembly.instructions[target].pc, 0x23);
+        // PUSH1 0x80 does not point at a JUMPDEST
+        assert_eq!(disassembly.jump_target(0), None);
+    }
+
+    #[test]
+    fn test_find() {
+        let disassembly = disassemble(&sample_code());
+        let selfdestruct = disassembly.find("selfdestruct", 0, true).unwrap();
+        assert_eq!(
+            disassembly.instructions[selfdestruct].name(),
+            "SELFDESTRUCT"
+        );
+        // Wraps around going backwards
+        assert_eq!(
+            disassembly.find("SELFDESTRUCT", 0, false),
+            Some(selfdestruct)
+        );
+        assert_eq!(disassembly.find("DELEGATECALL", 0, true), None);
+
+        let push = disassembly.find("push", 0, true).unwrap();
+        assert_eq!(disassembly.instructions[push].name(), "PUSH1");
+        let selector = disassembly.find("0xa9059cbb", 0, true).unwrap();
+        assert_eq!(disassembly.instructions[selector].name(), "PUSH4");
+        // CALL does not match CALLDATALOAD
+        assert_eq!(disassembly.find("CALL", 0, true), None);
+    }
+
+    #[test]
+    fn test_base58() {
+        assert_eq!(base58(b"hello world"), "StV1DL6CwTryKyV");
+        assert_eq!(base58(&[0, 0, 1]), "112");
+    }
+}
@@ -0,0 +1,344 @@
+use alloy::json_abi::JsonAbi;
+use alloy::primitives::{Address, B256, U256};
+use serde_json::Value;
+
+use crate::data::abi::{self, Lookup};
+use crate::data::signatures;
+use crate::data::types::{ChainConfig, TransactionSummary, TxStatus, TxType};
+
+/// Etherscan's multichain API; the chain is picked by the `chainid` parameter.
+const ETHERSCAN_V2_URL: &str = "https://api.etherscan.io/v2/api";
+
+/// Transactions fetched for an address's history.
+const HISTORY_SIZE: usize = 20;
+
+/// The block explorer API a chain's ABIs, sources and history come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExplorerApi {
+    /// Etherscan v2, covering every Etherscan-family explorer with one API key
+    EtherscanV2,
+    /// A Blockscout instance's Etherscan-compatible API, by base URL
+    Blockscout(String),
+}
+
+/// Name and compiler of a contract's verified source on an explorer.
+#[derive(Debug, Clone)]
+pub struct VerifiedSource {
+    pub name: String,
+    pub compiler: String,
+}
+
+/// Client for the explorer configured for a chain.
+#[derive(Clone)]
+pub struct ExplorerClient {
+    client: reqwest::Client,
+    api: ExplorerApi,
+    chain_id: u64,
+    api_key: Option<String>,
+}
+
+impl ExplorerClient {
+    /// Client for a chain's explorer. Etherscan needs an API key, so without
+    /// one there is no client; Blockscout works without.
+    pub fn from_chain(config: &ChainConfig) -> Option<Self> {
+        let api = config.explorer_api.clone()?;
+        if api == ExplorerApi::EtherscanV2 && config.explorer_api_key.is_none() {
+            return None;
+        }
+        Some(Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            api,
+            chain_id: config.chain_id,
+            api_key: config.explorer_api_key.clone(),
+        })
+    }
+
+    /// Explorer name, shown as the source of resolved ABIs.
+    pub fn name(&self) -> &'static str {
+        match self.api {
+            ExplorerApi::EtherscanV2 => "Etherscan",
+            ExplorerApi::Blockscout(_) => "Blockscout",
+        }
+    }
+
+    /// Request URL for a query string such as `module=contract&action=getabi`.
+    fn url(&self, query: &str) -> String {
+        let mut url = match self.api {
+            ExplorerApi::EtherscanV2 => {
+                format!("{ETHERSCAN_V2_URL}?chainid={}&{query}", self.chain_id)
+            }
+            ExplorerApi::Blockscout(ref base) => {
+                let base = base.trim_end_matches('/');
+                let base = base.strip_suffix("/api").unwrap_or(base);
+                format!("{base}/api?{query}")
+            }
+        };
+        if let Some(ref key) = self.api_key {
+            url.push_str(&format!("&apikey={key}"));
+        }
+        url
+    }
+
+    /// Run a query and return its `result`.
+    async fn query(&self, query: &str) -> Lookup<Value> {
+        match abi::get_json(&self.client, &self.url(query)).await {
+            Lookup::Found(body) => parse_response(body),
+            other => other,
+        }
+    }
+
+    /// ABI of a verified contract.
+    /// GET ?module=contract&action=getabi&address={address}
+    pub async fn abi(&self, address: Address) -> Lookup<JsonAbi> {
+        self.query(&format!("module=contract&action=getabi&address={address}"))
+            .await
+            // The ABI comes back as a JSON string
+            .and_then(|result| serde_json::from_str(result.as_str()?).ok())
+    }
+
+    /// Contract name and compiler of a verified contract.
+    /// GET ?module=contract&action=getsourcecode&address={address}
+    pub async fn source(&self, address: Address) -> Option<VerifiedSource> {
+        let result = self
+            .query(&format!(
+                "module=contract&action=getsourcecode&address={address}"
+            ))
+            .await
+            .found()?;
+        parse_source_code(result.as_array()?.first()?)
+    }
+
+    /// Most recent transactions to and from an address.
+    /// GET ?module=account&action=txlist&address={address}&sort=desc
+    pub async fn tx_history(&self, address: Address) -> Vec<TransactionSummary> {
+        let query = format!(
+            "module=account&action=txlist&address={address}&startblock=0&endblock=99999999&page=1&offset={HISTORY_SIZE}&sort=desc"
+        );
+        match self.query(&query).await {
+            Lookup::Found(Value::Array(items)) => items.iter().filter_map(parse_tx).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// The `result` of a response. Both APIs report success with status "1".
+/// Status "0" is an answer when it says the contract isn't verified or has
+/// no transactions; rate limits, bad keys and other errors are transient.
+fn parse_response(mut body: Value) -> Lookup<Value> {
+    if body.get("status").and_then(Value::as_str) == Some("1") {
+        return match body.get_mut("result") {
+            Some(result) => Lookup::Found(result.take()),
+            None => Lookup::Transient,
+        };
+    }
+    let says = |field: &str| {
+        body.get(field)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_lowercase()
+    };
+    let reason = format!("{} {}", says("message"), says("result"));
+    if reason.contains("not verified") || reason.contains("no transactions found") {
+        Lookup::NotFound
+    } else {
+        Lookup::Transient
+    }
+}
+
+/// Parse one `getsourcecode` result. Unverified contracts come back with
+/// an empty source.
+pub fn parse_source_code(item: &Value) -> Option<VerifiedSource> {
+    if item.get("SourceCode")?.as_str()?.is_empty() {
+        return None;
+    }
+    let name = item
+        .get("ContractName")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let compiler = item
+        .get("CompilerVersion")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    Some(VerifiedSource {
+        name,
+        compiler,
+    })
+}
+
+/// Parse one `txlist` entry.
+fn parse_tx(item: &Value) -> Option<TransactionSummary> {
+    let hash = item["hash"].as_str().and_then(|s| s.parse::<B256>().ok())?;
+    let block_number = item["blockNumber"]
+        .as_str()
+        .and_then(|s| s.parse::<u64>().ok());
+    let timestamp = item["timeStamp"]
+        .as_str()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(0);
+    let from = item["from"]
+        .as_str()
+        .and_then(|s| s.parse::<Address>().ok())
+        .unwrap_or(Address::ZERO);
+    let to = item["to"].as_str().and_then(|s| s.parse::<Address>().ok());
+    let value = item["value"]
+        .as_str()
+        .and_then(|s| s.parse::<U256>().ok())
+        .unwrap_or(U256::ZERO);
+    let gas_used = item["gasUsed"].as_str().and_then(|s| s.parse::<u64>().ok());
+    let gas_price = item["gasPrice"]
+        .as_str()
+        .and_then(|s| s.parse::<u128>().ok());
+    let is_error = item["isError"].as_str().unwrap_or("0") == "1";
+
+    let input_str = item["input"].as_str().unwrap_or("0x");
+    let method_id = if input_str.len() >= 10 {
+        let hex = input_str.trim_start_matches("0x");
+        alloy::primitives::hex::decode(&hex[..8])
+            .ok()
+            .and_then(|b| {
+                let arr: [u8; 4] = b.try_into().ok()?;
+                Some(arr)
+            })
+    } else {
+        None
+    };
+
+    Some(TransactionSummary {
+        hash,
+        block_number,
+        timestamp,
+        from,
+        to,
+        value,
+        gas_used,
+        gas_price,
+        method_id,
+        method_name: method_id.and_then(|sel| signatures::global().function_name(sel)),
+        tx_type: TxType::EIP1559,
+        status: if is_error {
+            TxStatus::Failed
+        } else {
+            TxStatus::Success
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(api: Option<ExplorerApi>, api_key: Option<&str>) -> ChainConfig {
+        ChainConfig {
+            name: "Base".to_string(),
+            chain_id: 8453,
+            rpc_url: String::new(),
+            symbol: "ETH".to_string(),
+            explorer_url: None,
+            explorer_api: api,
+            explorer_api_key: api_key.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_etherscan_needs_api_key() {
+        assert!(
+            ExplorerClient::from_chain(&config(Some(ExplorerApi::EtherscanV2), None)).is_none()
+        );
+        assert!(ExplorerClient::from_chain(&config(None, Some("KEY"))).is_none());
+    }
+
+    #[test]
+    fn test_etherscan_url_selects_chain() {
+        let client =
+            ExplorerClient::from_chain(&config(Some(ExplorerApi::EtherscanV2), Some("KEY")))
+                .unwrap();
+        assert_eq!(client.name(), "Etherscan");
+        assert_eq!(
+            client.url("module=contract&action=getabi&address=0x00"),
+            "https://api.etherscan.io/v2/api?chainid=8453&module=contract&action=getabi&address=0x00&apikey=KEY"
+        );
+    }
+
+    #[test]
+    fn test_blockscout_url_without_key() {
+        for base in [
+            "https://base.blockscout.com",
+            "https://base.blockscout.com/api/",
+        ] {
+            let api = ExplorerApi::Blockscout(base.to_string());
+            let client = ExplorerClient::from_chain(&config(Some(api), None)).unwrap();
+            assert_eq!(client.name(), "Blockscout");
+            assert_eq!(
+                client.url("module=account&action=txlist"),
+                "https://base.blockscout.com/api?module=account&action=txlist"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_verified_source() {
+        let item = json!({
+            "SourceCode": "contract Token {}",
+            "ContractName": "Token",
+            "CompilerVersion": "v0.8.24+commit.e11b9ed9",
+        });
+        let source = parse_source_code(&item).unwrap();
+        assert_eq!(source.name, "Token");
+        assert_eq!(source.compiler, "v0.8.24+commit.e11b9ed9");
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let ok = json!({ "status": "1", "message": "OK", "result": "[]" });
+        assert_eq!(parse_response(ok), Lookup::Found(json!("[]")));
+
+        let unverified = json!({
+            "status": "0",
+            "message": "NOTOK",
+            "result": "Contract source code not verified",
+        });
+        assert_eq!(parse_response(unverified), Lookup::NotFound);
+
+        let rate_limited = json!({
+            "status": "0",
+            "message": "NOTOK",
+            "result": "Max calls per sec rate limit reached (5/sec)",
+        });
+        assert_eq!(parse_response(rate_limited), Lookup::Transient);
+    }
+
+    #[test]
+    fn test_parse_unverified_source() {
+        let item = json!({ "SourceCode": "", "ABI": "Contract source code not verified" });
+        assert!(parse_source_code(&item).is_none());
+    }
+
+    #[test]
+    fn test_parse_tx() {
+        let item = json!({
+            "hash": format!("{}", B256::repeat_byte(1)),
+            "blockNumber": "19000000",
+            "timeStamp": "1700000000",
+            "from": format!("{}", Address::repeat_byte(2)),
+            "to": "",
+            "value": "1000",
+            "gasUsed": "21000",
+            "gasPrice": "30000000000",
+            "isError": "1",
+            "input": "0xa9059cbb0000",
+        });
+        let tx = parse_tx(&item).unwrap();
+        assert_eq!(tx.block_number, Some(19_000_000));
+        assert_eq!(tx.to, None);
+        assert_eq!(tx.value, U256::from(1000));
+        assert_eq!(tx.method_id, Some([0xa9, 0x05, 0x9c, 0xbb]));
+        assert_eq!(tx.status, TxStatus::Failed);
+    }
+}
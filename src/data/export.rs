@@ -1,7 +1,11 @@
 use std::fs;
 use std::io::Write;
 
-use crate::data::types::{AddressInfo, BlockSummary, TransactionDetail};
+use alloy::dyn_abi::DynSolValue;
+use alloy::json_abi::Param;
+
+use crate::data::types::{AddressInfo, BlockSummary, GasProfile, TokenStandard, TransactionDetail};
+use crate::utils;
 
 /// Export block summaries to CSV format.
 ///
@@ -69,20 +73,36 @@ pub fn export_tx_json(detail: &TransactionDetail, path: &str) -> Result<String,
         "input_data": format!("0x{}", alloy::primitives::hex::encode(&detail.input_data)),
         "decoded_input": detail.decoded_input.as_ref().map(|d| serde_json::json!({
             "function": d.function_name,
-            "params": d.params.iter().map(|(name, val)| serde_json::json!({
-                "name": name,
-                "value": val,
+            "params": d.params.iter().map(|p| serde_json::json!({
+                "name": p.name(),
+                "type": p.param.selector_type(),
+                "value": p.value.as_ref().map(|v| sol_value_json(&p.param, v)),
             })).collect::<Vec<_>>(),
         })),
-        "token_transfers": detail.token_transfers.iter().map(|t| serde_json::json!({
-            "token": format!("{:#x}", t.token_address),
-            "from": format!("{:#x}", t.from),
-            "to": format!("{:#x}", t.to),
-            "value": t.value.to_string(),
-            "token_name": t.token_name,
-            "token_symbol": t.token_symbol,
-            "decimals": t.decimals,
-        })).collect::<Vec<_>>(),
+        "token_transfers": detail.token_transfers.iter()
+            .filter(|t| t.standard == TokenStandard::ERC20)
+            .map(|t| serde_json::json!({
+                "token": format!("{:#x}", t.token_address),
+                "from": format!("{:#x}", t.from),
+                "to": format!("{:#x}", t.to),
+                "value": t.value.to_string(),
+                "amount": t.decimals.map(|d| utils::format_u256_as_decimal(t.value, d)),
+                "token_name": t.token_name,
+                "token_symbol": t.token_symbol,
+                "decimals": t.decimals,
+            })).collect::<Vec<_>>(),
+        "nft_transfers": detail.token_transfers.iter()
+            .filter(|t| t.standard != TokenStandard::ERC20)
+            .map(|t| serde_json::json!({
+                "token": format!("{:#x}", t.token_address),
+                "standard": t.standard.to_string(),
+                "from": format!("{:#x}", t.from),
+                "to": format!("{:#x}", t.to),
+                "token_id": t.token_id.map(|id| id.to_string()),
+                "amount": t.value.to_string(),
+                "token_name": t.token_name,
+                "token_symbol": t.token_symbol,
+            })).collect::<Vec<_>>(),
         "logs_count": detail.logs_count,
         "confirmations": detail.confirmations,
     });
@@ -97,6 +117,56 @@ pub fn export_tx_json(detail: &TransactionDetail, path: &str) -> Result<String,
     Ok(format!("Exported transaction to {path}"))
 }
 
+/// A decoded value as JSON, in full: bytes as complete hex, numbers as
+/// decimal strings so no precision is lost, arrays as arrays and tuples as
+/// objects keyed by field name (arrays when the fields are unnamed).
+fn sol_value_json(param: &Param, value: &DynSolValue) -> serde_json::Value {
+    match value {
+        DynSolValue::Bool(b) => serde_json::json!(b),
+        DynSolValue::Int(i, _) => serde_json::json!(i.to_string()),
+        DynSolValue::Uint(u, _) => serde_json::json!(u.to_string()),
+        DynSolValue::Address(a) => serde_json::json!(format!("{:#x}", a)),
+        DynSolValue::FixedBytes(word, size) => {
+            serde_json::json!(format!("0x{}", alloy::hex::encode(&word[..*size])))
+        }
+        DynSolValue::Function(f) => serde_json::json!(format!("0x{}", alloy::hex::encode(f))),
+        DynSolValue::Bytes(b) => serde_json::json!(format!("0x{}", alloy::hex::encode(b))),
+        DynSolValue::String(s) => serde_json::json!(s),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) => {
+            // Elements of a tuple array share the array's components
+            let element = Param {
+                ty: param.ty.rfind('[').map_or(param.ty.as_str(), |i| &param.ty[..i]).to_string(),
+                name: String::new(),
+                components: param.components.clone(),
+                internal_type: None,
+            };
+            items.iter().map(|item| sol_value_json(&element, item)).collect()
+        }
+        DynSolValue::Tuple(items) => {
+            let named = items.len() == param.components.len()
+                && param.components.iter().all(|c| !c.name.is_empty());
+            if named {
+                let fields = param
+                    .components
+                    .iter()
+                    .zip(items)
+                    .map(|(c, item)| (c.name.clone(), sol_value_json(c, item)))
+                    .collect::<serde_json::Map<_, _>>();
+                serde_json::Value::Object(fields)
+            } else {
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| match param.components.get(i) {
+                        Some(c) => sol_value_json(c, item),
+                        None => sol_value_json(&Param::default(), item),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
 /// Export address info to JSON format.
 pub fn export_address_json(info: &AddressInfo, path: &str) -> Result<String, String> {
     let json = serde_json::json!({
@@ -106,8 +176,18 @@ pub fn export_address_json(info: &AddressInfo, path: &str) -> Result<String, Str
         "is_contract": info.is_contract,
         "contract_info": info.contract_info.as_ref().map(|c| serde_json::json!({
             "abi_source": c.abi_source,
-            "is_proxy": c.is_proxy,
-            "implementation": c.implementation.map(|a| format!("{:#x}", a)),
+            "is_proxy": c.proxy.is_some(),
+            "implementation": c.proxy.as_ref().and_then(|p| p.implementation).map(|a| format!("{:#x}", a)),
+            "proxy": c.proxy.as_ref().map(|p| serde_json::json!({
+                "kind": p.kind.to_string(),
+                "implementation": p.implementation.map(|a| format!("{:#x}", a)),
+                "admin": p.admin.map(|a| format!("{:#x}", a)),
+                "beacon": p.beacon.map(|a| format!("{:#x}", a)),
+                "facets": p.facets.iter().map(|(facet, selectors)| serde_json::json!({
+                    "address": format!("{:#x}", facet),
+                    "selectors": selectors,
+                })).collect::<Vec<_>>(),
+            })),
             "contract_type": c.contract_type.map(|t| t.to_string()),
             "name": c.name,
             "symbol": c.symbol,
@@ -133,12 +213,27 @@ pub fn export_address_json(info: &AddressInfo, path: &str) -> Result<String, Str
     Ok(format!("Exported address info to {path}"))
 }
 
+/// Export a gas profile as folded stacks (`frame;frame;OPCODE gas` per line),
+/// the input format of flamegraph.pl, inferno and speedscope.
+pub fn export_gas_folded(profile: &GasProfile, path: &str) -> Result<String, String> {
+    let mut file = fs::File::create(path).map_err(|e| format!("Failed to create file: {e}"))?;
+    for (stack, gas) in &profile.folded {
+        writeln!(file, "{stack} {gas}").map_err(|e| format!("Failed to write file: {e}"))?;
+    }
+
+    Ok(format!("Exported {} gas stacks to {path}", profile.folded.len()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy::primitives::{Address, B256, U256};
+    use alloy::primitives::{Address, Bytes, B256, U256};
     use std::fs;
 
+    use crate::data::types::{
+        DecodedCall, DecodedParam, TokenTransfer, TransactionSummary, TxStatus, TxType,
+    };
+
     fn sample_blocks() -> Vec<BlockSummary> {
         vec![
             BlockSummary {
@@ -181,6 +276,143 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn test_export_gas_folded() {
+        let profile = GasProfile {
+            gas_used: 21_100,
+            intrinsic: 21_000,
+            folded: vec![
+                ("0xaa::run;PUSH1".to_string(), 100),
+                ("[intrinsic]".to_string(), 21_000),
+            ],
+            ..Default::default()
+        };
+        let path = "/tmp/eth-tui-test-gas.folded";
+        let result = export_gas_folded(&profile, path);
+        assert!(result.unwrap().contains("2 gas stacks"));
+
+        let contents = fs::read_to_string(path).unwrap();
+        assert_eq!(contents, "0xaa::run;PUSH1 100\n[intrinsic] 21000\n");
+
+        let _ = fs::remove_file(path);
+    }
+
+    fn sample_detail() -> TransactionDetail {
+        TransactionDetail {
+            summary: TransactionSummary {
+                hash: B256::repeat_byte(0x11),
+                block_number: Some(19000000),
+                timestamp: 1700000000,
+                from: Address::repeat_byte(0x22),
+                to: Some(Address::repeat_byte(0x33)),
+                value: U256::ZERO,
+                gas_used: Some(50_000),
+                gas_price: Some(30_000_000_000),
+                method_id: None,
+                method_name: None,
+                tx_type: TxType::EIP1559,
+                status: TxStatus::Success,
+            },
+            nonce: 7,
+            input_data: Bytes::new(),
+            decoded_input: None,
+            gas_limit: 100_000,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            effective_gas_price: None,
+            token_transfers: Vec::new(),
+            logs_count: 0,
+            confirmations: 1,
+        }
+    }
+
+    fn export_json(detail: &TransactionDetail, path: &str) -> serde_json::Value {
+        export_tx_json(detail, path).unwrap();
+        let contents = fs::read_to_string(path).unwrap();
+        let _ = fs::remove_file(path);
+        serde_json::from_str(&contents).unwrap()
+    }
+
+    #[test]
+    fn test_export_tx_json_full_values() {
+        let order: Param = serde_json::from_value(serde_json::json!({
+            "name": "order",
+            "type": "tuple",
+            "components": [
+                {"name": "maker", "type": "address"},
+                {"name": "amounts", "type": "uint256[]"},
+                {"name": "salt", "type": "bytes4"},
+            ],
+        }))
+        .unwrap();
+        let data = vec![0xab; 100];
+        let mut detail = sample_detail();
+        detail.decoded_input = Some(DecodedCall {
+            function_name: "fill".to_string(),
+            params: vec![
+                DecodedParam {
+                    param: order,
+                    value: Some(DynSolValue::Tuple(vec![
+                        DynSolValue::Address(Address::repeat_byte(0x44)),
+                        DynSolValue::Array(vec![
+                            DynSolValue::Uint(U256::MAX, 256),
+                            DynSolValue::Uint(U256::from(2), 256),
+                        ]),
+                        DynSolValue::FixedBytes(B256::right_padding_from(&[0xde, 0xad]), 4),
+                    ])),
+                },
+                DecodedParam::new("data", "bytes", DynSolValue::Bytes(data.clone())),
+            ],
+            inner: Vec::new(),
+        });
+
+        let json = export_json(&detail, "/tmp/eth-tui-test-tx-values.json");
+        let params = &json["decoded_input"]["params"];
+        assert_eq!(params[0]["type"], "(address,uint256[],bytes4)");
+        assert_eq!(
+            params[0]["value"],
+            serde_json::json!({
+                "maker": format!("{:#x}", Address::repeat_byte(0x44)),
+                "amounts": [U256::MAX.to_string(), "2"],
+                "salt": "0xdead0000",
+            })
+        );
+        // Long bytes are exported in full, not as the truncated display string
+        assert_eq!(params[1]["value"], format!("0x{}", "ab".repeat(100)));
+    }
+
+    #[test]
+    fn test_export_tx_json_token_amount() {
+        let transfer = |token: u8, value: U256, decimals: Option<u8>| TokenTransfer {
+            token_address: Address::repeat_byte(token),
+            standard: TokenStandard::ERC20,
+            from: Address::repeat_byte(0x22),
+            to: Address::repeat_byte(0x33),
+            value,
+            token_id: None,
+            token_name: None,
+            token_symbol: decimals.map(|_| "TKN".to_string()),
+            decimals,
+        };
+        let mut detail = sample_detail();
+        detail.token_transfers = vec![
+            transfer(0x01, U256::from(1_500_000), Some(6)),
+            transfer(0x02, U256::from(42), Some(0)),
+            transfer(0x03, U256::from(1_500_000), None),
+        ];
+
+        let json = export_json(&detail, "/tmp/eth-tui-test-tx-amount.json");
+        let transfers = json["token_transfers"].as_array().unwrap();
+        assert_eq!(transfers[0]["value"], "1500000");
+        assert_eq!(transfers[0]["amount"], "1.5");
+        assert_eq!(transfers[0]["decimals"], 6);
+        assert_eq!(transfers[1]["amount"], "42.0");
+        // Without metadata only the raw value is known
+        assert_eq!(transfers[2]["value"], "1500000");
+        assert!(transfers[2]["amount"].is_null());
+        assert!(transfers[2]["decimals"].is_null());
+    }
+
     #[test]
     fn test_export_blocks_csv_empty() {
         let path = "/tmp/eth-tui-test-blocks-empty.csv";
@@ -0,0 +1,1491 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use alloy::consensus::Transaction as ConsensusTransaction;
+use alloy::eips::eip7702::SignedAuthorization;
+use alloy::eips::Typed2718;
+use alloy::primitives::{address, Address, Bytes, B256, U256};
+use alloy::rpc::types::{Block, Header, Transaction};
+use color_eyre::eyre::{eyre, Report, Result};
+use revm::bytecode::opcode::{self, OpCode};
+use revm::bytecode::Bytecode;
+use revm::context::result::ExecutionResult;
+use revm::context::transaction::{
+    AccessList, AccessListItem, Authorization, SignedAuthorization as RevmSignedAuthorization,
+};
+use revm::context::{BlockEnv, CfgEnv, ContextTr, CreateScheme, DBErrorMarker, TxEnv};
+use revm::context_interface::either::Either;
+use revm::database::CacheDB;
+use revm::interpreter::interpreter_types::{Jumps, LoopControl, ReturnData};
+use revm::interpreter::{
+    CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, InstructionResult,
+    Interpreter, InterpreterResult,
+};
+use revm::primitives::hardfork::SpecId;
+use revm::primitives::{TxKind, KECCAK_EMPTY};
+use revm::state::AccountInfo;
+use revm::{
+    Context, Database, DatabaseRef, ExecuteCommitEvm, InspectCommitEvm, InspectEvm, Inspector,
+    MainBuilder, MainContext,
+};
+
+use crate::data::block_trace::BlockCalls;
+use crate::data::decoder::TxDecoder;
+use crate::data::provider::EthProvider;
+use crate::data::trace::TraceOptions;
+use crate::data::types::{ExecutionTrace, InternalCall, TraceStep};
+
+/// EIP-4788 beacon roots contract and the length of its ring buffer.
+const BEACON_ROOTS_ADDRESS: Address = address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+const BEACON_ROOTS_HISTORY: u64 = 8191;
+
+/// EIP-2935 block hash history contract and the length of its ring buffer.
+const HISTORY_STORAGE_ADDRESS: Address = address!("0000F90827F1C53a10cb7A02335B175320002935");
+const HISTORY_SERVE_WINDOW: u64 = 8191;
+
+/// Blob base fee update fractions of Cancun, Prague and the first two
+/// blob-parameter-only forks.
+const BLOB_FRACTION_CANCUN: u64 = 3_338_477;
+const BLOB_FRACTION_PRAGUE: u64 = 5_007_716;
+const BLOB_FRACTION_BPO1: u64 = 8_346_193;
+const BLOB_FRACTION_BPO2: u64 = 11_684_671;
+
+/// Hard forks of a chain, each list ordered from the latest activation.
+struct ForkSchedule {
+    /// Forks activated by block number, up to the merge
+    by_number: &'static [(u64, SpecId)],
+    /// Forks activated by timestamp, from Shanghai on
+    by_timestamp: &'static [(u64, SpecId)],
+    /// Blob base fee update fraction by activation timestamp
+    blob_fractions: &'static [(u64, u64)],
+}
+
+const MAINNET: ForkSchedule = ForkSchedule {
+    by_number: &[
+        (15_537_394, SpecId::MERGE),
+        (15_050_000, SpecId::GRAY_GLACIER),
+        (13_773_000, SpecId::ARROW_GLACIER),
+        (12_965_000, SpecId::LONDON),
+        (12_244_000, SpecId::BERLIN),
+        (9_200_000, SpecId::MUIR_GLACIER),
+        (9_069_000, SpecId::ISTANBUL),
+        (7_280_000, SpecId::PETERSBURG),
+        (4_370_000, SpecId::BYZANTIUM),
+        (2_675_000, SpecId::SPURIOUS_DRAGON),
+        (2_463_000, SpecId::TANGERINE),
+        (1_920_000, SpecId::DAO_FORK),
+        (1_150_000, SpecId::HOMESTEAD),
+    ],
+    by_timestamp: &[
+        (1_764_798_551, SpecId::OSAKA),
+        (1_746_612_311, SpecId::PRAGUE),
+        (1_710_338_135, SpecId::CANCUN),
+        (1_681_338_455, SpecId::SHANGHAI),
+    ],
+    blob_fractions: &[
+        (1_767_747_671, BLOB_FRACTION_BPO2),
+        (1_765_290_071, BLOB_FRACTION_BPO1),
+        (1_746_612_311, BLOB_FRACTION_PRAGUE),
+    ],
+};
+
+/// Sepolia started out under London.
+const SEPOLIA: ForkSchedule = ForkSchedule {
+    by_number: &[(1_735_371, SpecId::MERGE), (0, SpecId::LONDON)],
+    by_timestamp: &[
+        (1_760_427_360, SpecId::OSAKA),
+        (1_741_159_776, SpecId::PRAGUE),
+        (1_706_655_072, SpecId::CANCUN),
+        (1_677_557_088, SpecId::SHANGHAI),
+    ],
+    blob_fractions: &[
+        (1_761_607_008, BLOB_FRACTION_BPO2),
+        (1_761_017_184, BLOB_FRACTION_BPO1),
+        (1_741_159_776, BLOB_FRACTION_PRAGUE),
+    ],
+};
+
+/// Hoodi started out under Cancun.
+const HOODI: ForkSchedule = ForkSchedule {
+    by_number: &[(0, SpecId::MERGE)],
+    by_timestamp: &[
+        (1_761_677_592, SpecId::OSAKA),
+        (1_742_999_832, SpecId::PRAGUE),
+        (0, SpecId::CANCUN),
+    ],
+    blob_fractions: &[
+        (1_762_955_544, BLOB_FRACTION_BPO2),
+        (1_762_365_720, BLOB_FRACTION_BPO1),
+        (1_742_999_832, BLOB_FRACTION_PRAGUE),
+    ],
+};
+
+/// The schedule of a chain local replay knows, by chain id. Rollups and
+/// sidechains add system transactions and fee rules of their own, so only
+/// Ethereum and its testnets are replayed.
+fn fork_schedule(chain_id: u64) -> Option<&'static ForkSchedule> {
+    match chain_id {
+        1 => Some(&MAINNET),
+        11_155_111 => Some(&SEPOLIA),
+        560_048 => Some(&HOODI),
+        _ => None,
+    }
+}
+
+/// Whether transactions of a chain can be re-executed locally.
+pub fn replays_chain(chain_id: u64) -> bool {
+    fork_schedule(chain_id).is_some()
+}
+
+/// Account fields the EVM loads on first access.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountState {
+    pub balance: U256,
+    pub nonce: u64,
+    pub code: Bytes,
+}
+
+/// Pre-transaction state the local EVM reads from.
+///
+/// Every value is read as of the end of the parent block; changes made by
+/// earlier transactions of the same block are replayed on top of it.
+pub trait StateSource {
+    fn account(&self, address: Address) -> Result<AccountState>;
+    fn storage(&self, address: Address, slot: U256) -> Result<U256>;
+    fn block_hash(&self, number: u64) -> Result<B256>;
+}
+
+/// State fetched lazily from the node with the `eth_get*` methods every
+/// endpoint serves.
+///
+/// The EVM is synchronous, so requests are driven with `block_on`; this must
+/// run on a blocking thread, not a runtime worker.
+pub struct RpcState {
+    provider: Arc<EthProvider>,
+    block: u64,
+    handle: tokio::runtime::Handle,
+}
+
+impl RpcState {
+    pub fn new(provider: Arc<EthProvider>, block: u64, handle: tokio::runtime::Handle) -> Self {
+        Self {
+            provider,
+            block,
+            handle,
+        }
+    }
+}
+
+impl StateSource for RpcState {
+    fn account(&self, address: Address) -> Result<AccountState> {
+        self.handle.block_on(async {
+            let (balance, nonce, code) = tokio::try_join!(
+                self.provider.get_balance_at_block(address, self.block),
+                self.provider.get_nonce_at_block(address, self.block),
+                self.provider.get_code_at_block(address, self.block),
+            )?;
+            Ok(AccountState {
+                balance,
+                nonce,
+                code,
+            })
+        })
+    }
+
+    fn storage(&self, address: Address, slot: U256) -> Result<U256> {
+        self.handle.block_on(
+            self.provider
+                .get_storage_at_block(address, slot, self.block),
+        )
+    }
+
+    fn block_hash(&self, number: u64) -> Result<B256> {
+        self.handle.block_on(self.provider.get_block_hash(number))
+    }
+}
+
+/// Block fields that make up the EVM's block environment.
+#[derive(Debug, Clone, Default)]
+pub struct ForkBlock {
+    pub chain_id: u64,
+    pub number: u64,
+    /// Stored in the EIP-2935 history contract before the first transaction
+    /// runs
+    pub parent_hash: B256,
+    pub timestamp: u64,
+    pub coinbase: Address,
+    pub gas_limit: u64,
+    pub base_fee: Option<u64>,
+    pub difficulty: U256,
+    /// `mixHash`, which holds PREVRANDAO since the merge
+    pub prevrandao: B256,
+    pub excess_blob_gas: Option<u64>,
+    /// Stored in the EIP-4788 beacon roots contract before the first
+    /// transaction runs
+    pub parent_beacon_block_root: Option<B256>,
+}
+
+impl ForkBlock {
+    pub fn from_header(header: &Header, chain_id: u64) -> Self {
+        Self {
+            chain_id,
+            number: header.number,
+            parent_hash: header.parent_hash,
+            timestamp: header.timestamp,
+            coinbase: header.beneficiary,
+            gas_limit: header.gas_limit,
+            base_fee: header.base_fee_per_gas,
+            difficulty: header.difficulty,
+            prevrandao: header.mix_hash,
+            excess_blob_gas: header.excess_blob_gas,
+            parent_beacon_block_root: header.parent_beacon_block_root,
+        }
+    }
+}
+
+/// A transaction to execute, reduced to what the EVM needs.
+#[derive(Debug, Clone, Default)]
+pub struct ForkTx {
+    /// EIP-2718 type, which decides the fields the EVM validates
+    pub tx_type: u8,
+    pub chain_id: Option<u64>,
+    pub from: Address,
+    /// None for contract creation
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Bytes,
+    pub gas_limit: u64,
+    /// Gas price, or the fee cap of dynamic-fee transactions
+    pub gas_price: u128,
+    pub priority_fee: Option<u128>,
+    pub access_list: Vec<(Address, Vec<B256>)>,
+    pub blob_hashes: Vec<B256>,
+    pub max_fee_per_blob_gas: Option<u128>,
+    /// EIP-7702 delegations the sender signed
+    pub authorization_list: Vec<SignedAuthorization>,
+}
+
+impl ForkTx {
+    pub fn from_rpc(tx: &Transaction) -> Self {
+        Self {
+            tx_type: tx.inner.ty(),
+            chain_id: tx.inner.chain_id(),
+            from: tx.inner.signer(),
+            to: tx.inner.to(),
+            value: tx.inner.value(),
+            input: tx.inner.input().clone(),
+            gas_limit: tx.inner.gas_limit(),
+            gas_price: tx.inner.max_fee_per_gas(),
+            priority_fee: tx.inner.max_priority_fee_per_gas(),
+            access_list: tx
+                .inner
+                .access_list()
+                .map(|list| {
+                    list.iter()
+                        .map(|item| (item.address, item.storage_keys.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            blob_hashes: tx
+                .inner
+                .blob_versioned_hashes()
+                .map(|hashes| hashes.to_vec())
+                .unwrap_or_default(),
+            max_fee_per_blob_gas: tx.inner.max_fee_per_blob_gas(),
+            authorization_list: tx
+                .inner
+                .authorization_list()
+                .map(|list| list.to_vec())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Result of executing a transaction locally.
+#[derive(Debug, Clone)]
+pub struct ForkTrace {
+    pub trace: ExecutionTrace,
+    pub calls: Vec<InternalCall>,
+}
+
+/// Re-execute a mined transaction on a local EVM and trace it, reading
+/// state from the node at the parent block.
+///
+/// Earlier transactions of the same block are replayed first so the target
+/// sees the state it ran against; late transactions of busy blocks take
+/// correspondingly longer.
+pub async fn trace_transaction(
+    provider: Arc<EthProvider>,
+    tx_hash: B256,
+    options: TraceOptions,
+) -> Result<ForkTrace> {
+    let (state, env, prior, tx) = load_transaction(provider, tx_hash).await?;
+    tokio::task::spawn_blocking(move || replay(state, &env, &prior, &tx, options)).await?
+}
+
+/// Re-execute a mined transaction like `trace_transaction`, recording only
+/// its call tree.
+pub async fn trace_calls(provider: Arc<EthProvider>, tx_hash: B256) -> Result<Vec<InternalCall>> {
+    let (state, env, prior, tx) = load_transaction(provider, tx_hash).await?;
+    tokio::task::spawn_blocking(move || replay_calls(state, &env, &prior, &tx)).await?
+}
+
+/// The state a mined transaction ran against: the node at the parent block,
+/// the block's environment and the block's transactions before it.
+async fn load_transaction(
+    provider: Arc<EthProvider>,
+    tx_hash: B256,
+) -> Result<(RpcState, ForkBlock, Vec<ForkTx>, ForkTx)> {
+    let target = provider
+        .get_transaction(tx_hash)
+        .await?
+        .ok_or_else(|| eyre!("Transaction {tx_hash} not found"))?;
+    let number = target
+        .block_number
+        .ok_or_else(|| eyre!("Pending transactions cannot be replayed"))?;
+    let block = provider
+        .get_block(number)
+        .await?
+        .ok_or_else(|| eyre!("Block {number} not found"))?;
+
+    let transactions = block.transactions.as_transactions().unwrap_or_default();
+    let index = target
+        .transaction_index
+        .map(|i| i as usize)
+        .unwrap_or(transactions.len())
+        .min(transactions.len());
+    let prior: Vec<_> = transactions[..index].iter().map(ForkTx::from_rpc).collect();
+    let tx = ForkTx::from_rpc(&target);
+    let env = ForkBlock::from_header(&block.header, provider.chain_id());
+
+    let state = RpcState::new(
+        provider,
+        number.saturating_sub(1),
+        tokio::runtime::Handle::current(),
+    );
+    Ok((state, env, prior, tx))
+}
+
+/// Re-execute every transaction of a block locally, recording only their
+/// call trees.
+pub async fn trace_block(provider: Arc<EthProvider>, block: &Block) -> Result<BlockCalls> {
+    let txs = block
+        .transactions
+        .as_transactions()
+        .unwrap_or_default()
+        .iter()
+        .map(|t| (*t.inner.tx_hash(), ForkTx::from_rpc(t)))
+        .collect::<Vec<_>>();
+    let env = ForkBlock::from_header(&block.header, provider.chain_id());
+
+    let state = RpcState::new(
+        provider,
+        env.number.saturating_sub(1),
+        tokio::runtime::Handle::current(),
+    );
+    tokio::task::spawn_blocking(move || replay_block(state, &env, &txs)).await?
+}
+
+/// Execute a block's transactions in order on top of `state`.
+pub fn replay_block<S: StateSource>(
+    state: S,
+    block: &ForkBlock,
+    txs: &[(B256, ForkTx)],
+) -> Result<BlockCalls> {
+    let spec = spec_for_block(block)?;
+    let mut db = CacheDB::new(SourceDb(state));
+    apply_system_calls(&mut db, block, spec)?;
+    let mut blocks = Vec::with_capacity(txs.len());
+
+    for (tx_hash, tx) in txs {
+        let mut evm =
+            context(&mut db, block, spec).build_mainnet_with_inspector(ForkTracer::calls_only());
+        let result = evm
+            .inspect_tx_commit(tx_env(tx))
+            .map_err(|e| eyre!("Executing transaction {tx_hash} failed: {e}"))?;
+        let mut calls = evm.into_inspector().calls;
+        if let Some(root) = calls.first_mut() {
+            root.gas_used = result.gas_used();
+        }
+        blocks.push((*tx_hash, calls));
+    }
+    Ok(blocks)
+}
+
+/// Execute `prior` on top of `state`, then trace `tx`.
+pub fn replay<S: StateSource>(
+    state: S,
+    block: &ForkBlock,
+    prior: &[ForkTx],
+    tx: &ForkTx,
+    options: TraceOptions,
+) -> Result<ForkTrace> {
+    let (result, mut tracer) = replay_with(state, block, prior, tx, ForkTracer::new(options))?;
+
+    let gas_used = result.gas_used();
+    // The top-level frame reports the transaction's gas, as callTracer does
+    if let Some(root) = tracer.calls.first_mut() {
+        root.gas_used = gas_used;
+    }
+    let failed = !result.is_success();
+    let return_value = match result {
+        ExecutionResult::Success { output, .. } => to_bytes(output.into_data()),
+        ExecutionResult::Revert { output, .. } => to_bytes(output),
+        ExecutionResult::Halt { .. } => Bytes::new(),
+    };
+    let revert_reason = if failed {
+        TxDecoder::decode_revert(&return_value, None)
+    } else {
+        None
+    };
+
+    Ok(ForkTrace {
+        trace: ExecutionTrace {
+            steps: tracer.steps,
+            gas_used,
+            failed,
+            return_value,
+            revert_reason,
+        },
+        calls: tracer.calls,
+    })
+}
+
+/// Execute `prior` on top of `state`, then record the call tree of `tx`.
+pub fn replay_calls<S: StateSource>(
+    state: S,
+    block: &ForkBlock,
+    prior: &[ForkTx],
+    tx: &ForkTx,
+) -> Result<Vec<InternalCall>> {
+    let (result, tracer) = replay_with(state, block, prior, tx, ForkTracer::calls_only())?;
+    let mut calls = tracer.calls;
+    if let Some(root) = calls.first_mut() {
+        root.gas_used = result.gas_used();
+    }
+    Ok(calls)
+}
+
+/// Execute `prior` on top of `state`, then `tx` under `tracer` without
+/// committing it.
+fn replay_with<S: StateSource>(
+    state: S,
+    block: &ForkBlock,
+    prior: &[ForkTx],
+    tx: &ForkTx,
+    tracer: ForkTracer,
+) -> Result<(ExecutionResult, ForkTracer)> {
+    let spec = spec_for_block(block)?;
+    let mut db = CacheDB::new(SourceDb(state));
+    apply_system_calls(&mut db, block, spec)?;
+
+    for (i, prior_tx) in prior.iter().enumerate() {
+        let mut evm = context(&mut db, block, spec).build_mainnet();
+        evm.transact_commit(tx_env(prior_tx))
+            .map_err(|e| eyre!("Replaying transaction {i} of the block failed: {e}"))?;
+    }
+
+    let mut evm = context(&mut db, block, spec).build_mainnet_with_inspector(tracer);
+    let result = evm
+        .inspect_tx(tx_env(tx))
+        .map_err(|e| eyre!("Executing the transaction failed: {e}"))?
+        .result;
+    Ok((result, evm.into_inspector()))
+}
+
+/// Hard fork in effect for a block. Blocks of chains without a known
+/// schedule are refused rather than replayed under the wrong rules.
+pub fn spec_for_block(block: &ForkBlock) -> Result<SpecId> {
+    let schedule = fork_schedule(block.chain_id).ok_or_else(|| {
+        eyre!(
+            "Local replay does not know the hard forks of chain {}",
+            block.chain_id
+        )
+    })?;
+    // Post-merge forks activate by timestamp, earlier ones by block number
+    let by_timestamp = schedule
+        .by_timestamp
+        .iter()
+        .find(|(activation, _)| block.timestamp >= *activation);
+    let by_number = schedule
+        .by_number
+        .iter()
+        .find(|(activation, _)| block.number >= *activation);
+    Ok(by_timestamp
+        .or(by_number)
+        .map(|(_, spec)| *spec)
+        .unwrap_or(SpecId::FRONTIER))
+}
+
+/// Blob base fee update fraction in effect for a block; blob-parameter-only
+/// forks change it without a new EVM spec.
+fn blob_fraction(block: &ForkBlock) -> u64 {
+    fork_schedule(block.chain_id)
+        .and_then(|schedule| {
+            schedule
+                .blob_fractions
+                .iter()
+                .find(|(activation, _)| block.timestamp >= *activation)
+        })
+        .map(|(_, fraction)| *fraction)
+        .unwrap_or(BLOB_FRACTION_CANCUN)
+}
+
+/// An EVM context over `db` with the block's environment and rules.
+fn context<DB: Database>(
+    db: DB,
+    block: &ForkBlock,
+    spec: SpecId,
+) -> Context<BlockEnv, TxEnv, CfgEnv, DB> {
+    let mut cfg = CfgEnv::new_with_spec(spec).with_chain_id(block.chain_id);
+    // The sender's nonce was already checked when the transaction was mined
+    cfg.disable_nonce_check = true;
+    Context::mainnet()
+        .with_db(db)
+        .with_cfg(cfg)
+        .with_block(block_env(block))
+}
+
+/// Store what the system calls at the start of a block write: the EIP-4788
+/// beacon root from Cancun on, and the EIP-2935 parent hash from Prague on.
+fn apply_system_calls<S: StateSource>(
+    db: &mut CacheDB<SourceDb<S>>,
+    block: &ForkBlock,
+    spec: SpecId,
+) -> Result<()> {
+    apply_beacon_root(db, block, spec)?;
+    apply_parent_hash(db, block, spec)
+}
+
+/// Store the block's timestamp and its parent beacon block root in the ring
+/// buffer of the beacon roots contract.
+fn apply_beacon_root<S: StateSource>(
+    db: &mut CacheDB<SourceDb<S>>,
+    block: &ForkBlock,
+    spec: SpecId,
+) -> Result<()> {
+    let Some(root) = block.parent_beacon_block_root else {
+        return Ok(());
+    };
+    if !spec.is_enabled_in(SpecId::CANCUN) || !is_deployed(db, BEACON_ROOTS_ADDRESS)? {
+        return Ok(());
+    }
+    let address = to_revm_address(BEACON_ROOTS_ADDRESS);
+    let slot = block.timestamp % BEACON_ROOTS_HISTORY;
+    db.insert_account_storage(address, U256::from(slot), U256::from(block.timestamp))
+        .map_err(|e| e.0)?;
+    db.insert_account_storage(
+        address,
+        U256::from(slot + BEACON_ROOTS_HISTORY),
+        U256::from_be_bytes(root.0),
+    )
+    .map_err(|e| e.0)?;
+    Ok(())
+}
+
+/// Store the parent block's hash in the ring buffer of the history contract.
+fn apply_parent_hash<S: StateSource>(
+    db: &mut CacheDB<SourceDb<S>>,
+    block: &ForkBlock,
+    spec: SpecId,
+) -> Result<()> {
+    if block.number == 0
+        || !spec.is_enabled_in(SpecId::PRAGUE)
+        || !is_deployed(db, HISTORY_STORAGE_ADDRESS)?
+    {
+        return Ok(());
+    }
+    let slot = (block.number - 1) % HISTORY_SERVE_WINDOW;
+    db.insert_account_storage(
+        to_revm_address(HISTORY_STORAGE_ADDRESS),
+        U256::from(slot),
+        U256::from_be_bytes(block.parent_hash.0),
+    )
+    .map_err(|e| e.0)?;
+    Ok(())
+}
+
+/// Whether a system contract has code; its system call does nothing before
+/// it is deployed.
+fn is_deployed<S: StateSource>(db: &mut CacheDB<SourceDb<S>>, address: Address) -> Result<bool> {
+    let info = db.basic(to_revm_address(address)).map_err(|e| e.0)?;
+    Ok(info.is_some_and(|info| info.code_hash != KECCAK_EMPTY))
+}
+
+fn block_env(block: &ForkBlock) -> BlockEnv {
+    let mut env = BlockEnv {
+        number: U256::from(block.number),
+        timestamp: U256::from(block.timestamp),
+        beneficiary: to_revm_address(block.coinbase),
+        gas_limit: block.gas_limit,
+        basefee: block.base_fee.unwrap_or_default(),
+        difficulty: block.difficulty,
+        prevrandao: Some(to_revm_b256(block.prevrandao)),
+        ..Default::default()
+    };
+    if let Some(excess_blob_gas) = block.excess_blob_gas {
+        env.set_blob_excess_gas_and_price(excess_blob_gas, blob_fraction(block));
+    }
+    env
+}
+
+fn tx_env(tx: &ForkTx) -> TxEnv {
+    TxEnv {
+        tx_type: tx.tx_type,
+        chain_id: tx.chain_id,
+        caller: to_revm_address(tx.from),
+        kind: match tx.to {
+            Some(to) => TxKind::Call(to_revm_address(to)),
+            None => TxKind::Create,
+        },
+        value: tx.value,
+        data: to_revm_bytes(&tx.input),
+        gas_limit: tx.gas_limit,
+        gas_price: tx.gas_price,
+        gas_priority_fee: tx.priority_fee,
+        access_list: AccessList(
+            tx.access_list
+                .iter()
+                .map(|(address, keys)| AccessListItem {
+                    address: to_revm_address(*address),
+                    storage_keys: keys.iter().map(|key| to_revm_b256(*key)).collect(),
+                })
+                .collect(),
+        ),
+        blob_hashes: tx.blob_hashes.iter().map(|h| to_revm_b256(*h)).collect(),
+        max_fee_per_blob_gas: tx.max_fee_per_blob_gas.unwrap_or_default(),
+        // The EVM recovers each authority from its signature
+        authorization_list: tx
+            .authorization_list
+            .iter()
+            .map(|auth| {
+                let inner = Authorization {
+                    chain_id: auth.chain_id,
+                    address: to_revm_address(auth.address),
+                    nonce: auth.nonce,
+                };
+                Either::Left(RevmSignedAuthorization::new_unchecked(
+                    inner,
+                    auth.y_parity(),
+                    auth.r(),
+                    auth.s(),
+                ))
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// A failed state lookup, carried through the EVM as its database error.
+#[derive(Debug)]
+struct StateError(Report);
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl DBErrorMarker for StateError {}
+
+/// Adapts a `StateSource` to revm's read-only database interface.
+///
+/// revm is built against its own copy of the primitive types, so addresses,
+/// hashes and bytes are converted at this boundary.
+struct SourceDb<S>(S);
+
+impl<S: StateSource> DatabaseRef for SourceDb<S> {
+    type Error = StateError;
+
+    fn basic_ref(
+        &self,
+        address: revm::primitives::Address,
+    ) -> Result<Option<AccountInfo>, Self::Error> {
+        let account = self
+            .0
+            .account(from_revm_address(address))
+            .map_err(StateError)?;
+        if account == AccountState::default() {
+            return Ok(None);
+        }
+        // Delegated accounts hold an EIP-7702 designator as their code
+        let code = Bytecode::new_raw_checked(to_revm_bytes(&account.code))
+            .map_err(|e| StateError(eyre!("Malformed code at {address}: {e}")))?;
+        let code_hash = if account.code.is_empty() {
+            KECCAK_EMPTY
+        } else {
+            code.hash_slow()
+        };
+        Ok(Some(AccountInfo::new(
+            account.balance,
+            account.nonce,
+            code_hash,
+            code,
+        )))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: revm::primitives::B256) -> Result<Bytecode, Self::Error> {
+        // Code is returned with the account, so the cache already holds it
+        Err(StateError(eyre!("Unknown code hash {code_hash}")))
+    }
+
+    fn storage_ref(
+        &self,
+        address: revm::primitives::Address,
+        index: U256,
+    ) -> Result<U256, Self::Error> {
+        self.0
+            .storage(from_revm_address(address), index)
+            .map_err(StateError)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<revm::primitives::B256, Self::Error> {
+        self.0
+            .block_hash(number)
+            .map(to_revm_b256)
+            .map_err(StateError)
+    }
+}
+
+/// One executing call frame.
+struct Frame {
+    /// Index of the frame's entry in `ForkTracer::calls`
+    call: usize,
+    /// Address whose code runs; None for creations, which run at their own address
+    code_address: Option<Address>,
+}
+
+/// Records struct-log style steps and the call tree while the EVM runs.
+struct ForkTracer {
+    /// Whether to record opcode steps, or only the call tree
+    record_steps: bool,
+    options: TraceOptions,
+    steps: Vec<TraceStep>,
+    calls: Vec<InternalCall>,
+    frames: Vec<Frame>,
+    /// Slots each contract touched so far, as the struct logger reports them
+    storage: HashMap<Address, BTreeMap<U256, U256>>,
+}
+
+impl ForkTracer {
+    fn new(options: TraceOptions) -> Self {
+        Self {
+            record_steps: true,
+            options,
+            steps: Vec::new(),
+            calls: Vec::new(),
+            frames: Vec::new(),
+            storage: HashMap::new(),
+        }
+    }
+
+    fn calls_only() -> Self {
+        Self {
+            record_steps: false,
+            ..Self::new(TraceOptions::default())
+        }
+    }
+
+    fn enter(&mut self, call: InternalCall, code_address: Option<Address>) {
+        self.frames.push(Frame {
+            call: self.calls.len(),
+            code_address,
+        });
+        self.calls.push(call);
+    }
+
+    fn exit(&mut self, result: &InterpreterResult, created: Option<Address>) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        let call = &mut self.calls[frame.call];
+        call.gas_used = result.gas.spent();
+        call.output = to_bytes(result.output.clone());
+        if let Some(created) = created {
+            call.to = created;
+        }
+        if !result.result.is_ok() {
+            call.error = Some(error_message(result.result));
+        }
+    }
+
+    fn record_storage(&mut self, contract: Address, slot: U256, value: U256) {
+        let slots = self.storage.entry(contract).or_default();
+        slots.insert(slot, value);
+        if let Some(step) = self.steps.last_mut() {
+            step.storage = slots.iter().map(|(k, v)| (*k, *v)).collect();
+        }
+    }
+}
+
+impl<CTX: ContextTr> Inspector<CTX> for ForkTracer {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut CTX) {
+        if !self.record_steps {
+            return;
+        }
+        let target = from_revm_address(interp.input.target_address);
+        let address = self
+            .frames
+            .last()
+            .and_then(|frame| frame.code_address)
+            .unwrap_or(target);
+        let in_create = self.frames.last().is_some_and(|frame| frame.code_address.is_none());
+        let opcode = interp.bytecode.opcode();
+        let op = OpCode::new(opcode)
+            .map(|op| op.as_str().to_string())
+            .unwrap_or_else(|| format!("opcode {opcode:#04x} not defined"));
+        let memory = if self.options.enable_memory {
+            Bytes::copy_from_slice(&interp.memory.context_memory())
+        } else {
+            Bytes::new()
+        };
+
+        self.steps.push(TraceStep {
+            address,
+            in_create,
+            pc: interp.bytecode.pc() as u64,
+            op,
+            gas: interp.gas.remaining(),
+            gas_cost: 0,
+            depth: self.frames.len(),
+            stack: interp.stack.data().clone(),
+            memory,
+            storage: Vec::new(),
+            return_data: to_bytes(interp.return_data.buffer().clone()),
+            error: None,
+        });
+
+        // A store's slot and value are known before it executes
+        let store = match interp.stack.data().as_slice() {
+            [.., value, slot] if opcode == opcode::SSTORE => Some((*slot, *value)),
+            _ => None,
+        };
+        if let Some((slot, value)) = store.filter(|_| self.options.enable_storage) {
+            self.record_storage(target, slot, value);
+        }
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, _context: &mut CTX) {
+        if !self.record_steps {
+            return;
+        }
+        let Some(step) = self.steps.last_mut() else {
+            return;
+        };
+        step.gas_cost = step.gas.saturating_sub(interp.gas.remaining());
+        let result = interp
+            .bytecode
+            .action()
+            .as_ref()
+            .and_then(|action| action.instruction_result());
+        if let Some(result) = result.filter(|result| result.is_error()) {
+            step.error = Some(error_message(result));
+            return;
+        }
+
+        // A load's value is on top of the stack once it executed
+        if self.options.enable_storage && step.op == "SLOAD" {
+            let slot = step.stack.last().copied();
+            if let (Some(slot), Ok(value)) = (slot, interp.stack.peek(0)) {
+                let target = from_revm_address(interp.input.target_address);
+                self.record_storage(target, slot, value);
+            }
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let call_type = match inputs.scheme {
+            CallScheme::Call => "CALL",
+            CallScheme::CallCode => "CALLCODE",
+            CallScheme::DelegateCall => "DELEGATECALL",
+            CallScheme::StaticCall => "STATICCALL",
+        };
+        // A delegate call keeps the original sender; report the delegating contract
+        let from = if inputs.scheme == CallScheme::DelegateCall {
+            inputs.target_address
+        } else {
+            inputs.caller
+        };
+        let code_address = from_revm_address(inputs.bytecode_address);
+        self.enter(
+            InternalCall {
+                from: from_revm_address(from),
+                to: code_address,
+                value: inputs.value.transfer().unwrap_or_default(),
+                call_type: call_type.to_string(),
+                gas_used: 0,
+                input: to_bytes(inputs.input.bytes(context)),
+                output: Bytes::new(),
+                depth: self.frames.len(),
+                error: None,
+                decoded: None,
+                decoded_output: Vec::new(),
+                revert_reason: None,
+            },
+            Some(code_address),
+        );
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.exit(&outcome.result, None);
+    }
+
+    fn create(&mut self, _context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let call_type = match inputs.scheme {
+            CreateScheme::Create2 { .. } => "CREATE2",
+            _ => "CREATE",
+        };
+        self.enter(
+            InternalCall {
+                from: from_revm_address(inputs.caller),
+                to: Address::ZERO,
+                value: inputs.value,
+                call_type: call_type.to_string(),
+                gas_used: 0,
+                input: to_bytes(inputs.init_code.clone()),
+                output: Bytes::new(),
+                depth: self.frames.len(),
+                error: None,
+                decoded: None,
+                decoded_output: Vec::new(),
+                revert_reason: None,
+            },
+            None,
+        );
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.exit(&outcome.result, outcome.address.map(from_revm_address));
+    }
+}
+
+/// Describe a failed frame or instruction the way Geth does.
+fn error_message(result: InstructionResult) -> String {
+    match result {
+        InstructionResult::Revert => "execution reverted".to_string(),
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG => "out of gas".to_string(),
+        InstructionResult::OpcodeNotFound | InstructionResult::InvalidFEOpcode => {
+            "invalid opcode".to_string()
+        }
+        InstructionResult::InvalidJump => "invalid jump destination".to_string(),
+        InstructionResult::StackUnderflow => "stack underflow".to_string(),
+        InstructionResult::StackOverflow => "stack limit reached 1024".to_string(),
+        InstructionResult::StateChangeDuringStaticCall
+        | InstructionResult::CallNotAllowedInsideStatic => "write protection".to_string(),
+        InstructionResult::CallTooDeep => "max call depth exceeded".to_string(),
+        InstructionResult::OutOfFunds => "insufficient balance for transfer".to_string(),
+        InstructionResult::CreateCollision => "contract address collision".to_string(),
+        InstructionResult::CreateContractSizeLimit => "max code size exceeded".to_string(),
+        other => format!("{other:?}"),
+    }
+}
+
+fn to_revm_address(address: Address) -> revm::primitives::Address {
+    revm::primitives::Address::new(address.into_array())
+}
+
+fn from_revm_address(address: revm::primitives::Address) -> Address {
+    Address::new(address.into_array())
+}
+
+fn to_revm_b256(hash: B256) -> revm::primitives::B256 {
+    revm::primitives::B256::new(hash.0)
+}
+
+fn to_revm_bytes(bytes: &Bytes) -> revm::primitives::Bytes {
+    revm::primitives::Bytes::copy_from_slice(bytes)
+}
+
+fn to_bytes(bytes: revm::primitives::Bytes) -> Bytes {
+    Bytes::copy_from_slice(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    const SENDER: Address = Address::new([0x11; 20]);
+    const TARGET: Address = Address::new([0xaa; 20]);
+    const CALLEE: Address = Address::new([0xbb; 20]);
+
+    /// In-memory state standing in for the node.
+    #[derive(Default)]
+    struct MockState {
+        accounts: HashMap<Address, AccountState>,
+        storage: HashMap<(Address, U256), U256>,
+        /// Every account lookup, to check state is fetched lazily and once
+        lookups: RefCell<Vec<Address>>,
+    }
+
+    impl MockState {
+        fn with_code(mut self, address: Address, code: &[u8]) -> Self {
+            self.accounts.insert(
+                address,
+                AccountState {
+                    code: Bytes::copy_from_slice(code),
+                    ..Default::default()
+                },
+            );
+            self
+        }
+    }
+
+    impl StateSource for MockState {
+        fn account(&self, address: Address) -> Result<AccountState> {
+            self.lookups.borrow_mut().push(address);
+            Ok(self.accounts.get(&address).cloned().unwrap_or_default())
+        }
+
+        fn storage(&self, address: Address, slot: U256) -> Result<U256> {
+            Ok(self
+                .storage
+                .get(&(address, slot))
+                .copied()
+                .unwrap_or_default())
+        }
+
+        fn block_hash(&self, number: u64) -> Result<B256> {
+            Ok(B256::from(U256::from(number)))
+        }
+    }
+
+    fn block() -> ForkBlock {
+        ForkBlock {
+            chain_id: 1,
+            number: 20_000_000,
+            timestamp: 1_717_000_000,
+            gas_limit: 30_000_000,
+            ..Default::default()
+        }
+    }
+
+    fn call(to: Address, input: &[u8]) -> ForkTx {
+        ForkTx {
+            from: SENDER,
+            to: Some(to),
+            input: Bytes::copy_from_slice(input),
+            gas_limit: 1_000_000,
+            ..Default::default()
+        }
+    }
+
+    // SSTORE(1, SLOAD(1) + 1); STOP
+    const INCREMENT: &[u8] = &[0x60, 0x01, 0x54, 0x60, 0x01, 0x01, 0x60, 0x01, 0x55, 0x00];
+
+    // STATICCALL(gas, CALLEE, 0, 0, 0, 0); REVERT(0, 0)
+    fn call_then_revert() -> Vec<u8> {
+        let mut code = vec![0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73];
+        code.extend_from_slice(CALLEE.as_slice());
+        code.extend_from_slice(&[0x5a, 0xfa, 0x50, 0x60, 0x00, 0x60, 0x00, 0xfd]);
+        code
+    }
+
+    #[test]
+    fn test_replay_records_steps_and_storage() {
+        let mut state = MockState::default().with_code(TARGET, INCREMENT);
+        state
+            .storage
+            .insert((TARGET, U256::from(1)), U256::from(41));
+        let options = TraceOptions {
+            enable_memory: false,
+            enable_storage: true,
+        };
+
+        let fork = replay(state, &block(), &[], &call(TARGET, &[]), options).unwrap();
+        let trace = &fork.trace;
+        assert!(!trace.failed);
+        let ops: Vec<&str> = trace.steps.iter().map(|s| s.op.as_str()).collect();
+        assert_eq!(
+            ops,
+            ["PUSH1", "SLOAD", "PUSH1", "ADD", "PUSH1", "SSTORE", "STOP"]
+        );
+        assert!(
+            trace
+                .steps
+                .iter()
+                .all(|s| s.depth == 1 && s.address == TARGET)
+        );
+
+        let sload = &trace.steps[1];
+        assert_eq!(sload.gas_cost, 2_100);
+        assert_eq!(sload.storage, vec![(U256::from(1), U256::from(41))]);
+        let sstore = &trace.steps[5];
+        assert_eq!(sstore.stack.last(), Some(&U256::from(1)));
+        assert_eq!(sstore.storage, vec![(U256::from(1), U256::from(42))]);
+
+        assert_eq!(fork.calls.len(), 1);
+        assert_eq!(fork.calls[0].from, SENDER);
+        assert_eq!(fork.calls[0].to, TARGET);
+        assert_eq!(fork.calls[0].gas_used, trace.gas_used);
+    }
+
+    #[test]
+    fn test_replay_records_nested_calls_and_revert() {
+        // CALLEE returns immediately
+        let state = MockState::default()
+            .with_code(TARGET, &call_then_revert())
+            .with_code(CALLEE, &[0x00]);
+
+        let fork = replay(
+            state,
+            &block(),
+            &[],
+            &call(TARGET, &[]),
+            TraceOptions::default(),
+        )
+        .unwrap();
+        assert!(fork.trace.failed);
+        assert!(fork.trace.return_value.is_empty());
+
+        assert_eq!(fork.calls.len(), 2);
+        assert_eq!(fork.calls[0].error.as_deref(), Some("execution reverted"));
+        let inner = &fork.calls[1];
+        assert_eq!(inner.call_type, "STATICCALL");
+        assert_eq!((inner.from, inner.to, inner.depth), (TARGET, CALLEE, 1));
+        assert!(inner.error.is_none());
+
+        // The callee's STOP runs one level deeper, attributed to the callee
+        let stop = fork.trace.steps.iter().find(|s| s.depth == 2).unwrap();
+        assert_eq!((stop.op.as_str(), stop.address), ("STOP", CALLEE));
+        assert_eq!(fork.trace.steps.last().unwrap().op, "REVERT");
+    }
+
+    #[test]
+    fn test_replay_applies_prior_transactions() {
+        let state = MockState::default().with_code(TARGET, INCREMENT);
+        let options = TraceOptions {
+            enable_memory: false,
+            enable_storage: true,
+        };
+        let tx = call(TARGET, &[]);
+
+        let fork = replay(state, &block(), &[tx.clone(), tx.clone()], &tx, options).unwrap();
+        // Two earlier increments already ran in the block
+        let sstore = fork.trace.steps.iter().find(|s| s.op == "SSTORE").unwrap();
+        assert_eq!(sstore.storage, vec![(U256::from(1), U256::from(3))]);
+    }
+
+    #[test]
+    fn test_replay_calls_matches_full_replay() {
+        let state = || {
+            MockState::default()
+                .with_code(TARGET, &call_then_revert())
+                .with_code(CALLEE, INCREMENT)
+        };
+        let tx = call(TARGET, &[]);
+        let prior = [call(CALLEE, &[])];
+
+        let calls = replay_calls(state(), &block(), &prior, &tx).unwrap();
+        let fork = replay(state(), &block(), &prior, &tx, TraceOptions::default()).unwrap();
+        let summary = |calls: &[InternalCall]| {
+            calls
+                .iter()
+                .map(|c| (c.call_type.clone(), c.to, c.depth, c.gas_used, c.error.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&calls), summary(&fork.calls));
+        assert_eq!(calls[0].gas_used, fork.trace.gas_used);
+    }
+
+    #[test]
+    fn test_replay_block_records_calls_per_transaction() {
+        let state = MockState::default()
+            .with_code(TARGET, &call_then_revert())
+            .with_code(CALLEE, INCREMENT);
+        let txs = [
+            (B256::repeat_byte(1), call(CALLEE, &[])),
+            (B256::repeat_byte(2), call(TARGET, &[])),
+        ];
+
+        let blocks = replay_block(state, &block(), &txs).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].0, B256::repeat_byte(1));
+        assert_eq!(blocks[0].1.len(), 1);
+        assert!(blocks[0].1[0].gas_used > 21_000);
+
+        let calls = &blocks[1].1;
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].error.as_deref(), Some("execution reverted"));
+        // The static call into the incrementing contract is refused its SSTORE
+        assert_eq!(calls[1].error.as_deref(), Some("write protection"));
+    }
+
+    #[test]
+    fn test_replay_marks_failing_step() {
+        // JUMP to 0, which is not a JUMPDEST
+        let state = MockState::default().with_code(TARGET, &[0x60, 0x00, 0x56]);
+
+        let fork = replay(
+            state,
+            &block(),
+            &[],
+            &call(TARGET, &[]),
+            TraceOptions::default(),
+        )
+        .unwrap();
+        assert!(fork.trace.failed);
+        let jump = fork.trace.steps.last().unwrap();
+        assert_eq!(jump.op, "JUMP");
+        assert_eq!(jump.error.as_deref(), Some("invalid jump destination"));
+        assert_eq!(
+            fork.calls[0].error.as_deref(),
+            Some("invalid jump destination")
+        );
+        // A failed transaction consumes all of its gas
+        assert_eq!(fork.trace.gas_used, 1_000_000);
+    }
+
+    #[test]
+    fn test_replay_fetches_each_account_once() {
+        let state = MockState::default().with_code(TARGET, INCREMENT);
+        let tx = call(TARGET, &[]);
+        let mut db = CacheDB::new(SourceDb(state));
+        for _ in 0..2 {
+            let mut evm = context(&mut db, &block(), SpecId::CANCUN).build_mainnet();
+            evm.transact_commit(tx_env(&tx)).unwrap();
+        }
+        let lookups = db.db.0.lookups.borrow();
+        assert_eq!(lookups.iter().filter(|a| **a == TARGET).count(), 1);
+    }
+
+    #[test]
+    fn test_replay_runs_delegated_code() {
+        // SENDER delegated to CALLEE under EIP-7702; the code runs on SENDER's storage
+        let mut designator = vec![0xef, 0x01, 0x00];
+        designator.extend_from_slice(CALLEE.as_slice());
+        let state = MockState::default()
+            .with_code(SENDER, &designator)
+            .with_code(CALLEE, INCREMENT);
+        let block = ForkBlock {
+            timestamp: 1_750_000_000,
+            ..block()
+        };
+        let options = TraceOptions {
+            enable_memory: false,
+            enable_storage: true,
+        };
+
+        let fork = replay(state, &block, &[], &call(SENDER, &[]), options).unwrap();
+        assert!(!fork.trace.failed);
+        let sstore = fork.trace.steps.iter().find(|s| s.op == "SSTORE").unwrap();
+        assert_eq!(sstore.storage, vec![(U256::from(1), U256::from(1))]);
+    }
+
+    #[test]
+    fn test_spec_for_block() {
+        let at = |chain_id: u64, number: u64, timestamp: u64| {
+            spec_for_block(&ForkBlock {
+                chain_id,
+                number,
+                timestamp,
+                ..Default::default()
+            })
+        };
+        assert_eq!(at(1, 1, 0).unwrap(), SpecId::FRONTIER);
+        assert_eq!(at(1, 12_965_000, 0).unwrap(), SpecId::LONDON);
+        assert_eq!(at(1, 17_034_870, 1_681_338_455).unwrap(), SpecId::SHANGHAI);
+        assert_eq!(at(1, 19_426_587, 1_710_338_135).unwrap(), SpecId::CANCUN);
+        assert_eq!(at(1, 22_431_084, 1_746_612_311).unwrap(), SpecId::PRAGUE);
+        assert_eq!(at(1, 23_935_694, 1_764_798_551).unwrap(), SpecId::OSAKA);
+        // Testnets start out past the first forks
+        assert_eq!(at(11_155_111, 0, 0).unwrap(), SpecId::LONDON);
+        assert_eq!(at(560_048, 0, 0).unwrap(), SpecId::CANCUN);
+        // Rollups are not replayed
+        assert!(at(10, 0, 0).is_err());
+        assert!(!replays_chain(8453));
+    }
+
+    #[test]
+    fn test_blob_fraction() {
+        let at = |timestamp: u64| {
+            blob_fraction(&ForkBlock {
+                chain_id: 1,
+                timestamp,
+                ..Default::default()
+            })
+        };
+        assert_eq!(at(1_717_000_000), BLOB_FRACTION_CANCUN);
+        assert_eq!(at(1_764_798_551), BLOB_FRACTION_PRAGUE);
+        assert_eq!(at(1_765_290_071), BLOB_FRACTION_BPO1);
+        assert_eq!(at(1_767_747_671), BLOB_FRACTION_BPO2);
+    }
+
+    #[test]
+    fn test_apply_beacon_root() {
+        let state = MockState::default().with_code(BEACON_ROOTS_ADDRESS, &[0x00]);
+        let root = B256::repeat_byte(0xcc);
+        let block = ForkBlock {
+            parent_beacon_block_root: Some(root),
+            ..block()
+        };
+        let mut db = CacheDB::new(SourceDb(state));
+        apply_beacon_root(&mut db, &block, SpecId::CANCUN).unwrap();
+
+        let address = to_revm_address(BEACON_ROOTS_ADDRESS);
+        let slot = block.timestamp % BEACON_ROOTS_HISTORY;
+        assert_eq!(
+            db.storage(address, U256::from(slot)).unwrap(),
+            U256::from(block.timestamp)
+        );
+        assert_eq!(
+            db.storage(address, U256::from(slot + BEACON_ROOTS_HISTORY))
+                .unwrap(),
+            U256::from_be_bytes(root.0)
+        );
+
+        // Nothing is written before Cancun
+        let mut db = CacheDB::new(SourceDb(MockState::default()));
+        apply_beacon_root(&mut db, &block, SpecId::SHANGHAI).unwrap();
+        assert_eq!(db.cache.accounts.len(), 0);
+    }
+
+    #[test]
+    fn test_apply_parent_hash() {
+        let state = MockState::default().with_code(HISTORY_STORAGE_ADDRESS, &[0x00]);
+        let block = ForkBlock {
+            parent_hash: B256::repeat_byte(0xdd),
+            ..block()
+        };
+        let mut db = CacheDB::new(SourceDb(state));
+        apply_parent_hash(&mut db, &block, SpecId::PRAGUE).unwrap();
+
+        let slot = (block.number - 1) % HISTORY_SERVE_WINDOW;
+        assert_eq!(
+            db.storage(to_revm_address(HISTORY_STORAGE_ADDRESS), U256::from(slot))
+                .unwrap(),
+            U256::from_be_bytes(block.parent_hash.0)
+        );
+
+        // Nothing is written before Prague
+        let mut db = CacheDB::new(SourceDb(MockState::default()));
+        apply_parent_hash(&mut db, &block, SpecId::CANCUN).unwrap();
+        assert_eq!(db.cache.accounts.len(), 0);
+    }
+
+    /// A JSON-RPC node on a local port holding INCREMENT at TARGET, with 41
+    /// in its slot 1. Returns its URL and every request it received.
+    async fn mock_node() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let log = Arc::clone(&log);
+                tokio::spawn(async move {
+                    let mut socket = BufReader::new(socket);
+                    // Connections are kept alive, so serve until the client hangs up
+                    loop {
+                        let mut length = 0;
+                        loop {
+                            let mut line = String::new();
+                            if socket.read_line(&mut line).await.unwrap_or(0) == 0 {
+                                return;
+                            }
+                            if line == "\r\n" {
+                                break;
+                            }
+                            let line = line.to_ascii_lowercase();
+                            if let Some(value) = line.strip_prefix("content-length:") {
+                                length = value.trim().parse().unwrap();
+                            }
+                        }
+                        let mut body = vec![0; length];
+                        socket.read_exact(&mut body).await.unwrap();
+                        let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                        let response = serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": request["id"],
+                            "result": mock_result(&request),
+                        })
+                        .to_string();
+                        log.lock().unwrap().push(request);
+                        let reply = format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{response}",
+                            response.len()
+                        );
+                        socket.get_mut().write_all(reply.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+        (url, requests)
+    }
+
+    fn mock_result(request: &serde_json::Value) -> serde_json::Value {
+        let params = &request["params"];
+        let at_target = params[0].as_str().and_then(|a| a.parse::<Address>().ok()) == Some(TARGET);
+        match request["method"].as_str().unwrap() {
+            "eth_chainId" => serde_json::json!("0x1"),
+            "eth_getBalance" | "eth_getTransactionCount" => serde_json::json!("0x0"),
+            "eth_getCode" if at_target => {
+                serde_json::json!(format!("0x{}", alloy::hex::encode(INCREMENT)))
+            }
+            "eth_getCode" => serde_json::json!("0x"),
+            "eth_getStorageAt" if at_target && params[1] == "0x1" => {
+                serde_json::json!(U256::from(41))
+            }
+            "eth_getStorageAt" => serde_json::json!(U256::ZERO),
+            method => panic!("unexpected {method}"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_replay_against_rpc_node() {
+        let (url, requests) = mock_node().await;
+        let provider = Arc::new(EthProvider::connect(&url).await.unwrap());
+        let state = RpcState::new(provider, 99, tokio::runtime::Handle::current());
+        let options = TraceOptions {
+            enable_memory: false,
+            enable_storage: true,
+        };
+
+        let fork = tokio::task::spawn_blocking(move || {
+            replay(state, &block(), &[], &call(TARGET, &[]), options)
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        let sstore = fork.trace.steps.iter().find(|s| s.op == "SSTORE").unwrap();
+        assert_eq!(sstore.storage, vec![(U256::from(1), U256::from(42))]);
+
+        // State is read as of the parent block
+        let requests = requests.lock().unwrap();
+        let reads: Vec<_> = requests
+            .iter()
+            .filter(|r| r["method"] != "eth_chainId")
+            .collect();
+        assert!(reads.iter().any(|r| r["method"] == "eth_getStorageAt"));
+        assert!(
+            reads
+                .iter()
+                .all(|r| r["params"].as_array().unwrap().last() == Some(&"0x63".into()))
+        );
+    }
+
+    #[test]
+    fn test_error_message() {
+        assert_eq!(
+            error_message(InstructionResult::Revert),
+            "execution reverted"
+        );
+        assert_eq!(error_message(InstructionResult::MemoryOOG), "out of gas");
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use alloy::primitives::Address;
+
+use crate::data::types::{
+    AccessCounts, GasEntry, GasProfile, InternalCall, OpClass, TraceStats, TraceStep,
+};
+
+/// Number of most expensive steps kept in `TraceStats::hotspots`.
+const HOTSPOT_COUNT: usize = 10;
+
+/// Build a gas profile from a struct log trace and the transaction's call tree.
+///
+/// Each step is charged its own cost: for ops that open a call frame the gas
+/// spent inside the callee is excluded, so that summing every step gives the
+/// execution gas without double counting. Call frames are labelled with the
+/// decoded function of the matching `InternalCall`, when one is available.
+pub fn build_gas_profile(steps: &[TraceStep], calls: &[InternalCall], gas_used: u64) -> GasProfile {
+    let mut by_contract: HashMap<Address, (u64, u64)> = HashMap::new();
+    let mut by_function: HashMap<(Address, String), (u64, u64)> = HashMap::new();
+    let mut by_opcode: HashMap<String, (u64, u64)> = HashMap::new();
+    let mut folded: HashMap<String, u64> = HashMap::new();
+
+    // (address, function) of every open frame, outermost first
+    let mut frames: Vec<(Address, String)> = Vec::new();
+    let mut call_cursor = 0;
+    if let Some(first) = steps.first() {
+        let function = match calls.first() {
+            Some(call) if call.depth == 0 => {
+                call_cursor = 1;
+                function_name(call)
+            }
+            _ => "?".to_string(),
+        };
+        frames.push((first.address, function));
+    }
+
+    let mut total = 0u64;
+    for (i, step) in steps.iter().enumerate() {
+        frames.truncate(step.depth.max(1));
+        let cost = self_cost(steps, i);
+        total += cost;
+
+        let (address, function) = frames.last().cloned().unwrap_or_default();
+        add(by_contract.entry(address).or_default(), cost);
+        add(by_function.entry((address, function)).or_default(), cost);
+        add(by_opcode.entry(step.op.clone()).or_default(), cost);
+
+        let stack = frames
+            .iter()
+            .map(|(address, function)| format!("{address}::{function}"))
+            .collect::<Vec<_>>()
+            .join(";");
+        *folded.entry(format!("{stack};{}", step.op)).or_default() += cost;
+
+        // Entering a call frame: find the matching call in the tree (calls to
+        // accounts without code appear in the tree but open no frame)
+        if let Some(next) = steps.get(i + 1).filter(|next| next.depth > step.depth) {
+            let found = calls[call_cursor.min(calls.len())..]
+                .iter()
+                .position(|c| c.depth == step.depth && c.to == next.address);
+            let function = match found {
+                Some(offset) => {
+                    call_cursor += offset + 1;
+                    function_name(&calls[call_cursor - 1])
+                }
+                None => "?".to_string(),
+            };
+            frames.push((next.address, function));
+        }
+    }
+
+    let intrinsic = gas_used.saturating_sub(total);
+    if intrinsic > 0 {
+        folded.insert("[intrinsic]".to_string(), intrinsic);
+    }
+
+    let mut folded: Vec<(String, u64)> = folded.into_iter().filter(|(_, gas)| *gas > 0).collect();
+    folded.sort();
+
+    GasProfile {
+        gas_used,
+        intrinsic,
+        by_contract: sorted(
+            by_contract
+                .into_iter()
+                .map(|(address, (gas, count))| entry(Some(address), String::new(), gas, count)),
+        ),
+        by_function: sorted(
+            by_function
+                .into_iter()
+                .map(|((address, function), (gas, count))| {
+                    entry(Some(address), function, gas, count)
+                }),
+        ),
+        by_opcode: sorted(
+            by_opcode
+                .into_iter()
+                .map(|(op, (gas, count))| entry(None, op, gas, count)),
+        ),
+        folded,
+    }
+}
+
+/// Summarize a struct log trace: opcode frequencies, own gas per opcode
+/// class, cold and warm storage accesses, the deepest call and the most
+/// expensive steps.
+pub fn trace_stats(steps: &[TraceStep]) -> TraceStats {
+    let mut counts: HashMap<&str, u64> = HashMap::new();
+    let mut class_gas: HashMap<OpClass, u64> = HashMap::new();
+    let mut stats = TraceStats::default();
+    let mut costs: Vec<(usize, u64)> = Vec::with_capacity(steps.len());
+
+    for (i, step) in steps.iter().enumerate() {
+        *counts.entry(step.op.as_str()).or_default() += 1;
+        let cost = self_cost(steps, i);
+        *class_gas.entry(op_class(&step.op)).or_default() += cost;
+        stats.max_depth = stats.max_depth.max(step.depth);
+        if cost > 0 {
+            costs.push((i, cost));
+        }
+
+        match step.op.as_str() {
+            "SLOAD" => count_access(&mut stats.sload, step.gas_cost, &[2_100], &[100]),
+            // Cold SSTOREs pay the 2_100 access surcharge on top of the warm cost
+            "SSTORE" => count_access(
+                &mut stats.sstore,
+                step.gas_cost,
+                &[2_200, 5_000, 22_100],
+                &[100, 2_900, 20_000],
+            ),
+            _ => {}
+        }
+    }
+
+    let mut opcode_counts: Vec<(String, u64)> = counts
+        .into_iter()
+        .map(|(op, count)| (op.to_string(), count))
+        .collect();
+    opcode_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    stats.opcode_counts = opcode_counts;
+
+    let mut class_gas: Vec<(OpClass, u64)> = class_gas.into_iter().collect();
+    class_gas.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    stats.class_gas = class_gas;
+
+    costs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    costs.truncate(HOTSPOT_COUNT);
+    stats.hotspots = costs;
+    stats
+}
+
+/// Class an opcode is charged to in `TraceStats::class_gas`.
+pub fn op_class(op: &str) -> OpClass {
+    match op {
+        "SLOAD" | "SSTORE" | "TLOAD" | "TSTORE" => OpClass::Storage,
+        "MLOAD" | "MSTORE" | "MSTORE8" | "MCOPY" | "MSIZE" | "CALLDATACOPY" | "CODECOPY"
+        | "RETURNDATACOPY" => OpClass::Memory,
+        "CALL" | "CALLCODE" | "DELEGATECALL" | "STATICCALL" | "CREATE" | "CREATE2"
+        | "SELFDESTRUCT" => OpClass::Calls,
+        "KECCAK256" | "SHA3" => OpClass::Hashing,
+        "BALANCE" | "EXTCODESIZE" | "EXTCODECOPY" | "EXTCODEHASH" => OpClass::Accounts,
+        _ if op.starts_with("LOG") => OpClass::Logs,
+        _ => OpClass::Other,
+    }
+}
+
+fn count_access(counts: &mut AccessCounts, cost: u64, cold: &[u64], warm: &[u64]) {
+    if cold.contains(&cost) {
+        counts.cold += 1;
+    } else if warm.contains(&cost) {
+        counts.warm += 1;
+    } else {
+        counts.unknown += 1;
+    }
+}
+
+/// Gas charged to step `index` itself, excluding any callee execution.
+fn self_cost(steps: &[TraceStep], index: usize) -> u64 {
+    let step = &steps[index];
+    let Some(next) = steps.get(index + 1) else {
+        return step.gas_cost;
+    };
+
+    if next.depth == step.depth {
+        // Actual gas consumed, which for calls into accounts without code
+        // excludes the forwarded gas that `gasCost` reports
+        return step.gas.saturating_sub(next.gas);
+    }
+    if next.depth < step.depth {
+        return step.gas_cost;
+    }
+
+    // Opened a frame: total spent across the call minus what the callee used
+    let Some(ret) = steps[index + 1..]
+        .iter()
+        .position(|s| s.depth <= step.depth)
+    else {
+        return step.gas_cost;
+    };
+    let ret = index + 1 + ret;
+    let last = &steps[ret - 1];
+    let callee_used = next
+        .gas
+        .saturating_sub(last.gas.saturating_sub(last.gas_cost));
+    step.gas
+        .saturating_sub(steps[ret].gas)
+        .saturating_sub(callee_used)
+}
+
+fn function_name(call: &InternalCall) -> String {
+    match call.decoded {
+        Some(ref decoded) => decoded.function_name.clone(),
+        None if call.input.len() >= 4 => alloy::hex::encode_prefixed(&call.input[..4]),
+        None if call.call_type.starts_with("CREATE") => "constructor".to_string(),
+        None => "fallback".to_string(),
+    }
+}
+
+fn add(slot: &mut (u64, u64), cost: u64) {
+    slot.0 += cost;
+    slot.1 += 1;
+}
+
+fn entry(address: Option<Address>, label: String, gas: u64, count: u64) -> GasEntry {
+    GasEntry {
+        address,
+        label,
+        gas,
+        count,
+    }
+}
+
+fn sorted(entries: impl Iterator<Item = GasEntry>) -> Vec<GasEntry> {
+    let mut entries: Vec<GasEntry> = entries.collect();
+    entries.sort_by(|a, b| b.gas.cmp(&a.gas).then_with(|| a.label.cmp(&b.label)));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::types::DecodedCall;
+    use alloy::primitives::{Bytes, U256};
+
+    const ROOT: Address = Address::repeat_byte(0x11);
+    const CALLEE: Address = Address::repeat_byte(0x22);
+
+    fn step(op: &str, address: Address, depth: usize, gas: u64, gas_cost: u64) -> TraceStep {
+        TraceStep {
+            address,
+            in_create: false,
+            pc: 0,
+            op: op.to_string(),
+            gas,
+            gas_cost,
+            depth,
+            stack: Vec::new(),
+            memory: Bytes::new(),
+            storage: Vec::new(),
+            return_data: Bytes::new(),
+            error: None,
+        }
+    }
+
+    fn call(to: Address, depth: usize, name: &str) -> InternalCall {
+        InternalCall {
+            from: Address::ZERO,
+            to,
+            value: U256::ZERO,
+            call_type: "CALL".to_string(),
+            gas_used: 0,
+            input: Bytes::from(vec![1, 2, 3, 4]),
+            output: Bytes::new(),
+            depth,
+            error: None,
+            decoded: Some(DecodedCall {
+                function_name: name.to_string(),
+                params: vec![],
+                inner: vec![],
+            }),
+            decoded_output: vec![],
+            revert_reason: None,
+        }
+    }
+
+    /// Root runs PUSH1, CALLs the callee (which runs SSTORE + STOP), then STOPs.
+    fn sample_steps() -> Vec<TraceStep> {
+        vec![
+            step("PUSH1", ROOT, 1, 10_000, 3),
+            // gasCost of CALL includes the 5_000 forwarded to the callee
+            step("CALL", ROOT, 1, 9_997, 5_100),
+            step("SSTORE", CALLEE, 2, 5_000, 2_900),
+            step("STOP", CALLEE, 2, 2_100, 0),
+            // The callee used 2_900 and returned the rest, so CALL itself cost 100
+            step("STOP", ROOT, 1, 6_997, 0),
+        ]
+    }
+
+    #[test]
+    fn test_self_cost_excludes_callee() {
+        let steps = sample_steps();
+        assert_eq!(self_cost(&steps, 0), 3);
+        assert_eq!(self_cost(&steps, 1), 100);
+        assert_eq!(self_cost(&steps, 2), 2_900);
+        assert_eq!(self_cost(&steps, 3), 0);
+    }
+
+    #[test]
+    fn test_build_gas_profile() {
+        let steps = sample_steps();
+        let calls = vec![call(ROOT, 0, "run"), call(CALLEE, 1, "store")];
+        let profile = build_gas_profile(&steps, &calls, 30_000);
+
+        assert_eq!(profile.by_opcode[0].label, "SSTORE");
+        assert_eq!(profile.by_opcode[0].gas, 2_900);
+
+        assert_eq!(profile.by_contract[0].address, Some(CALLEE));
+        assert_eq!(profile.by_contract[0].gas, 2_900);
+        assert_eq!(profile.by_contract[1].address, Some(ROOT));
+        assert_eq!(profile.by_contract[1].gas, 103);
+
+        assert_eq!(profile.by_function[0].label, "store");
+        assert_eq!(profile.by_function[1].label, "run");
+
+        assert_eq!(profile.intrinsic, 30_000 - 3_003);
+        let sstore = profile
+            .folded
+            .iter()
+            .find(|(stack, _)| stack.ends_with(";SSTORE"))
+            .unwrap();
+        assert_eq!(sstore.0, format!("{ROOT}::run;{CALLEE}::store;SSTORE"));
+        assert_eq!(sstore.1, 2_900);
+        assert!(profile.folded.iter().all(|(_, gas)| *gas > 0));
+    }
+
+    #[test]
+    fn test_trace_stats() {
+        let mut steps = sample_steps();
+        // A cold SLOAD and a warm one in the callee, ahead of its SSTORE
+        steps.insert(2, step("SLOAD", CALLEE, 2, 9_000, 2_100));
+        steps.insert(3, step("SLOAD", CALLEE, 2, 6_900, 100));
+        steps[4].gas = 6_800;
+        steps[5].gas = 3_900;
+        steps[6].gas = 4_797;
+
+        let stats = trace_stats(&steps);
+        assert_eq!(stats.opcode_counts[0], ("SLOAD".to_string(), 2));
+        assert_eq!(stats.opcode_counts[1], ("STOP".to_string(), 2));
+        assert_eq!(
+            stats.sload,
+            AccessCounts {
+                cold: 1,
+                warm: 1,
+                unknown: 0
+            }
+        );
+        assert_eq!(stats.sstore.warm, 1);
+        assert_eq!(stats.max_depth, 2);
+
+        // The CALL is charged only its own 100 gas, not the callee's
+        assert_eq!(stats.class_gas[0], (OpClass::Storage, 5_100));
+        assert!(stats.class_gas.contains(&(OpClass::Calls, 100)));
+        assert!(stats.class_gas.contains(&(OpClass::Other, 3)));
+        assert_eq!(stats.hotspots, vec![(4, 2_900), (2, 2_100), (1, 100), (3, 100), (0, 3)]);
+    }
+
+    #[test]
+    fn test_op_class() {
+        assert_eq!(op_class("SSTORE"), OpClass::Storage);
+        assert_eq!(op_class("CALLDATACOPY"), OpClass::Memory);
+        assert_eq!(op_class("CREATE2"), OpClass::Calls);
+        assert_eq!(op_class("KECCAK256"), OpClass::Hashing);
+        assert_eq!(op_class("LOG3"), OpClass::Logs);
+        assert_eq!(op_class("EXTCODEHASH"), OpClass::Accounts);
+        assert_eq!(op_class("ADD"), OpClass::Other);
+    }
+
+    #[test]
+    fn test_build_gas_profile_without_call_tree() {
+        let profile = build_gas_profile(&sample_steps(), &[], 0);
+        assert_eq!(profile.intrinsic, 0);
+        assert!(profile.by_function.iter().all(|e| e.label == "?"));
+    }
+}
@@ -0,0 +1,130 @@
+use alloy::primitives::keccak256;
+
+use crate::data::disasm;
+use crate::data::types::ContractType;
+
+/// Functions of the ERC-165 interface ids 0x36372b07 (ERC-20), 0x80ac58cd
+/// (ERC-721) and 0xd9b67a26 (ERC-1155). A contract implements a standard
+/// if it dispatches every one of them.
+const ERC20_FUNCTIONS: &[&str] = &[
+    "totalSupply()",
+    "balanceOf(address)",
+    "transfer(address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+    "allowance(address,address)",
+];
+const ERC721_FUNCTIONS: &[&str] = &[
+    "balanceOf(address)",
+    "ownerOf(uint256)",
+    "safeTransferFrom(address,address,uint256,bytes)",
+    "safeTransferFrom(address,address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+    "setApprovalForAll(address,bool)",
+    "getApproved(uint256)",
+    "isApprovedForAll(address,address)",
+];
+const ERC1155_FUNCTIONS: &[&str] = &[
+    "safeTransferFrom(address,address,uint256,uint256,bytes)",
+    "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+    "balanceOf(address,uint256)",
+    "balanceOfBatch(address[],uint256[])",
+    "setApprovalForAll(address,bool)",
+    "isApprovedForAll(address,address)",
+];
+
+/// Selectors the function dispatcher of runtime code compares calldata
+/// against, in dispatch order.
+pub fn dispatch_selectors(code: &[u8]) -> Vec<[u8; 4]> {
+    let mut selectors: Vec<[u8; 4]> = Vec::new();
+    for entry in disasm::disassemble(code).dispatch {
+        if !selectors.contains(&entry.selector) {
+            selectors.push(entry.selector);
+        }
+    }
+    selectors
+}
+
+/// The token standard a contract implements, judged by whether it has every
+/// function of the standard's interface. ERC-1155 and ERC-721 are checked
+/// first as they share functions with ERC-20.
+pub fn contract_type(selectors: &[[u8; 4]]) -> Option<ContractType> {
+    let implements = |functions: &[&str]| {
+        functions
+            .iter()
+            .all(|signature| selectors.contains(&selector(signature)))
+    };
+    if implements(ERC1155_FUNCTIONS) {
+        Some(ContractType::ERC1155)
+    } else if implements(ERC721_FUNCTIONS) {
+        Some(ContractType::ERC721)
+    } else if implements(ERC20_FUNCTIONS) {
+        Some(ContractType::ERC20)
+    } else {
+        None
+    }
+}
+
+fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selectors(functions: &[&str]) -> Vec<[u8; 4]> {
+        functions.iter().map(|f| selector(f)).collect()
+    }
+
+    /// XOR of the selectors, as ERC-165 defines an interface id.
+    fn interface_id(functions: &[&str]) -> [u8; 4] {
+        selectors(functions).iter().fold([0; 4], |mut id, s| {
+            for (a, b) in id.iter_mut().zip(s) {
+                *a ^= b;
+            }
+            id
+        })
+    }
+
+    #[test]
+    fn test_interface_ids() {
+        assert_eq!(interface_id(ERC20_FUNCTIONS), [0x36, 0x37, 0x2b, 0x07]);
+        assert_eq!(interface_id(ERC721_FUNCTIONS), [0x80, 0xac, 0x58, 0xcd]);
+        assert_eq!(interface_id(ERC1155_FUNCTIONS), [0xd9, 0xb6, 0x7a, 0x26]);
+    }
+
+    #[test]
+    fn test_contract_type() {
+        let mut token = selectors(ERC20_FUNCTIONS);
+        token.push(selector("decimals()"));
+        assert_eq!(contract_type(&token), Some(ContractType::ERC20));
+
+        let mut nft = selectors(ERC721_FUNCTIONS);
+        nft.push(selector("totalSupply()"));
+        assert_eq!(contract_type(&nft), Some(ContractType::ERC721));
+
+        assert_eq!(
+            contract_type(&selectors(ERC1155_FUNCTIONS)),
+            Some(ContractType::ERC1155)
+        );
+
+        // Missing allowance(address,address)
+        assert_eq!(contract_type(&selectors(&ERC20_FUNCTIONS[..5])), None);
+        assert_eq!(contract_type(&[]), None);
+    }
+
+    #[test]
+    fn test_dispatch_selectors() {
+        let code = alloy::hex::decode(concat!(
+            "8063a9059cbb1461001657", // DUP1 PUSH4 transfer EQ PUSH2 0x0016 JUMPI
+            "8063a9059cbb1461001657", // the same branch again
+            "5b00",                   // 0x16: JUMPDEST STOP
+        ))
+        .unwrap();
+        assert_eq!(dispatch_selectors(&code), vec![[0xa9, 0x05, 0x9c, 0xbb]]);
+        assert!(dispatch_selectors(&[]).is_empty());
+    }
+}
@@ -1,10 +1,26 @@
 pub mod abi;
+pub mod abi_cache;
+pub mod abi_store;
+pub mod block_trace;
 pub mod cache;
 pub mod chains;
 pub mod decoder;
+pub mod disasm;
 pub mod ens;
+pub mod explorer;
 pub mod export;
+pub mod fork;
+pub mod gas_profile;
+pub mod interface;
+pub mod nested;
 pub mod provider;
+pub mod proxy;
+pub mod signatures;
+pub mod simulate;
+pub mod sourcemap;
+pub mod state_diff;
+pub mod storage_layout;
+pub mod trace;
 pub mod types;
 pub mod watchlist;
 pub mod ws;
@@ -12,6 +28,7 @@ pub mod ws;
 use std::sync::Arc;
 
 use alloy::consensus::Transaction as ConsensusTransaction;
+use alloy::json_abi::StateMutability;
 use alloy::primitives::{Address, Bytes, B256, U256};
 use alloy::rpc::types::{Block, Transaction, TransactionReceipt};
 use alloy::sol;
@@ -22,7 +39,10 @@ use tokio::sync::RwLock;
 use crate::data::abi::AbiResolver;
 use crate::data::cache::DataCache;
 use crate::data::decoder::TxDecoder;
+use crate::data::explorer::ExplorerClient;
 use crate::data::provider::EthProvider;
+use crate::data::storage_layout::StorageItem;
+use crate::data::trace::TraceOptions;
 use crate::data::types::*;
 use crate::events::{AppEvent, SearchTarget, View};
 
@@ -36,35 +56,29 @@ sol! {
     }
 }
 
-/// EIP-1967 implementation storage slot
-const EIP1967_IMPL_SLOT: U256 = {
-    U256::from_be_bytes([
-        0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d,
-        0xb9, 0x8d, 0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50,
-        0x5d, 0x38, 0x2b, 0xbc,
-    ])
-};
-
 pub struct DataService {
     provider: Arc<EthProvider>,
     cache: Arc<RwLock<DataCache>>,
     abi_resolver: Arc<AbiResolver>,
     event_tx: mpsc::UnboundedSender<AppEvent>,
-    etherscan_api_key: Option<String>,
+    explorer: Option<ExplorerClient>,
 }
 
 impl DataService {
     pub fn new(
         provider: EthProvider,
-        etherscan_api_key: Option<String>,
+        explorer: Option<ExplorerClient>,
         event_tx: mpsc::UnboundedSender<AppEvent>,
     ) -> Self {
+        let provider = Arc::new(provider);
+        let abi_resolver =
+            AbiResolver::new(explorer.clone()).with_provider(Arc::clone(&provider));
         Self {
-            provider: Arc::new(provider),
+            provider,
             cache: Arc::new(RwLock::new(DataCache::new())),
-            abi_resolver: Arc::new(AbiResolver::new(etherscan_api_key.clone())),
+            abi_resolver: Arc::new(abi_resolver),
             event_tx,
-            etherscan_api_key,
+            explorer,
         }
     }
 
@@ -217,6 +231,59 @@ impl DataService {
         });
     }
 
+    /// Trace every transaction of a block to count internal calls and list
+    /// internal ETH transfers and contract creations.
+    pub fn fetch_block_trace(&self, number: u64) {
+        let provider = Arc::clone(&self.provider);
+        let tx = self.event_tx.clone();
+
+        tokio::spawn(async move {
+            let fail = |error: String| {
+                let _ = tx.send(AppEvent::BlockTraceFailed { number, error });
+            };
+
+            // Try trace_block first (Parity/Erigon), then debug_traceBlockByNumber (Geth)
+            let blocks = match block_trace::fetch_trace_block(&provider, number).await {
+                Ok(blocks) => blocks,
+                Err(_) => {
+                    let block = match provider.get_block(number).await {
+                        Ok(Some(b)) => b,
+                        Ok(None) => return fail(format!("Block {number} not found")),
+                        Err(e) => return fail(format!("Failed to fetch block {number}: {e}")),
+                    };
+                    let tx_hashes: Vec<B256> = block.transactions.hashes().collect();
+                    match block_trace::fetch_debug_trace_block(&provider, number, &tx_hashes)
+                        .await
+                    {
+                        Ok(blocks) => blocks,
+                        // Replay only stands in for a node without tracing, and
+                        // only knows the hard forks of Ethereum
+                        Err(rpc_error)
+                            if !trace::method_unsupported(&rpc_error)
+                                || !fork::replays_chain(provider.chain_id()) =>
+                        {
+                            return fail(format!("Failed to trace block {number}: {rpc_error}"));
+                        }
+                        // Plain JSON-RPC endpoint: re-execute the block locally
+                        Err(rpc_error) => {
+                            match fork::trace_block(Arc::clone(&provider), &block).await {
+                                Ok(blocks) => blocks,
+                                Err(e) => {
+                                    return fail(format!(
+                                        "Failed to trace block {number}: {rpc_error}; local replay failed: {e}"
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                }
+            };
+
+            let trace = block_trace::summarize_block_trace(number, &blocks);
+            let _ = tx.send(AppEvent::BlockTraceLoaded(Box::new(trace)));
+        });
+    }
+
     /// Fetch full transaction detail with receipt, decoded input, and token transfers.
     pub fn fetch_transaction_detail(&self, hash: B256) {
         let provider = Arc::clone(&self.provider);
@@ -229,7 +296,10 @@ impl DataService {
             {
                 let mut c = cache.write().await;
                 if let Some(cached) = c.get_transaction(hash) {
+                    let transfers = cached.token_transfers.clone();
                     let _ = tx.send(AppEvent::TransactionDetailLoaded(Box::new(cached)));
+                    drop(c);
+                    send_token_metadata(&provider, &cache, &tx, &transfers).await;
                     return;
                 }
             }
@@ -281,20 +351,34 @@ impl DataService {
                 let selector: [u8; 4] = input_data[..4].try_into().unwrap_or([0; 4]);
                 let to_address = transaction.inner.to();
 
+                let chain_id = provider.chain_id();
                 let mut decoded = None;
+                let mut decode_abi = None;
                 let mut mname = None;
 
                 if let Some(to) = to_address {
                     // Try resolving ABI for the target contract
-                    let chain_id = provider.chain_id();
                     if let Some(resolved) = abi_resolver.resolve(chain_id, to).await {
                         decoded = TxDecoder::decode_input(&resolved.abi, &input_data);
-                        if let Some(ref d) = decoded {
-                            mname = Some(d.function_name.clone());
-                        }
+                        decode_abi = Some(resolved.abi);
                     }
                 }
 
+                // Without a matching ABI, decode against the signature database
+                if decoded.is_none() {
+                    decoded = signatures::global().decode_call(&input_data);
+                    decode_abi = signatures::global().abi_for_call(&input_data);
+                }
+
+                // Unpack batched and wrapped calls carried in the arguments
+                if let (Some(d), Some(abi), Some(to)) = (decoded.as_mut(), &decode_abi, to_address) {
+                    d.inner =
+                        decode_nested_calls(&abi_resolver, chain_id, to, abi, &input_data, 0).await;
+                }
+                if let Some(ref d) = decoded {
+                    mname = Some(d.function_name.clone());
+                }
+
                 // If no method name from ABI decode, try builtin selectors first (fast, local)
                 if mname.is_none() {
                     mname = abi_resolver.match_builtin_selector(selector);
@@ -354,16 +438,19 @@ impl DataService {
                 c.put_transaction(hash, detail.clone());
             }
 
+            let transfers = detail.token_transfers.clone();
             let _ = tx.send(AppEvent::TransactionDetailLoaded(Box::new(detail)));
+            send_token_metadata(&provider, &cache, &tx, &transfers).await;
         });
     }
 
-    /// Fetch address information: balance, nonce, contract status, proxy detection, and tx history.
+    /// Fetch address information: balance, nonce, contract status, proxy detection,
+    /// verified source, and tx history.
     pub fn fetch_address_info(&self, address: Address) {
         let provider = Arc::clone(&self.provider);
         let abi_resolver = Arc::clone(&self.abi_resolver);
         let tx = self.event_tx.clone();
-        let etherscan_key = self.etherscan_api_key.clone();
+        let explorer = self.explorer.clone();
 
         tokio::spawn(async move {
             // Fetch balance, nonce, and code in parallel
@@ -382,35 +469,32 @@ impl DataService {
                 let chain_id = provider.chain_id();
                 let resolved = abi_resolver.resolve(chain_id, address).await;
 
-                // EIP-1967 proxy detection
-                let (is_proxy, implementation) =
-                    match provider.get_storage_at(address, EIP1967_IMPL_SLOT).await {
-                        Ok(slot_value) => {
-                            if slot_value != U256::ZERO {
-                                // Convert U256 to Address (last 20 bytes)
-                                let bytes: [u8; 32] = slot_value.to_be_bytes();
-                                let impl_addr = Address::from_slice(&bytes[12..]);
-                                (true, Some(impl_addr))
-                            } else {
-                                (false, None)
-                            }
-                        }
-                        Err(_) => (false, None),
-                    };
+                // Proxy detection; resolving the ABI above already merged in
+                // the implementation's or facets'
+                let proxy = abi_resolver.proxy(address).await;
 
-                // If proxy, also resolve the implementation ABI
-                if is_proxy {
-                    if let Some(impl_addr) = implementation {
-                        let _ = abi_resolver.resolve(chain_id, impl_addr).await;
-                    }
-                }
+                let verified = match explorer {
+                    Some(ref explorer) => explorer.source(address).await,
+                    None => None,
+                };
+
+                // Judge the token standard from the functions the ABI has,
+                // which says nothing when it is the built-in fallback
+                let contract_type = resolved
+                    .as_ref()
+                    .filter(|r| r.source != abi::FALLBACK_SOURCE)
+                    .and_then(|r| {
+                        let selectors: Vec<[u8; 4]> =
+                            r.abi.functions().map(|f| f.selector().0).collect();
+                        interface::contract_type(&selectors)
+                    });
 
                 Some(ContractInfo {
                     abi_source: resolved.map(|r| r.source),
-                    is_proxy,
-                    implementation,
-                    contract_type: None,
-                    name: None,
+                    proxy,
+                    contract_type,
+                    name: verified.as_ref().map(|v| v.name.clone()),
+                    compiler: verified.map(|v| v.compiler),
                     symbol: None,
                     decimals: None,
                 })
@@ -418,11 +502,10 @@ impl DataService {
                 None
             };
 
-            // Fetch recent transactions from Etherscan if API key available
-            let transactions = if let Some(ref api_key) = etherscan_key {
-                fetch_etherscan_tx_history(address, api_key).await
-            } else {
-                vec![]
+            // Fetch recent transactions from the block explorer if configured
+            let transactions = match explorer {
+                Some(ref explorer) => explorer.tx_history(address).await,
+                None => vec![],
             };
 
             let info = AddressInfo {
@@ -438,6 +521,121 @@ impl DataService {
         });
     }
 
+    /// Fetch the view and pure functions a contract can be read through,
+    /// merging in the implementation's or facets' behind a proxy. Functions
+    /// of an inferred interface that only 4byte named may be reads too, but
+    /// nothing says what they return, so they come separately.
+    pub fn fetch_contract_functions(&self, address: Address) {
+        let provider = Arc::clone(&self.provider);
+        let abi_resolver = Arc::clone(&self.abi_resolver);
+        let tx = self.event_tx.clone();
+
+        tokio::spawn(async move {
+            let chain_id = provider.chain_id();
+            let mut functions = Vec::new();
+            let mut untyped = Vec::new();
+            if let Some(resolved) = abi_resolver.resolve(chain_id, address).await {
+                let inferred = resolved.source == abi::INFERRED_SOURCE;
+                for function in resolved.abi.functions() {
+                    if matches!(
+                        function.state_mutability,
+                        StateMutability::View | StateMutability::Pure
+                    ) {
+                        functions.push(function.clone());
+                    } else if inferred
+                        && signatures::global()
+                            .function(function.selector().0)
+                            .is_none()
+                    {
+                        untyped.push(function.clone());
+                    }
+                }
+            }
+            let _ = tx.send(AppEvent::ContractFunctionsLoaded {
+                address,
+                functions,
+                untyped,
+            });
+        });
+    }
+
+    /// Fetch a contract's runtime code and disassemble it, naming the
+    /// dispatcher's selectors from its ABI or the signature database.
+    pub fn fetch_disassembly(&self, address: Address) {
+        let provider = Arc::clone(&self.provider);
+        let abi_resolver = Arc::clone(&self.abi_resolver);
+        let tx = self.event_tx.clone();
+
+        tokio::spawn(async move {
+            let code = match provider.get_code(address).await {
+                Ok(code) => code,
+                Err(e) => {
+                    let _ = tx.send(AppEvent::Error(format!("Failed to fetch code: {e}")));
+                    return;
+                }
+            };
+            let mut disassembly = disasm::disassemble(&code);
+
+            if !disassembly.dispatch.is_empty() {
+                let chain_id = provider.chain_id();
+                let abi = abi_resolver.resolve(chain_id, address).await.map(|r| r.abi);
+                for entry in disassembly.dispatch.iter_mut() {
+                    entry.name = abi
+                        .as_ref()
+                        .and_then(|abi| {
+                            abi.functions()
+                                .find(|f| f.selector() == entry.selector)
+                                .map(|f| f.name.clone())
+                        })
+                        .or_else(|| signatures::global().function_name(entry.selector));
+                }
+            }
+
+            let _ = tx.send(AppEvent::DisassemblyLoaded {
+                address,
+                disassembly: Box::new(disassembly),
+            });
+        });
+    }
+
+    /// Fetch the storage layout of a verified contract, or of the
+    /// implementation behind a proxy.
+    pub fn fetch_storage_layout(&self, address: Address) {
+        let provider = Arc::clone(&self.provider);
+        let abi_resolver = Arc::clone(&self.abi_resolver);
+        let tx = self.event_tx.clone();
+
+        tokio::spawn(async move {
+            let chain_id = provider.chain_id();
+            let layout = abi_resolver
+                .resolve_storage_layout(chain_id, address)
+                .await
+                .map(Box::new);
+            let _ = tx.send(AppEvent::StorageLayoutLoaded { address, layout });
+        });
+    }
+
+    /// Read and decode storage items of a contract concurrently.
+    pub fn fetch_storage_items(&self, address: Address, items: Vec<StorageItem>) {
+        let provider = Arc::clone(&self.provider);
+        let tx = self.event_tx.clone();
+
+        tokio::spawn(async move {
+            let values = futures::future::join_all(
+                items
+                    .iter()
+                    .map(|item| storage_layout::read_item(&provider, address, item)),
+            )
+            .await;
+            let values = items
+                .into_iter()
+                .zip(values)
+                .filter_map(|(item, value)| Some((item, value?)))
+                .collect();
+            let _ = tx.send(AppEvent::StorageItemsLoaded { address, values });
+        });
+    }
+
     /// Fetch gas price information from fee history.
     pub fn fetch_gas_info(&self) {
         let provider = Arc::clone(&self.provider);
@@ -529,8 +727,13 @@ impl DataService {
     }
 
     /// Fetch internal transactions (execution trace) for a given transaction.
-    pub fn fetch_internal_transactions(&self, tx_hash: B256) {
+    ///
+    /// Without tracing on the node, the transaction is re-executed locally
+    /// if `replay` is set; this replays the block up to the transaction, so
+    /// it is left to views the user opens for the call tree.
+    pub fn fetch_internal_transactions(&self, tx_hash: B256, replay: bool) {
         let provider = Arc::clone(&self.provider);
+        let abi_resolver = Arc::clone(&self.abi_resolver);
         let tx = self.event_tx.clone();
 
         tokio::spawn(async move {
@@ -541,16 +744,43 @@ impl DataService {
                     // Fallback to debug_traceTransaction with callTracer
                     match fetch_debug_trace(&provider, tx_hash).await {
                         Ok(calls) => calls,
-                        Err(e) => {
+                        // Replay only stands in for a node without tracing, and
+                        // only knows the hard forks of Ethereum
+                        Err(rpc_error)
+                            if !trace::method_unsupported(&rpc_error)
+                                || !fork::replays_chain(provider.chain_id()) =>
+                        {
                             let _ = tx.send(AppEvent::Error(format!(
-                                "Failed to trace transaction: {e}"
+                                "Failed to trace transaction: {rpc_error}"
                             )));
                             return;
                         }
+                        // The node cannot trace; leave the calls out until asked
+                        Err(_) if !replay => return,
+                        // Plain JSON-RPC endpoint: re-execute the transaction locally
+                        Err(rpc_error) => {
+                            match fork::trace_calls(Arc::clone(&provider), tx_hash).await {
+                                Ok(calls) => calls,
+                                Err(e) => {
+                                    let _ = tx.send(AppEvent::Error(format!(
+                                        "Failed to trace transaction: {rpc_error}; local replay failed: {e}"
+                                    )));
+                                    return;
+                                }
+                            }
+                        }
                     }
                 }
             };
 
+            // Show the raw tree right away, then again once callees are decoded
+            let mut calls = calls;
+            let _ = tx.send(AppEvent::InternalTransactionsLoaded {
+                tx_hash,
+                calls: calls.clone(),
+            });
+
+            decode_internal_calls(&abi_resolver, provider.chain_id(), &mut calls).await;
             let _ = tx.send(AppEvent::InternalTransactionsLoaded {
                 tx_hash,
                 calls,
@@ -558,61 +788,280 @@ impl DataService {
         });
     }
 
-    /// Fetch token metadata (name, symbol, decimals) for a single address.
-    pub fn fetch_token_metadata(&self, address: Address) {
+    /// Decode the logs of a transaction's receipt.
+    pub fn fetch_decoded_logs(&self, tx_hash: B256) {
         let provider = Arc::clone(&self.provider);
+        let abi_resolver = Arc::clone(&self.abi_resolver);
         let tx = self.event_tx.clone();
 
         tokio::spawn(async move {
-            let name_data = Bytes::from(IERC20Metadata::nameCall {}.abi_encode());
-            let symbol_data = Bytes::from(IERC20Metadata::symbolCall {}.abi_encode());
-            let decimals_data = Bytes::from(IERC20Metadata::decimalsCall {}.abi_encode());
-
-            // Try multicall first, fall back to individual calls
-            let (name, symbol, decimals) =
-                match provider
-                    .multicall(vec![
-                        (address, name_data.clone()),
-                        (address, symbol_data.clone()),
-                        (address, decimals_data.clone()),
-                    ])
-                    .await
+            let receipt = match provider.get_transaction_receipt(tx_hash).await {
+                Ok(Some(receipt)) => receipt,
+                _ => return,
+            };
+            let logs =
+                decode_emitted_logs(&abi_resolver, provider.chain_id(), receipt.inner.logs()).await;
+            let _ = tx.send(AppEvent::DecodedLogsLoaded { tx_hash, logs });
+        });
+    }
+
+    /// Fetch the balance, nonce, code and storage changes made by a transaction.
+    pub fn fetch_state_diff(&self, tx_hash: B256) {
+        let provider = Arc::clone(&self.provider);
+        let tx = self.event_tx.clone();
+
+        tokio::spawn(async move {
+            // Try prestateTracer in diff mode (Geth/Reth), then trace_replayTransaction (Parity/Erigon)
+            let diffs = match state_diff::fetch_prestate_diff(&provider, tx_hash).await {
+                Ok(diffs) => diffs,
+                Err(_) => match state_diff::fetch_replay_state_diff(&provider, tx_hash).await {
+                    Ok(diffs) => diffs,
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::Error(format!(
+                            "Failed to fetch state diff: {e}"
+                        )));
+                        return;
+                    }
+                },
+            };
+
+            let _ = tx.send(AppEvent::StateDiffLoaded { tx_hash, diffs });
+        });
+    }
+
+    /// Fetch an opcode-level execution trace for the transaction debugger.
+    pub fn fetch_execution_trace(&self, tx_hash: B256, options: TraceOptions) {
+        let provider = Arc::clone(&self.provider);
+        let abi_resolver = Arc::clone(&self.abi_resolver);
+        let tx = self.event_tx.clone();
+
+        tokio::spawn(async move {
+            let (root, deployment) = trace_root(&provider, tx_hash).await;
+
+            let trace = match trace::fetch_struct_log_trace(
+                &provider, tx_hash, root, deployment, options,
+            )
+            .await
+            {
+                Ok(trace) => Ok(trace),
+                // Replay only stands in for a node without tracing, and only
+                // knows the hard forks of Ethereum
+                Err(rpc_error)
+                    if !trace::method_unsupported(&rpc_error)
+                        || !fork::replays_chain(provider.chain_id()) =>
                 {
-                    Ok(results) if results.len() == 3 => {
-                        let name = decode_string_result(&results[0]);
-                        let symbol = decode_string_result(&results[1]);
-                        let decimals = decode_u8_result(&results[2]);
-                        (name, symbol, decimals)
+                    Err(rpc_error.to_string())
+                }
+                // Plain JSON-RPC endpoint: re-execute the transaction locally
+                Err(rpc_error) => fork::trace_transaction(Arc::clone(&provider), tx_hash, options)
+                    .await
+                    .map(|fork| fork.trace)
+                    .map_err(|e| format!("{rpc_error}; local replay failed: {e}")),
+            };
+            match trace {
+                Ok(mut trace) => {
+                    if trace.failed {
+                        let chain_id = provider.chain_id();
+                        let abi = abi_resolver.resolve(chain_id, root).await.map(|r| r.abi);
+                        if let Some(reason) =
+                            decode_revert_reason(&abi_resolver, abi.as_ref(), &trace.return_value)
+                                .await
+                        {
+                            trace.revert_reason = Some(reason);
+                        }
                     }
-                    _ => {
-                        // Fall back to individual calls
-                        let name = provider
-                            .call(address, name_data)
-                            .await
-                            .ok()
-                            .and_then(|r| decode_string_result(&r));
-                        let symbol = provider
-                            .call(address, symbol_data)
-                            .await
-                            .ok()
-                            .and_then(|r| decode_string_result(&r));
-                        let decimals = provider
-                            .call(address, decimals_data)
-                            .await
-                            .ok()
-                            .and_then(|r| decode_u8_result(&r));
-                        (name, symbol, decimals)
+
+                    let mut addresses: Vec<Address> = Vec::new();
+                    for step in &trace.steps {
+                        if !step.address.is_zero() && !addresses.contains(&step.address) {
+                            addresses.push(step.address);
+                        }
                     }
-                };
+                    let _ = tx.send(AppEvent::TraceLoaded { tx_hash, trace });
 
-            let metadata = TokenMetadata {
-                address,
-                name: name.unwrap_or_else(|| "Unknown".to_string()),
-                symbol: symbol.unwrap_or_else(|| "???".to_string()),
-                decimals: decimals.unwrap_or(18),
+                    // Attach verified sources for source-level stepping
+                    let chain_id = provider.chain_id();
+                    for address in addresses {
+                        if let Some(source) = abi_resolver.resolve_source(chain_id, address).await {
+                            let _ = tx.send(AppEvent::ContractSourceLoaded {
+                                tx_hash,
+                                address,
+                                source: Box::new(source),
+                            });
+                        }
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.send(AppEvent::TraceFailed {
+                        tx_hash,
+                        error: format!("Failed to trace transaction: {e}"),
+                    });
+                }
+            }
+        });
+    }
+
+    /// Profile where a transaction's gas went, combining the opcode-level
+    /// trace with the decoded call tree.
+    pub fn fetch_gas_profile(&self, tx_hash: B256) {
+        let provider = Arc::clone(&self.provider);
+        let abi_resolver = Arc::clone(&self.abi_resolver);
+        let tx = self.event_tx.clone();
+
+        tokio::spawn(async move {
+            let (root, deployment) = trace_root(&provider, tx_hash).await;
+            let (trace, mut calls) = match trace::fetch_struct_log_trace(
+                &provider,
+                tx_hash,
+                root,
+                deployment,
+                TraceOptions::default(),
+            )
+            .await
+            {
+                Ok(trace) => {
+                    // The call tree only labels frames, so profile without it if unavailable
+                    let calls = match fetch_trace_transaction(&provider, tx_hash).await {
+                        Ok(calls) => calls,
+                        Err(_) => fetch_debug_trace(&provider, tx_hash).await.unwrap_or_default(),
+                    };
+                    (trace, calls)
+                }
+                // Replay only stands in for a node without tracing, and only
+                // knows the hard forks of Ethereum
+                Err(rpc_error)
+                    if !trace::method_unsupported(&rpc_error)
+                        || !fork::replays_chain(provider.chain_id()) =>
+                {
+                    let _ = tx.send(AppEvent::Error(format!(
+                        "Failed to trace transaction: {rpc_error}"
+                    )));
+                    return;
+                }
+                // Plain JSON-RPC endpoint: re-execute the transaction locally
+                Err(rpc_error) => match fork::trace_transaction(
+                    Arc::clone(&provider),
+                    tx_hash,
+                    TraceOptions::default(),
+                )
+                .await
+                {
+                    Ok(fork) => (fork.trace, fork.calls),
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::Error(format!(
+                            "Failed to trace transaction: {rpc_error}; local replay failed: {e}"
+                        )));
+                        return;
+                    }
+                },
             };
+            decode_internal_calls(&abi_resolver, provider.chain_id(), &mut calls).await;
 
-            let _ = tx.send(AppEvent::TokenMetadataLoaded(metadata));
+            let profile = gas_profile::build_gas_profile(&trace.steps, &calls, trace.gas_used);
+            let _ = tx.send(AppEvent::GasProfileLoaded {
+                tx_hash,
+                profile: Box::new(profile),
+            });
+        });
+    }
+
+    /// Simulate a call against a chosen block, tracing it with
+    /// `debug_traceCall` when available and falling back to `eth_call`.
+    pub fn simulate(&self, request: SimulationRequest) {
+        let provider = Arc::clone(&self.provider);
+        let cache = Arc::clone(&self.cache);
+        let abi_resolver = Arc::clone(&self.abi_resolver);
+        let tx = self.event_tx.clone();
+
+        tokio::spawn(async move {
+            let chain_id = provider.chain_id();
+            let abi = match request.to {
+                Some(to) => abi_resolver.resolve(chain_id, to).await.map(|r| r.abi),
+                None => None,
+            };
+            let input = match simulate::encode_calldata(abi.as_ref(), &request.calldata) {
+                Ok(input) => input,
+                Err(e) => {
+                    let _ = tx.send(AppEvent::SimulationFailed(e));
+                    return;
+                }
+            };
+            let call = simulate::transaction_request(&request, input.clone());
+
+            let mut result = SimulationResult {
+                success: true,
+                gas_used: None,
+                decoded_input: abi.as_ref().and_then(|abi| TxDecoder::decode_input(abi, &input)),
+                input,
+                output: Bytes::new(),
+                decoded_output: Vec::new(),
+                revert_reason: None,
+                calls: Vec::new(),
+                logs: Vec::new(),
+                token_transfers: Vec::new(),
+                traced: false,
+            };
+
+            match simulate::fetch_trace_call(&provider, &call, &request).await {
+                Ok(frame) => {
+                    let mut calls = Vec::new();
+                    parse_call_frame(&frame, 0, &mut calls);
+                    let mut logs = Vec::new();
+                    simulate::collect_logs(&frame, &mut logs);
+                    decode_internal_calls(&abi_resolver, chain_id, &mut calls).await;
+
+                    if let Some(root) = calls.first() {
+                        result.success = root.error.is_none();
+                        result.output = root.output.clone();
+                        result.decoded_output = root.decoded_output.clone();
+                        result.revert_reason = root.revert_reason.clone();
+                        if result.decoded_input.is_none() {
+                            result.decoded_input = root.decoded.clone();
+                        }
+                    }
+                    result.gas_used = simulate::frame_gas_used(&frame);
+                    result.token_transfers = TxDecoder::extract_token_transfers(&logs);
+                    result.logs = decode_emitted_logs(&abi_resolver, chain_id, &logs).await;
+                    result.calls = calls;
+                    result.traced = true;
+                }
+                Err(_) => {
+                    match provider
+                        .call_at(
+                            &call,
+                            request.block,
+                            &request.state_overrides,
+                            request.block_overrides.as_ref(),
+                        )
+                        .await
+                    {
+                        Ok(output) => {
+                            if let Some(ref abi) = abi {
+                                result.decoded_output =
+                                    TxDecoder::decode_output(abi, &result.input, &output)
+                                        .unwrap_or_default();
+                            }
+                            result.output = output;
+                        }
+                        Err(e) => {
+                            let Some(data) = revert_data(&e) else {
+                                let _ = tx.send(AppEvent::SimulationFailed(format!(
+                                    "Simulation failed: {e}"
+                                )));
+                                return;
+                            };
+                            result.success = false;
+                            result.revert_reason =
+                                decode_revert_reason(&abi_resolver, abi.as_ref(), &data).await;
+                            result.output = data;
+                        }
+                    }
+                }
+            }
+
+            let transfers = result.token_transfers.clone();
+            let _ = tx.send(AppEvent::SimulationLoaded(Box::new(result)));
+            send_token_metadata(&provider, &cache, &tx, &transfers).await;
         });
     }
 
@@ -729,66 +1178,93 @@ async fn fetch_trace_transaction(
         .as_array()
         .ok_or_else(|| color_eyre::eyre::eyre!("Expected array from trace_transaction"))?;
 
-    let mut calls = Vec::new();
-    for trace in traces {
-        let action = &trace["action"];
-        let result_field = &trace["result"];
+    Ok(traces.iter().filter_map(parse_parity_trace).collect())
+}
 
-        let from = action["from"]
-            .as_str()
-            .and_then(|s| s.parse::<Address>().ok())
-            .unwrap_or(Address::ZERO);
-        let to = action["to"]
+/// Parse one Parity-style trace entry into an InternalCall.
+/// Block reward entries carry no call and yield None.
+fn parse_parity_trace(trace: &serde_json::Value) -> Option<InternalCall> {
+    let action = &trace["action"];
+    let result_field = &trace["result"];
+
+    let address = |field: &serde_json::Value| {
+        field
             .as_str()
             .and_then(|s| s.parse::<Address>().ok())
-            .unwrap_or(Address::ZERO);
-        let value = action["value"]
-            .as_str()
-            .and_then(|s| U256::from_str_radix(s.trim_start_matches("0x"), 16).ok())
-            .unwrap_or(U256::ZERO);
-        let call_type = action["callType"]
-            .as_str()
-            .unwrap_or("call")
-            .to_string();
-        let gas_used = result_field["gasUsed"]
-            .as_str()
-            .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
-            .unwrap_or(0);
-        let input = action["input"]
-            .as_str()
-            .and_then(|s| {
-                let s = s.trim_start_matches("0x");
-                alloy::primitives::hex::decode(s).ok()
-            })
-            .map(Bytes::from)
-            .unwrap_or_default();
-        let output = result_field["output"]
+            .unwrap_or(Address::ZERO)
+    };
+    let hex_bytes = |field: &serde_json::Value| {
+        field
             .as_str()
             .and_then(|s| {
                 let s = s.trim_start_matches("0x");
                 alloy::primitives::hex::decode(s).ok()
             })
             .map(Bytes::from)
-            .unwrap_or_default();
-
-        let trace_addr = trace["traceAddress"].as_array();
-        let depth = trace_addr.map(|a| a.len()).unwrap_or(0);
-        let error = trace["error"].as_str().map(|s| s.to_string());
-
-        calls.push(InternalCall {
-            from,
-            to,
-            value,
-            call_type,
-            gas_used,
-            input,
-            output,
-            depth,
-            error,
-        });
-    }
+            .unwrap_or_default()
+    };
 
-    Ok(calls)
+    // Creations and self-destructs name their fields differently from calls
+    let (call_type, from, to, value, input, output) = match trace["type"].as_str() {
+        Some("reward") => return None,
+        Some("create") => (
+            action["creationMethod"]
+                .as_str()
+                .unwrap_or("create")
+                .to_uppercase(),
+            address(&action["from"]),
+            address(&result_field["address"]),
+            &action["value"],
+            hex_bytes(&action["init"]),
+            hex_bytes(&result_field["code"]),
+        ),
+        Some("suicide") => (
+            "SELFDESTRUCT".to_string(),
+            address(&action["address"]),
+            address(&action["refundAddress"]),
+            &action["balance"],
+            Bytes::new(),
+            Bytes::new(),
+        ),
+        _ => (
+            action["callType"]
+                .as_str()
+                .unwrap_or("call")
+                .to_uppercase(),
+            address(&action["from"]),
+            address(&action["to"]),
+            &action["value"],
+            hex_bytes(&action["input"]),
+            hex_bytes(&result_field["output"]),
+        ),
+    };
+    let value = value
+        .as_str()
+        .and_then(|s| U256::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+        .unwrap_or(U256::ZERO);
+    let gas_used = result_field["gasUsed"]
+        .as_str()
+        .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+        .unwrap_or(0);
+
+    let trace_addr = trace["traceAddress"].as_array();
+    let depth = trace_addr.map(|a| a.len()).unwrap_or(0);
+    let error = trace["error"].as_str().map(|s| s.to_string());
+
+    Some(InternalCall {
+        from,
+        to,
+        value,
+        call_type,
+        gas_used,
+        input,
+        output,
+        depth,
+        error,
+        decoded: None,
+        decoded_output: Vec::new(),
+        revert_reason: None,
+    })
 }
 
 /// Fetch internal calls using Geth-style debug_traceTransaction with callTracer.
@@ -859,6 +1335,9 @@ fn parse_call_frame(frame: &serde_json::Value, depth: usize, calls: &mut Vec<Int
         output,
         depth,
         error,
+        decoded: None,
+        decoded_output: Vec::new(),
+        revert_reason: None,
     });
 
     // Recurse into child calls
@@ -869,8 +1348,311 @@ fn parse_call_frame(frame: &serde_json::Value, depth: usize, calls: &mut Vec<Int
     }
 }
 
+/// Address whose code runs in a transaction's top-level frame: the
+/// recipient, or the new contract's init code for deployments. The flag
+/// is set for deployments.
+async fn trace_root(provider: &EthProvider, tx_hash: B256) -> (Address, bool) {
+    match provider.get_transaction(tx_hash).await {
+        Ok(Some(t)) => match t.inner.to() {
+            Some(to) => (to, false),
+            None => {
+                let created = provider
+                    .get_transaction_receipt(tx_hash)
+                    .await
+                    .ok()
+                    .flatten()
+                    .and_then(|r| r.contract_address)
+                    .unwrap_or(Address::ZERO);
+                (created, true)
+            }
+        },
+        _ => (Address::ZERO, false),
+    }
+}
+
+/// Decode each internal call's input and output against its callee's ABI,
+/// falling back to a selector lookup for the function name.
+async fn decode_internal_calls(
+    abi_resolver: &AbiResolver,
+    chain_id: u64,
+    calls: &mut [InternalCall],
+) {
+    let mut abis: std::collections::HashMap<Address, Option<abi::ResolvedAbi>> =
+        std::collections::HashMap::new();
+
+    for call in calls.iter_mut() {
+        let selector = TxDecoder::extract_selector(&call.input);
+        if selector.is_none() && call.error.is_none() {
+            continue;
+        }
+
+        let resolved = match abis.get(&call.to) {
+            Some(cached) => cached.clone(),
+            None => {
+                let resolved = abi_resolver.resolve(chain_id, call.to).await;
+                abis.insert(call.to, resolved.clone());
+                resolved
+            }
+        };
+        if let Some(ref resolved) = resolved {
+            call.decoded = TxDecoder::decode_input(&resolved.abi, &call.input);
+            if call.decoded.is_some() && call.error.is_none() {
+                call.decoded_output =
+                    TxDecoder::decode_output(&resolved.abi, &call.input, &call.output)
+                        .unwrap_or_default();
+            }
+        }
+        if call.decoded.is_none()
+            && let Some(abi) = signatures::global().abi_for_call(&call.input)
+        {
+            call.decoded = TxDecoder::decode_input(&abi, &call.input);
+            if call.decoded.is_some() && call.error.is_none() {
+                call.decoded_output =
+                    TxDecoder::decode_output(&abi, &call.input, &call.output)
+                        .unwrap_or_default();
+            }
+        }
+
+        if call.error.is_some() {
+            let abi = resolved.as_ref().map(|r| &r.abi);
+            call.revert_reason = decode_revert_reason(abi_resolver, abi, &call.output).await;
+        }
+
+        let Some(selector) = selector else {
+            continue;
+        };
+        if call.decoded.is_none() {
+            let name = match abi_resolver.match_builtin_selector(selector) {
+                Some(name) => Some(name),
+                None => abi_resolver
+                    .resolve_selector(selector)
+                    .await
+                    .map(|sig| sig.split('(').next().unwrap_or(&sig).to_string()),
+            };
+            call.decoded = name.map(|function_name| DecodedCall {
+                function_name,
+                params: vec![],
+                inner: vec![],
+            });
+        }
+    }
+}
+
+/// How many levels of calls inside calls are decoded.
+const MAX_NESTED_DEPTH: usize = 4;
+
+/// Find the calls carried in `input` (a call to `to` declared by `abi`) and
+/// decode each against its target's ABI or the signature database, then
+/// descend into those in turn.
+fn decode_nested_calls<'a>(
+    abi_resolver: &'a AbiResolver,
+    chain_id: u64,
+    to: Address,
+    abi: &'a alloy::json_abi::JsonAbi,
+    input: &'a [u8],
+    depth: usize,
+) -> futures::future::BoxFuture<'a, Vec<InnerCall>> {
+    Box::pin(async move {
+        let mut calls = nested::inner_calls(abi, to, input);
+        for call in calls.iter_mut().filter(|call| call.decoded.is_none()) {
+            let mut call_abi = abi_resolver
+                .resolve(chain_id, call.target)
+                .await
+                .map(|resolved| resolved.abi);
+            call.decoded = call_abi
+                .as_ref()
+                .and_then(|abi| TxDecoder::decode_input(abi, &call.input));
+            if call.decoded.is_none() {
+                call.decoded = signatures::global().decode_call(&call.input);
+                call_abi = signatures::global().abi_for_call(&call.input);
+            }
+
+            if depth + 1 < MAX_NESTED_DEPTH
+                && let (Some(decoded), Some(call_abi)) = (call.decoded.as_mut(), &call_abi)
+            {
+                decoded.inner = decode_nested_calls(
+                    abi_resolver,
+                    chain_id,
+                    call.target,
+                    call_abi,
+                    &call.input,
+                    depth + 1,
+                )
+                .await;
+            }
+        }
+        calls
+    })
+}
+
+/// Decode revert data against the reverting contract's ABI, falling back to
+/// the signature database and then a selector lookup for custom errors the
+/// ABI does not declare.
+async fn decode_revert_reason(
+    abi_resolver: &AbiResolver,
+    abi: Option<&alloy::json_abi::JsonAbi>,
+    output: &Bytes,
+) -> Option<String> {
+    if let Some(reason) = TxDecoder::decode_revert(output, abi) {
+        return Some(reason);
+    }
+    if let Some(reason) = signatures::global().decode_error(output) {
+        return Some(reason);
+    }
+    let selector = TxDecoder::extract_selector(output)?;
+    let signature = abi_resolver.resolve_selector(selector).await?;
+    TxDecoder::decode_error_signature(&signature, output)
+        .or_else(|| Some(format!("custom error {}", crate::utils::format_selector(&selector))))
+}
+
+/// Decode logs against their emitters' ABIs, then the signature database,
+/// then by fitting a looked-up event signature to the log's topics; logs that
+/// do not decode are kept with their topic0 as the name.
+async fn decode_emitted_logs(
+    abi_resolver: &AbiResolver,
+    chain_id: u64,
+    logs: &[alloy::rpc::types::Log],
+) -> Vec<DecodedLog> {
+    let mut abis: std::collections::HashMap<Address, Option<alloy::json_abi::JsonAbi>> =
+        std::collections::HashMap::new();
+    let mut decoded = Vec::with_capacity(logs.len());
+
+    for log in logs {
+        let address = log.inner.address;
+        let abi = match abis.get(&address) {
+            Some(cached) => cached.clone(),
+            None => {
+                let abi = abi_resolver.resolve(chain_id, address).await.map(|r| r.abi);
+                abis.insert(address, abi.clone());
+                abi
+            }
+        };
+        let topic0 = log.inner.data.topics().first().copied().unwrap_or_default();
+        let from_abi =
+            abi.and_then(|abi| TxDecoder::decode_logs(&abi, std::slice::from_ref(log)).pop());
+        // An ABI match with no decoded params is usually a fallback ABI
+        // guessing at a same-topic event with different indexing
+        let entry = match from_abi {
+            Some(entry) if !entry.params.is_empty() => Some(entry),
+            from_abi => match signatures::global().decode_log(log) {
+                Some(entry) => Some(entry),
+                None => abi_resolver
+                    .resolve_event_signature(topic0)
+                    .await
+                    .and_then(|signature| TxDecoder::decode_log_heuristic(&signature, log))
+                    .or(from_abi),
+            },
+        };
+        decoded.push(entry.unwrap_or_else(|| DecodedLog {
+            address,
+            event_name: crate::utils::truncate_hash(&topic0),
+            params: Vec::new(),
+            topic0,
+            heuristic: false,
+        }));
+    }
+
+    decoded
+}
+
+/// Revert data carried by a failed `eth_call`, or None for other errors.
+fn revert_data(error: &color_eyre::eyre::Report) -> Option<Bytes> {
+    let payload = error
+        .downcast_ref::<alloy::transports::TransportError>()?
+        .as_error_resp()?;
+    payload
+        .as_revert_data()
+        .or_else(|| payload.message.contains("revert").then(Bytes::new))
+}
+
 // --- Token metadata helpers ---
 
+/// Look up the tokens moved by `transfers` and send their metadata to the
+/// views showing them.
+async fn send_token_metadata(
+    provider: &EthProvider,
+    cache: &RwLock<DataCache>,
+    tx: &mpsc::UnboundedSender<AppEvent>,
+    transfers: &[TokenTransfer],
+) {
+    let mut tokens: Vec<Address> = transfers.iter().map(|t| t.token_address).collect();
+    tokens.sort();
+    tokens.dedup();
+    let metadata = load_token_metadata(provider, cache, &tokens).await;
+    if !metadata.is_empty() {
+        let _ = tx.send(AppEvent::TokenMetadataLoaded(metadata));
+    }
+}
+
+/// Name, symbol and decimals of each token: cached ones from the cache, the
+/// rest in one Multicall3 batch (or individual calls where Multicall3 is not
+/// deployed). Tokens answering none of the three calls are left out.
+async fn load_token_metadata(
+    provider: &EthProvider,
+    cache: &RwLock<DataCache>,
+    tokens: &[Address],
+) -> Vec<TokenMetadata> {
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+    {
+        let mut c = cache.write().await;
+        for &token in tokens {
+            match c.get_token_metadata(token) {
+                Some(metadata) => found.push(metadata),
+                None => missing.push(token),
+            }
+        }
+    }
+    if missing.is_empty() {
+        return found;
+    }
+
+    let name_data = Bytes::from(IERC20Metadata::nameCall {}.abi_encode());
+    let symbol_data = Bytes::from(IERC20Metadata::symbolCall {}.abi_encode());
+    let decimals_data = Bytes::from(IERC20Metadata::decimalsCall {}.abi_encode());
+    let calls: Vec<(Address, Bytes)> = missing
+        .iter()
+        .flat_map(|&token| {
+            [
+                (token, name_data.clone()),
+                (token, symbol_data.clone()),
+                (token, decimals_data.clone()),
+            ]
+        })
+        .collect();
+
+    // Try multicall first, fall back to individual calls
+    let results = match provider.multicall(calls.clone()).await {
+        Ok(results) if results.len() == calls.len() => results,
+        _ => {
+            let mut results = Vec::with_capacity(calls.len());
+            for (target, data) in calls {
+                results.push(provider.call(target, data).await.unwrap_or_default());
+            }
+            results
+        }
+    };
+
+    let mut c = cache.write().await;
+    for (&address, answers) in missing.iter().zip(results.chunks(3)) {
+        let name = decode_string_result(&answers[0]);
+        let symbol = decode_string_result(&answers[1]);
+        let decimals = decode_u8_result(&answers[2]);
+        if name.is_none() && symbol.is_none() && decimals.is_none() {
+            continue;
+        }
+        let metadata = TokenMetadata {
+            address,
+            name: name.unwrap_or_else(|| "Unknown".to_string()),
+            symbol: symbol.unwrap_or_else(|| "???".to_string()),
+            decimals: decimals.unwrap_or(18),
+        };
+        c.put_token_metadata(address, metadata.clone());
+        found.push(metadata);
+    }
+    found
+}
+
 fn decode_string_result(data: &[u8]) -> Option<String> {
     if data.len() < 64 {
         return None;
@@ -895,99 +1677,6 @@ fn decode_u8_result(data: &[u8]) -> Option<u8> {
     Some(data[31])
 }
 
-// --- Etherscan tx history ---
-
-async fn fetch_etherscan_tx_history(
-    address: Address,
-    api_key: &str,
-) -> Vec<TransactionSummary> {
-    let url = format!(
-        "https://api.etherscan.io/api?module=account&action=txlist&address={address}&startblock=0&endblock=99999999&page=1&offset=20&sort=desc&apikey={api_key}"
-    );
-
-    let client = reqwest::Client::new();
-    let resp = match client.get(&url).send().await {
-        Ok(r) => r,
-        Err(_) => return vec![],
-    };
-
-    let body: serde_json::Value = match resp.json().await {
-        Ok(v) => v,
-        Err(_) => return vec![],
-    };
-
-    let results = match body["result"].as_array() {
-        Some(arr) => arr,
-        None => return vec![],
-    };
-
-    results
-        .iter()
-        .filter_map(|item| {
-            let hash = item["hash"]
-                .as_str()
-                .and_then(|s| s.parse::<B256>().ok())?;
-            let block_number = item["blockNumber"]
-                .as_str()
-                .and_then(|s| s.parse::<u64>().ok());
-            let timestamp = item["timeStamp"]
-                .as_str()
-                .and_then(|s| s.parse::<u64>().ok())
-                .unwrap_or(0);
-            let from = item["from"]
-                .as_str()
-                .and_then(|s| s.parse::<Address>().ok())
-                .unwrap_or(Address::ZERO);
-            let to = item["to"]
-                .as_str()
-                .and_then(|s| s.parse::<Address>().ok());
-            let value = item["value"]
-                .as_str()
-                .and_then(|s| s.parse::<U256>().ok())
-                .unwrap_or(U256::ZERO);
-            let gas_used = item["gasUsed"]
-                .as_str()
-                .and_then(|s| s.parse::<u64>().ok());
-            let gas_price = item["gasPrice"]
-                .as_str()
-                .and_then(|s| s.parse::<u128>().ok());
-            let is_error = item["isError"].as_str().unwrap_or("0") == "1";
-
-            let input_str = item["input"].as_str().unwrap_or("0x");
-            let method_id = if input_str.len() >= 10 {
-                let hex = input_str.trim_start_matches("0x");
-                alloy::primitives::hex::decode(&hex[..8])
-                    .ok()
-                    .and_then(|b| {
-                        let arr: [u8; 4] = b.try_into().ok()?;
-                        Some(arr)
-                    })
-            } else {
-                None
-            };
-
-            Some(TransactionSummary {
-                hash,
-                block_number,
-                timestamp,
-                from,
-                to,
-                value,
-                gas_used,
-                gas_price,
-                method_id,
-                method_name: None,
-                tx_type: TxType::EIP1559,
-                status: if is_error {
-                    TxStatus::Failed
-                } else {
-                    TxStatus::Success
-                },
-            })
-        })
-        .collect()
-}
-
 // --- Conversion helpers ---
 
 /// Convert an alloy `Block` to our `BlockSummary`.
@@ -1079,8 +1768,17 @@ pub(crate) fn tx_to_summary(
         gas_used,
         gas_price: tx.inner.gas_price(),
         method_id,
-        method_name: None,
+        method_name: method_id.and_then(|sel| signatures::global().function_name(sel)),
         tx_type,
         status,
     }
 }
+
+/// A fresh scratch directory for one test.
+#[cfg(test)]
+pub(crate) fn scratch(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("eth-tui-test-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,388 @@
+use alloy::dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy::json_abi::{Function, JsonAbi};
+use alloy::primitives::{Address, Bytes, U256};
+
+use crate::data::decoder::TxDecoder;
+use crate::data::types::{DecodedCall, DecodedParam, InnerCall};
+
+/// Selector of Safe's `multiSend(bytes)`, whose argument packs transactions.
+const MULTI_SEND_SELECTOR: [u8; 4] = [0x8d, 0x80, 0xff, 0x0a];
+
+/// Flag on a Universal Router command byte that lets the command revert.
+const ALLOW_REVERT_FLAG: u8 = 0x80;
+
+/// Mask of the command type in a Universal Router command byte.
+const COMMAND_TYPE_MASK: u8 = 0x3f;
+
+/// Universal Router commands with the parameters their inputs encode.
+const ROUTER_COMMANDS: &[(u8, &str, &str)] = &[
+    (
+        0x00,
+        "V3_SWAP_EXACT_IN",
+        "address recipient, uint256 amountIn, uint256 amountOutMin, bytes path, bool payerIsUser",
+    ),
+    (
+        0x01,
+        "V3_SWAP_EXACT_OUT",
+        "address recipient, uint256 amountOut, uint256 amountInMax, bytes path, bool payerIsUser",
+    ),
+    (
+        0x02,
+        "PERMIT2_TRANSFER_FROM",
+        "address token, address recipient, uint160 amount",
+    ),
+    (
+        0x03,
+        "PERMIT2_PERMIT_BATCH",
+        "((address,uint160,uint48,uint48)[],address,uint256) permitBatch, bytes signature",
+    ),
+    (
+        0x04,
+        "SWEEP",
+        "address token, address recipient, uint256 amountMin",
+    ),
+    (
+        0x05,
+        "TRANSFER",
+        "address token, address recipient, uint256 value",
+    ),
+    (
+        0x06,
+        "PAY_PORTION",
+        "address token, address recipient, uint256 bips",
+    ),
+    (
+        0x08,
+        "V2_SWAP_EXACT_IN",
+        "address recipient, uint256 amountIn, uint256 amountOutMin, address[] path, bool payerIsUser",
+    ),
+    (
+        0x09,
+        "V2_SWAP_EXACT_OUT",
+        "address recipient, uint256 amountOut, uint256 amountInMax, address[] path, bool payerIsUser",
+    ),
+    (
+        0x0a,
+        "PERMIT2_PERMIT",
+        "((address,uint160,uint48,uint48),address,uint256) permitSingle, bytes signature",
+    ),
+    (0x0b, "WRAP_ETH", "address recipient, uint256 amountMin"),
+    (0x0c, "UNWRAP_WETH", "address recipient, uint256 amountMin"),
+    (
+        0x0d,
+        "PERMIT2_TRANSFER_FROM_BATCH",
+        "(address,address,uint160,address)[] batchDetails",
+    ),
+    (
+        0x0e,
+        "BALANCE_CHECK_ERC20",
+        "address owner, address token, uint256 minBalance",
+    ),
+];
+
+/// Calls carried in the arguments of `input`, a call to `to` that `abi`
+/// declares. Batches (`multicall`, Multicall3, Safe `multiSend`) and wrapped
+/// calls (Safe `execTransaction`, account `execute`) yield their calls
+/// undecoded; Universal Router commands, which are not calls, are decoded
+/// from the command table.
+pub fn inner_calls(abi: &JsonAbi, to: Address, input: &[u8]) -> Vec<InnerCall> {
+    let Some(selector) = input.get(..4) else {
+        return Vec::new();
+    };
+    let Some(function) = abi.functions().find(|f| f.selector() == selector) else {
+        return Vec::new();
+    };
+    let Ok(values) = function.abi_decode_input(&input[4..], false) else {
+        return Vec::new();
+    };
+
+    match values.first() {
+        Some(DynSolValue::Bytes(packed)) if selector == MULTI_SEND_SELECTOR => {
+            return unpack_multi_send(packed);
+        }
+        _ => {}
+    }
+    if let Some(commands) = router_commands(function, &values, to) {
+        return commands;
+    }
+
+    let mut calls = Vec::new();
+    collect_calls(&values, to, &mut calls);
+    calls
+}
+
+/// Find calldata among sibling values (function arguments or tuple fields).
+/// A call goes to the first sibling address, with the last `uint256` before
+/// it as its value; without an address it goes to `target`.
+fn collect_calls(values: &[DynSolValue], target: Address, calls: &mut Vec<InnerCall>) {
+    let address = values.iter().find_map(|v| v.as_address());
+    let mut value = U256::ZERO;
+
+    for v in values {
+        match v {
+            DynSolValue::Uint(amount, 256) if address.is_some() => value = *amount,
+            DynSolValue::Bytes(data) if looks_like_calldata(data) => calls.push(InnerCall {
+                target: address.unwrap_or(target),
+                value: if address.is_some() { value } else { U256::ZERO },
+                input: Bytes::copy_from_slice(data),
+                decoded: None,
+            }),
+            DynSolValue::Tuple(fields) => collect_calls(fields, address.unwrap_or(target), calls),
+            DynSolValue::Array(items) | DynSolValue::FixedArray(items) => {
+                for item in items {
+                    match item {
+                        DynSolValue::Tuple(fields) => collect_calls(fields, target, calls),
+                        other => collect_calls(
+                            std::slice::from_ref(other),
+                            address.unwrap_or(target),
+                            calls,
+                        ),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A selector followed by whole ABI words.
+fn looks_like_calldata(data: &[u8]) -> bool {
+    data.len() >= 4 && (data.len() - 4).is_multiple_of(32)
+}
+
+/// Unpack Safe MultiSend transactions, each encoded as
+/// `operation (1) | to (20) | value (32) | data length (32) | data`.
+fn unpack_multi_send(packed: &[u8]) -> Vec<InnerCall> {
+    let mut calls = Vec::new();
+    let mut rest = packed;
+    while rest.len() >= 85 {
+        let to = Address::from_slice(&rest[1..21]);
+        let value = U256::from_be_slice(&rest[21..53]);
+        let Ok(len) = usize::try_from(U256::from_be_slice(&rest[53..85])) else {
+            break;
+        };
+        let Some(end) = 85usize.checked_add(len) else {
+            break;
+        };
+        let Some(data) = rest.get(85..end) else {
+            break;
+        };
+        calls.push(InnerCall {
+            target: to,
+            value,
+            input: Bytes::copy_from_slice(data),
+            decoded: None,
+        });
+        rest = &rest[end..];
+    }
+    calls
+}
+
+/// Decode Universal Router `execute(bytes commands, bytes[] inputs, ...)`
+/// into one entry per command, or None for any other function.
+fn router_commands(
+    function: &Function,
+    values: &[DynSolValue],
+    router: Address,
+) -> Option<Vec<InnerCall>> {
+    if function.name != "execute" {
+        return None;
+    }
+    let [DynSolValue::Bytes(commands), DynSolValue::Array(inputs), ..] = values else {
+        return None;
+    };
+    if commands.len() != inputs.len() {
+        return None;
+    }
+
+    let calls = commands
+        .iter()
+        .zip(inputs)
+        .map(|(&command, input)| {
+            let input = input.as_bytes().unwrap_or_default();
+            let code = command & COMMAND_TYPE_MASK;
+            let mut decoded = match ROUTER_COMMANDS.iter().find(|(c, _, _)| *c == code) {
+                Some((_, name, params)) => decode_command(name, params, input),
+                None => DecodedCall {
+                    function_name: format!("COMMAND_{code:#04x}"),
+                    params: Vec::new(),
+                    inner: Vec::new(),
+                },
+            };
+            if command & ALLOW_REVERT_FLAG != 0 {
+                decoded.params.push(DecodedParam::new(
+                    "allowRevert",
+                    "bool",
+                    DynSolValue::Bool(true),
+                ));
+            }
+            InnerCall {
+                target: router,
+                value: U256::ZERO,
+                input: Bytes::copy_from_slice(input),
+                decoded: Some(decoded),
+            }
+        })
+        .collect();
+    Some(calls)
+}
+
+/// Decode a command's ABI-encoded input as if it were a call's arguments.
+fn decode_command(name: &str, params: &str, input: &[u8]) -> DecodedCall {
+    let decoded = Function::parse(&format!("function {name}({params})"))
+        .ok()
+        .and_then(|function| {
+            let mut calldata = function.selector().to_vec();
+            calldata.extend_from_slice(input);
+            let abi: JsonAbi = std::iter::once(function.into()).collect();
+            TxDecoder::decode_input(&abi, &Bytes::from(calldata))
+        });
+    decoded.unwrap_or_else(|| DecodedCall {
+        function_name: name.to_string(),
+        params: Vec::new(),
+        inner: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::dyn_abi::DynSolValue;
+
+    fn encode_call(declaration: &str, args: &[DynSolValue]) -> (JsonAbi, Vec<u8>) {
+        let function = Function::parse(declaration).unwrap();
+        let input = function.abi_encode_input(args).unwrap();
+        (std::iter::once(function.into()).collect(), input)
+    }
+
+    fn transfer_calldata(amount: u64) -> Vec<u8> {
+        let (_, input) = encode_call(
+            "function transfer(address to, uint256 amount)",
+            &[
+                DynSolValue::Address(Address::repeat_byte(9)),
+                DynSolValue::Uint(U256::from(amount), 256),
+            ],
+        );
+        input
+    }
+
+    #[test]
+    fn test_self_multicall_targets_outer_contract() {
+        let outer = Address::repeat_byte(1);
+        let (abi, input) = encode_call(
+            "function multicall(uint256 deadline, bytes[] data)",
+            &[
+                DynSolValue::Uint(U256::from(77), 256),
+                DynSolValue::Array(vec![
+                    DynSolValue::Bytes(transfer_calldata(1)),
+                    DynSolValue::Bytes(transfer_calldata(2)),
+                ]),
+            ],
+        );
+        let calls = inner_calls(&abi, outer, &input);
+        assert_eq!(calls.len(), 2);
+        assert!(calls.iter().all(|c| c.target == outer && c.value.is_zero()));
+        assert_eq!(calls[1].input.as_ref(), transfer_calldata(2).as_slice());
+    }
+
+    #[test]
+    fn test_aggregate3_value_uses_tuple_fields() {
+        let token = Address::repeat_byte(2);
+        let (abi, input) = encode_call(
+            "function aggregate3Value((address,bool,uint256,bytes)[] calls)",
+            &[DynSolValue::Array(vec![DynSolValue::Tuple(vec![
+                DynSolValue::Address(token),
+                DynSolValue::Bool(false),
+                DynSolValue::Uint(U256::from(5), 256),
+                DynSolValue::Bytes(transfer_calldata(3)),
+            ])])],
+        );
+        let calls = inner_calls(&abi, Address::repeat_byte(1), &input);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].target, token);
+        assert_eq!(calls[0].value, U256::from(5));
+    }
+
+    #[test]
+    fn test_wrapped_call_and_non_calldata_bytes() {
+        let safe_target = Address::repeat_byte(3);
+        let (abi, input) = encode_call(
+            "function execTransaction(address to, uint256 value, bytes data, uint8 operation, bytes signatures)",
+            &[
+                DynSolValue::Address(safe_target),
+                DynSolValue::Uint(U256::from(10), 256),
+                DynSolValue::Bytes(transfer_calldata(4)),
+                DynSolValue::Uint(U256::ZERO, 8),
+                // 65-byte signature is not calldata
+                DynSolValue::Bytes(vec![0xab; 65]),
+            ],
+        );
+        let calls = inner_calls(&abi, Address::repeat_byte(1), &input);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].target, safe_target);
+        assert_eq!(calls[0].value, U256::from(10));
+    }
+
+    #[test]
+    fn test_unpack_multi_send() {
+        let data = transfer_calldata(6);
+        let mut packed = vec![0u8];
+        packed.extend_from_slice(Address::repeat_byte(4).as_slice());
+        packed.extend_from_slice(&U256::from(8).to_be_bytes::<32>());
+        packed.extend_from_slice(&U256::from(data.len()).to_be_bytes::<32>());
+        packed.extend_from_slice(&data);
+        // Second transaction with no data
+        packed.push(1);
+        packed.extend_from_slice(Address::repeat_byte(5).as_slice());
+        packed.extend_from_slice(&[0; 64]);
+
+        let (abi, input) = encode_call(
+            "function multiSend(bytes transactions)",
+            &[DynSolValue::Bytes(packed)],
+        );
+        let calls = inner_calls(&abi, Address::repeat_byte(1), &input);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].target, Address::repeat_byte(4));
+        assert_eq!(calls[0].value, U256::from(8));
+        assert_eq!(calls[0].input.as_ref(), data.as_slice());
+        assert!(calls[1].input.is_empty());
+    }
+
+    #[test]
+    fn test_unpack_multi_send_overflowing_length() {
+        let mut packed = vec![0u8];
+        packed.extend_from_slice(Address::repeat_byte(4).as_slice());
+        packed.extend_from_slice(&[0; 32]);
+        packed.extend_from_slice(&U256::from(usize::MAX).to_be_bytes::<32>());
+        assert!(unpack_multi_send(&packed).is_empty());
+    }
+
+    #[test]
+    fn test_router_commands_are_decoded() {
+        let router = Address::repeat_byte(6);
+        let wrap = DynSolValue::Tuple(vec![
+            DynSolValue::Address(Address::repeat_byte(7)),
+            DynSolValue::Uint(U256::from(100), 256),
+        ])
+        .abi_encode_params();
+        let (abi, input) = encode_call(
+            "function execute(bytes commands, bytes[] inputs, uint256 deadline)",
+            &[
+                DynSolValue::Bytes(vec![0x0b, 0x80 | 0x3f]),
+                DynSolValue::Array(vec![DynSolValue::Bytes(wrap), DynSolValue::Bytes(vec![])]),
+                DynSolValue::Uint(U256::from(1), 256),
+            ],
+        );
+        let calls = inner_calls(&abi, router, &input);
+        assert_eq!(calls.len(), 2);
+        let wrap = calls[0].decoded.as_ref().unwrap();
+        assert_eq!(wrap.function_name, "WRAP_ETH");
+        assert_eq!(wrap.params[1].name(), "amountMin");
+        assert_eq!(wrap.params[1].formatted(), "100");
+        let unknown = calls[1].decoded.as_ref().unwrap();
+        assert_eq!(unknown.function_name, "COMMAND_0x3f");
+        assert_eq!(unknown.params.len(), 1);
+        assert_eq!(unknown.params[0].name(), "allowRevert");
+        assert_eq!(unknown.params[0].value, Some(DynSolValue::Bool(true)));
+    }
+}
@@ -1,7 +1,10 @@
 use alloy::eips::BlockId;
 use alloy::primitives::{Address, Bytes, B256, U256};
 use alloy::providers::{Provider, ProviderBuilder};
-use alloy::rpc::types::{Block, BlockNumberOrTag, TransactionReceipt};
+use alloy::rpc::types::state::StateOverride;
+use alloy::rpc::types::{
+    Block, BlockNumberOrTag, BlockOverrides, TransactionReceipt, TransactionRequest,
+};
 use alloy::sol;
 use alloy::sol_types::SolCall;
 use color_eyre::eyre::Result;
@@ -158,6 +161,61 @@ impl EthProvider {
         Ok(value)
     }
 
+    /// Get the ETH balance of an address at the end of a given block.
+    pub async fn get_balance_at_block(&self, address: Address, block: u64) -> Result<U256> {
+        let balance = self
+            .provider
+            .get_balance(address)
+            .block_id(BlockId::number(block))
+            .await?;
+        Ok(balance)
+    }
+
+    /// Get the nonce of an address at the end of a given block.
+    pub async fn get_nonce_at_block(&self, address: Address, block: u64) -> Result<u64> {
+        let nonce = self
+            .provider
+            .get_transaction_count(address)
+            .block_id(BlockId::number(block))
+            .await?;
+        Ok(nonce)
+    }
+
+    /// Get the code at an address at the end of a given block.
+    pub async fn get_code_at_block(&self, address: Address, block: u64) -> Result<Bytes> {
+        let code = self
+            .provider
+            .get_code_at(address)
+            .block_id(BlockId::number(block))
+            .await?;
+        Ok(code)
+    }
+
+    /// Read a storage slot at the end of a given block.
+    pub async fn get_storage_at_block(
+        &self,
+        address: Address,
+        slot: U256,
+        block: u64,
+    ) -> Result<U256> {
+        let value = self
+            .provider
+            .get_storage_at(address, slot)
+            .block_id(BlockId::number(block))
+            .await?;
+        Ok(value)
+    }
+
+    /// Get the hash of a block by number.
+    pub async fn get_block_hash(&self, number: u64) -> Result<B256> {
+        let block = self
+            .provider
+            .get_block_by_number(BlockNumberOrTag::Number(number))
+            .await?
+            .ok_or_else(|| color_eyre::eyre::eyre!("Block {number} not found"))?;
+        Ok(block.header.hash)
+    }
+
     /// Execute a raw JSON-RPC request (for trace/debug RPCs).
     /// Uses raw_request_dyn which works on trait objects (Box<dyn Provider>).
     pub async fn raw_request(
@@ -184,6 +242,27 @@ impl EthProvider {
         Ok(result)
     }
 
+    /// Execute an eth_call against a given block with optional state and
+    /// block overrides. Revert data is returned as the error's payload.
+    pub async fn call_at(
+        &self,
+        tx: &TransactionRequest,
+        block: BlockNumberOrTag,
+        state_overrides: &StateOverride,
+        block_overrides: Option<&BlockOverrides>,
+    ) -> Result<Bytes> {
+        let mut params = vec![serde_json::to_value(tx)?, serde_json::to_value(block)?];
+        if !state_overrides.is_empty() || block_overrides.is_some() {
+            params.push(serde_json::to_value(state_overrides)?);
+        }
+        if let Some(block_overrides) = block_overrides {
+            params.push(serde_json::to_value(block_overrides)?);
+        }
+        let result = self.raw_request("eth_call", params.into()).await?;
+        let output: Bytes = serde_json::from_value(result)?;
+        Ok(output)
+    }
+
     /// Batch multiple calls via Multicall3.aggregate3.
     /// Each call is (target_address, calldata). Returns the raw return bytes per call.
     pub async fn multicall(&self, calls: Vec<(Address, Bytes)>) -> Result<Vec<Bytes>> {
@@ -0,0 +1,273 @@
+use alloy::primitives::{b256, Address, Bytes, B256, U256};
+use alloy::sol;
+use alloy::sol_types::SolCall;
+
+use crate::data::disasm;
+use crate::data::provider::EthProvider;
+use crate::data::types::{ProxyInfo, ProxyKind};
+
+/// EIP-1967 implementation slot: keccak256("eip1967.proxy.implementation") - 1
+const EIP1967_IMPL_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// EIP-1967 beacon slot: keccak256("eip1967.proxy.beacon") - 1
+const EIP1967_BEACON_SLOT: B256 =
+    b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+
+/// EIP-1967 admin slot: keccak256("eip1967.proxy.admin") - 1
+const EIP1967_ADMIN_SLOT: B256 =
+    b256!("b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103");
+
+/// OpenZeppelin legacy implementation slot: keccak256("org.zeppelinos.proxy.implementation")
+const OZ_IMPL_SLOT: B256 =
+    b256!("7050c9e0f4ca769c69bd3a8ef740bc37934f8e2c036e5a723fd8ee048ed3f8c3");
+
+/// OpenZeppelin legacy admin slot: keccak256("org.zeppelinos.proxy.admin")
+const OZ_ADMIN_SLOT: B256 =
+    b256!("10d6a54a4754c8869d6886b5f5d7fbfa5b4522237ea5c60d11bc4e7a1ff9390b");
+
+/// EIP-1167 runtime code around the 20-byte target.
+const MINIMAL_PROXY_PREFIX: [u8; 10] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
+const MINIMAL_PROXY_SUFFIX: [u8; 15] = [
+    0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91, 0x60, 0x2b, 0x57, 0xfd, 0x5b, 0xf3,
+];
+
+const DELEGATECALL: u8 = 0xf4;
+
+/// `masterCopy()`, which Safe proxies answer from their own code.
+const SAFE_MASTER_COPY_SELECTOR: [u8; 4] = [0xa6, 0x19, 0x48, 0x6e];
+
+sol! {
+    #[derive(Debug)]
+    interface IBeacon {
+        function implementation() external view returns (address);
+    }
+
+    #[derive(Debug)]
+    interface IProxiable {
+        function proxiableUUID() external view returns (bytes32);
+    }
+
+    #[derive(Debug)]
+    interface IDiamondLoupe {
+        struct Facet {
+            address facetAddress;
+            bytes4[] functionSelectors;
+        }
+
+        function facets() external view returns (Facet[] memory);
+    }
+}
+
+/// Work out whether a contract is a proxy and where its calls go, trying
+/// its runtime code, then the well-known storage slots, then the Diamond
+/// loupe. Code that never delegates is no proxy, and costs no RPC calls.
+pub async fn detect(provider: &EthProvider, address: Address, code: &[u8]) -> Option<ProxyInfo> {
+    if code.is_empty() {
+        return None;
+    }
+    if let Some(target) = minimal_proxy_target(code) {
+        return Some(ProxyInfo::new(ProxyKind::MinimalProxy, Some(target)));
+    }
+    if !delegates(code) {
+        return None;
+    }
+
+    let (implementation, beacon, admin, oz_implementation, oz_admin) = tokio::join!(
+        slot_address(provider, address, EIP1967_IMPL_SLOT),
+        slot_address(provider, address, EIP1967_BEACON_SLOT),
+        slot_address(provider, address, EIP1967_ADMIN_SLOT),
+        slot_address(provider, address, OZ_IMPL_SLOT),
+        slot_address(provider, address, OZ_ADMIN_SLOT),
+    );
+
+    if let Some(implementation) = implementation {
+        let kind = if admin.is_some() {
+            ProxyKind::Transparent
+        } else if is_uups(provider, implementation).await {
+            ProxyKind::Uups
+        } else {
+            ProxyKind::Eip1967
+        };
+        let mut info = ProxyInfo::new(kind, Some(implementation));
+        info.admin = admin;
+        return Some(info);
+    }
+
+    if let Some(beacon) = beacon {
+        let implementation = call(provider, beacon, IBeacon::implementationCall {})
+            .await
+            .map(|r| r._0)
+            .filter(|a| !a.is_zero());
+        let mut info = ProxyInfo::new(ProxyKind::Beacon, implementation);
+        info.beacon = Some(beacon);
+        info.admin = admin;
+        return Some(info);
+    }
+
+    if let Some(implementation) = oz_implementation {
+        let mut info = ProxyInfo::new(ProxyKind::OpenZeppelinLegacy, Some(implementation));
+        info.admin = oz_admin;
+        return Some(info);
+    }
+
+    let singleton = match contains(code, &SAFE_MASTER_COPY_SELECTOR) {
+        true => slot_address(provider, address, B256::ZERO).await,
+        false => None,
+    };
+    if let Some(singleton) = singleton {
+        return Some(ProxyInfo::new(ProxyKind::Safe, Some(singleton)));
+    }
+
+    let facets = call(provider, address, IDiamondLoupe::facetsCall {})
+        .await?
+        ._0;
+    let mut info = ProxyInfo::new(ProxyKind::Diamond, None);
+    info.facets = diamond_facets(&facets);
+    (!info.facets.is_empty()).then_some(info)
+}
+
+/// Target of an EIP-1167 minimal proxy, read from its runtime code.
+pub fn minimal_proxy_target(code: &[u8]) -> Option<Address> {
+    let rest = code.strip_prefix(&MINIMAL_PROXY_PREFIX[..])?;
+    if rest.len() != 20 + MINIMAL_PROXY_SUFFIX.len() || !rest.ends_with(&MINIMAL_PROXY_SUFFIX) {
+        return None;
+    }
+    Some(Address::from_slice(&rest[..20]))
+}
+
+/// Whether runtime code has a DELEGATECALL instruction, as every kind of
+/// proxy forwards calls with one. Bytes of push data don't count. The
+/// metadata trailer is left unparsed, so a stray byte in it may count.
+pub fn delegates(code: &[u8]) -> bool {
+    disasm::decode(code).iter().any(|i| i.opcode == DELEGATECALL)
+}
+
+/// The address held in the low 20 bytes of a storage word, if any.
+pub fn word_address(word: U256) -> Option<Address> {
+    let bytes: [u8; 32] = word.to_be_bytes();
+    let address = Address::from_slice(&bytes[12..]);
+    (!address.is_zero()).then_some(address)
+}
+
+/// Facets serving at least one selector, with how many each serves. The
+/// loupe lists removed facets with no selectors on some implementations.
+fn diamond_facets(facets: &[IDiamondLoupe::Facet]) -> Vec<(Address, usize)> {
+    let mut found: Vec<(Address, usize)> = Vec::new();
+    for facet in facets {
+        if facet.facetAddress.is_zero() || facet.functionSelectors.is_empty() {
+            continue;
+        }
+        match found.iter_mut().find(|(a, _)| *a == facet.facetAddress) {
+            Some((_, count)) => *count += facet.functionSelectors.len(),
+            None => found.push((facet.facetAddress, facet.functionSelectors.len())),
+        }
+    }
+    found
+}
+
+/// An EIP-1967 implementation is UUPS if it reports the implementation slot
+/// as its `proxiableUUID`.
+async fn is_uups(provider: &EthProvider, implementation: Address) -> bool {
+    call(provider, implementation, IProxiable::proxiableUUIDCall {})
+        .await
+        .is_some_and(|r| r._0 == EIP1967_IMPL_SLOT)
+}
+
+async fn slot_address(provider: &EthProvider, address: Address, slot: B256) -> Option<Address> {
+    let word = provider
+        .get_storage_at(address, U256::from_be_bytes(slot.0))
+        .await
+        .ok()?;
+    word_address(word)
+}
+
+async fn call<C: SolCall>(provider: &EthProvider, to: Address, call: C) -> Option<C::Return> {
+    let output = provider
+        .call(to, Bytes::from(call.abi_encode()))
+        .await
+        .ok()?;
+    C::abi_decode_returns(&output, true).ok()
+}
+
+fn contains(code: &[u8], needle: &[u8]) -> bool {
+    code.windows(needle.len()).any(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{keccak256, FixedBytes};
+
+    #[test]
+    fn test_slot_constants() {
+        let eip1967 =
+            |label: &str| B256::from(U256::from_be_bytes(keccak256(label).0) - U256::from(1));
+        assert_eq!(EIP1967_IMPL_SLOT, eip1967("eip1967.proxy.implementation"));
+        assert_eq!(EIP1967_BEACON_SLOT, eip1967("eip1967.proxy.beacon"));
+        assert_eq!(EIP1967_ADMIN_SLOT, eip1967("eip1967.proxy.admin"));
+        assert_eq!(
+            OZ_IMPL_SLOT,
+            keccak256("org.zeppelinos.proxy.implementation")
+        );
+        assert_eq!(OZ_ADMIN_SLOT, keccak256("org.zeppelinos.proxy.admin"));
+    }
+
+    #[test]
+    fn test_minimal_proxy_target() {
+        let target = Address::repeat_byte(0xbe);
+        let mut code = MINIMAL_PROXY_PREFIX.to_vec();
+        code.extend_from_slice(target.as_slice());
+        code.extend_from_slice(&MINIMAL_PROXY_SUFFIX);
+        assert_eq!(minimal_proxy_target(&code), Some(target));
+
+        // Anything appended means it isn't a bare clone
+        code.push(0x00);
+        assert_eq!(minimal_proxy_target(&code), None);
+        assert_eq!(minimal_proxy_target(&[0x60, 0x80, 0x60, 0x40]), None);
+    }
+
+    #[test]
+    fn test_delegates() {
+        // CALLDATASIZE PUSH1 0 PUSH1 0 CALLDATACOPY PUSH20 0xf4f4..f4, then GAS DELEGATECALL
+        let mut code = vec![0x36, 0x60, 0x00, 0x60, 0x00, 0x37, 0x73];
+        code.extend_from_slice(Address::repeat_byte(0xf4).as_slice());
+        assert!(!delegates(&code));
+        code.extend_from_slice(&[0x5a, 0xf4]);
+        assert!(delegates(&code));
+    }
+
+    #[test]
+    fn test_word_address() {
+        assert_eq!(word_address(U256::ZERO), None);
+        let address = Address::repeat_byte(0x11);
+        let word = U256::from_be_slice(address.as_slice());
+        assert_eq!(word_address(word), Some(address));
+    }
+
+    #[test]
+    fn test_diamond_facets() {
+        let facet = |byte: u8, selectors: usize| IDiamondLoupe::Facet {
+            facetAddress: Address::repeat_byte(byte),
+            functionSelectors: vec![FixedBytes::<4>::ZERO; selectors],
+        };
+        let facets = [
+            facet(1, 3),
+            facet(2, 0),
+            facet(1, 2),
+            facet(0, 4),
+            facet(3, 1),
+        ];
+        assert_eq!(
+            diamond_facets(&facets),
+            vec![(Address::repeat_byte(1), 5), (Address::repeat_byte(3), 1)]
+        );
+    }
+
+    #[test]
+    fn test_safe_selector_in_code() {
+        let code = [0x7f, 0xa6, 0x19, 0x48, 0x6e, 0x00];
+        assert!(contains(&code, &SAFE_MASTER_COPY_SELECTOR));
+        assert!(!contains(&code[2..], &SAFE_MASTER_COPY_SELECTOR));
+    }
+}
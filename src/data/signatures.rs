@@ -0,0 +1,383 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use alloy::json_abi::{AbiItem, Error, Event, Function, JsonAbi};
+use alloy::primitives::B256;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+
+use crate::data::abi_store;
+use crate::data::decoder::TxDecoder;
+use crate::data::types::{DecodedCall, DecodedLog};
+
+const APP_DIR: &str = "eth-tui";
+const SIGNATURES_FILE: &str = "signatures.txt";
+
+/// Declarations bundled with the binary, in the same format as user imports.
+const BUNDLED: &str = include_str!("../../abis/signatures.txt");
+
+static GLOBAL: OnceLock<SignatureDb> = OnceLock::new();
+
+/// Offline database of function, event and error declarations, indexed by
+/// selector and event topic. Used to name and decode calls, logs and reverts
+/// of contracts without an ABI.
+#[derive(Debug, Default)]
+pub struct SignatureDb {
+    functions: HashMap<[u8; 4], Vec<Function>>,
+    events: HashMap<B256, Vec<Event>>,
+    errors: HashMap<[u8; 4], Vec<Error>>,
+}
+
+/// The bundled declarations plus those imported by the user, loaded once.
+pub fn global() -> &'static SignatureDb {
+    GLOBAL.get_or_init(|| {
+        let mut db = SignatureDb::default();
+        db.add_text(BUNDLED);
+        if let Some(text) = user_path().and_then(|path| fs::read_to_string(path).ok()) {
+            db.add_text(&text);
+        }
+        db
+    })
+}
+
+impl SignatureDb {
+    /// Add declarations, one per line (`function ...`, `event ...`,
+    /// `error ...`, or a bare `name(types)` function signature). Blank lines
+    /// and `#` comments are skipped. Returns the number of new entries.
+    pub fn add_text(&mut self, text: &str) -> usize {
+        text.lines()
+            .filter_map(parse_line)
+            .filter(|item| self.add(item))
+            .count()
+    }
+
+    /// Add a function, event or error unless an identical one is known.
+    pub fn add(&mut self, item: &AbiItem<'_>) -> bool {
+        match item {
+            AbiItem::Function(f) => {
+                let known = self.functions.entry(f.selector().0).or_default();
+                if known.iter().any(|k| k.signature() == f.signature()) {
+                    return false;
+                }
+                known.push(f.clone().into_owned());
+            }
+            AbiItem::Event(e) => {
+                // Events sharing a topic can differ in which inputs are indexed
+                let known = self.events.entry(e.selector()).or_default();
+                let indexed = |e: &Event| e.inputs.iter().map(|i| i.indexed).collect::<Vec<_>>();
+                if known.iter().any(|k| indexed(k) == indexed(e)) {
+                    return false;
+                }
+                known.push(e.clone().into_owned());
+            }
+            AbiItem::Error(e) => {
+                let known = self.errors.entry(e.selector().0).or_default();
+                if known.iter().any(|k| k.signature() == e.signature()) {
+                    return false;
+                }
+                known.push(e.clone().into_owned());
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Text signature of the first function with this selector.
+    pub fn function_signature(&self, selector: [u8; 4]) -> Option<String> {
+        Some(self.functions.get(&selector)?.first()?.signature())
+    }
+
+    /// Declaration of the first function with this selector, with its
+    /// mutability and outputs when it was imported with them.
+    pub fn function(&self, selector: [u8; 4]) -> Option<Function> {
+        self.functions.get(&selector)?.first().cloned()
+    }
+
+    /// Name of the first function with this selector.
+    pub fn function_name(&self, selector: [u8; 4]) -> Option<String> {
+        Some(self.functions.get(&selector)?.first()?.name.clone())
+    }
+
+    /// Declaration of the first event with this topic.
+    pub fn event_declaration(&self, topic: B256) -> Option<String> {
+        Some(self.events.get(&topic)?.first()?.full_signature())
+    }
+
+    /// Text signatures of every function, event and error matching a 4-byte
+    /// selector or a 32-byte event topic.
+    pub fn lookup(&self, selector: &[u8]) -> Vec<String> {
+        let mut found = Vec::new();
+        if let Ok(selector) = <[u8; 4]>::try_from(selector) {
+            let functions = self.functions.get(&selector).into_iter().flatten();
+            found.extend(functions.map(|f| f.full_signature()));
+            let errors = self.errors.get(&selector).into_iter().flatten();
+            found.extend(errors.map(error_declaration));
+        }
+        if selector.len() == 32 {
+            let events = self.events.get(&B256::from_slice(selector)).into_iter().flatten();
+            found.extend(events.map(|e| e.full_signature()));
+        }
+        found
+    }
+
+    /// An ABI holding the known functions for the selector of `input`, for
+    /// decoding calldata and return data.
+    pub fn abi_for_call(&self, input: &[u8]) -> Option<JsonAbi> {
+        let selector: [u8; 4] = input.get(..4)?.try_into().ok()?;
+        let functions = self.functions.get(&selector)?;
+        Some(
+            functions
+                .iter()
+                .map(|f| AbiItem::Function(Cow::Borrowed(f)))
+                .collect(),
+        )
+    }
+
+    /// Decode calldata against the known functions for its selector.
+    pub fn decode_call(&self, input: &alloy::primitives::Bytes) -> Option<DecodedCall> {
+        TxDecoder::decode_input(&self.abi_for_call(input)?, input)
+    }
+
+    /// Decode a custom error from revert data.
+    pub fn decode_error(&self, output: &[u8]) -> Option<String> {
+        let selector: [u8; 4] = output.get(..4)?.try_into().ok()?;
+        let abi: JsonAbi = self
+            .errors
+            .get(&selector)?
+            .iter()
+            .map(|e| AbiItem::Error(Cow::Borrowed(e)))
+            .collect();
+        TxDecoder::decode_revert(output, Some(&abi))
+    }
+
+    /// Decode a log against the known events for its topic whose indexed
+    /// inputs match the log's topics and whose data decodes.
+    pub fn decode_log(&self, log: &alloy::rpc::types::Log) -> Option<DecodedLog> {
+        let topics = log.inner.data.topics();
+        let candidates = self.events.get(topics.first()?)?;
+        candidates
+            .iter()
+            .filter(|event| event.num_topics() == topics.len())
+            .find_map(|event| {
+                let abi: JsonAbi = std::iter::once(AbiItem::Event(Cow::Borrowed(event))).collect();
+                TxDecoder::decode_logs(&abi, std::slice::from_ref(log))
+                    .pop()
+                    .filter(|decoded| event.inputs.is_empty() || !decoded.params.is_empty())
+            })
+    }
+}
+
+/// Import declarations from a text file (one per line) or from a JSON ABI
+/// or compiler artifact into the user's signature list. Returns the number of
+/// declarations that were not already known.
+pub fn import_file(path: &Path) -> Result<usize> {
+    let data =
+        fs::read_to_string(path).wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+    let lines: Vec<String> = if data.trim_start().starts_with(['[', '{']) {
+        let stored = abi_store::load_abi_file(path)?;
+        abi_declarations(&stored.abi)
+    } else {
+        data.lines()
+            .filter(|line| parse_line(line).is_some())
+            .map(|line| line.trim().to_string())
+            .collect()
+    };
+
+    // Only keep what neither the bundled list nor earlier imports know
+    let mut known = SignatureDb::default();
+    known.add_text(BUNDLED);
+    let user = user_path().ok_or_else(|| eyre!("Could not determine config directory"))?;
+    if let Ok(text) = fs::read_to_string(&user) {
+        known.add_text(&text);
+    }
+    let new: Vec<&String> = lines
+        .iter()
+        .filter(|line| parse_line(line).is_some_and(|item| known.add(&item)))
+        .collect();
+    if new.is_empty() {
+        return Ok(0);
+    }
+
+    if let Some(parent) = user.parent() {
+        fs::create_dir_all(parent)
+            .wrap_err_with(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&user)
+        .wrap_err_with(|| format!("Failed to open {}", user.display()))?;
+    for line in &new {
+        writeln!(file, "{line}")?;
+    }
+    Ok(new.len())
+}
+
+/// Declarations of every function, event and error in an ABI.
+fn abi_declarations(abi: &JsonAbi) -> Vec<String> {
+    let functions = abi.functions().map(|f| f.full_signature());
+    let events = abi.events().map(|e| e.full_signature());
+    let errors = abi.errors().map(error_declaration);
+    functions.chain(events).chain(errors).collect()
+}
+
+/// `error Name(type name, ...)`, keeping parameter names.
+fn error_declaration(error: &Error) -> String {
+    let params = error
+        .inputs
+        .iter()
+        .map(|p| match p.name.as_str() {
+            "" => p.selector_type().into_owned(),
+            name => format!("{} {name}", p.selector_type()),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("error {}({params})", error.name)
+}
+
+/// Parse one declaration line; comments, blanks and invalid lines yield None.
+fn parse_line(line: &str) -> Option<AbiItem<'static>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    match AbiItem::parse(line) {
+        Ok(item) => Some(item),
+        // A bare text signature, as listed by 4byte.directory
+        Err(_) => Function::parse(line)
+            .ok()
+            .map(|f| AbiItem::Function(Cow::Owned(f))),
+    }
+}
+
+/// Path of the user's imported signatures.
+fn user_path() -> Option<PathBuf> {
+    let config_dir = dirs::config_dir()?;
+    Some(config_dir.join(APP_DIR).join(SIGNATURES_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{hex, Address, Bytes, LogData, U256};
+
+    fn log(topics: Vec<B256>, data: Vec<u8>) -> alloy::rpc::types::Log {
+        alloy::rpc::types::Log {
+            inner: alloy::primitives::Log {
+                address: Address::repeat_byte(0xaa),
+                data: LogData::new_unchecked(topics, Bytes::from(data)),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn topic(address: Address) -> B256 {
+        address.into_word()
+    }
+
+    fn counts(db: &SignatureDb) -> (usize, usize, usize) {
+        (
+            db.functions.values().map(Vec::len).sum(),
+            db.events.values().map(Vec::len).sum(),
+            db.errors.values().map(Vec::len).sum(),
+        )
+    }
+
+    #[test]
+    fn test_bundled_signatures_parse() {
+        let lines = BUNDLED
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        for line in lines {
+            assert!(parse_line(line).is_some(), "invalid declaration: {line}");
+        }
+        let db = global();
+        let (functions, events, errors) = counts(db);
+        assert!(functions > 100 && events > 40 && errors > 40);
+    }
+
+    #[test]
+    fn test_function_lookup_and_decode() {
+        let db = global();
+        let selector = [0xa9, 0x05, 0x9c, 0xbb];
+        assert_eq!(
+            db.function_signature(selector).as_deref(),
+            Some("transfer(address,uint256)")
+        );
+
+        let mut input = selector.to_vec();
+        input.extend_from_slice(topic(Address::repeat_byte(1)).as_slice());
+        input.extend_from_slice(&U256::from(5).to_be_bytes::<32>());
+        let decoded = db.decode_call(&Bytes::from(input)).unwrap();
+        assert_eq!(decoded.function_name, "transfer");
+        assert_eq!(decoded.params[0].name(), "to");
+        assert_eq!(decoded.params[1].formatted(), "5");
+    }
+
+    #[test]
+    fn test_decode_log_picks_matching_indexed_layout() {
+        let db = global();
+        let transfer = B256::from(hex!(
+            "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        ));
+        let from = topic(Address::repeat_byte(1));
+        let to = topic(Address::repeat_byte(2));
+
+        // ERC-20: value in data
+        let erc20 = log(vec![transfer, from, to], U256::from(7).to_be_bytes::<32>().to_vec());
+        let decoded = db.decode_log(&erc20).unwrap();
+        assert_eq!(decoded.params[2].name(), "value");
+        assert_eq!(decoded.params[2].formatted(), "7");
+
+        // ERC-721: token id as a third topic
+        let token_id = B256::from(U256::from(9));
+        let erc721 = log(vec![transfer, from, to, token_id], Vec::new());
+        let decoded = db.decode_log(&erc721).unwrap();
+        assert_eq!(decoded.params[2].name(), "tokenId");
+        assert_eq!(decoded.params[2].formatted(), "9");
+
+        assert!(db.decode_log(&log(vec![B256::ZERO], Vec::new())).is_none());
+        // No declaration indexes only the sender
+        assert!(db.decode_log(&log(vec![transfer, from], Vec::new())).is_none());
+    }
+
+    #[test]
+    fn test_decode_error() {
+        let db = global();
+        // OwnableUnauthorizedAccount(address)
+        let mut output = hex!("118cdaa7").to_vec();
+        output.extend_from_slice(topic(Address::repeat_byte(3)).as_slice());
+        let reason = db.decode_error(&output).unwrap();
+        assert!(reason.starts_with("OwnableUnauthorizedAccount(account: 0x0303"));
+    }
+
+    #[test]
+    fn test_add_text_skips_duplicates_and_accepts_bare_signatures() {
+        let mut db = SignatureDb::default();
+        let text = "# comment\n\nfoo(uint256)\nfunction foo(uint256 x)\nevent Bar(address indexed a)\nnot a signature\n";
+        assert_eq!(db.add_text(text), 2);
+        assert_eq!(counts(&db), (1, 1, 0));
+        let selector = alloy::json_abi::Function::parse("foo(uint256)").unwrap().selector();
+        assert_eq!(db.lookup(selector.as_slice()).len(), 1);
+    }
+
+    #[test]
+    fn test_abi_declarations_roundtrip() {
+        let abi = JsonAbi::parse([
+            "function swap(uint256 amount, (address,bytes) route) returns (bool ok)",
+            "event Swapped(address indexed user, uint256 amount)",
+            "error TooLittle(uint256 got, uint256)",
+        ])
+        .unwrap();
+        let lines = abi_declarations(&abi);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2], "error TooLittle(uint256 got, uint256)");
+
+        let mut db = SignatureDb::default();
+        assert_eq!(db.add_text(&lines.join("\n")), 3);
+    }
+}
@@ -0,0 +1,416 @@
+use std::str::FromStr;
+
+use alloy::dyn_abi::{DynSolType, DynSolValue, JsonAbiExt, Specifier};
+use alloy::json_abi::{Function, JsonAbi};
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{Address, Bytes, B256, U256};
+use alloy::rpc::types::state::{AccountOverride, StateOverride};
+use alloy::rpc::types::{BlockNumberOrTag, BlockOverrides, Log, TransactionRequest};
+use color_eyre::eyre::Result;
+use serde_json::Value;
+
+use crate::data::provider::EthProvider;
+use crate::data::trace::{parse_hex_bytes, parse_u64};
+use crate::data::types::SimulationRequest;
+
+/// Trace a call with Geth's `callTracer` (including logs) via `debug_traceCall`.
+pub async fn fetch_trace_call(
+    provider: &EthProvider,
+    tx: &TransactionRequest,
+    request: &SimulationRequest,
+) -> Result<Value> {
+    let mut options = serde_json::json!({
+        "tracer": "callTracer",
+        "tracerConfig": {"withLog": true}
+    });
+    if !request.state_overrides.is_empty() {
+        options["stateOverrides"] = serde_json::to_value(&request.state_overrides)?;
+    }
+    if let Some(ref block_overrides) = request.block_overrides {
+        options["blockOverrides"] = serde_json::to_value(block_overrides)?;
+    }
+    let params = serde_json::json!([tx, request.block, options]);
+    provider.raw_request("debug_traceCall", params).await
+}
+
+/// Build the call object of a simulation once its calldata is encoded.
+pub fn transaction_request(request: &SimulationRequest, input: Bytes) -> TransactionRequest {
+    let mut tx = TransactionRequest::default()
+        .value(request.value)
+        .input(input.into());
+    tx = match request.to {
+        Some(to) => tx.to(to),
+        None => tx.into_create(),
+    };
+    if let Some(from) = request.from {
+        tx = tx.from(from);
+    }
+    if let Some(gas) = request.gas {
+        tx = tx.gas_limit(gas);
+    }
+    tx
+}
+
+/// Collect the logs emitted by a `callTracer` frame and its children,
+/// dropping those of frames that reverted.
+pub fn collect_logs(frame: &Value, logs: &mut Vec<Log>) {
+    if frame.get("error").is_some_and(|e| !e.is_null()) {
+        return;
+    }
+    if let Some(frame_logs) = frame["logs"].as_array() {
+        for log in frame_logs {
+            let Some(address) = log["address"]
+                .as_str()
+                .and_then(|s| s.parse::<Address>().ok())
+            else {
+                continue;
+            };
+            let topics = log["topics"]
+                .as_array()
+                .map(|topics| {
+                    topics
+                        .iter()
+                        .filter_map(|t| t.as_str().and_then(|s| s.parse::<B256>().ok()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let data = log["data"]
+                .as_str()
+                .and_then(parse_hex_bytes)
+                .unwrap_or_default();
+            logs.push(Log {
+                inner: alloy::primitives::Log::new_unchecked(address, topics, data),
+                ..Default::default()
+            });
+        }
+    }
+    if let Some(calls) = frame["calls"].as_array() {
+        for call in calls {
+            collect_logs(call, logs);
+        }
+    }
+}
+
+/// Gas used by the top-level `callTracer` frame.
+pub fn frame_gas_used(frame: &Value) -> Option<u64> {
+    parse_u64(&frame["gasUsed"])
+}
+
+/// Parse an ether amount: plain wei, hex, or a number with a unit (`1.5ether`, `30gwei`).
+pub fn parse_value(input: &str) -> Result<U256, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(U256::ZERO);
+    }
+    match DynSolType::Uint(256).coerce_str(input) {
+        Ok(DynSolValue::Uint(value, _)) => Ok(value),
+        _ => Err(format!("Invalid amount: {input}")),
+    }
+}
+
+/// Parse a block number or tag; empty means latest.
+pub fn parse_block(input: &str) -> Result<BlockNumberOrTag, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(BlockNumberOrTag::Latest);
+    }
+    if let Ok(number) = input.parse::<u64>() {
+        return Ok(BlockNumberOrTag::Number(number));
+    }
+    BlockNumberOrTag::from_str(input).map_err(|_| format!("Invalid block: {input}"))
+}
+
+/// Parse state overrides written as
+/// `0xaddr balance=1ether nonce=2 code=0x.. 0xslot=0xvalue; 0xaddr2 ...`.
+pub fn parse_state_overrides(input: &str) -> Result<StateOverride, String> {
+    let mut overrides = StateOverride::default();
+    for entry in input.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let mut tokens = entry.split_whitespace();
+        let address = tokens
+            .next()
+            .and_then(|a| a.parse::<Address>().ok())
+            .ok_or_else(|| format!("Expected an address at the start of '{entry}'"))?;
+        let account = overrides
+            .entry(address)
+            .or_insert_with(AccountOverride::default);
+
+        for token in tokens {
+            let (key, value) = token
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value, got '{token}'"))?;
+            match key {
+                "balance" => account.balance = Some(parse_value(value)?),
+                "nonce" => {
+                    account.nonce = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid nonce: {value}"))?,
+                    )
+                }
+                "code" => {
+                    account.code = Some(
+                        parse_hex_bytes(value).ok_or_else(|| format!("Invalid code: {value}"))?,
+                    )
+                }
+                slot => {
+                    let slot =
+                        parse_word(slot).ok_or_else(|| format!("Unknown override: {slot}"))?;
+                    let value =
+                        parse_word(value).ok_or_else(|| format!("Invalid slot value: {value}"))?;
+                    account
+                        .state_diff
+                        .get_or_insert_with(Default::default)
+                        .insert(slot, value);
+                }
+            }
+        }
+    }
+    Ok(overrides)
+}
+
+/// Parse block overrides written as `number=N time=T basefee=X gaslimit=N coinbase=0x..`.
+pub fn parse_block_overrides(input: &str) -> Result<Option<BlockOverrides>, String> {
+    let mut overrides = BlockOverrides::default();
+    let mut any = false;
+    for token in input.split_whitespace() {
+        let (key, value) = token
+            .split_once('=')
+            .ok_or_else(|| format!("Expected key=value, got '{token}'"))?;
+        let number = || parse_value(value).map(|v| v.saturating_to::<u64>());
+        match key {
+            "number" => overrides.number = Some(parse_value(value)?),
+            "time" | "timestamp" => overrides.time = Some(number()?),
+            "basefee" => overrides.base_fee = Some(parse_value(value)?),
+            "gaslimit" => overrides.gas_limit = Some(number()?),
+            "coinbase" => {
+                overrides.coinbase = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid coinbase: {value}"))?,
+                )
+            }
+            _ => return Err(format!("Unknown block override: {key}")),
+        }
+        any = true;
+    }
+    Ok(any.then_some(overrides))
+}
+
+/// Encode calldata from hex, from `name(args)` matched against `abi`, or from
+/// an explicit `name(types)(args)` signature.
+pub fn encode_calldata(abi: Option<&JsonAbi>, input: &str) -> Result<Bytes, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(Bytes::new());
+    }
+    if input.starts_with("0x") {
+        return parse_hex_bytes(input).ok_or_else(|| "Invalid hex calldata".to_string());
+    }
+
+    let open = input
+        .find('(')
+        .ok_or_else(|| "Expected hex calldata or name(args)".to_string())?;
+    let close = matching_paren(input, open).ok_or_else(|| "Unbalanced parentheses".to_string())?;
+    let (head, rest) = (&input[..=close], input[close + 1..].trim());
+
+    // name(types)(args): the signature carries the parameter types
+    if rest.starts_with('(') {
+        let function = Function::parse(head).map_err(|e| format!("Invalid signature: {e}"))?;
+        return encode_args(&function, rest);
+    }
+    if !rest.is_empty() {
+        return Err(format!("Unexpected input after call: {rest}"));
+    }
+
+    let name = input[..open].trim();
+    let args = &input[open..=close];
+    let abi = abi.ok_or_else(|| {
+        format!(
+            "No ABI for the target; give the signature, e.g. {name}(address,uint256)({})",
+            &args[1..args.len() - 1]
+        )
+    })?;
+    let functions = abi
+        .function(name)
+        .ok_or_else(|| format!("Function {name} not found in ABI"))?;
+    let mut last_error = String::new();
+    for function in functions {
+        match encode_args(function, args) {
+            Ok(data) => return Ok(data),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+fn encode_args(function: &Function, args: &str) -> Result<Bytes, String> {
+    let types = function
+        .inputs
+        .iter()
+        .map(|p| p.resolve())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let values = match DynSolType::Tuple(types).coerce_str(args) {
+        Ok(DynSolValue::Tuple(values)) => values,
+        Ok(other) => {
+            return Err(format!(
+                "Invalid arguments for {}: expected a tuple, got {other:?}",
+                function.signature()
+            ));
+        }
+        Err(e) => {
+            return Err(format!(
+                "Invalid arguments for {}: {e}",
+                function.signature()
+            ));
+        }
+    };
+    function
+        .abi_encode_input(&values)
+        .map(Bytes::from)
+        .map_err(|e| e.to_string())
+}
+
+/// Index of the parenthesis closing the one at `open`.
+fn matching_paren(s: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices().skip_while(|(i, _)| *i < open) {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_word(s: &str) -> Option<B256> {
+    parse_value(s).ok().map(B256::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const A: &str = "0x00000000000000000000000000000000000000aa";
+
+    #[test]
+    fn test_parse_value_units() {
+        assert_eq!(parse_value("").unwrap(), U256::ZERO);
+        assert_eq!(parse_value("42").unwrap(), U256::from(42));
+        assert_eq!(parse_value("0x10").unwrap(), U256::from(16));
+        assert_eq!(parse_value("2gwei").unwrap(), U256::from(2_000_000_000u64));
+        assert_eq!(
+            parse_value("1.5ether").unwrap(),
+            U256::from(1_500_000_000_000_000_000u128)
+        );
+        assert!(parse_value("abc").is_err());
+    }
+
+    #[test]
+    fn test_parse_block() {
+        assert_eq!(parse_block("").unwrap(), BlockNumberOrTag::Latest);
+        assert_eq!(parse_block("100").unwrap(), BlockNumberOrTag::Number(100));
+        assert_eq!(parse_block("pending").unwrap(), BlockNumberOrTag::Pending);
+        assert!(parse_block("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_state_overrides() {
+        let input = format!("{A} balance=1ether nonce=3 0x1=0x2; {A} code=0x6000");
+        let overrides = parse_state_overrides(&input).unwrap();
+        let account = &overrides[&A.parse::<Address>().unwrap()];
+        assert_eq!(account.balance, Some(U256::from(10u128.pow(18))));
+        assert_eq!(account.nonce, Some(3));
+        assert_eq!(account.code.as_ref().map(|c| c.len()), Some(2));
+        let state_diff = account.state_diff.as_ref().unwrap();
+        assert_eq!(
+            state_diff[&B256::from(U256::from(1))],
+            B256::from(U256::from(2))
+        );
+
+        assert!(parse_state_overrides("").unwrap().is_empty());
+        assert!(parse_state_overrides("balance=1").is_err());
+        assert!(parse_state_overrides(&format!("{A} colour=red")).is_err());
+    }
+
+    #[test]
+    fn test_parse_block_overrides() {
+        assert!(parse_block_overrides("  ").unwrap().is_none());
+        let overrides = parse_block_overrides(&format!(
+            "number=5 time=1700000000 basefee=1gwei coinbase={A}"
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(overrides.number, Some(U256::from(5)));
+        assert_eq!(overrides.time, Some(1_700_000_000));
+        assert_eq!(overrides.base_fee, Some(U256::from(1_000_000_000u64)));
+        assert_eq!(overrides.coinbase, Some(A.parse().unwrap()));
+        assert!(parse_block_overrides("difficulty=1").is_err());
+    }
+
+    #[test]
+    fn test_encode_calldata_hex_and_signature() {
+        assert_eq!(encode_calldata(None, "0xa9059cbb").unwrap().len(), 4);
+        assert!(encode_calldata(None, "").unwrap().is_empty());
+
+        let data = encode_calldata(None, &format!("transfer(address,uint256)({A}, 1)")).unwrap();
+        assert_eq!(&data[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(data.len(), 4 + 64);
+        assert_eq!(data[data.len() - 1], 1);
+
+        // A name alone needs the target's ABI
+        assert!(encode_calldata(None, &format!("transfer({A}, 1)")).is_err());
+        assert!(encode_calldata(None, "transfer(address").is_err());
+    }
+
+    #[test]
+    fn test_encode_calldata_with_abi() {
+        let abi = JsonAbi::parse([
+            "function transfer(address to, uint256 amount) returns (bool)",
+            "function totalSupply() view returns (uint256)",
+        ])
+        .unwrap();
+        let data = encode_calldata(Some(&abi), &format!("transfer({A}, 2)")).unwrap();
+        assert_eq!(&data[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(
+            encode_calldata(Some(&abi), "totalSupply()").unwrap(),
+            Bytes::from(vec![0x18, 0x16, 0x0d, 0xdd])
+        );
+        assert!(encode_calldata(Some(&abi), "mint(1)").is_err());
+        assert!(encode_calldata(Some(&abi), "transfer(1)").is_err());
+    }
+
+    #[test]
+    fn test_collect_logs_skips_reverted_frames() {
+        let log = |n: u64| {
+            json!({
+                "address": A,
+                "topics": [format!("{:?}", B256::from(U256::from(n)))],
+                "data": "0x"
+            })
+        };
+        let frame = json!({
+            "gasUsed": "0x5208",
+            "logs": [log(1)],
+            "calls": [
+                { "logs": [log(2)] },
+                { "error": "execution reverted", "logs": [log(3)], "calls": [{ "logs": [log(4)] }] }
+            ]
+        });
+        let mut logs = Vec::new();
+        collect_logs(&frame, &mut logs);
+        let topics: Vec<B256> = logs.iter().map(|l| l.inner.data.topics()[0]).collect();
+        assert_eq!(
+            topics,
+            vec![B256::from(U256::from(1)), B256::from(U256::from(2))]
+        );
+        assert_eq!(frame_gas_used(&frame), Some(21_000));
+    }
+}
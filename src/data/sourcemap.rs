@@ -0,0 +1,313 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+
+/// One verified source file of a contract.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// A decoded entry of a solc source map (one per instruction).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceMapEntry {
+    /// Byte offset of the source range
+    pub offset: usize,
+    /// Byte length of the source range
+    pub length: usize,
+    /// Source file index, or None for compiler-generated code (`-1`)
+    pub file: Option<usize>,
+}
+
+/// Where in the Solidity sources an instruction comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: usize,
+    pub offset: usize,
+    pub length: usize,
+    /// 1-based line of the start of the range
+    pub line: usize,
+    /// 1-based line of the end of the range
+    pub end_line: usize,
+}
+
+/// Verified sources and deployed source map for a contract.
+#[derive(Debug, Clone)]
+pub struct ContractSource {
+    pub name: String,
+    pub files: BTreeMap<usize, SourceFile>,
+    pub source_map: Vec<SourceMapEntry>,
+    /// Maps a program counter to its instruction index in the source map
+    pc_to_index: HashMap<usize, usize>,
+}
+
+impl ContractSource {
+    pub fn new(
+        name: String,
+        files: BTreeMap<usize, SourceFile>,
+        source_map: Vec<SourceMapEntry>,
+        runtime_code: &[u8],
+    ) -> Self {
+        Self {
+            name,
+            files,
+            source_map,
+            pc_to_index: instruction_indices(runtime_code),
+        }
+    }
+
+    /// Map a program counter to a source location. Returns None for pcs
+    /// outside the code and for compiler-generated instructions.
+    pub fn locate(&self, pc: u64) -> Option<SourceLocation> {
+        let index = *self.pc_to_index.get(&(pc as usize))?;
+        let entry = self.source_map.get(index)?;
+        let file = entry.file?;
+        let content = &self.files.get(&file)?.content;
+        if entry.offset > content.len() {
+            return None;
+        }
+        let end = (entry.offset + entry.length).min(content.len());
+        Some(SourceLocation {
+            file,
+            offset: entry.offset,
+            length: entry.length,
+            line: line_of(content, entry.offset),
+            end_line: line_of(content, end.saturating_sub(1).max(entry.offset)),
+        })
+    }
+
+    pub fn file(&self, index: usize) -> Option<&SourceFile> {
+        self.files.get(&index)
+    }
+}
+
+/// Decode a compressed solc source map (`s:l:f:j:m;...`). Empty fields
+/// inherit the value of the previous entry.
+pub fn parse_source_map(map: &str) -> Vec<SourceMapEntry> {
+    if map.is_empty() {
+        return Vec::new();
+    }
+
+    let mut entries = Vec::new();
+    let mut offset: i64 = 0;
+    let mut length: i64 = 0;
+    let mut file: i64 = -1;
+
+    for item in map.split(';') {
+        let mut fields = item.split(':');
+        if let Some(v) = fields.next().and_then(|f| f.parse().ok()) {
+            offset = v;
+        }
+        if let Some(v) = fields.next().and_then(|f| f.parse().ok()) {
+            length = v;
+        }
+        if let Some(v) = fields.next().and_then(|f| f.parse().ok()) {
+            file = v;
+        }
+        entries.push(SourceMapEntry {
+            offset: offset.max(0) as usize,
+            length: length.max(0) as usize,
+            file: usize::try_from(file).ok(),
+        });
+    }
+
+    entries
+}
+
+/// Build a pc -> instruction index table, skipping PUSH immediates.
+pub fn instruction_indices(code: &[u8]) -> HashMap<usize, usize> {
+    let mut indices = HashMap::new();
+    let mut pc = 0;
+    let mut index = 0;
+    while pc < code.len() {
+        indices.insert(pc, index);
+        let op = code[pc];
+        pc += 1;
+        // PUSH1..PUSH32
+        if (0x60..=0x7f).contains(&op) {
+            pc += (op - 0x5f) as usize;
+        }
+        index += 1;
+    }
+    indices
+}
+
+/// 1-based line number containing byte `offset`.
+pub fn line_of(content: &str, offset: usize) -> usize {
+    let offset = offset.min(content.len());
+    content.as_bytes()[..offset]
+        .iter()
+        .filter(|&&b| b == b'\n')
+        .count()
+        + 1
+}
+
+/// Parse a Sourcify v2 contract response requested with the `sources`,
+/// `sourceIds`, `runtimeBytecode` and `compilation` fields.
+pub fn parse_sourcify_contract(body: &Value) -> Option<ContractSource> {
+    let sources = body.get("sources")?.as_object()?;
+    let runtime = body.get("runtimeBytecode")?;
+    let source_map = parse_source_map(runtime.get("sourceMap")?.as_str()?);
+    let code_hex = runtime
+        .get("onchainBytecode")
+        .and_then(|v| v.as_str())
+        .or_else(|| runtime.get("recompiledBytecode").and_then(|v| v.as_str()))?;
+    let code = alloy::hex::decode(code_hex.trim_start_matches("0x")).ok()?;
+
+    let ids = body.get("sourceIds").and_then(|v| v.as_object());
+    let mut files = BTreeMap::new();
+    // solc numbers source units in sorted path order, which is also what
+    // serde_json's map iteration yields when sourceIds is absent
+    for (position, (path, source)) in sources.iter().enumerate() {
+        let Some(content) = source.get("content").and_then(|c| c.as_str()) else {
+            continue;
+        };
+        let id = ids
+            .and_then(|ids| ids.get(path))
+            .and_then(|v| v.get("id"))
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(position);
+        files.insert(
+            id,
+            SourceFile {
+                path: path.clone(),
+                content: content.to_string(),
+            },
+        );
+    }
+
+    let name = body
+        .get("compilation")
+        .and_then(|c| c.get("name"))
+        .and_then(|n| n.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    Some(ContractSource::new(name, files, source_map, &code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_source_map_inherits_fields() {
+        let entries = parse_source_map("10:5:0:-:0;;20;:3;-1:-1:-1");
+        assert_eq!(entries.len(), 5);
+        assert_eq!(
+            entries[0],
+            SourceMapEntry {
+                offset: 10,
+                length: 5,
+                file: Some(0)
+            }
+        );
+        assert_eq!(entries[1], entries[0]);
+        assert_eq!(
+            entries[2],
+            SourceMapEntry {
+                offset: 20,
+                length: 5,
+                file: Some(0)
+            }
+        );
+        assert_eq!(
+            entries[3],
+            SourceMapEntry {
+                offset: 20,
+                length: 3,
+                file: Some(0)
+            }
+        );
+        assert_eq!(entries[4].file, None);
+    }
+
+    #[test]
+    fn test_parse_source_map_empty() {
+        assert!(parse_source_map("").is_empty());
+    }
+
+    #[test]
+    fn test_instruction_indices_skip_push_data() {
+        // PUSH1 0x80, PUSH2 0x0102, ADD, STOP
+        let code = [0x60, 0x80, 0x61, 0x01, 0x02, 0x01, 0x00];
+        let indices = instruction_indices(&code);
+        assert_eq!(indices.get(&0), Some(&0));
+        assert_eq!(indices.get(&1), None);
+        assert_eq!(indices.get(&2), Some(&1));
+        assert_eq!(indices.get(&5), Some(&2));
+        assert_eq!(indices.get(&6), Some(&3));
+    }
+
+    #[test]
+    fn test_line_of() {
+        let src = "line1\nline2\nline3";
+        assert_eq!(line_of(src, 0), 1);
+        assert_eq!(line_of(src, 6), 2);
+        assert_eq!(line_of(src, 13), 3);
+        assert_eq!(line_of(src, 1000), 3);
+    }
+
+    fn sample_contract() -> Value {
+        json!({
+            "sources": {
+                "contracts/B.sol": { "content": "library B {}\n" },
+                "contracts/A.sol": { "content": "contract A {\n  uint x;\n  function f() public { x = 1; }\n}\n" }
+            },
+            "sourceIds": {
+                "contracts/A.sol": { "id": 0 },
+                "contracts/B.sol": { "id": 1 }
+            },
+            "runtimeBytecode": {
+                "onchainBytecode": "0x6001600055",
+                "sourceMap": "36:5:0;45:1:0;36:9:0;-1:-1:-1"
+            },
+            "compilation": { "name": "A" }
+        })
+    }
+
+    #[test]
+    fn test_parse_sourcify_contract() {
+        let source = parse_sourcify_contract(&sample_contract()).unwrap();
+        assert_eq!(source.name, "A");
+        assert_eq!(source.files[&0].path, "contracts/A.sol");
+        assert_eq!(source.files[&1].path, "contracts/B.sol");
+        assert_eq!(source.source_map.len(), 4);
+    }
+
+    #[test]
+    fn test_locate_pc() {
+        let source = parse_sourcify_contract(&sample_contract()).unwrap();
+        // pc 2 is the second PUSH1 -> entry 45:1 on line 3
+        let loc = source.locate(2).unwrap();
+        assert_eq!(loc.file, 0);
+        assert_eq!(loc.offset, 45);
+        assert_eq!(loc.line, 3);
+        assert_eq!(loc.end_line, 3);
+        // pc 1 is push data, not an instruction
+        assert!(source.locate(1).is_none());
+    }
+
+    #[test]
+    fn test_locate_compiler_generated() {
+        let value = json!({
+            "sources": { "A.sol": { "content": "contract A {}" } },
+            "runtimeBytecode": {
+                "onchainBytecode": "0x00",
+                "sourceMap": "-1:-1:-1"
+            }
+        });
+        let source = parse_sourcify_contract(&value).unwrap();
+        assert_eq!(source.files[&0].path, "A.sol");
+        assert!(source.locate(0).is_none());
+    }
+
+    #[test]
+    fn test_parse_sourcify_missing_source_map() {
+        let value = json!({ "sources": {}, "runtimeBytecode": { "onchainBytecode": "0x00" } });
+        assert!(parse_sourcify_contract(&value).is_none());
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::BTreeSet;
+
+use alloy::primitives::{Address, Bytes, B256, U256};
+use color_eyre::eyre::{eyre, Result};
+use serde_json::Value;
+
+use crate::data::provider::EthProvider;
+use crate::data::trace::{parse_hex_bytes, parse_hex_u256, parse_u64};
+use crate::data::types::{AccountDiff, StorageChange};
+
+/// Fetch the state changes of a transaction using Geth's `prestateTracer`
+/// in diff mode.
+pub async fn fetch_prestate_diff(
+    provider: &EthProvider,
+    tx_hash: B256,
+) -> Result<Vec<AccountDiff>> {
+    let params = serde_json::json!([
+        format!("{tx_hash:?}"),
+        {"tracer": "prestateTracer", "tracerConfig": {"diffMode": true}}
+    ]);
+    let result = provider
+        .raw_request("debug_traceTransaction", params)
+        .await?;
+    parse_prestate_diff(&result)
+}
+
+/// Fetch the state changes of a transaction using Parity-style
+/// `trace_replayTransaction` with the `stateDiff` trace type.
+pub async fn fetch_replay_state_diff(
+    provider: &EthProvider,
+    tx_hash: B256,
+) -> Result<Vec<AccountDiff>> {
+    let params = serde_json::json!([format!("{tx_hash:?}"), ["stateDiff"]]);
+    let result = provider
+        .raw_request("trace_replayTransaction", params)
+        .await?;
+    parse_replay_state_diff(&result)
+}
+
+/// Parse a `prestateTracer` diff mode result (`{pre, post}`).
+///
+/// `pre` holds the full prior state of every modified account, while `post`
+/// only holds the fields that changed. An account missing from `post` was
+/// self-destructed, and a storage slot missing from `post` was cleared.
+pub fn parse_prestate_diff(result: &Value) -> Result<Vec<AccountDiff>> {
+    let pre = result["pre"]
+        .as_object()
+        .ok_or_else(|| eyre!("Expected pre object from prestateTracer"))?;
+    let post = result["post"]
+        .as_object()
+        .ok_or_else(|| eyre!("Expected post object from prestateTracer"))?;
+
+    let addresses: BTreeSet<&String> = pre.keys().chain(post.keys()).collect();
+    let mut diffs = Vec::new();
+
+    for key in addresses {
+        let Ok(address) = key.parse::<Address>() else {
+            continue;
+        };
+        let before = pre.get(key).unwrap_or(&Value::Null);
+        let after = post.get(key).unwrap_or(&Value::Null);
+
+        let destroyed = after.is_null();
+
+        // Unchanged fields are omitted from `post`, so fall back to `pre`
+        let balance_of = |v: &Value| v["balance"].as_str().and_then(parse_hex_u256);
+        let nonce_of = |v: &Value| parse_u64(&v["nonce"]);
+        let code_of = |v: &Value| v["code"].as_str().and_then(parse_hex_bytes);
+        let old_balance = balance_of(before).unwrap_or_default();
+        let old_nonce = nonce_of(before).unwrap_or(0);
+        let old_code = code_of(before).unwrap_or_default();
+        let (new_balance, new_nonce, new_code) = if destroyed {
+            (U256::ZERO, 0, Bytes::new())
+        } else {
+            (
+                balance_of(after).unwrap_or(old_balance),
+                nonce_of(after).unwrap_or(old_nonce),
+                code_of(after).unwrap_or_else(|| old_code.clone()),
+            )
+        };
+
+        let mut diff = AccountDiff {
+            address,
+            balance: changed(old_balance, new_balance),
+            nonce: changed(old_nonce, new_nonce),
+            code: changed(old_code, new_code),
+            storage: Vec::new(),
+        };
+
+        let empty = serde_json::Map::new();
+        let pre_storage = before["storage"].as_object().unwrap_or(&empty);
+        let post_storage = after["storage"].as_object().unwrap_or(&empty);
+        let slots: BTreeSet<&String> = pre_storage.keys().chain(post_storage.keys()).collect();
+        for slot in slots {
+            let word = |m: &serde_json::Map<String, Value>| {
+                m.get(slot)
+                    .and_then(|v| v.as_str())
+                    .and_then(parse_word)
+                    .unwrap_or_default()
+            };
+            let (Some(slot), old, new) = (parse_word(slot), word(pre_storage), word(post_storage))
+            else {
+                continue;
+            };
+            if old != new {
+                diff.storage.push(StorageChange {
+                    slot,
+                    before: old,
+                    after: new,
+                });
+            }
+        }
+
+        if diff.balance.is_some()
+            || diff.nonce.is_some()
+            || diff.code.is_some()
+            || !diff.storage.is_empty()
+        {
+            diffs.push(diff);
+        }
+    }
+
+    Ok(diffs)
+}
+
+/// Parse a `trace_replayTransaction` result with a `stateDiff` object.
+///
+/// Each field is `"="` (unchanged), `{"+": new}` (born), `{"-": old}`
+/// (died) or `{"*": {"from": old, "to": new}}` (changed).
+pub fn parse_replay_state_diff(result: &Value) -> Result<Vec<AccountDiff>> {
+    let state_diff = result["stateDiff"]
+        .as_object()
+        .ok_or_else(|| eyre!("Expected stateDiff object from trace_replayTransaction"))?;
+
+    let mut diffs = Vec::new();
+    for (key, account) in state_diff {
+        let Ok(address) = key.parse::<Address>() else {
+            continue;
+        };
+
+        let mut diff = AccountDiff {
+            address,
+            balance: replay_change(&account["balance"], |v| v.as_str().and_then(parse_hex_u256)),
+            nonce: replay_change(&account["nonce"], parse_u64),
+            code: replay_change(&account["code"], |v| v.as_str().and_then(parse_hex_bytes)),
+            storage: Vec::new(),
+        };
+
+        if let Some(storage) = account["storage"].as_object() {
+            for (slot, change) in storage {
+                let Some(slot) = parse_word(slot) else {
+                    continue;
+                };
+                if let Some((before, after)) =
+                    replay_change(change, |v| v.as_str().and_then(parse_word))
+                {
+                    diff.storage.push(StorageChange {
+                        slot,
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+        diff.storage.sort_by_key(|c| c.slot);
+        diffs.push(diff);
+    }
+
+    diffs.sort_by_key(|d| d.address);
+    Ok(diffs)
+}
+
+fn changed<T: PartialEq>(before: T, after: T) -> Option<(T, T)> {
+    (before != after).then_some((before, after))
+}
+
+/// Decode one Parity diff field into `(before, after)`, treating a missing
+/// side of a born/died value as the type's default.
+fn replay_change<T: Default + PartialEq>(
+    field: &Value,
+    parse: impl Fn(&Value) -> Option<T>,
+) -> Option<(T, T)> {
+    if let Some(new) = field.get("+") {
+        return changed(T::default(), parse(new)?);
+    }
+    if let Some(old) = field.get("-") {
+        return changed(parse(old)?, T::default());
+    }
+    let change = field.get("*")?;
+    changed(parse(&change["from"])?, parse(&change["to"])?)
+}
+
+fn parse_word(s: &str) -> Option<B256> {
+    parse_hex_u256(s).map(|v: U256| B256::from(v))
+}
+
+/// Code changes are summarized by size since full bytecode is too long to show.
+pub fn describe_code(code: &Bytes) -> String {
+    if code.is_empty() {
+        "(none)".to_string()
+    } else {
+        format!("{} bytes", code.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const A: &str = "0x00000000000000000000000000000000000000aa";
+    const B: &str = "0x00000000000000000000000000000000000000bb";
+
+    fn word(n: u64) -> B256 {
+        B256::from(U256::from(n))
+    }
+
+    #[test]
+    fn test_parse_prestate_diff() {
+        let result = json!({
+            "pre": {
+                A: {
+                    "balance": "0x10",
+                    "nonce": 1,
+                    "code": "0x6001",
+                    "storage": {
+                        "0x01": "0x05",
+                        "0x02": "0x07"
+                    }
+                }
+            },
+            "post": {
+                A: {
+                    "balance": "0x08",
+                    "nonce": 2,
+                    "storage": {
+                        "0x01": "0x06",
+                        "0x03": "0x01"
+                    }
+                },
+                B: {
+                    "balance": "0x08",
+                    "code": "0x6000"
+                }
+            }
+        });
+
+        let diffs = parse_prestate_diff(&result).unwrap();
+        assert_eq!(diffs.len(), 2);
+
+        let a = &diffs[0];
+        assert_eq!(a.address, A.parse::<Address>().unwrap());
+        assert_eq!(a.balance, Some((U256::from(16), U256::from(8))));
+        assert_eq!(a.nonce, Some((1, 2)));
+        assert!(a.code.is_none());
+        // slot 2 was cleared, slot 3 is new
+        assert_eq!(
+            a.storage,
+            vec![
+                StorageChange {
+                    slot: word(1),
+                    before: word(5),
+                    after: word(6)
+                },
+                StorageChange {
+                    slot: word(2),
+                    before: word(7),
+                    after: B256::ZERO
+                },
+                StorageChange {
+                    slot: word(3),
+                    before: B256::ZERO,
+                    after: word(1)
+                },
+            ]
+        );
+
+        // Newly created account
+        let b = &diffs[1];
+        assert_eq!(b.balance, Some((U256::ZERO, U256::from(8))));
+        assert_eq!(b.code.as_ref().map(|(_, after)| after.len()), Some(2));
+    }
+
+    #[test]
+    fn test_parse_prestate_diff_missing_fields() {
+        assert!(parse_prestate_diff(&json!({"pre": {}})).is_err());
+        assert!(
+            parse_prestate_diff(&json!({"pre": {}, "post": {}}))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_parse_prestate_diff_unchanged_and_destroyed() {
+        let result = json!({
+            "pre": {
+                A: { "balance": "0x10", "nonce": 1 },
+                B: { "balance": "0x05", "nonce": 1, "code": "0x6000" }
+            },
+            "post": {
+                A: { "nonce": 2 }
+            }
+        });
+
+        let diffs = parse_prestate_diff(&result).unwrap();
+        // A's balance is omitted from post because it did not change
+        assert!(diffs[0].balance.is_none());
+        assert_eq!(diffs[0].nonce, Some((1, 2)));
+        // B is missing from post: self-destructed
+        assert_eq!(diffs[1].balance, Some((U256::from(5), U256::ZERO)));
+        assert_eq!(
+            diffs[1].code.as_ref().map(|(_, after)| after.len()),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_parse_replay_state_diff() {
+        let result = json!({
+            "output": "0x",
+            "stateDiff": {
+                B: {
+                    "balance": {"+": "0x08"},
+                    "nonce": {"+": "0x1"},
+                    "code": {"+": "0x6000"},
+                    "storage": {}
+                },
+                A: {
+                    "balance": {"*": {"from": "0x10", "to": "0x08"}},
+                    "nonce": "=",
+                    "code": "=",
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":
+                            {"*": {"from": "0x05", "to": "0x06"}},
+                        "0x0000000000000000000000000000000000000000000000000000000000000002":
+                            {"-": "0x07"}
+                    }
+                }
+            }
+        });
+
+        let diffs = parse_replay_state_diff(&result).unwrap();
+        assert_eq!(diffs.len(), 2);
+
+        let a = &diffs[0];
+        assert_eq!(a.balance, Some((U256::from(16), U256::from(8))));
+        assert!(a.nonce.is_none());
+        assert!(a.code.is_none());
+        assert_eq!(a.storage.len(), 2);
+        assert_eq!(a.storage[1].after, B256::ZERO);
+
+        let b = &diffs[1];
+        assert_eq!(b.nonce, Some((0, 1)));
+        assert_eq!(b.balance, Some((U256::ZERO, U256::from(8))));
+    }
+
+    #[test]
+    fn test_parse_replay_state_diff_missing() {
+        assert!(parse_replay_state_diff(&json!({"output": "0x"})).is_err());
+    }
+
+    #[test]
+    fn test_describe_code() {
+        assert_eq!(describe_code(&Bytes::new()), "(none)");
+        assert_eq!(describe_code(&Bytes::from(vec![0u8; 3])), "3 bytes");
+    }
+}
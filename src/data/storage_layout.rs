@@ -0,0 +1,691 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{keccak256, Address, B256, I256, U256};
+use serde_json::Value;
+
+use crate::data::provider::EthProvider;
+
+/// Slots read at most for one long `bytes` or `string` value.
+const MAX_BYTES_SLOTS: usize = 32;
+
+/// How solc places a type in storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Packed into the variable's own slots: value types, structs and
+    /// static arrays
+    Inplace,
+    Mapping,
+    DynamicArray,
+    /// `bytes` and `string`: short values inline, long ones at keccak(slot)
+    Bytes,
+}
+
+/// A state variable, or a struct member relative to the struct's slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageVariable {
+    pub label: String,
+    pub slot: U256,
+    /// Byte offset within the slot, counted from the low-order end
+    pub offset: usize,
+    pub type_id: String,
+}
+
+/// An entry of the layout's `types`, keyed by ids like `t_uint256`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageType {
+    /// Solidity spelling, e.g. `mapping(address => uint256)`
+    pub label: String,
+    pub encoding: Encoding,
+    pub number_of_bytes: usize,
+    /// Key and value types of a mapping
+    pub key: Option<String>,
+    pub value: Option<String>,
+    /// Element type of an array
+    pub base: Option<String>,
+    pub members: Vec<StorageVariable>,
+}
+
+/// The `storageLayout` solc emits for a contract.
+#[derive(Debug, Clone, Default)]
+pub struct StorageLayout {
+    /// Contract the layout was compiled for, e.g. `src/Token.sol:Token`
+    pub contract: String,
+    pub variables: Vec<StorageVariable>,
+    pub types: HashMap<String, StorageType>,
+}
+
+/// A place in a contract's storage: a state variable, struct member,
+/// mapping entry or array element, named by the path that leads to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageItem {
+    /// e.g. `balances[0x…]` or `config.owner`
+    pub path: String,
+    pub slot: U256,
+    pub offset: usize,
+    pub type_id: String,
+    pub label: String,
+    pub encoding: Encoding,
+    pub size: usize,
+}
+
+/// A word read from storage and the value it holds for an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageValue {
+    pub raw: B256,
+    pub decoded: String,
+}
+
+impl StorageLayout {
+    /// Items for the contract's state variables, in slot order.
+    pub fn roots(&self) -> Vec<StorageItem> {
+        self.variables
+            .iter()
+            .filter_map(|v| self.item(v.label.clone(), v.slot, v.offset, &v.type_id))
+            .collect()
+    }
+
+    pub fn type_of(&self, item: &StorageItem) -> Option<&StorageType> {
+        self.types.get(&item.type_id)
+    }
+
+    /// Members of a struct, placed relative to its slot.
+    pub fn members(&self, item: &StorageItem) -> Vec<StorageItem> {
+        let Some(ty) = self.type_of(item) else {
+            return Vec::new();
+        };
+        ty.members
+            .iter()
+            .filter_map(|m| {
+                let path = format!("{}.{}", item.path, m.label);
+                self.item(path, item.slot + m.slot, m.offset, &m.type_id)
+            })
+            .collect()
+    }
+
+    /// Length of a static array, from the `[N]` its label ends with.
+    pub fn static_length(&self, item: &StorageItem) -> Option<U256> {
+        let ty = self.type_of(item)?;
+        if item.encoding != Encoding::Inplace || ty.base.is_none() {
+            return None;
+        }
+        let length = ty.label.strip_suffix(']')?.rsplit_once('[')?.1;
+        length.parse().ok()
+    }
+
+    /// The value a mapping holds for `key`, given as the key type is
+    /// written (address, number, bool, hex bytes or a string).
+    pub fn mapping_entry(&self, item: &StorageItem, key: &str) -> Result<StorageItem, String> {
+        let ty = self.type_of(item).ok_or("Unknown mapping type")?;
+        let (Some(key_type), Some(value_type)) = (&ty.key, &ty.value) else {
+            return Err(format!("{} is not a mapping", item.path));
+        };
+        let key_label = self
+            .types
+            .get(key_type)
+            .map_or(key_type.as_str(), |t| t.label.as_str());
+        let mut preimage = encode_key(key_label, key)?;
+        preimage.extend_from_slice(&item.slot.to_be_bytes::<32>());
+        let slot = U256::from_be_bytes(keccak256(&preimage).0);
+        let path = format!("{}[{}]", item.path, key.trim());
+        self.item(path, slot, 0, value_type)
+            .ok_or_else(|| format!("Unknown type {value_type}"))
+    }
+
+    /// Element `index` of a dynamic or static array. Elements of up to 16
+    /// bytes share slots; larger ones take whole slots each.
+    pub fn array_element(&self, item: &StorageItem, index: U256) -> Result<StorageItem, String> {
+        let ty = self.type_of(item).ok_or("Unknown array type")?;
+        let Some(ref base) = ty.base else {
+            return Err(format!("{} is not an array", item.path));
+        };
+        let element_size = self
+            .types
+            .get(base)
+            .ok_or_else(|| format!("Unknown type {base}"))?
+            .number_of_bytes;
+        let length = self.static_length(item);
+        if let Some(length) = length.filter(|length| index >= *length) {
+            return Err(format!("{} has {length} elements", item.path));
+        }
+        let start = match item.encoding {
+            Encoding::DynamicArray => {
+                U256::from_be_bytes(keccak256(item.slot.to_be_bytes::<32>()).0)
+            }
+            _ => item.slot,
+        };
+        let (slot, offset) = if element_size == 0 {
+            return Err(format!("{base} has no size"));
+        } else if element_size <= 16 {
+            let per_slot = U256::from(32 / element_size);
+            let offset = (index % per_slot).to::<usize>() * element_size;
+            (start + index / per_slot, offset)
+        } else {
+            (start + index * U256::from(element_size.div_ceil(32)), 0)
+        };
+        let path = format!("{}[{index}]", item.path);
+        self.item(path, slot, offset, base)
+            .ok_or_else(|| format!("Unknown type {base}"))
+    }
+
+    fn item(&self, path: String, slot: U256, offset: usize, type_id: &str) -> Option<StorageItem> {
+        let ty = self.types.get(type_id)?;
+        Some(StorageItem {
+            path,
+            slot,
+            offset,
+            type_id: type_id.to_string(),
+            label: ty.label.clone(),
+            encoding: ty.encoding,
+            size: ty.number_of_bytes,
+        })
+    }
+}
+
+/// Parse solc's `storageLayout` output: `storage` entries and their `types`.
+pub fn parse_storage_layout(value: &Value) -> Option<StorageLayout> {
+    let storage = value.get("storage")?.as_array()?;
+    let variables = storage
+        .iter()
+        .map(parse_variable)
+        .collect::<Option<Vec<_>>>()?;
+    let contract = storage
+        .first()
+        .and_then(|v| v.get("contract"))
+        .and_then(|c| c.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let mut types = HashMap::new();
+    if let Some(entries) = value.get("types").and_then(|t| t.as_object()) {
+        for (id, ty) in entries {
+            types.insert(id.clone(), parse_type(ty)?);
+        }
+    }
+    Some(StorageLayout {
+        contract,
+        variables,
+        types,
+    })
+}
+
+fn parse_variable(value: &Value) -> Option<StorageVariable> {
+    Some(StorageVariable {
+        label: value.get("label")?.as_str()?.to_string(),
+        slot: number(value.get("slot")?)?,
+        offset: number(value.get("offset")?)?.try_into().ok()?,
+        type_id: value.get("type")?.as_str()?.to_string(),
+    })
+}
+
+fn parse_type(value: &Value) -> Option<StorageType> {
+    let encoding = match value.get("encoding")?.as_str()? {
+        "inplace" => Encoding::Inplace,
+        "mapping" => Encoding::Mapping,
+        "dynamic_array" => Encoding::DynamicArray,
+        "bytes" => Encoding::Bytes,
+        _ => return None,
+    };
+    let text = |key: &str| value.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let members = match value.get("members").and_then(|m| m.as_array()) {
+        Some(members) => members
+            .iter()
+            .map(parse_variable)
+            .collect::<Option<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+    Some(StorageType {
+        label: text("label")?,
+        encoding,
+        number_of_bytes: number(value.get("numberOfBytes")?)?.try_into().ok()?,
+        key: text("key"),
+        value: text("value"),
+        base: text("base"),
+        members,
+    })
+}
+
+/// solc writes slots and sizes as decimal strings; offsets as numbers.
+fn number(value: &Value) -> Option<U256> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.as_u64().map(U256::from),
+        _ => None,
+    }
+}
+
+/// A mapping key as hashed into the slot: value types padded to a word,
+/// `string` and `bytes` keys as their raw bytes.
+pub fn encode_key(label: &str, key: &str) -> Result<Vec<u8>, String> {
+    let key = key.trim();
+    let invalid = || format!("Not a valid {label} key: {key}");
+    let word: [u8; 32] = if is_address(label) {
+        let address: Address = key.parse().map_err(|_| invalid())?;
+        address.into_word().0
+    } else if label == "bool" {
+        match key {
+            "true" => U256::from(1).to_be_bytes(),
+            "false" => [0; 32],
+            _ => return Err(invalid()),
+        }
+    } else if label.starts_with("uint") || label.starts_with("enum ") {
+        // Enums are stored as the smallest uint that fits, at most uint8
+        let bits = match label.strip_prefix("uint") {
+            Some(size) => int_bits(size).ok_or_else(invalid)?,
+            None => 8,
+        };
+        let value = parse_uint(key).ok_or_else(invalid)?;
+        if value.bit_len() > bits {
+            return Err(invalid());
+        }
+        value.to_be_bytes()
+    } else if let Some(size) = label.strip_prefix("int") {
+        let bits = int_bits(size).ok_or_else(invalid)?;
+        // intN spans -2^(N-1) ..= 2^(N-1) - 1; check the magnitude before
+        // negating, since -2^255 has no positive counterpart in an I256
+        let (negative, abs) = match key.strip_prefix('-') {
+            Some(abs) => (true, abs),
+            None => (false, key),
+        };
+        let abs = parse_uint(abs).ok_or_else(invalid)?;
+        let limit = U256::from(1) << (bits - 1);
+        if abs > limit || (abs == limit && !negative) {
+            return Err(invalid());
+        }
+        let value = if negative { abs.wrapping_neg() } else { abs };
+        value.to_be_bytes::<32>()
+    } else if label == "string" {
+        return Ok(key.as_bytes().to_vec());
+    } else if label == "bytes" {
+        return alloy::hex::decode(key.trim_start_matches("0x")).map_err(|_| invalid());
+    } else if let Some(size) = label
+        .strip_prefix("bytes")
+        .and_then(|n| n.parse::<usize>().ok())
+    {
+        let bytes = alloy::hex::decode(key.trim_start_matches("0x")).map_err(|_| invalid())?;
+        if bytes.len() > size {
+            return Err(invalid());
+        }
+        let mut word = [0u8; 32];
+        word[..bytes.len()].copy_from_slice(&bytes);
+        word
+    } else {
+        return Err(format!("Keys of type {label} are not supported"));
+    };
+    Ok(word.to_vec())
+}
+
+/// A decimal or `0x` hex unsigned number.
+pub fn parse_uint(input: &str) -> Option<U256> {
+    let input = input.trim();
+    match input.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_str_radix(input, 10).ok(),
+    }
+}
+
+/// The bit width of an `intN`/`uintN` from the text after its prefix;
+/// a bare `int` or `uint` is 256 bits.
+fn int_bits(size: &str) -> Option<usize> {
+    if size.is_empty() {
+        return Some(256);
+    }
+    let bits = size.parse::<usize>().ok()?;
+    (bits > 0 && bits <= 256 && bits % 8 == 0).then_some(bits)
+}
+
+fn is_address(label: &str) -> bool {
+    label == "address" || label == "address payable" || label.starts_with("contract ")
+}
+
+/// The bytes of a value packed at `offset` in a slot, in big-endian order.
+pub fn packed_bytes(word: &B256, offset: usize, size: usize) -> &[u8] {
+    let end = 32usize.saturating_sub(offset);
+    &word[end.saturating_sub(size)..end]
+}
+
+/// A value type decoded from its packed bytes; hex for types without a
+/// friendlier form, like function pointers and user-defined value types.
+pub fn decode_value(label: &str, bytes: &[u8]) -> String {
+    let uint = U256::from_be_slice(bytes);
+    if is_address(label) {
+        Address::from_word(uint.into()).to_checksum(None)
+    } else if label == "bool" {
+        (!uint.is_zero()).to_string()
+    } else if label.starts_with("uint") || label.starts_with("enum ") {
+        uint.to_string()
+    } else if label.starts_with("int") && !bytes.is_empty() {
+        // Sign-extend from the value's own width
+        let bits = bytes.len() * 8;
+        let value = if bits < 256 && uint.bit(bits - 1) {
+            uint | (U256::MAX << bits)
+        } else {
+            uint
+        };
+        I256::from_raw(value).to_string()
+    } else {
+        format!("0x{}", alloy::hex::encode(bytes))
+    }
+}
+
+/// Where a `bytes` or `string` value is: inline in its slot when shorter
+/// than 32 bytes, otherwise its length and data starting at keccak(slot).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytesStorage {
+    Short(Vec<u8>),
+    Long(usize),
+}
+
+pub fn bytes_storage(word: &B256) -> BytesStorage {
+    if word[31] & 1 == 0 {
+        let len = (word[31] / 2) as usize;
+        BytesStorage::Short(word[..len.min(31)].to_vec())
+    } else {
+        let len = (U256::from_be_bytes(word.0) - U256::from(1)) / U256::from(2);
+        BytesStorage::Long(len.saturating_to())
+    }
+}
+
+/// Slots holding the first `len` bytes of a long value stored at `slot`,
+/// up to `MAX_BYTES_SLOTS` of them.
+pub fn bytes_data_slots(slot: U256, len: usize) -> Vec<U256> {
+    let start = U256::from_be_bytes(keccak256(slot.to_be_bytes::<32>()).0);
+    (0..len.div_ceil(32).min(MAX_BYTES_SLOTS))
+        .map(|i| start + U256::from(i))
+        .collect()
+}
+
+/// A `string` quoted, or `bytes` in hex, noting the full length when only
+/// the start was read.
+pub fn format_bytes(label: &str, data: &[u8], len: usize) -> String {
+    let data = &data[..data.len().min(len)];
+    let shown = if label == "string" {
+        format!("{:?}", String::from_utf8_lossy(data))
+    } else {
+        format!("0x{}", alloy::hex::encode(data))
+    };
+    if data.len() < len {
+        format!("{shown}\u{2026} ({len} bytes)")
+    } else {
+        shown
+    }
+}
+
+/// Read an item's slot, plus the data slots of a long `bytes` or `string`,
+/// and decode it. Mappings hold nothing at their own slot; structs and
+/// static arrays are read through their members.
+pub async fn read_item(
+    provider: &EthProvider,
+    address: Address,
+    item: &StorageItem,
+) -> Option<StorageValue> {
+    let raw = B256::from(provider.get_storage_at(address, item.slot).await.ok()?);
+    let decoded = match item.encoding {
+        Encoding::Inplace => decode_value(&item.label, packed_bytes(&raw, item.offset, item.size)),
+        Encoding::DynamicArray => format!("length {}", U256::from_be_bytes(raw.0)),
+        Encoding::Mapping => String::new(),
+        Encoding::Bytes => match bytes_storage(&raw) {
+            BytesStorage::Short(data) => format_bytes(&item.label, &data, data.len()),
+            BytesStorage::Long(len) => {
+                let words = futures::future::join_all(
+                    bytes_data_slots(item.slot, len)
+                        .into_iter()
+                        .map(|slot| provider.get_storage_at(address, slot)),
+                )
+                .await;
+                let mut data = Vec::new();
+                for word in words {
+                    data.extend_from_slice(&word.ok()?.to_be_bytes::<32>());
+                }
+                format_bytes(&item.label, &data, len)
+            }
+        },
+    };
+    Some(StorageValue { raw, decoded })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::b256;
+    use serde_json::json;
+
+    /// contract Vault {
+    ///     address owner; bool paused; uint8 version;
+    ///     mapping(address => uint256) balances;
+    ///     uint64[] stamps;
+    ///     struct Config { uint128 fee; address admin; }
+    ///     Config config;
+    ///     string name;
+    ///     bytes4[3] selectors;
+    /// }
+    fn sample_layout() -> StorageLayout {
+        parse_storage_layout(&json!({
+            "storage": [
+                {"astId": 1, "contract": "src/Vault.sol:Vault", "label": "owner", "offset": 0, "slot": "0", "type": "t_address"},
+                {"astId": 2, "contract": "src/Vault.sol:Vault", "label": "paused", "offset": 20, "slot": "0", "type": "t_bool"},
+                {"astId": 3, "contract": "src/Vault.sol:Vault", "label": "version", "offset": 21, "slot": "0", "type": "t_uint8"},
+                {"astId": 4, "contract": "src/Vault.sol:Vault", "label": "balances", "offset": 0, "slot": "1", "type": "t_mapping(t_address,t_uint256)"},
+                {"astId": 5, "contract": "src/Vault.sol:Vault", "label": "stamps", "offset": 0, "slot": "2", "type": "t_array(t_uint64)dyn_storage"},
+                {"astId": 6, "contract": "src/Vault.sol:Vault", "label": "config", "offset": 0, "slot": "3", "type": "t_struct(Config)10_storage"},
+                {"astId": 7, "contract": "src/Vault.sol:Vault", "label": "name", "offset": 0, "slot": "5", "type": "t_string_storage"},
+                {"astId": 8, "contract": "src/Vault.sol:Vault", "label": "selectors", "offset": 0, "slot": "6", "type": "t_array(t_bytes4)3_storage"}
+            ],
+            "types": {
+                "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+                "t_bool": {"encoding": "inplace", "label": "bool", "numberOfBytes": "1"},
+                "t_uint8": {"encoding": "inplace", "label": "uint8", "numberOfBytes": "1"},
+                "t_uint64": {"encoding": "inplace", "label": "uint64", "numberOfBytes": "8"},
+                "t_uint128": {"encoding": "inplace", "label": "uint128", "numberOfBytes": "16"},
+                "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"},
+                "t_bytes4": {"encoding": "inplace", "label": "bytes4", "numberOfBytes": "4"},
+                "t_string_storage": {"encoding": "bytes", "label": "string", "numberOfBytes": "32"},
+                "t_mapping(t_address,t_uint256)": {"encoding": "mapping", "key": "t_address", "label": "mapping(address => uint256)", "numberOfBytes": "32", "value": "t_uint256"},
+                "t_array(t_uint64)dyn_storage": {"base": "t_uint64", "encoding": "dynamic_array", "label": "uint64[]", "numberOfBytes": "32"},
+                "t_array(t_bytes4)3_storage": {"base": "t_bytes4", "encoding": "inplace", "label": "bytes4[3]", "numberOfBytes": "32"},
+                "t_struct(Config)10_storage": {
+                    "encoding": "inplace", "label": "struct Vault.Config", "numberOfBytes": "64",
+                    "members": [
+                        {"astId": 9, "contract": "src/Vault.sol:Vault", "label": "fee", "offset": 0, "slot": "0", "type": "t_uint128"},
+                        {"astId": 10, "contract": "src/Vault.sol:Vault", "label": "admin", "offset": 0, "slot": "1", "type": "t_address"}
+                    ]
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn root(layout: &StorageLayout, label: &str) -> StorageItem {
+        layout
+            .roots()
+            .into_iter()
+            .find(|i| i.path == label)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_storage_layout() {
+        let layout = sample_layout();
+        assert_eq!(layout.contract, "src/Vault.sol:Vault");
+        let roots = layout.roots();
+        assert_eq!(roots.len(), 8);
+        assert_eq!(roots[1].offset, 20);
+        assert_eq!(roots[3].encoding, Encoding::Mapping);
+        assert_eq!(roots[6].encoding, Encoding::Bytes);
+        assert!(parse_storage_layout(&json!({"types": {}})).is_none());
+    }
+
+    #[test]
+    fn test_parse_out_of_range_numbers() {
+        let layout = json!({
+            "storage": [
+                {"label": "x", "offset": 0, "slot": "0", "type": "t_uint256"}
+            ],
+            "types": {
+                "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "1"}
+            }
+        });
+        assert!(parse_storage_layout(&layout).is_some());
+
+        let mut huge_size = layout.clone();
+        huge_size["types"]["t_uint256"]["numberOfBytes"] = json!("1".repeat(40));
+        assert!(parse_storage_layout(&huge_size).is_none());
+
+        let mut huge_offset = layout;
+        huge_offset["storage"][0]["offset"] = json!("1".repeat(40));
+        assert!(parse_storage_layout(&huge_offset).is_none());
+    }
+
+    #[test]
+    fn test_packed_values() {
+        // version = 3, paused = true, owner = 0x11…11
+        let word = b256!("0000000000000000000003011111111111111111111111111111111111111111");
+        let layout = sample_layout();
+        let decode = |label: &str| {
+            let item = root(&layout, label);
+            decode_value(&item.label, packed_bytes(&word, item.offset, item.size))
+        };
+        assert_eq!(
+            decode("owner"),
+            Address::repeat_byte(0x11).to_checksum(None)
+        );
+        assert_eq!(decode("paused"), "true");
+        assert_eq!(decode("version"), "3");
+    }
+
+    #[test]
+    fn test_decode_signed_and_fixed_bytes() {
+        assert_eq!(decode_value("int8", &[0xff]), "-1");
+        assert_eq!(decode_value("int16", &[0x00, 0x80]), "128");
+        assert_eq!(decode_value("int256", &[0xff; 32]), "-1");
+        assert_eq!(
+            decode_value("bytes4", &[0xa9, 0x05, 0x9c, 0xbb]),
+            "0xa9059cbb"
+        );
+        assert_eq!(decode_value("enum Vault.State", &[2]), "2");
+    }
+
+    #[test]
+    fn test_mapping_entry_slot() {
+        let layout = sample_layout();
+        let balances = root(&layout, "balances");
+        let key = Address::repeat_byte(0xab);
+        let entry = layout.mapping_entry(&balances, &key.to_string()).unwrap();
+
+        let mut preimage = key.into_word().to_vec();
+        preimage.extend_from_slice(&U256::from(1).to_be_bytes::<32>());
+        assert_eq!(entry.slot, U256::from_be_bytes(keccak256(&preimage).0));
+        assert_eq!(entry.label, "uint256");
+        assert!(entry.path.starts_with("balances[0x"));
+        assert!(layout.mapping_entry(&balances, "not an address").is_err());
+    }
+
+    #[test]
+    fn test_encode_key() {
+        // keccak256(uint256(0) . uint256(0)), the slot of m[0] for a mapping at slot 0
+        let mut preimage = encode_key("uint256", "0").unwrap();
+        preimage.extend_from_slice(&[0; 32]);
+        assert_eq!(
+            keccak256(&preimage),
+            b256!("ad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5")
+        );
+        assert_eq!(encode_key("int8", "-1").unwrap(), vec![0xff; 32]);
+        assert_eq!(encode_key("uint8", "0x10").unwrap()[31], 0x10);
+        assert_eq!(encode_key("bool", "true").unwrap()[31], 1);
+        assert_eq!(encode_key("string", "abc").unwrap(), b"abc".to_vec());
+        assert_eq!(
+            &encode_key("bytes4", "0xa9059cbb").unwrap()[..5],
+            &[0xa9, 0x05, 0x9c, 0xbb, 0]
+        );
+        assert!(encode_key("bytes2", "0xa9059cbb").is_err());
+    }
+
+    #[test]
+    fn test_encode_key_bit_width() {
+        assert_eq!(encode_key("uint8", "255").unwrap()[31], 0xff);
+        assert!(encode_key("uint8", "256").is_err());
+        assert!(encode_key("uint16", "0x10000").is_err());
+        assert!(encode_key("enum Side", "256").is_err());
+        assert!(encode_key("int8", "1000").is_err());
+        assert!(encode_key("int8", "-1000").is_err());
+        assert!(encode_key("int8", "128").is_err());
+        assert_eq!(encode_key("int8", "127").unwrap()[31], 0x7f);
+        let min = encode_key("int8", "-128").unwrap();
+        assert_eq!(min, I256::try_from(-128).unwrap().to_be_bytes::<32>().to_vec());
+
+        // The int256 minimum, whose magnitude does not fit an I256
+        let half = U256::from(1) << 255usize;
+        let min = format!("-{half}");
+        assert_eq!(encode_key("int256", &min).unwrap(), I256::MIN.to_be_bytes::<32>().to_vec());
+        assert_eq!(encode_key("int", &min).unwrap(), I256::MIN.to_be_bytes::<32>().to_vec());
+        assert!(encode_key("int256", &half.to_string()).is_err());
+        assert_eq!(
+            encode_key("int256", &I256::MAX.to_string()).unwrap(),
+            I256::MAX.to_be_bytes::<32>().to_vec()
+        );
+        assert!(encode_key("uint256", &U256::MAX.to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_dynamic_array_elements() {
+        let layout = sample_layout();
+        let stamps = root(&layout, "stamps");
+        let start = U256::from_be_bytes(keccak256(U256::from(2).to_be_bytes::<32>()).0);
+        // Four uint64 elements share a slot
+        let fifth = layout.array_element(&stamps, U256::from(5)).unwrap();
+        assert_eq!(fifth.slot, start + U256::from(1));
+        assert_eq!(fifth.offset, 8);
+        assert_eq!(fifth.path, "stamps[5]");
+    }
+
+    #[test]
+    fn test_static_array_elements() {
+        let layout = sample_layout();
+        let selectors = root(&layout, "selectors");
+        assert_eq!(layout.static_length(&selectors), Some(U256::from(3)));
+        let third = layout.array_element(&selectors, U256::from(2)).unwrap();
+        assert_eq!(third.slot, U256::from(6));
+        assert_eq!(third.offset, 8);
+        assert!(layout.array_element(&selectors, U256::from(3)).is_err());
+        assert_eq!(layout.static_length(&root(&layout, "stamps")), None);
+    }
+
+    #[test]
+    fn test_struct_members() {
+        let layout = sample_layout();
+        let members = layout.members(&root(&layout, "config"));
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[1].path, "config.admin");
+        assert_eq!(members[1].slot, U256::from(4));
+    }
+
+    #[test]
+    fn test_bytes_storage() {
+        // "abc": data in the high bytes, length * 2 in the lowest
+        let mut word = [0u8; 32];
+        word[..3].copy_from_slice(b"abc");
+        word[31] = 6;
+        let short = bytes_storage(&B256::from(word));
+        assert_eq!(short, BytesStorage::Short(b"abc".to_vec()));
+
+        // 100 bytes: length * 2 + 1
+        let long = B256::from(U256::from(201));
+        assert_eq!(bytes_storage(&long), BytesStorage::Long(100));
+        let slots = bytes_data_slots(U256::ZERO, 100);
+        assert_eq!(slots.len(), 4);
+        assert_eq!(
+            B256::from(slots[0]),
+            b256!("290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563")
+        );
+        assert_eq!(bytes_data_slots(U256::ZERO, 10_000).len(), MAX_BYTES_SLOTS);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes("string", b"hi", 2), "\"hi\"");
+        assert_eq!(format_bytes("bytes", &[0xde, 0xad], 2), "0xdead");
+        assert_eq!(
+            format_bytes("bytes", &[0xde, 0xad], 40),
+            "0xdead\u{2026} (40 bytes)"
+        );
+    }
+}
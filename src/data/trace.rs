@@ -0,0 +1,834 @@
+use alloy::primitives::{Address, Bytes, B256, U256};
+use alloy::transports::TransportError;
+use color_eyre::eyre::{eyre, Report, Result};
+
+use crate::data::decoder::TxDecoder;
+use crate::data::provider::EthProvider;
+use crate::data::types::{ExecutionTrace, TraceStep};
+
+/// Options for the default (struct log) tracer of `debug_traceTransaction`.
+///
+/// The stack and return data are always captured since the debugger cannot
+/// work without them. Memory and storage capture are opt-in because they
+/// make responses for large transactions very big.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TraceOptions {
+    pub enable_memory: bool,
+    pub enable_storage: bool,
+}
+
+impl TraceOptions {
+    /// Build the tracer config object passed as the second RPC parameter.
+    pub fn to_tracer_config(self) -> serde_json::Value {
+        serde_json::json!({
+            "disableStack": false,
+            "enableMemory": self.enable_memory,
+            "disableStorage": !self.enable_storage,
+            "enableReturnData": true,
+        })
+    }
+}
+
+/// Opcodes that open a new call frame executing the code of `stack[1]`.
+const CALL_OPS: &[&str] = &["CALL", "CALLCODE", "DELEGATECALL", "STATICCALL"];
+const CREATE_OPS: &[&str] = &["CREATE", "CREATE2"];
+
+/// Fetch an opcode-level execution trace using the struct logger.
+///
+/// `root` is the address whose code runs in the top-level frame (the
+/// recipient, or the created contract for deployments); it is used to
+/// attribute every step to the contract executing it. `deployment` marks
+/// the top-level frame as running init code.
+pub async fn fetch_struct_log_trace(
+    provider: &EthProvider,
+    tx_hash: B256,
+    root: Address,
+    deployment: bool,
+    options: TraceOptions,
+) -> Result<ExecutionTrace> {
+    let params = serde_json::json!([format!("{tx_hash:?}"), options.to_tracer_config()]);
+    let result = provider
+        .raw_request("debug_traceTransaction", params)
+        .await?;
+    let mut trace = parse_struct_logs(&result)?;
+    annotate_code_addresses(&mut trace.steps, root, deployment);
+    Ok(trace)
+}
+
+/// Parse a struct logger result (`{gas, failed, returnValue, structLogs}`)
+/// into an `ExecutionTrace`.
+pub fn parse_struct_logs(result: &serde_json::Value) -> Result<ExecutionTrace> {
+    let logs = result["structLogs"]
+        .as_array()
+        .ok_or_else(|| eyre!("Expected structLogs array from debug_traceTransaction"))?;
+
+    let steps = logs.iter().map(parse_struct_log).collect();
+
+    let failed = result["failed"].as_bool().unwrap_or(false);
+    let return_value = result["returnValue"]
+        .as_str()
+        .and_then(parse_hex_bytes)
+        .unwrap_or_default();
+    // Custom errors need the contract's ABI; the caller can refine this
+    let revert_reason = if failed {
+        TxDecoder::decode_revert(&return_value, None)
+    } else {
+        None
+    };
+
+    Ok(ExecutionTrace {
+        steps,
+        gas_used: parse_u64(&result["gas"]).unwrap_or(0),
+        failed,
+        return_value,
+        revert_reason,
+    })
+}
+
+fn parse_struct_log(log: &serde_json::Value) -> TraceStep {
+    // Geth reports the stack bottom-first, so the last entry is the top.
+    let stack = log["stack"]
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().and_then(parse_hex_u256))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Memory comes as a list of 32-byte words
+    let memory = log["memory"]
+        .as_array()
+        .map(|words| {
+            words
+                .iter()
+                .filter_map(|w| w.as_str().and_then(parse_hex_bytes))
+                .flat_map(|w| w.to_vec())
+                .collect::<Vec<u8>>()
+        })
+        .map(Bytes::from)
+        .unwrap_or_default();
+
+    let mut storage: Vec<(U256, U256)> = log["storage"]
+        .as_object()
+        .map(|slots| {
+            slots
+                .iter()
+                .filter_map(|(slot, value)| {
+                    Some((parse_hex_u256(slot)?, parse_hex_u256(value.as_str()?)?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    storage.sort_by_key(|(slot, _)| *slot);
+
+    TraceStep {
+        address: Address::ZERO,
+        in_create: false,
+        pc: parse_u64(&log["pc"]).unwrap_or(0),
+        op: log["op"].as_str().unwrap_or("UNKNOWN").to_string(),
+        gas: parse_u64(&log["gas"]).unwrap_or(0),
+        gas_cost: parse_u64(&log["gasCost"]).unwrap_or(0),
+        depth: parse_u64(&log["depth"]).unwrap_or(0) as usize,
+        stack,
+        memory,
+        storage,
+        return_data: log["returnData"]
+            .as_str()
+            .and_then(parse_hex_bytes)
+            .unwrap_or_default(),
+        error: log["error"]
+            .as_str()
+            .filter(|e| !e.is_empty())
+            .map(|e| e.to_string()),
+    }
+}
+
+/// Whether a failed trace request was refused because the node does not
+/// serve the method, as opposed to failing while tracing.
+///
+/// Nodes answer with "method not found" (-32601), "method not supported"
+/// (-32004) or a server error naming the method as unavailable.
+pub fn method_unsupported(error: &Report) -> bool {
+    let Some(payload) = error
+        .downcast_ref::<TransportError>()
+        .and_then(|e| e.as_error_resp())
+    else {
+        return false;
+    };
+    if matches!(payload.code, -32601 | -32004) {
+        return true;
+    }
+    let message = payload.message.to_ascii_lowercase();
+    message.contains("method")
+        && ["not supported", "unsupported", "not available", "does not exist", "not found"]
+            .iter()
+            .any(|phrase| message.contains(phrase))
+}
+
+/// Fill in `TraceStep::address` and `TraceStep::in_create` by following
+/// call frames from the root, which runs init code if `deployment` is set.
+///
+/// CALL-family frames run the code at `stack[1]`; the address of a CREATE
+/// frame is only known once it returns and the parent's stack holds it.
+pub fn annotate_code_addresses(steps: &mut [TraceStep], root: Address, deployment: bool) {
+    struct Frame {
+        address: Address,
+        start: usize,
+        depth: usize,
+        is_create: bool,
+    }
+
+    let mut frames: Vec<Frame> = Vec::new();
+    for i in 0..steps.len() {
+        let depth = steps[i].depth;
+
+        // Pop frames that have returned; patch created addresses now known
+        while frames.last().is_some_and(|f| f.depth > depth) {
+            let frame = frames.pop().expect("checked by is_some_and");
+            if frame.is_create {
+                let created = stack_arg(&steps[i], 0)
+                    .map(|v| Address::from_word(v.into()))
+                    .unwrap_or(Address::ZERO);
+                for step in steps[frame.start..i].iter_mut() {
+                    if step.depth == frame.depth {
+                        step.address = created;
+                    }
+                }
+            }
+        }
+        if frames.is_empty() {
+            frames.push(Frame {
+                address: root,
+                start: i,
+                depth,
+                is_create: deployment,
+            });
+        }
+
+        let current = frames.last().expect("at least the root frame");
+        steps[i].address = current.address;
+        steps[i].in_create = current.is_create;
+
+        // A new frame starts only if the next step is deeper; calls to
+        // precompiles and EOAs do not produce steps.
+        let enters_frame = steps.get(i + 1).is_some_and(|next| next.depth > depth);
+        if !enters_frame {
+            continue;
+        }
+        let op = steps[i].op.as_str();
+        if CALL_OPS.contains(&op) {
+            let callee = stack_arg(&steps[i], 1)
+                .map(|v| Address::from_word(v.into()))
+                .unwrap_or(Address::ZERO);
+            frames.push(Frame {
+                address: callee,
+                start: i + 1,
+                depth: depth + 1,
+                is_create: false,
+            });
+        } else if CREATE_OPS.contains(&op) {
+            frames.push(Frame {
+                address: Address::ZERO,
+                start: i + 1,
+                depth: depth + 1,
+                is_create: true,
+            });
+        }
+    }
+}
+
+/// Step over the current op: if it opens a call frame, skip to the first
+/// step back in the current frame, otherwise move to the next step.
+pub fn step_over(steps: &[TraceStep], index: usize) -> usize {
+    let Some(current) = steps.get(index) else {
+        return index;
+    };
+    steps
+        .iter()
+        .enumerate()
+        .skip(index + 1)
+        .find(|(_, s)| s.depth <= current.depth)
+        .map(|(i, _)| i)
+        .unwrap_or(steps.len().saturating_sub(1))
+}
+
+/// Step out of the current frame to the first step back in the parent.
+/// In the top-level frame this moves to the last step.
+pub fn step_out(steps: &[TraceStep], index: usize) -> usize {
+    let Some(current) = steps.get(index) else {
+        return index;
+    };
+    steps
+        .iter()
+        .enumerate()
+        .skip(index + 1)
+        .find(|(_, s)| s.depth < current.depth)
+        .map(|(i, _)| i)
+        .unwrap_or(steps.len().saturating_sub(1))
+}
+
+/// Find the next step after `index` that satisfies `pred`.
+pub fn find_next(
+    steps: &[TraceStep],
+    index: usize,
+    pred: impl Fn(&TraceStep) -> bool,
+) -> Option<usize> {
+    steps
+        .iter()
+        .enumerate()
+        .skip(index + 1)
+        .find(|(_, s)| pred(s))
+        .map(|(i, _)| i)
+}
+
+/// Find the closest step before `index` that satisfies `pred`.
+pub fn find_prev(
+    steps: &[TraceStep],
+    index: usize,
+    pred: impl Fn(&TraceStep) -> bool,
+) -> Option<usize> {
+    steps[..index.min(steps.len())]
+        .iter()
+        .rposition(pred)
+}
+
+/// Locate the first failing step: one the node flagged with an error, or
+/// failing that the first REVERT/INVALID.
+pub fn first_error(steps: &[TraceStep]) -> Option<usize> {
+    steps
+        .iter()
+        .position(|s| s.error.is_some())
+        .or_else(|| steps.iter().position(|s| s.op == "REVERT" || s.op == "INVALID"))
+}
+
+/// A user breakpoint in the debugger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Program counter, optionally restricted to one contract's code.
+    Pc { pc: u64, address: Option<Address> },
+    Opcode(String),
+    /// Any step executing this contract's code.
+    Address(Address),
+}
+
+impl Breakpoint {
+    /// Parse user input: a `0x` address, a program counter (decimal or
+    /// `pc:` prefixed, optionally `@0x..` scoped), or an opcode name.
+    pub fn parse(input: &str) -> Option<Breakpoint> {
+        let input = input.trim();
+        if input.is_empty() {
+            return None;
+        }
+
+        let (pc_part, scope) = match input.split_once('@') {
+            Some((pc, addr)) => (pc, Some(addr.trim().parse::<Address>().ok()?)),
+            None => (input, None),
+        };
+        let pc_str = pc_part.trim().trim_start_matches("pc:").trim();
+        let pc = match pc_str.strip_prefix("0x") {
+            Some(hex) if scope.is_some() || input.starts_with("pc:") => {
+                u64::from_str_radix(hex, 16).ok()
+            }
+            Some(_) => None,
+            None => pc_str.parse::<u64>().ok(),
+        };
+        if let Some(pc) = pc {
+            return Some(Breakpoint::Pc { pc, address: scope });
+        }
+        if scope.is_some() {
+            return None;
+        }
+
+        if input.starts_with("0x") {
+            return input.parse::<Address>().ok().map(Breakpoint::Address);
+        }
+
+        if input.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Some(Breakpoint::Opcode(input.to_ascii_uppercase()));
+        }
+
+        None
+    }
+
+    pub fn matches(&self, step: &TraceStep) -> bool {
+        match self {
+            Breakpoint::Pc { pc, address } => {
+                step.pc == *pc && address.is_none_or(|a| a == step.address)
+            }
+            Breakpoint::Opcode(op) => step.op == *op,
+            Breakpoint::Address(address) => step.address == *address,
+        }
+    }
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Breakpoint::Pc { pc, address: Some(a) } => write!(f, "pc {pc} @ {a}"),
+            Breakpoint::Pc { pc, address: None } => write!(f, "pc {pc}"),
+            Breakpoint::Opcode(op) => write!(f, "op {op}"),
+            Breakpoint::Address(a) => write!(f, "addr {a}"),
+        }
+    }
+}
+
+/// A storage slot accessed by SLOAD or SSTORE within a call frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageAccess {
+    pub slot: U256,
+    /// Loaded or stored value, if it can be determined from the trace.
+    pub value: Option<U256>,
+    pub written: bool,
+}
+
+/// Return the `n`-th stack item counted from the top (0 = top).
+pub fn stack_arg(step: &TraceStep, n: usize) -> Option<U256> {
+    step.stack.len().checked_sub(n + 1).map(|i| step.stack[i])
+}
+
+/// Memory regions `(offset, length)` read or written by the step's opcode,
+/// derived from its stack arguments.
+pub fn memory_access_ranges(step: &TraceStep) -> Vec<(usize, usize)> {
+    // (offset stack index, size stack index or fixed size)
+    enum Size {
+        Fixed(usize),
+        Stack(usize),
+    }
+    let regions: &[(usize, Size)] = match step.op.as_str() {
+        "MLOAD" | "MSTORE" => &[(0, Size::Fixed(32))],
+        "MSTORE8" => &[(0, Size::Fixed(1))],
+        "KECCAK256" | "SHA3" | "RETURN" | "REVERT" => &[(0, Size::Stack(1))],
+        "LOG0" | "LOG1" | "LOG2" | "LOG3" | "LOG4" => &[(0, Size::Stack(1))],
+        "CALLDATACOPY" | "CODECOPY" | "RETURNDATACOPY" => &[(0, Size::Stack(2))],
+        "EXTCODECOPY" => &[(1, Size::Stack(3))],
+        "MCOPY" => &[(0, Size::Stack(2)), (1, Size::Stack(2))],
+        "CREATE" | "CREATE2" => &[(1, Size::Stack(2))],
+        "CALL" | "CALLCODE" => &[(3, Size::Stack(4)), (5, Size::Stack(6))],
+        "DELEGATECALL" | "STATICCALL" => &[(2, Size::Stack(3)), (4, Size::Stack(5))],
+        _ => &[],
+    };
+
+    regions
+        .iter()
+        .filter_map(|(offset_idx, size)| {
+            let offset: usize = stack_arg(step, *offset_idx)?.try_into().ok()?;
+            let len = match size {
+                Size::Fixed(n) => *n,
+                Size::Stack(idx) => stack_arg(step, *idx)?.try_into().ok()?,
+            };
+            (len > 0).then_some((offset, len))
+        })
+        .collect()
+}
+
+/// Collect the storage slots read or written in the call frame of `index`,
+/// up to and including that step. Later accesses to a slot replace earlier ones.
+pub fn frame_storage_accesses(steps: &[TraceStep], index: usize) -> Vec<StorageAccess> {
+    let Some(current) = steps.get(index) else {
+        return Vec::new();
+    };
+    let depth = current.depth;
+
+    // Walk back to the first step of the current frame, skipping child frames
+    let mut start = index;
+    while start > 0 && steps[start - 1].depth >= depth {
+        start -= 1;
+    }
+
+    let mut accesses: Vec<StorageAccess> = Vec::new();
+    for i in start..=index {
+        let step = &steps[i];
+        if step.depth != depth {
+            continue;
+        }
+        let access = match step.op.as_str() {
+            "SSTORE" => stack_arg(step, 0).map(|slot| StorageAccess {
+                slot,
+                value: stack_arg(step, 1),
+                written: true,
+            }),
+            "SLOAD" => stack_arg(step, 0).map(|slot| StorageAccess {
+                slot,
+                // The loaded value is on top of the stack at the next step
+                value: steps.get(i + 1).and_then(|next| stack_arg(next, 0)),
+                written: false,
+            }),
+            _ => None,
+        };
+        if let Some(access) = access {
+            match accesses.iter_mut().find(|a| a.slot == access.slot) {
+                Some(existing) => {
+                    existing.written |= access.written;
+                    existing.value = access.value;
+                }
+                None => accesses.push(access),
+            }
+        }
+    }
+
+    // Captured storage is authoritative for values when available
+    for (slot, value) in &current.storage {
+        if let Some(existing) = accesses.iter_mut().find(|a| a.slot == *slot) {
+            existing.value = Some(*value);
+        }
+    }
+
+    accesses
+}
+
+/// Parse a number that nodes report either as a JSON number or a hex string.
+pub(crate) fn parse_u64(value: &serde_json::Value) -> Option<u64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+/// Parse a hex word with or without the `0x` prefix (older Geth omits it).
+pub(crate) fn parse_hex_u256(s: &str) -> Option<U256> {
+    let hex = s.trim_start_matches("0x");
+    if hex.is_empty() {
+        return Some(U256::ZERO);
+    }
+    U256::from_str_radix(hex, 16).ok()
+}
+
+pub(crate) fn parse_hex_bytes(s: &str) -> Option<Bytes> {
+    alloy::primitives::hex::decode(s.trim_start_matches("0x"))
+        .ok()
+        .map(Bytes::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracer_config_defaults() {
+        let config = TraceOptions::default().to_tracer_config();
+        assert_eq!(config["disableStack"], false);
+        assert_eq!(config["enableMemory"], false);
+        assert_eq!(config["disableStorage"], true);
+        assert_eq!(config["enableReturnData"], true);
+    }
+
+    #[test]
+    fn test_tracer_config_with_memory_and_storage() {
+        let options = TraceOptions {
+            enable_memory: true,
+            enable_storage: true,
+        };
+        let config = options.to_tracer_config();
+        assert_eq!(config["enableMemory"], true);
+        assert_eq!(config["disableStorage"], false);
+    }
+
+    #[test]
+    fn test_parse_struct_logs() {
+        let result = serde_json::json!({
+            "gas": 21164,
+            "failed": false,
+            "returnValue": "0x01",
+            "structLogs": [
+                {"pc": 0, "op": "PUSH1", "gas": 78636, "gasCost": 3, "depth": 1, "stack": []},
+                {"pc": 2, "op": "PUSH1", "gas": 78633, "gasCost": 3, "depth": 1, "stack": ["0x80"]},
+                {"pc": 4, "op": "MSTORE", "gas": 78630, "gasCost": 12, "depth": 1,
+                 "stack": ["0x80", "0x40"]}
+            ]
+        });
+
+        let trace = parse_struct_logs(&result).unwrap();
+        assert_eq!(trace.gas_used, 21164);
+        assert!(!trace.failed);
+        assert_eq!(trace.return_value, Bytes::from(vec![0x01]));
+        assert_eq!(trace.steps.len(), 3);
+        assert_eq!(trace.steps[2].op, "MSTORE");
+        assert_eq!(trace.steps[2].gas_cost, 12);
+        assert_eq!(trace.steps[2].depth, 1);
+        assert_eq!(trace.steps[2].stack, vec![U256::from(0x80), U256::from(0x40)]);
+        assert!(trace.revert_reason.is_none());
+    }
+
+    #[test]
+    fn test_parse_struct_logs_revert_reason() {
+        use alloy::sol_types::SolError;
+
+        let data = alloy::sol_types::Panic::from(0x12).abi_encode();
+        let result = serde_json::json!({
+            "gas": 22000,
+            "failed": true,
+            "returnValue": alloy::hex::encode(&data),
+            "structLogs": []
+        });
+
+        let trace = parse_struct_logs(&result).unwrap();
+        assert!(trace.failed);
+        assert_eq!(
+            trace.revert_reason.as_deref(),
+            Some("Panic(0x12): division or modulo by zero")
+        );
+    }
+
+    #[test]
+    fn test_parse_struct_logs_legacy_stack_format() {
+        // Older Geth versions emit 64-char words without the 0x prefix
+        let result = serde_json::json!({
+            "gas": "0x5208",
+            "failed": true,
+            "returnValue": "",
+            "structLogs": [
+                {"pc": 10, "op": "REVERT", "gas": 100, "gasCost": 0, "depth": 2,
+                 "stack": ["0000000000000000000000000000000000000000000000000000000000000004"],
+                 "error": "execution reverted"}
+            ]
+        });
+
+        let trace = parse_struct_logs(&result).unwrap();
+        assert_eq!(trace.gas_used, 21000);
+        assert!(trace.failed);
+        assert!(trace.return_value.is_empty());
+        assert_eq!(trace.steps[0].stack, vec![U256::from(4)]);
+        assert_eq!(trace.steps[0].error.as_deref(), Some("execution reverted"));
+    }
+
+    #[test]
+    fn test_parse_struct_logs_memory_storage_return_data() {
+        let result = serde_json::json!({
+            "gas": 30000,
+            "failed": false,
+            "returnValue": "",
+            "structLogs": [
+                {"pc": 7, "op": "SLOAD", "gas": 100, "gasCost": 2100, "depth": 1,
+                 "stack": ["0x0"],
+                 "memory": [
+                    "0000000000000000000000000000000000000000000000000000000000000000",
+                    "00000000000000000000000000000000000000000000000000000000000000ff"
+                 ],
+                 "storage": {
+                    "0000000000000000000000000000000000000000000000000000000000000002": "0x05",
+                    "0x01": "0x0a"
+                 },
+                 "returnData": "0xdeadbeef"}
+            ]
+        });
+
+        let trace = parse_struct_logs(&result).unwrap();
+        let step = &trace.steps[0];
+        assert_eq!(step.memory.len(), 64);
+        assert_eq!(step.memory[63], 0xff);
+        assert_eq!(
+            step.storage,
+            vec![
+                (U256::from(1), U256::from(10)),
+                (U256::from(2), U256::from(5)),
+            ]
+        );
+        assert_eq!(step.return_data, Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]));
+    }
+
+    fn step(op: &str, depth: usize, stack: &[u64]) -> TraceStep {
+        TraceStep {
+            address: Address::ZERO,
+            in_create: false,
+            pc: 0,
+            op: op.to_string(),
+            gas: 0,
+            gas_cost: 0,
+            depth,
+            stack: stack.iter().map(|v| U256::from(*v)).collect(),
+            memory: Bytes::new(),
+            storage: vec![],
+            return_data: Bytes::new(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_memory_access_ranges() {
+        // Stack is bottom-first, so the last element is the top
+        assert_eq!(memory_access_ranges(&step("MSTORE", 1, &[7, 0x40])), vec![(0x40, 32)]);
+        assert_eq!(memory_access_ranges(&step("MSTORE8", 1, &[7, 0x10])), vec![(0x10, 1)]);
+        assert_eq!(memory_access_ranges(&step("RETURN", 1, &[0x20, 0x80])), vec![(0x80, 0x20)]);
+        // CALL: gas, addr, value, argsOffset, argsSize, retOffset, retSize (top first)
+        assert_eq!(
+            memory_access_ranges(&step("CALL", 1, &[0x20, 0x100, 0x44, 0x80, 0, 1, 5000])),
+            vec![(0x80, 0x44), (0x100, 0x20)]
+        );
+        // Zero-length regions and unrelated ops are ignored
+        assert!(memory_access_ranges(&step("REVERT", 1, &[0, 0])).is_empty());
+        assert!(memory_access_ranges(&step("ADD", 1, &[1, 2])).is_empty());
+        // Missing stack items yield nothing rather than panicking
+        assert!(memory_access_ranges(&step("MLOAD", 1, &[])).is_empty());
+    }
+
+    #[test]
+    fn test_frame_storage_accesses() {
+        let steps = vec![
+            step("SLOAD", 1, &[3]),
+            step("PUSH1", 1, &[42]),
+            // Child frame accesses are not part of the parent frame
+            step("SSTORE", 2, &[9, 1]),
+            step("SSTORE", 1, &[7, 3]),
+            step("SLOAD", 1, &[4]),
+            step("STOP", 1, &[11]),
+        ];
+
+        let accesses = frame_storage_accesses(&steps, 5);
+        assert_eq!(
+            accesses,
+            vec![
+                StorageAccess { slot: U256::from(3), value: Some(U256::from(7)), written: true },
+                StorageAccess { slot: U256::from(4), value: Some(U256::from(11)), written: false },
+            ]
+        );
+
+        // Inside the child frame only its own accesses are visible
+        let child = frame_storage_accesses(&steps, 2);
+        assert_eq!(child.len(), 1);
+        assert_eq!(child[0].slot, U256::from(1));
+        assert!(child[0].written);
+    }
+
+    fn nested_call_steps() -> Vec<TraceStep> {
+        let callee = U256::from_be_slice(&[0x22; 20]);
+        let mut call = step("CALL", 1, &[]);
+        // CALL args top-first: gas, addr, value, argsOffset, argsSize, retOffset, retSize
+        call.stack = vec![U256::ZERO, U256::ZERO, U256::ZERO, U256::ZERO, U256::ZERO, callee, U256::from(5000)];
+        vec![
+            step("PUSH1", 1, &[]),       // 0
+            call,                        // 1
+            step("PUSH1", 2, &[]),       // 2
+            step("SSTORE", 2, &[1, 2]),  // 3
+            step("RETURN", 2, &[0, 0]),  // 4
+            step("ISZERO", 1, &[1]),     // 5
+            step("CREATE", 1, &[0, 0, 0]), // 6
+            step("PUSH1", 2, &[]),       // 7
+            step("RETURN", 2, &[0, 0]),  // 8
+            step("POP", 1, &[0x33]),     // 9
+            step("STOP", 1, &[]),        // 10
+        ]
+    }
+
+    #[test]
+    fn test_annotate_code_addresses() {
+        let root = Address::from_slice(&[0x11; 20]);
+        let mut steps = nested_call_steps();
+        annotate_code_addresses(&mut steps, root, false);
+
+        assert_eq!(steps[0].address, root);
+        assert_eq!(steps[1].address, root);
+        assert_eq!(steps[2].address, Address::from_slice(&[0x22; 20]));
+        assert_eq!(steps[4].address, Address::from_slice(&[0x22; 20]));
+        assert_eq!(steps[5].address, root);
+        // The CREATE frame is attributed to the address it returned
+        let mut created = [0u8; 20];
+        created[19] = 0x33;
+        assert_eq!(steps[7].address, Address::from(created));
+        assert_eq!(steps[8].address, Address::from(created));
+        assert_eq!(steps[10].address, root);
+        // Only the CREATE frame runs init code
+        assert!(steps[7].in_create && steps[8].in_create);
+        assert!(!steps[2].in_create && !steps[6].in_create && !steps[9].in_create);
+
+        // A deployment's top-level frame is init code too
+        let mut steps = nested_call_steps();
+        annotate_code_addresses(&mut steps, root, true);
+        assert_eq!(steps[0].address, root);
+        assert!(steps[0].in_create && steps[10].in_create);
+        assert!(!steps[2].in_create);
+    }
+
+    #[test]
+    fn test_step_over_and_out() {
+        let steps = nested_call_steps();
+        // Over a CALL lands on the first step back in the caller
+        assert_eq!(step_over(&steps, 1), 5);
+        // Over a plain op is the next step
+        assert_eq!(step_over(&steps, 0), 1);
+        // Out of the callee returns to the caller
+        assert_eq!(step_out(&steps, 3), 5);
+        // Out of the top-level frame goes to the end
+        assert_eq!(step_out(&steps, 5), 10);
+    }
+
+    #[test]
+    fn test_find_next_prev_and_first_error() {
+        let mut steps = nested_call_steps();
+        assert_eq!(find_next(&steps, 0, |s| s.op == "SSTORE"), Some(3));
+        assert_eq!(find_next(&steps, 3, |s| s.op == "SSTORE"), None);
+        assert_eq!(find_prev(&steps, 10, |s| s.op == "RETURN"), Some(8));
+        assert_eq!(find_prev(&steps, 2, |s| s.op == "RETURN"), None);
+
+        assert_eq!(first_error(&steps), None);
+        steps[8].op = "REVERT".to_string();
+        assert_eq!(first_error(&steps), Some(8));
+        steps[4].error = Some("out of gas".to_string());
+        assert_eq!(first_error(&steps), Some(4));
+    }
+
+    #[test]
+    fn test_breakpoint_parse() {
+        let addr: Address = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".parse().unwrap();
+        assert_eq!(Breakpoint::parse("123"), Some(Breakpoint::Pc { pc: 123, address: None }));
+        assert_eq!(Breakpoint::parse("pc:0x1f"), Some(Breakpoint::Pc { pc: 31, address: None }));
+        assert_eq!(
+            Breakpoint::parse("42@0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"),
+            Some(Breakpoint::Pc { pc: 42, address: Some(addr) })
+        );
+        assert_eq!(Breakpoint::parse("sstore"), Some(Breakpoint::Opcode("SSTORE".to_string())));
+        assert_eq!(
+            Breakpoint::parse("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"),
+            Some(Breakpoint::Address(addr))
+        );
+        assert_eq!(Breakpoint::parse(""), None);
+        assert_eq!(Breakpoint::parse("0x1234"), None);
+        assert_eq!(Breakpoint::parse("not an op"), None);
+    }
+
+    #[test]
+    fn test_breakpoint_matches() {
+        let root = Address::from_slice(&[0x11; 20]);
+        let mut steps = nested_call_steps();
+        annotate_code_addresses(&mut steps, root, false);
+        steps[3].pc = 7;
+
+        assert!(Breakpoint::Opcode("SSTORE".to_string()).matches(&steps[3]));
+        assert!(Breakpoint::Pc { pc: 7, address: None }.matches(&steps[3]));
+        assert!(!Breakpoint::Pc { pc: 7, address: Some(root) }.matches(&steps[3]));
+        assert!(Breakpoint::Address(root).matches(&steps[0]));
+        assert!(!Breakpoint::Address(root).matches(&steps[3]));
+    }
+
+    #[test]
+    fn test_method_unsupported() {
+        let response = |code: i64, message: &str| {
+            let payload = serde_json::json!({"code": code, "message": message});
+            let error: TransportError =
+                TransportError::ErrorResp(serde_json::from_value(payload).unwrap());
+            Report::new(error)
+        };
+        assert!(method_unsupported(&response(-32601, "Method not found")));
+        assert!(method_unsupported(&response(-32004, "Method not supported")));
+        assert!(method_unsupported(&response(
+            -32000,
+            "the method debug_traceTransaction does not exist/is not available"
+        )));
+        // Failures while tracing are the node's to report
+        assert!(!method_unsupported(&response(-32000, "transaction 0x12 not found")));
+        assert!(!method_unsupported(&response(-32000, "execution timeout")));
+        assert!(!method_unsupported(&response(429, "Too Many Requests")));
+        assert!(!method_unsupported(&eyre!("connection reset")));
+    }
+
+    #[test]
+    fn test_parse_struct_logs_missing_array() {
+        let result = serde_json::json!({"gas": 0});
+        assert!(parse_struct_logs(&result).is_err());
+    }
+}
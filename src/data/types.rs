@@ -1,6 +1,14 @@
+use std::collections::HashMap;
+
+use alloy::dyn_abi::DynSolValue;
+use alloy::json_abi::Param;
 use alloy::primitives::{Address, Bytes, B256, U256};
+use alloy::rpc::types::state::StateOverride;
+use alloy::rpc::types::{BlockNumberOrTag, BlockOverrides};
 use serde::{Deserialize, Serialize};
 
+use crate::data::explorer::ExplorerApi;
+
 #[derive(Debug, Clone)]
 pub struct BlockSummary {
     pub number: u64,
@@ -96,15 +104,101 @@ impl std::fmt::Display for TxStatus {
 #[derive(Debug, Clone)]
 pub struct DecodedCall {
     pub function_name: String,
-    pub params: Vec<(String, String)>,
+    pub params: Vec<DecodedParam>,
+    /// Calls carried in the arguments (multicall batches, Safe transactions,
+    /// router commands), decoded recursively
+    pub inner: Vec<InnerCall>,
 }
 
+/// A decoded argument, event input or return value, with the ABI parameter it
+/// was decoded against (type, name and struct components).
+#[derive(Debug, Clone)]
+pub struct DecodedParam {
+    pub param: Param,
+    /// `None` for an event input the log had no value for, kept so later
+    /// inputs stay in place
+    pub value: Option<DynSolValue>,
+}
+
+impl DecodedParam {
+    /// A parameter of a plain (non-tuple) type.
+    pub fn new(name: &str, ty: &str, value: DynSolValue) -> Self {
+        Self {
+            param: Param {
+                ty: ty.to_string(),
+                name: name.to_string(),
+                components: Vec::new(),
+                internal_type: None,
+            },
+            value: Some(value),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.param.name
+    }
+
+    /// An indexed event input of a dynamic type, of which the log only holds
+    /// the keccak256 hash.
+    pub fn is_topic_hash(&self) -> bool {
+        let ty = self.param.ty.as_str();
+        matches!(self.value, Some(DynSolValue::FixedBytes(_, 32)))
+            && (ty == "string" || ty == "bytes" || ty.ends_with(']') || ty.starts_with("tuple"))
+    }
+
+    /// The value on one line, with long bytes truncated.
+    pub fn formatted(&self) -> String {
+        let Some(value) = &self.value else {
+            return "?".to_string();
+        };
+        let value = crate::data::decoder::format_sol_value(value);
+        if self.is_topic_hash() {
+            format!("{value} (hash)")
+        } else {
+            value
+        }
+    }
+}
+
+/// A call encoded as data inside another call's arguments.
+#[derive(Debug, Clone)]
+pub struct InnerCall {
+    pub target: Address,
+    pub value: U256,
+    pub input: Bytes,
+    pub decoded: Option<DecodedCall>,
+}
+
+/// Token standard a transfer event belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenStandard {
+    ERC20,
+    ERC721,
+    ERC1155,
+}
+
+impl std::fmt::Display for TokenStandard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenStandard::ERC20 => write!(f, "ERC-20"),
+            TokenStandard::ERC721 => write!(f, "ERC-721"),
+            TokenStandard::ERC1155 => write!(f, "ERC-1155"),
+        }
+    }
+}
+
+/// One token moved by a transfer event. An ERC-1155 batch becomes one
+/// transfer per token id.
 #[derive(Debug, Clone)]
 pub struct TokenTransfer {
     pub token_address: Address,
+    pub standard: TokenStandard,
     pub from: Address,
     pub to: Address,
+    /// Amount moved; always 1 for ERC-721
     pub value: U256,
+    /// Id of the ERC-721 or ERC-1155 token moved
+    pub token_id: Option<U256>,
     pub token_name: Option<String>,
     pub token_symbol: Option<String>,
     pub decimals: Option<u8>,
@@ -123,14 +217,84 @@ pub struct AddressInfo {
 #[derive(Debug, Clone)]
 pub struct ContractInfo {
     pub abi_source: Option<String>,
-    pub is_proxy: bool,
-    pub implementation: Option<Address>,
+    /// How calls are forwarded, if the contract is a proxy
+    pub proxy: Option<ProxyInfo>,
     pub contract_type: Option<ContractType>,
     pub name: Option<String>,
+    /// Compiler of the verified source, when the explorer has it
+    pub compiler: Option<String>,
     pub symbol: Option<String>,
     pub decimals: Option<u8>,
 }
 
+/// The proxy pattern a contract forwards its calls with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyKind {
+    /// EIP-1167 clone with its target in the bytecode
+    MinimalProxy,
+    /// EIP-1967 proxy with an admin slot
+    Transparent,
+    /// EIP-1967 proxy upgraded through its implementation's `upgradeTo`
+    Uups,
+    /// EIP-1967 implementation slot with neither admin nor UUPS implementation
+    Eip1967,
+    /// EIP-1967 beacon slot; the beacon names the implementation
+    Beacon,
+    /// OpenZeppelin (zeppelinos) slots predating EIP-1967
+    OpenZeppelinLegacy,
+    /// Safe proxy with its singleton in slot 0
+    Safe,
+    /// EIP-2535 Diamond routing selectors to facets
+    Diamond,
+}
+
+impl std::fmt::Display for ProxyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProxyKind::MinimalProxy => write!(f, "EIP-1167 minimal proxy"),
+            ProxyKind::Transparent => write!(f, "EIP-1967 transparent proxy"),
+            ProxyKind::Uups => write!(f, "UUPS proxy"),
+            ProxyKind::Eip1967 => write!(f, "EIP-1967 proxy"),
+            ProxyKind::Beacon => write!(f, "EIP-1967 beacon proxy"),
+            ProxyKind::OpenZeppelinLegacy => write!(f, "OpenZeppelin legacy proxy"),
+            ProxyKind::Safe => write!(f, "Safe proxy"),
+            ProxyKind::Diamond => write!(f, "EIP-2535 Diamond"),
+        }
+    }
+}
+
+/// A detected proxy and where its calls go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyInfo {
+    pub kind: ProxyKind,
+    pub implementation: Option<Address>,
+    pub admin: Option<Address>,
+    pub beacon: Option<Address>,
+    /// Facets of a Diamond with the number of selectors each serves
+    pub facets: Vec<(Address, usize)>,
+}
+
+impl ProxyInfo {
+    pub fn new(kind: ProxyKind, implementation: Option<Address>) -> Self {
+        Self {
+            kind,
+            implementation,
+            admin: None,
+            beacon: None,
+            facets: Vec::new(),
+        }
+    }
+
+    /// Contracts whose code runs behind the proxy: the implementation or
+    /// every facet.
+    pub fn targets(&self) -> Vec<Address> {
+        self.implementation
+            .into_iter()
+            .chain(self.facets.iter().map(|(facet, _)| *facet))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContractType {
     ERC20,
@@ -177,6 +341,22 @@ mod tests {
         assert_eq!(ContractType::ERC1155.to_string(), "ERC-1155");
         assert_eq!(ContractType::Unknown.to_string(), "Contract");
     }
+
+    #[test]
+    fn test_proxy_targets() {
+        let implementation = Address::repeat_byte(1);
+        assert_eq!(
+            ProxyInfo::new(ProxyKind::Uups, Some(implementation)).targets(),
+            vec![implementation]
+        );
+
+        let mut diamond = ProxyInfo::new(ProxyKind::Diamond, None);
+        diamond.facets = vec![(Address::repeat_byte(2), 3), (Address::repeat_byte(3), 1)];
+        assert_eq!(
+            diamond.targets(),
+            vec![Address::repeat_byte(2), Address::repeat_byte(3)]
+        );
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -202,33 +382,212 @@ pub struct InternalCall {
     pub output: Bytes,
     pub depth: usize,
     pub error: Option<String>,
+    /// Callee function and arguments, once resolved
+    pub decoded: Option<DecodedCall>,
+    /// Decoded return values, once resolved
+    pub decoded_output: Vec<DecodedParam>,
+    /// Decoded revert data of a failed call, once resolved
+    pub revert_reason: Option<String>,
+}
+
+/// Internal activity of one transaction in a block-wide trace.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxTraceStats {
+    /// Internal calls, not counting the top-level call
+    pub call_count: usize,
+    /// ETH moved by internal calls that did not revert
+    pub internal_value: U256,
+}
+
+/// ETH moved or a contract created by an internal call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternalTransfer {
+    pub tx_hash: B256,
+    pub call_type: String,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub depth: usize,
+    /// The call or one of its callers reverted, so nothing moved
+    pub reverted: bool,
+}
+
+/// Internal calls of every transaction in a block, summarized.
+#[derive(Debug, Clone, Default)]
+pub struct BlockTrace {
+    pub number: u64,
+    pub stats: HashMap<B256, TxTraceStats>,
+    /// Value transfers and contract creations, in execution order
+    pub transfers: Vec<InternalTransfer>,
 }
 
 #[derive(Debug, Clone)]
 pub struct DecodedLog {
     pub address: Address,
     pub event_name: String,
-    pub params: Vec<(String, String)>,
+    pub params: Vec<DecodedParam>,
     pub topic0: B256,
+    /// Decoded from a bare event signature by guessing which inputs are indexed
+    pub heuristic: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct ExecutionTrace {
     pub steps: Vec<TraceStep>,
     pub gas_used: u64,
+    pub failed: bool,
+    pub return_value: Bytes,
+    /// Decoded revert data when the transaction failed
+    pub revert_reason: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct TraceStep {
+    /// Address whose code is executing (the callee for DELEGATECALL/CALLCODE).
+    pub address: Address,
+    /// Whether the step runs init code, whose pcs the runtime source map does not cover.
+    pub in_create: bool,
     pub pc: u64,
     pub op: String,
     pub gas: u64,
     pub gas_cost: u64,
     pub depth: usize,
     pub stack: Vec<U256>,
+    /// Memory before the op executes (empty unless memory capture is enabled).
+    pub memory: Bytes,
+    /// Storage slots the current contract touched so far (empty unless storage capture is enabled).
+    pub storage: Vec<(U256, U256)>,
+    /// Return data buffer of the most recent sub-call.
+    pub return_data: Bytes,
     pub error: Option<String>,
 }
 
+/// Gas attributed to one contract, function or opcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasEntry {
+    /// Contract the gas was spent in (None for opcode entries)
+    pub address: Option<Address>,
+    /// Function name, opcode, or empty for contract entries
+    pub label: String,
+    pub gas: u64,
+    /// Number of opcodes executed
+    pub count: u64,
+}
+
+/// Where a transaction's gas went, aggregated several ways.
+#[derive(Debug, Clone, Default)]
+pub struct GasProfile {
+    pub gas_used: u64,
+    /// Gas not spent executing opcodes (intrinsic cost net of refunds)
+    pub intrinsic: u64,
+    pub by_contract: Vec<GasEntry>,
+    pub by_function: Vec<GasEntry>,
+    pub by_opcode: Vec<GasEntry>,
+    /// Call stacks in flamegraph folded form (`frame;frame;OPCODE`) with their gas
+    pub folded: Vec<(String, u64)>,
+}
+
+/// Broad category of an opcode, for grouping gas in the trace summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OpClass {
+    Storage,
+    Memory,
+    Calls,
+    Hashing,
+    Logs,
+    /// Balance and code lookups of other accounts
+    Accounts,
+    /// Arithmetic, stack and control flow
+    Other,
+}
+
+impl std::fmt::Display for OpClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpClass::Storage => write!(f, "Storage"),
+            OpClass::Memory => write!(f, "Memory"),
+            OpClass::Calls => write!(f, "Calls"),
+            OpClass::Hashing => write!(f, "Hashing"),
+            OpClass::Logs => write!(f, "Logs"),
+            OpClass::Accounts => write!(f, "Accounts"),
+            OpClass::Other => write!(f, "Other"),
+        }
+    }
+}
+
+/// Cold and warm accesses of one storage opcode, told apart by gas cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccessCounts {
+    pub cold: u64,
+    pub warm: u64,
+    /// Costs that match neither (pre-Berlin rules or a failed op)
+    pub unknown: u64,
+}
+
+/// Aggregates over an opcode-level trace for the debugger's summary panel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TraceStats {
+    /// How often each opcode ran, most frequent first
+    pub opcode_counts: Vec<(String, u64)>,
+    /// Own gas spent per opcode class, most expensive first
+    pub class_gas: Vec<(OpClass, u64)>,
+    pub sload: AccessCounts,
+    pub sstore: AccessCounts,
+    pub max_depth: usize,
+    /// Most expensive steps as (step index, own gas), most expensive first
+    pub hotspots: Vec<(usize, u64)>,
+}
+
+/// State changes made by a transaction to one account. Each field is
+/// `Some((before, after))` only if it changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountDiff {
+    pub address: Address,
+    pub balance: Option<(U256, U256)>,
+    pub nonce: Option<(u64, u64)>,
+    pub code: Option<(Bytes, Bytes)>,
+    pub storage: Vec<StorageChange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageChange {
+    pub slot: B256,
+    pub before: B256,
+    pub after: B256,
+}
+
+/// A call to execute against a chosen block without sending a transaction.
+#[derive(Debug, Clone, Default)]
+pub struct SimulationRequest {
+    pub from: Option<Address>,
+    /// None deploys `calldata` as init code
+    pub to: Option<Address>,
+    pub value: U256,
+    /// Hex calldata, or a `name(args)` / `name(types)(args)` call to encode
+    pub calldata: String,
+    pub gas: Option<u64>,
+    pub block: BlockNumberOrTag,
+    pub state_overrides: StateOverride,
+    pub block_overrides: Option<BlockOverrides>,
+}
+
+/// Outcome of a simulated call.
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    pub success: bool,
+    pub gas_used: Option<u64>,
+    pub input: Bytes,
+    pub output: Bytes,
+    pub decoded_input: Option<DecodedCall>,
+    pub decoded_output: Vec<DecodedParam>,
+    pub revert_reason: Option<String>,
+    pub calls: Vec<InternalCall>,
+    pub logs: Vec<DecodedLog>,
+    pub token_transfers: Vec<TokenTransfer>,
+    /// Whether the call was traced; plain `eth_call` yields no calls or logs
+    pub traced: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WatchEntry {
     pub address: Address,
@@ -243,6 +602,8 @@ pub struct ChainConfig {
     pub rpc_url: String,
     pub symbol: String,
     pub explorer_url: Option<String>,
+    /// API that ABIs, verified sources and address history are fetched from
+    pub explorer_api: Option<ExplorerApi>,
     pub explorer_api_key: Option<String>,
 }
 
@@ -6,6 +6,7 @@ use alloy::providers::{Provider, ProviderBuilder, WsConnect};
 use futures::StreamExt;
 use tokio::sync::mpsc;
 
+use crate::data::signatures;
 use crate::data::types::{BlockSummary, TransactionSummary, TxStatus, TxType};
 use crate::events::AppEvent;
 
@@ -126,7 +127,7 @@ impl WsService {
                         gas_used: None,
                         gas_price: tx.inner.gas_price(),
                         method_id,
-                        method_name: None,
+                        method_name: method_id.and_then(|sel| signatures::global().function_name(sel)),
                         tx_type: TxType::EIP1559,
                         status: TxStatus::Pending,
                     };
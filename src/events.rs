@@ -1,8 +1,14 @@
-use alloy::primitives::{Address, B256, U256};
+use alloy::json_abi::Function;
+use alloy::primitives::{Address, Bytes, B256, U256};
 
+use crate::data::disasm::Disassembly;
+use crate::data::sourcemap::ContractSource;
+use crate::data::storage_layout::{StorageItem, StorageLayout, StorageValue};
+use crate::data::trace::TraceOptions;
 use crate::data::types::{
-    AddressInfo, BlockDetail, BlockSummary, DecodedLog, ExecutionTrace, GasInfo, InternalCall,
-    TokenMetadata, TransactionDetail, TransactionSummary, WatchEntry,
+    AccountDiff, AddressInfo, BlockDetail, BlockSummary, BlockTrace, DecodedLog, ExecutionTrace,
+    GasInfo, GasProfile, InternalCall, SimulationRequest, SimulationResult, TokenMetadata,
+    TransactionDetail, TransactionSummary, WatchEntry,
 };
 
 /// Views the user can navigate to
@@ -17,8 +23,13 @@ pub enum View {
     WatchList,
     Mempool,
     TxDebugger(B256),
+    CallTree(B256),
+    StateDiff(B256),
+    GasProfile(B256),
+    Simulation,
     ContractRead(Address),
     StorageInspector(Address),
+    Disassembly(Address),
 }
 
 /// Target identified from a search query
@@ -135,15 +146,33 @@ pub enum AppEvent {
     EnsNotFound(String),
 
     // Token metadata
-    TokenMetadataLoaded(TokenMetadata),
+    TokenMetadataLoaded(Vec<TokenMetadata>),
 
     // Internal transactions
     InternalTransactionsLoaded { tx_hash: B256, calls: Vec<InternalCall> },
 
+    // Block-wide tracing
+    RequestBlockTrace(u64),
+    BlockTraceLoaded(Box<BlockTrace>),
+    BlockTraceFailed { number: u64, error: String },
+
+    // Gas profile
+    GasProfileLoaded { tx_hash: B256, profile: Box<GasProfile> },
+
+    // State diff
+    StateDiffLoaded { tx_hash: B256, diffs: Vec<AccountDiff> },
+
     // Event logs decoded
     DecodedLogsLoaded { tx_hash: B256, logs: Vec<DecodedLog> },
 
+    // Bytecode
+    DisassemblyLoaded { address: Address, disassembly: Box<Disassembly> },
+
     // Contract read
+    /// View and pure functions of a contract, including those of the
+    /// implementation or facets behind a proxy, and the functions of an
+    /// inferred interface known only by their text signature
+    ContractFunctionsLoaded { address: Address, functions: Vec<Function>, untyped: Vec<Function> },
     ContractReadResult { address: Address, function: String, result: String },
 
     // Watch list
@@ -158,9 +187,26 @@ pub enum AppEvent {
 
     // Tx debugger
     TraceLoaded { tx_hash: B256, trace: ExecutionTrace },
+    TraceFailed { tx_hash: B256, error: String },
+    RequestTrace { tx_hash: B256, options: TraceOptions },
+    ContractSourceLoaded { tx_hash: B256, address: Address, source: Box<ContractSource> },
+
+    // Simulation
+    RequestSimulation(Box<SimulationRequest>),
+    SimulationLoaded(Box<SimulationResult>),
+    SimulationFailed(String),
+    OpenSimulation {
+        from: Option<Address>,
+        to: Option<Address>,
+        value: U256,
+        input: Bytes,
+        block: Option<u64>,
+    },
 
     // Storage
-    StorageValueLoaded { address: Address, slot: U256, value: B256 },
+    StorageLayoutLoaded { address: Address, layout: Option<Box<StorageLayout>> },
+    RequestStorageItems { address: Address, items: Vec<StorageItem> },
+    StorageItemsLoaded { address: Address, values: Vec<(StorageItem, StorageValue)> },
 
     // Export
     ExportComplete(String),
@@ -8,13 +8,20 @@ mod utils;
 
 use std::sync::Arc;
 
+use alloy::primitives::Address;
 use clap::Parser;
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 use tokio::sync::mpsc;
 
 use crate::app::App;
-use crate::config::Config;
+use crate::config::{AbiCommand, CacheCommand, Command, Config, SigCommand};
+use crate::data::abi::AbiResolver;
+use crate::data::abi_cache::AbiCache;
+use crate::data::abi_store::{self, AbiStore};
+use crate::data::explorer::{ExplorerApi, ExplorerClient};
 use crate::data::provider::EthProvider;
+use crate::data::signatures;
+use crate::data::types::ChainConfig;
 use crate::data::DataService;
 
 #[tokio::main]
@@ -23,6 +30,13 @@ async fn main() -> Result<()> {
 
     let config = Config::parse();
 
+    match config.command {
+        Some(Command::Abi(ref command)) => return run_abi_command(command, &config.chain),
+        Some(Command::Sig(ref command)) => return run_sig_command(command),
+        Some(Command::Cache(ref command)) => return run_cache_command(command, &config).await,
+        None => {}
+    }
+
     // Resolve RPC URL: use chain preset if not default ethereum
     let rpc_url = if config.chain != "ethereum" {
         if let Some(chain_config) = data::chains::get_chain_config(&config.chain) {
@@ -41,6 +55,13 @@ async fn main() -> Result<()> {
     let chain_id = provider.chain_id();
     eprintln!("Connected to chain {} (block data loading...)", chain_id);
 
+    // Explorer for the connected chain: the preset's if it is that chain,
+    // or Etherscan v2 by chain id
+    let chain_config = data::chains::get_chain_config(&config.chain)
+        .filter(|c| c.chain_id == chain_id)
+        .unwrap_or_else(|| data::chains::custom_chain_config(chain_id, &rpc_url));
+    let explorer = explorer_client(&config, chain_config);
+
     // Create event channel
     let (event_tx, event_rx) = mpsc::unbounded_channel();
 
@@ -50,7 +71,7 @@ async fn main() -> Result<()> {
     // Create data service
     let data_service = Arc::new(DataService::new(
         provider,
-        config.etherscan_api_key,
+        explorer,
         event_tx.clone(),
     ));
 
@@ -113,3 +134,118 @@ async fn main() -> Result<()> {
 
     result
 }
+
+/// Client for a chain's explorer, using the Etherscan key or Blockscout URL
+/// given on the command line.
+fn explorer_client(config: &Config, mut chain_config: ChainConfig) -> Option<ExplorerClient> {
+    match config.blockscout_url {
+        Some(ref url) => chain_config.explorer_api = Some(ExplorerApi::Blockscout(url.clone())),
+        None => chain_config.explorer_api_key = config.etherscan_api_key.clone(),
+    }
+    ExplorerClient::from_chain(&chain_config)
+}
+
+/// Run an `eth-tui abi ...` command against the local ABI library.
+fn run_abi_command(command: &AbiCommand, chain: &str) -> Result<()> {
+    let store = AbiStore::open().ok_or_else(|| eyre!("Could not determine config directory"))?;
+    match command {
+        AbiCommand::Import { path } => {
+            let summary = abi_store::import_project(&store, path)?;
+            println!("Found {} compiled contracts", summary.artifacts);
+            for d in &summary.imported {
+                println!("  imported {} at {} (chain {})", d.name, d.address, d.chain_id);
+            }
+            for d in &summary.missing {
+                println!("  skipped {} at {} (chain {}): no artifact", d.name, d.address, d.chain_id);
+            }
+            if summary.imported.is_empty() {
+                println!("No deployments found; use `eth-tui abi add` to save an ABI by address");
+            }
+        }
+        AbiCommand::Add {
+            address,
+            file,
+            chain_id,
+        } => {
+            let chain_id = match chain_id {
+                Some(id) => *id,
+                None => data::chains::get_chain_config(chain)
+                    .map(|c| c.chain_id)
+                    .ok_or_else(|| eyre!("Unknown chain '{chain}', pass --chain-id"))?,
+            };
+            let stored = abi_store::load_abi_file(file)?;
+            store.put(chain_id, *address, &stored)?;
+            println!("Saved {} at {address} (chain {chain_id})", stored.name);
+        }
+        AbiCommand::List => {
+            for (chain_id, address, name) in store.list() {
+                println!("{chain_id:>10}  {address}  {name}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_sig_command(command: &SigCommand) -> Result<()> {
+    match command {
+        SigCommand::Import { file } => {
+            let added = signatures::import_file(file)?;
+            println!("Imported {added} new signatures");
+        }
+        SigCommand::Lookup { selector } => {
+            let bytes = alloy::hex::decode(selector.trim())
+                .map_err(|e| eyre!("Invalid selector '{selector}': {e}"))?;
+            let found = signatures::global().lookup(&bytes);
+            if found.is_empty() {
+                println!("No known signature for {selector}");
+            }
+            for signature in found {
+                println!("{signature}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run an `eth-tui cache ...` command against the on-disk ABI cache.
+async fn run_cache_command(command: &CacheCommand, config: &Config) -> Result<()> {
+    let cache = AbiCache::open().ok_or_else(|| eyre!("Could not determine cache directory"))?;
+    let chain_id = |chain_id: &Option<u64>| match chain_id {
+        Some(id) => Ok(*id),
+        None => data::chains::get_chain_config(&config.chain)
+            .map(|c| c.chain_id)
+            .ok_or_else(|| eyre!("Unknown chain '{}', pass --chain-id", config.chain)),
+    };
+    match command {
+        CacheCommand::Purge { target, chain_id: id } => {
+            let bytes = alloy::hex::decode(target.trim())
+                .map_err(|e| eyre!("Invalid address, selector or topic '{target}': {e}"))?;
+            let purged = match bytes.len() {
+                20 => cache.purge_abi(chain_id(id)?, Address::from_slice(&bytes))?,
+                4 | 32 => cache.purge_signature(&bytes)?,
+                _ => return Err(eyre!("Expected an address, 4-byte selector or 32-byte topic")),
+            };
+            if purged {
+                println!("Purged {target}");
+            } else {
+                println!("Nothing cached for {target}");
+            }
+        }
+        CacheCommand::Refresh { address, chain_id: id } => {
+            let chain_id = chain_id(id)?;
+            let chain_config = data::chains::get_chain_config(&config.chain)
+                .filter(|c| c.chain_id == chain_id)
+                .unwrap_or_else(|| data::chains::custom_chain_config(chain_id, &config.rpc_url));
+            let resolver = AbiResolver::new(explorer_client(config, chain_config));
+            match resolver.refresh(chain_id, *address).await {
+                Some(resolved) => println!(
+                    "Cached {} functions of {address} from {}",
+                    resolved.abi.functions().count(),
+                    resolved.source
+                ),
+                None => println!("No ABI found for {address} (chain {chain_id}); will retry after a day"),
+            }
+        }
+    }
+    Ok(())
+}
@@ -115,6 +115,16 @@ pub fn format_selector(selector: &[u8; 4]) -> String {
     format!("0x{}", hex::encode(selector))
 }
 
+/// Cut `s` to at most `max` characters, marking the cut with an ellipsis.
+pub fn shorten(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let cut: String = s.chars().take(max.saturating_sub(3)).collect();
+        format!("{cut}...")
+    }
+}
+
 /// Determine gas utilization percentage
 pub fn gas_utilization_pct(used: u64, limit: u64) -> f64 {
     if limit == 0 {
@@ -202,6 +212,21 @@ mod tests {
         assert_eq!(format_u256_as_decimal(val, 6), "0.123456");
     }
 
+    #[test]
+    fn test_format_u256_as_decimal_zero_decimals() {
+        assert_eq!(format_u256_as_decimal(U256::from(42u64), 0), "42.0");
+    }
+
+    #[test]
+    fn test_format_u256_as_decimal_many_decimals() {
+        // 1.25 with 24 decimals
+        let val = U256::from(125u64) * U256::from(10u64).pow(U256::from(22));
+        assert_eq!(format_u256_as_decimal(val, 24), "1.25");
+        // The fraction is cut to six places
+        let val = U256::from(123_456_789u64) * U256::from(10u64).pow(U256::from(15));
+        assert_eq!(format_u256_as_decimal(val, 24), "0.123456");
+    }
+
     #[test]
     fn test_format_gwei_small() {
         let result = format_gwei(100_000); // 0.0001 Gwei
@@ -254,6 +279,15 @@ mod tests {
         assert_eq!(format_selector(&selector), "0xa9059cbb");
     }
 
+    #[test]
+    fn test_shorten() {
+        assert_eq!(shorten("0x1234", 6), "0x1234");
+        assert_eq!(shorten("0x123456", 6), "0x1...");
+        // Counts characters, not bytes
+        assert_eq!(shorten("αβγδ", 4), "αβγδ");
+        assert_eq!(shorten("αβγδε", 4), "α...");
+    }
+
     #[test]
     fn test_format_selector_zeros() {
         let selector = [0x00, 0x00, 0x00, 0x00];